@@ -8,7 +8,7 @@ use selium::std::traits::compression::CompressionLevel;
 #[tokio::main]
 async fn main() -> Result<()> {
     let connection = selium::custom()
-        .keep_alive(5_000)?
+        .keep_alive_interval(5_000)?
         .endpoint("127.0.0.1:7001")
         .with_certificate_authority("../certs/client/ca.der")?
         .with_cert_and_key(
@@ -8,7 +8,7 @@ const NUM_OF_MESSAGES: usize = 50_000;
 #[tokio::main]
 async fn main() -> Result<()> {
     let connection = selium::custom()
-        .keep_alive(5_000)?
+        .keep_alive_interval(5_000)?
         .endpoint("127.0.0.1:7001")
         .with_certificate_authority("../certs/client/ca.der")?
         .with_cert_and_key(
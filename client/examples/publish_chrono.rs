@@ -6,7 +6,7 @@ use selium::std::codecs::StringCodec;
 #[tokio::main]
 async fn main() -> Result<()> {
     let connection = selium::custom()
-        .keep_alive(chrono::Duration::seconds(5))?
+        .keep_alive_interval(chrono::Duration::seconds(5))?
         .endpoint("127.0.0.1:7001")
         .with_certificate_authority("../certs/client/ca.der")?
         .with_cert_and_key(
@@ -16,7 +16,7 @@ enum Response {
 #[tokio::main]
 async fn main() -> Result<()> {
     let connection = selium::custom()
-        .keep_alive(5_000)?
+        .keep_alive_interval(5_000)?
         .backoff_strategy(BackoffStrategy::constant().with_max_attempts(1))
         .endpoint("127.0.0.1:7001")
         .with_certificate_authority("../certs/client/ca.der")?
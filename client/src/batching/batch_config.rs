@@ -34,10 +34,22 @@ use std::time::Duration;
 /// let tweaked_high_throughput = BatchConfig::high_throughput()
 ///     .interval(Duration::from_millis(10));
 /// ```
+///
+/// By default, a batch is sent whenever it is ready, regardless of whether the underlying stream
+/// can accept it. To bound the amount of memory used by a fast producer on a slow network, specify
+/// a `max_buffered_bytes` limit, which applies backpressure to the
+/// [Publisher](crate::streams::pubsub::Publisher) stream once reached.
+///
+/// ```
+/// use selium::batching::BatchConfig;
+///
+/// let bounded = BatchConfig::balanced().max_buffered_bytes(64 * 1024);
+/// ```
 #[derive(Debug, Clone)]
 pub struct BatchConfig {
     pub(crate) batch_size: u32,
     pub(crate) interval: Duration,
+    pub(crate) max_buffered_bytes: Option<usize>,
 }
 
 impl Default for BatchConfig {
@@ -53,6 +65,7 @@ impl BatchConfig {
         Self {
             batch_size,
             interval,
+            max_buffered_bytes: None,
         }
     }
 
@@ -92,4 +105,16 @@ impl BatchConfig {
         self.interval = interval;
         self
     }
+
+    /// Limits the number of encoded bytes that can be buffered in a batch before it must be sent.
+    ///
+    /// Once the limit is reached, the batch is sent as soon as the underlying stream is writable.
+    /// While the stream is not writable, the [Publisher](crate::streams::pubsub::Publisher)
+    /// stream's [Sink](futures::Sink) implementation will return
+    /// [Poll::Pending](std::task::Poll::Pending) from `poll_ready`, throttling the producer rather
+    /// than buffering messages without bound.
+    pub fn max_buffered_bytes(mut self, bytes: usize) -> Self {
+        self.max_buffered_bytes = Some(bytes);
+        self
+    }
 }
@@ -0,0 +1,80 @@
+/// The condition that caused a message batch to be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushTrigger {
+    /// The batch reached the configured `batch_size`.
+    BatchSize,
+    /// The batching `interval` elapsed.
+    Interval,
+    /// The batch reached the configured `max_buffered_bytes` limit.
+    BufferedBytes,
+    /// The batch was sent explicitly, via [flush](crate::streams::pubsub::Publisher::flush) or
+    /// [finish](crate::streams::pubsub::Publisher::finish).
+    Manual,
+}
+
+/// Statistics describing the batches sent by a [Publisher](crate::streams::pubsub::Publisher)
+/// stream, which are useful for tuning a [BatchConfig](super::BatchConfig) empirically.
+///
+/// Stats are accumulated for the lifetime of the stream, and can be retrieved via
+/// [batch_stats](crate::streams::pubsub::Publisher::batch_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    batches_sent: u64,
+    messages_sent: u64,
+    max_batch_size: usize,
+    batch_size_flushes: u64,
+    interval_flushes: u64,
+    buffered_bytes_flushes: u64,
+    manual_flushes: u64,
+}
+
+impl BatchStats {
+    /// The total number of batches sent.
+    pub fn batches_sent(&self) -> u64 {
+        self.batches_sent
+    }
+
+    /// The total number of messages sent across all batches.
+    pub fn messages_sent(&self) -> u64 {
+        self.messages_sent
+    }
+
+    /// The average number of messages per batch, or `0.0` if no batches have been sent.
+    pub fn average_batch_size(&self) -> f64 {
+        if self.batches_sent == 0 {
+            return 0.0;
+        }
+
+        self.messages_sent as f64 / self.batches_sent as f64
+    }
+
+    /// The largest number of messages sent in a single batch.
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// The number of batches sent due to the provided `trigger`.
+    pub fn flushes(&self, trigger: FlushTrigger) -> u64 {
+        match trigger {
+            FlushTrigger::BatchSize => self.batch_size_flushes,
+            FlushTrigger::Interval => self.interval_flushes,
+            FlushTrigger::BufferedBytes => self.buffered_bytes_flushes,
+            FlushTrigger::Manual => self.manual_flushes,
+        }
+    }
+
+    pub(crate) fn record(&mut self, batch_size: usize, trigger: FlushTrigger) {
+        self.batches_sent += 1;
+        self.messages_sent += batch_size as u64;
+        self.max_batch_size = self.max_batch_size.max(batch_size);
+
+        let flushes = match trigger {
+            FlushTrigger::BatchSize => &mut self.batch_size_flushes,
+            FlushTrigger::Interval => &mut self.interval_flushes,
+            FlushTrigger::BufferedBytes => &mut self.buffered_bytes_flushes,
+            FlushTrigger::Manual => &mut self.manual_flushes,
+        };
+
+        *flushes += 1;
+    }
+}
@@ -1,23 +1,32 @@
-use super::BatchConfig;
+use super::{BatchConfig, BatchStats, FlushTrigger};
 use bytes::Bytes;
 use std::time::Instant;
 
 pub(crate) struct MessageBatch {
     batch: Vec<Bytes>,
+    buffered_bytes: usize,
     config: BatchConfig,
     last_run: Instant,
+    stats: BatchStats,
 }
 
 impl MessageBatch {
     pub fn push(&mut self, value: Bytes) {
+        self.buffered_bytes += value.len();
         self.batch.push(value);
     }
 
-    pub fn drain(&mut self) -> Vec<Bytes> {
+    pub fn drain(&mut self, trigger: FlushTrigger) -> Vec<Bytes> {
+        self.stats.record(self.batch.len(), trigger);
+        self.buffered_bytes = 0;
         let batch = self.batch.drain(..);
         batch.collect()
     }
 
+    pub fn len(&self) -> usize {
+        self.batch.len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.batch.is_empty()
     }
@@ -34,8 +43,31 @@ impl MessageBatch {
         self.batch.len() >= self.config.batch_size as usize
     }
 
-    pub fn is_ready(&self, now: Instant) -> bool {
-        self.exceeded_interval(now) || self.exceeded_batch_size()
+    pub fn exceeded_buffered_bytes(&self) -> bool {
+        self.config
+            .max_buffered_bytes
+            .is_some_and(|max| self.buffered_bytes >= max)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.config.max_buffered_bytes.is_some()
+    }
+
+    pub fn stats(&self) -> BatchStats {
+        self.stats
+    }
+
+    /// Returns the reason the batch is ready to be sent, or [None] if it isn't ready yet.
+    pub fn ready_trigger(&self, now: Instant) -> Option<FlushTrigger> {
+        if self.exceeded_batch_size() {
+            Some(FlushTrigger::BatchSize)
+        } else if self.exceeded_buffered_bytes() {
+            Some(FlushTrigger::BufferedBytes)
+        } else if self.exceeded_interval(now) {
+            Some(FlushTrigger::Interval)
+        } else {
+            None
+        }
     }
 }
 
@@ -46,8 +78,10 @@ impl From<BatchConfig> for MessageBatch {
 
         Self {
             batch,
+            buffered_bytes: 0,
             config,
             last_run,
+            stats: BatchStats::default(),
         }
     }
 }
@@ -24,6 +24,10 @@
 //! If a batch is incomplete prior to closing a [Publisher](crate::streams::pubsub::Publisher) stream, calling
 //! [finish](crate::streams::pubsub::Publisher::finish) on the stream will automatically flush the pending message
 //! batch to ensure that it is delivered to subscribers.
+//!
+//! To help with tuning a [BatchConfig], each [Publisher](crate::streams::pubsub::Publisher) stream
+//! records [BatchStats] describing the size of the batches it sends, and what caused each batch to
+//! be sent.
 
 //! # Subscriber
 //!
@@ -35,7 +39,9 @@
 //! individually.
 
 mod batch_config;
+mod batch_stats;
 mod message_batch;
 
 pub use batch_config::*;
+pub use batch_stats::*;
 pub(crate) use message_batch::*;
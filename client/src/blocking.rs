@@ -0,0 +1,247 @@
+//! A blocking facade over the asynchronous [Client](crate::Client), for use from synchronous
+//! code.
+//!
+//! The blocking [Client] owns a current-thread [tokio] runtime, which drives the connection and
+//! its streams whenever one of the blocking methods is called. As the runtime only runs while a
+//! method is blocking, it's best suited to short-lived tasks such as CLI tools that publish a few
+//! messages and exit. Long-running applications should prefer the asynchronous client.
+//!
+//! The blocking methods must not be called from within an asynchronous context, as blocking on
+//! the runtime from inside another runtime will panic.
+//!
+//! This module is only available with the `blocking` feature enabled.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use selium::blocking::Client;
+//! use selium::std::codecs::StringCodec;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let client = Client::connect(
+//!         selium::custom()
+//!             .endpoint("127.0.0.1:7001")
+//!             .with_certificate_authority("certs/client/ca.der")?
+//!             .with_cert_and_key(
+//!                 "certs/client/localhost.der",
+//!                 "certs/client/localhost.key.der",
+//!             )?
+//!             .connect(),
+//!     )?;
+//!
+//!     let mut publisher = client.publisher("/acmeco/stocks", StringCodec)?;
+//!     publisher.publish("Hello, world!".to_owned())?;
+//!     publisher.finish()?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::keep_alive::{pubsub::KeepAlive, reqrep};
+use crate::pubsub;
+use crate::request_reply;
+use crate::traits::Open;
+use futures::{Future, SinkExt, TryStreamExt};
+use selium_std::errors::Result;
+use selium_std::traits::codec::{MessageDecoder, MessageEncoder};
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+/// A blocking client, wrapping an asynchronous [Client](crate::Client) along with the runtime
+/// used to drive it.
+///
+/// Streams opened by the client share its runtime, so they can outlive the client itself.
+pub struct Client {
+    client: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Connects to a `Selium` server by blocking on the provided connection future, which is
+    /// typically returned by a [ClientBuilder](crate::ClientBuilder)'s `connect` method.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the runtime fails to be created, or if the connection fails.
+    pub fn connect<F>(connect: F) -> Result<Self>
+    where
+        F: Future<Output = Result<crate::Client>>,
+    {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let client = runtime.block_on(connect)?;
+
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Opens a [Publisher] stream to the provided topic, encoding messages with `encoder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to open.
+    pub fn publisher<E>(&self, topic: &str, encoder: E) -> Result<Publisher<E>>
+    where
+        E: MessageEncoder + Clone + Send + Unpin,
+    {
+        let open = self.client.publisher(topic).with_encoder(encoder).open();
+        let inner = self.runtime.block_on(open)?;
+
+        Ok(Publisher {
+            inner,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Opens a [Subscriber] stream to the provided topic, decoding messages with `decoder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to open.
+    pub fn subscriber<D>(&self, topic: &str, decoder: D) -> Result<Subscriber<D>>
+    where
+        D: MessageDecoder + Send + Unpin,
+    {
+        let open = self.client.subscriber(topic).with_decoder(decoder).open();
+        let inner = self.runtime.block_on(open)?;
+
+        Ok(Subscriber {
+            inner,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Opens a [Requestor] stream to the provided endpoint, encoding requests with `encoder` and
+    /// decoding replies with `decoder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to open.
+    pub fn requestor<E, D>(&self, endpoint: &str, encoder: E, decoder: D) -> Result<Requestor<E, D>>
+    where
+        E: MessageEncoder + Clone + Send + Unpin,
+        D: MessageDecoder + Clone + Send + Unpin,
+    {
+        let open = self
+            .client
+            .requestor(endpoint)
+            .with_request_encoder(encoder)
+            .with_reply_decoder(decoder)
+            .open();
+        let inner = self.runtime.block_on(open)?;
+
+        Ok(Requestor {
+            inner,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Blocks on the provided future using the client's runtime.
+    ///
+    /// Allows any asynchronous operation on the [inner](Client::inner) client to be carried out,
+    /// such as opening a stream with options not covered by the blocking client.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// The asynchronous [Client](crate::Client) wrapped by the blocking client.
+    pub fn inner(&self) -> &crate::Client {
+        &self.client
+    }
+}
+
+/// A blocking wrapper around a [Publisher](crate::pubsub::Publisher) stream.
+pub struct Publisher<E> {
+    inner: KeepAlive<pubsub::Publisher<E>>,
+    runtime: Arc<Runtime>,
+}
+
+impl<E> Publisher<E>
+where
+    E: MessageEncoder + Clone + Send + Unpin,
+    E::Item: Unpin + Send,
+{
+    /// Publishes a message, blocking until it has been sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded or sent.
+    pub fn publish(&mut self, item: E::Item) -> Result<()> {
+        self.runtime.block_on(self.inner.send(item))
+    }
+
+    /// Flushes any messages that have yet to be sent, including the current batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to flush.
+    pub fn flush(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.flush())
+    }
+
+    /// Gracefully closes the stream, sending any messages in the current batch first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to close gracefully.
+    pub fn finish(self) -> Result<()> {
+        self.runtime.block_on(self.inner.finish())
+    }
+}
+
+/// A blocking wrapper around a [Subscriber](crate::pubsub::Subscriber) stream.
+///
+/// Messages can be received one at a time via [recv](Subscriber::recv), or by iterating over
+/// the subscriber.
+pub struct Subscriber<D> {
+    inner: KeepAlive<pubsub::Subscriber<D>>,
+    runtime: Arc<Runtime>,
+}
+
+impl<D> Subscriber<D>
+where
+    D: MessageDecoder + Send + Unpin,
+{
+    /// Blocks until the next message is received, returning [None] once the stream has closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be received or decoded.
+    pub fn recv(&mut self) -> Result<Option<D::Item>> {
+        self.runtime.block_on(self.inner.try_next())
+    }
+}
+
+impl<D> Iterator for Subscriber<D>
+where
+    D: MessageDecoder + Send + Unpin,
+{
+    type Item = Result<D::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv().transpose()
+    }
+}
+
+/// A blocking wrapper around a [Requestor](crate::request_reply::Requestor) stream.
+pub struct Requestor<E, D> {
+    inner: reqrep::KeepAlive<request_reply::Requestor<E, D>>,
+    runtime: Arc<Runtime>,
+}
+
+impl<E, D> Requestor<E, D>
+where
+    E: MessageEncoder + Clone + Send + Unpin,
+    D: MessageDecoder + Clone + Send + Unpin,
+{
+    /// Sends a request, blocking until its reply is received.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the request fails to be sent, or if no reply is received before the
+    /// requestor's timeout.
+    pub fn request(&mut self, req: E::Item) -> Result<D::Item> {
+        self.runtime.block_on(self.inner.request(req))
+    }
+}
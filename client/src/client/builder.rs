@@ -1,7 +1,15 @@
-use crate::constants::KEEP_ALIVE_DEFAULT;
+use crate::constants::{
+    ALPN_DEFAULT, CERT_EXPIRY_WARNING_DEFAULT, CONNECT_TIMEOUT_DEFAULT,
+    DATAGRAM_BUFFER_SIZE_DEFAULT, IDLE_TIMEOUT_DEFAULT, KEEP_ALIVE_INTERVAL_DEFAULT,
+    SERVER_NAME_DEFAULT,
+};
 use crate::keep_alive::BackoffStrategy;
+use crate::logging;
 use crate::traits::TryIntoU64;
-use selium_std::errors::Result;
+use selium_std::errors::{Result, SeliumError};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 /// A convenient builder struct used to build a [Client](crate::Client) instance.
 ///
@@ -27,27 +35,58 @@ impl<T> ClientBuilder<T> {
 /// Common state for all client types.
 #[derive(Debug)]
 pub struct ClientCommon {
-    pub(crate) keep_alive: u64,
+    pub(crate) keep_alive_interval: u64,
+    pub(crate) idle_timeout: u64,
+    pub(crate) connect_timeout: u64,
     pub(crate) backoff_strategy: BackoffStrategy,
+    pub(crate) retry_initial_connect: bool,
+    pub(crate) alpn: String,
+    pub(crate) datagram_buffer_size: usize,
+    pub(crate) server_name: String,
+    pub(crate) cert_expiry_warning: u64,
+    pub(crate) cert_expiry_handler: Option<CertExpiryHandler>,
+}
+
+/// A callback invoked with the expiry time of a client certificate that's due to expire.
+#[derive(Clone)]
+pub(crate) struct CertExpiryHandler(Arc<dyn Fn(SystemTime) + Send + Sync>);
+
+impl fmt::Debug for CertExpiryHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CertExpiryHandler").finish()
+    }
 }
 
 impl Default for ClientCommon {
     fn default() -> Self {
         Self {
-            keep_alive: KEEP_ALIVE_DEFAULT,
+            keep_alive_interval: KEEP_ALIVE_INTERVAL_DEFAULT,
+            idle_timeout: IDLE_TIMEOUT_DEFAULT,
+            connect_timeout: CONNECT_TIMEOUT_DEFAULT,
             backoff_strategy: BackoffStrategy::default(),
+            retry_initial_connect: false,
+            alpn: ALPN_DEFAULT.to_owned(),
+            datagram_buffer_size: DATAGRAM_BUFFER_SIZE_DEFAULT,
+            server_name: SERVER_NAME_DEFAULT.to_owned(),
+            cert_expiry_warning: CERT_EXPIRY_WARNING_DEFAULT,
+            cert_expiry_handler: None,
         }
     }
 }
 
 impl ClientCommon {
-    /// Overrides the `keep_alive` interval for the client connection in milliseconds.
+    /// Overrides the `keep_alive_interval` for the client connection in milliseconds.
+    ///
+    /// The client sends a QUIC PING to the server whenever the connection has been silent for
+    /// this long, which keeps the connection, and any NAT mappings along the way, from expiring
+    /// while the client has nothing to send. The interval must be no more than half of the
+    /// [idle_timeout](ClientCommon::idle_timeout), otherwise connecting will fail.
     ///
     /// Accepts any `interval` argument that can be *fallibly* converted into a [u64] via the
     /// [TryIntoU64](crate::traits::TryIntoU64) trait.
     ///
-    /// **NOTE:** `Selium` already provides a reasonable default for the `keep_alive` interval (see
-    /// [KEEP_ALIVE_DEFAULT]), so this setting should only be overridden if it's not suitable for
+    /// **NOTE:** `Selium` already provides a reasonable default for the `keep_alive_interval` (see
+    /// [KEEP_ALIVE_INTERVAL_DEFAULT]), so this setting should only be overridden if it's not suitable for
     /// your use-case.
     ///
     /// # Errors
@@ -56,11 +95,11 @@ impl ClientCommon {
     ///
     /// # Examples
     ///
-    /// Overriding the default `keep_alive` interval as 6 seconds (represented in milliseconds).
+    /// Overriding the default `keep_alive_interval` as 6 seconds (represented in milliseconds).
     ///  
     /// ```
     /// let client = selium::custom()
-    ///     .keep_alive(6_000).unwrap();
+    ///     .keep_alive_interval(6_000).unwrap();
     /// ```
     ///
     /// You can even use any other type that can be converted to a [u64] via the
@@ -71,10 +110,76 @@ impl ClientCommon {
     /// use std::time::Duration;
     ///
     /// let client = selium::custom()
-    ///     .keep_alive(Duration::from_secs(6)).unwrap();
+    ///     .keep_alive_interval(Duration::from_secs(6)).unwrap();
     /// ```
+    pub fn keep_alive_interval<T: TryIntoU64>(&mut self, interval: T) -> Result<()> {
+        self.keep_alive_interval = interval.try_into_u64()?;
+        Ok(())
+    }
+
+    /// Overrides the `keep_alive` interval for the client connection in milliseconds.
+    ///
+    /// Unlike earlier releases, the interval must be no more than half of the
+    /// [idle_timeout](ClientCommon::idle_timeout), otherwise connecting will fail.
+    #[deprecated(note = "renamed to `keep_alive_interval`")]
     pub fn keep_alive<T: TryIntoU64>(&mut self, interval: T) -> Result<()> {
-        self.keep_alive = interval.try_into_u64()?;
+        self.keep_alive_interval(interval)
+    }
+
+    /// Overrides the `idle_timeout` for the client connection in milliseconds.
+    ///
+    /// The connection is closed if nothing is received from the server for this long. The
+    /// effective timeout is the lower of the client's and the server's idle timeouts. Defaults to
+    /// [IDLE_TIMEOUT_DEFAULT].
+    ///
+    /// Accepts any `timeout` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be convert to a [u64].
+    ///
+    /// # Examples
+    ///
+    /// Extending the `idle_timeout` to 1 minute, while keeping NAT mappings warm with a ping
+    /// every 10 seconds.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = selium::custom()
+    ///     .keep_alive_interval(Duration::from_secs(10)).unwrap()
+    ///     .idle_timeout(Duration::from_secs(60)).unwrap();
+    /// ```
+    pub fn idle_timeout<T: TryIntoU64>(&mut self, timeout: T) -> Result<()> {
+        self.idle_timeout = timeout.try_into_u64()?;
+        Ok(())
+    }
+
+    /// Overrides the `connect_timeout` for the client in milliseconds.
+    ///
+    /// The timeout bounds both establishing the connection to the `Selium` server, and the
+    /// handshake performed with the server when opening a stream, so that a server that stops
+    /// responding can't stall the client indefinitely. The same bound applies when reconnecting.
+    /// Defaults to [CONNECT_TIMEOUT_DEFAULT].
+    ///
+    /// Accepts any `timeout` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be convert to a [u64].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = selium::custom()
+    ///     .connect_timeout(Duration::from_secs(3)).unwrap();
+    /// ```
+    pub fn connect_timeout<T: TryIntoU64>(&mut self, timeout: T) -> Result<()> {
+        self.connect_timeout = timeout.try_into_u64()?;
         Ok(())
     }
 
@@ -102,4 +207,203 @@ impl ClientCommon {
     pub fn backoff_strategy(&mut self, strategy: BackoffStrategy) {
         self.backoff_strategy = strategy;
     }
+
+    /// Applies the `backoff_strategy` to the initial connection attempt.
+    ///
+    /// By default, the client fails fast if the `Selium` server can't be reached when
+    /// connecting. Enabling this setting retries transient connection failures following the
+    /// [backoff_strategy](ClientCommon::backoff_strategy) instead, which is useful when the client
+    /// may be started before the server, such as when deploying both at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use selium::keep_alive::BackoffStrategy;
+    /// use std::time::Duration;
+    ///
+    /// let strategy = BackoffStrategy::constant()
+    ///     .with_max_attempts(10)
+    ///     .with_step(Duration::from_secs(1));
+    ///
+    /// let client = selium::custom()
+    ///     .backoff_strategy(strategy)
+    ///     .retry_initial_connect();
+    /// ```
+    pub fn retry_initial_connect(&mut self) {
+        self.retry_initial_connect = true;
+    }
+
+    /// Overrides the ALPN protocol identifier offered to the `Selium` server during the TLS
+    /// handshake.
+    ///
+    /// This is useful when running `Selium` behind a load balancer that routes connections by
+    /// ALPN. The identifier must match the one configured on the server, otherwise the server
+    /// will refuse the connection. Defaults to [ALPN_DEFAULT].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::custom()
+    ///     .with_alpn("selium");
+    /// ```
+    pub fn alpn(&mut self, protocol: &str) {
+        self.alpn = protocol.to_owned();
+    }
+
+    /// Overrides the size in bytes of the buffer for datagrams received on the client
+    /// connection.
+    ///
+    /// Datagrams are used by [unreliable subscribers](crate::pubsub::UnreliableSubscriber) to
+    /// receive messages that bypass the topic's log. Once the buffer is full, further datagrams
+    /// are dropped until the buffered datagrams have been read. A size of 0 disables datagrams
+    /// for the connection. Defaults to [DATAGRAM_BUFFER_SIZE_DEFAULT].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::custom()
+    ///     .datagram_buffer_size(4 * 1024 * 1024);
+    /// ```
+    pub fn datagram_buffer_size(&mut self, size: usize) {
+        self.datagram_buffer_size = size;
+    }
+
+    /// Overrides the TLS server name (SNI) requested from the `Selium` server during the TLS
+    /// handshake.
+    ///
+    /// A server hosting several virtual hosts uses the server name to route the connection to
+    /// the topics of the matching host, and refuses the connection if it doesn't host the server
+    /// name. The server's certificate must be valid for the server name. Defaults to
+    /// [SERVER_NAME_DEFAULT].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::custom()
+    ///     .with_server_name("tenant-a.example.com");
+    /// ```
+    pub fn server_name(&mut self, server_name: &str) {
+        self.server_name = server_name.to_owned();
+    }
+
+    /// Overrides the `cert_expiry_warning` window for the client certificate in milliseconds.
+    ///
+    /// When connecting, the client warns if its certificate expires within this window, giving
+    /// operators a chance to rotate the certificate before the server starts refusing the
+    /// connection. Defaults to [CERT_EXPIRY_WARNING_DEFAULT].
+    ///
+    /// Accepts any `window` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided window fails to be convert to a [u64].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = selium::custom()
+    ///     .cert_expiry_warning(Duration::from_secs(60 * 60 * 24 * 30)).unwrap();
+    /// ```
+    pub fn cert_expiry_warning<T: TryIntoU64>(&mut self, window: T) -> Result<()> {
+        self.cert_expiry_warning = window.try_into_u64()?;
+        Ok(())
+    }
+
+    /// Invokes `handler` with the expiry time of the client certificate when connecting, if the
+    /// certificate expires within the [cert_expiry_warning](ClientCommon::cert_expiry_warning)
+    /// window.
+    ///
+    /// A warning is logged regardless, so the handler is only needed to act on the warning, such
+    /// as by raising an alert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::custom()
+    ///     .on_cert_expiry(|expiry| eprintln!("Client certificate expires at {expiry:?}"));
+    /// ```
+    pub fn on_cert_expiry<F>(&mut self, handler: F)
+    where
+        F: Fn(SystemTime) + Send + Sync + 'static,
+    {
+        self.cert_expiry_handler = Some(CertExpiryHandler(Arc::new(handler)));
+    }
+
+    /// Warns if the client certificate expires within the `cert_expiry_warning` window.
+    pub(crate) fn check_cert_expiry(&self, expiry: Option<SystemTime>) {
+        let window = Duration::from_millis(self.cert_expiry_warning);
+        let expiring = expiry.filter(|&expiry| {
+            SystemTime::now()
+                .checked_add(window)
+                .is_none_or(|deadline| expiry <= deadline)
+        });
+
+        if let Some(expiry) = expiring {
+            logging::connection::cert_expiring(expiry);
+
+            if let Some(CertExpiryHandler(handler)) = &self.cert_expiry_handler {
+                handler(expiry);
+            }
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        // Leave room for at least one more ping to be lost before the connection times out
+        if self.keep_alive_interval.saturating_mul(2) > self.idle_timeout {
+            return Err(SeliumError::InvalidKeepAliveInterval {
+                keep_alive_interval: self.keep_alive_interval,
+                idle_timeout: self.idle_timeout,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_default_keep_alive_settings() {
+        assert!(ClientCommon::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_keep_alive_interval_not_less_than_idle_timeout() {
+        let mut common = ClientCommon::default();
+        common.keep_alive_interval(10_000).unwrap();
+        common.idle_timeout(10_000).unwrap();
+
+        assert!(matches!(
+            common.validate(),
+            Err(SeliumError::InvalidKeepAliveInterval {
+                keep_alive_interval: 10_000,
+                idle_timeout: 10_000
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_keep_alive_interval_too_close_to_idle_timeout() {
+        let mut common = ClientCommon::default();
+        common.keep_alive_interval(6_000).unwrap();
+        common.idle_timeout(10_000).unwrap();
+        assert!(common.validate().is_err());
+
+        common.keep_alive_interval(5_000).unwrap();
+        assert!(common.validate().is_ok());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn keep_alive_sets_keep_alive_interval() {
+        let mut common = ClientCommon::default();
+        common.keep_alive(2_000).unwrap();
+
+        assert_eq!(common.keep_alive_interval, 2_000);
+    }
 }
@@ -11,12 +11,31 @@ use crate::{Client, ClientBuilder, ClientCommon};
 use selium_std::errors::{Result, SeliumError};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::Mutex;
 
 impl ClientBuilder<CloudWantsCertAndKey> {
-    /// See [keep_alive](ClientCommon::keep_alive) in [ClientCommon].
-    pub fn keep_alive<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
-        self.state.common.keep_alive(interval)?;
+    /// See [keep_alive_interval](ClientCommon::keep_alive_interval) in [ClientCommon].
+    pub fn keep_alive_interval<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
+        self.state.common.keep_alive_interval(interval)?;
+        Ok(self)
+    }
+
+    /// See [keep_alive_interval](ClientCommon::keep_alive_interval) in [ClientCommon].
+    #[deprecated(note = "renamed to `keep_alive_interval`")]
+    pub fn keep_alive<T: TryIntoU64>(self, interval: T) -> Result<Self> {
+        self.keep_alive_interval(interval)
+    }
+
+    /// See [idle_timeout](ClientCommon::idle_timeout) in [ClientCommon].
+    pub fn idle_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.common.idle_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// See [connect_timeout](ClientCommon::connect_timeout) in [ClientCommon].
+    pub fn connect_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.common.connect_timeout(timeout)?;
         Ok(self)
     }
 
@@ -26,6 +45,27 @@ impl ClientBuilder<CloudWantsCertAndKey> {
         self
     }
 
+    /// See [retry_initial_connect](ClientCommon::retry_initial_connect) in [ClientCommon].
+    pub fn retry_initial_connect(mut self) -> Self {
+        self.state.common.retry_initial_connect();
+        self
+    }
+
+    /// See [cert_expiry_warning](ClientCommon::cert_expiry_warning) in [ClientCommon].
+    pub fn cert_expiry_warning<T: TryIntoU64>(mut self, window: T) -> Result<Self> {
+        self.state.common.cert_expiry_warning(window)?;
+        Ok(self)
+    }
+
+    /// See [on_cert_expiry](ClientCommon::on_cert_expiry) in [ClientCommon].
+    pub fn on_cert_expiry<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(SystemTime) + Send + Sync + 'static,
+    {
+        self.state.common.on_cert_expiry(handler);
+        self
+    }
+
     /// Attempts to load a valid keypair from the filesystem to use with authenticating the QUIC connection.
     ///
     /// Keypairs can be encoded in either a Base64 ASCII (.pem) or binary (.der) format.
@@ -63,6 +103,7 @@ impl ClientBuilder<CloudWantsConnect> {
     ///
     /// Returns [Err] under the following conditions:
     ///
+    /// - If the `keep_alive_interval` is more than half of the `idle_timeout`.
     /// - If the connection cannot be established.
     pub async fn connect(self) -> Result<Client> {
         let CloudWantsConnect {
@@ -70,24 +111,53 @@ impl ClientBuilder<CloudWantsConnect> {
             certs,
             key,
             root_store,
+            cert_expiry,
         } = self.state;
+        common.validate()?;
+        common.check_cert_expiry(cert_expiry);
+
         let ClientCommon {
-            keep_alive,
+            keep_alive_interval,
+            idle_timeout,
+            connect_timeout,
             backoff_strategy,
+            retry_initial_connect,
+            alpn,
+            datagram_buffer_size,
+            server_name,
+            ..
         } = common;
 
-        let options = ConnectionOptions::new(certs.as_slice(), key, root_store, keep_alive);
+        let options = ConnectionOptions::new(
+            certs.as_slice(),
+            key,
+            root_store,
+            keep_alive_interval,
+            idle_timeout,
+            connect_timeout,
+            alpn,
+        )
+        .with_datagram_buffer_size(datagram_buffer_size)
+        .with_server_name(&server_name);
         logging::connection::get_cloud_endpoint();
         let endpoint = get_cloud_endpoint(options.clone()).await?;
 
         logging::connection::connect_to_address(&endpoint);
-        let connection = ClientConnection::connect(&endpoint, options).await?;
+        let connection = if retry_initial_connect {
+            ClientConnection::connect_with_retry(&endpoint, options, backoff_strategy.clone())
+                .await?
+        } else {
+            ClientConnection::connect(&endpoint, options).await?
+        };
+        let events = connection.events().clone();
         let connection = Arc::new(Mutex::new(connection));
         logging::connection::successful_connection(&endpoint);
 
         Ok(Client {
             connection,
             backoff_strategy,
+            events,
+            cert_expiry,
         })
     }
 }
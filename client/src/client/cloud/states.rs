@@ -1,7 +1,8 @@
 use crate::constants::CLOUD_CA;
-use crate::crypto::cert::load_root_store;
+use crate::crypto::cert::{cert_expiry, load_root_store};
 use crate::ClientCommon;
 use rustls::{Certificate, PrivateKey, RootCertStore};
+use std::time::SystemTime;
 
 #[doc(hidden)]
 pub struct CloudWantsCertAndKey {
@@ -28,6 +29,7 @@ pub struct CloudWantsConnect {
     pub(crate) root_store: RootCertStore,
     pub(crate) certs: Vec<Certificate>,
     pub(crate) key: PrivateKey,
+    pub(crate) cert_expiry: Option<SystemTime>,
 }
 
 impl CloudWantsConnect {
@@ -37,6 +39,7 @@ impl CloudWantsConnect {
             root_store: prev.root_store,
             certs: certs.to_owned(),
             key,
+            cert_expiry: cert_expiry(certs),
         }
     }
 }
@@ -3,20 +3,45 @@ pub use states::*;
 
 use crate::connection::{ClientConnection, ConnectionOptions};
 use crate::constants::SELIUM_CLOUD_REMOTE_URL;
-use crate::crypto::cert::{load_certs, load_keypair, load_root_store};
+#[cfg(feature = "native-roots")]
+use crate::crypto::cert::add_native_roots;
+use crate::crypto::cert::{
+    add_certificate_authority, load_certs, load_keypair, load_root_store,
+    load_root_store_from_files,
+};
 use crate::keep_alive::BackoffStrategy;
 use crate::logging;
 use crate::traits::TryIntoU64;
-use crate::{Client, ClientBuilder, ClientCommon};
+use crate::{Client, ClientBuilder, ClientCommon, ClientPool};
+use rustls::RootCertStore;
 use selium_std::errors::{Result, SeliumError};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::Mutex;
 
 impl ClientBuilder<CustomWantsEndpoint> {
-    /// See [keep_alive](ClientCommon::keep_alive) in [ClientCommon].
-    pub fn keep_alive<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
-        self.state.common.keep_alive(interval)?;
+    /// See [keep_alive_interval](ClientCommon::keep_alive_interval) in [ClientCommon].
+    pub fn keep_alive_interval<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
+        self.state.common.keep_alive_interval(interval)?;
+        Ok(self)
+    }
+
+    /// See [keep_alive_interval](ClientCommon::keep_alive_interval) in [ClientCommon].
+    #[deprecated(note = "renamed to `keep_alive_interval`")]
+    pub fn keep_alive<T: TryIntoU64>(self, interval: T) -> Result<Self> {
+        self.keep_alive_interval(interval)
+    }
+
+    /// See [idle_timeout](ClientCommon::idle_timeout) in [ClientCommon].
+    pub fn idle_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.common.idle_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// See [connect_timeout](ClientCommon::connect_timeout) in [ClientCommon].
+    pub fn connect_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.common.connect_timeout(timeout)?;
         Ok(self)
     }
 
@@ -26,6 +51,45 @@ impl ClientBuilder<CustomWantsEndpoint> {
         self
     }
 
+    /// See [retry_initial_connect](ClientCommon::retry_initial_connect) in [ClientCommon].
+    pub fn retry_initial_connect(mut self) -> Self {
+        self.state.common.retry_initial_connect();
+        self
+    }
+
+    /// See [alpn](ClientCommon::alpn) in [ClientCommon].
+    pub fn with_alpn(mut self, protocol: &str) -> Self {
+        self.state.common.alpn(protocol);
+        self
+    }
+
+    /// See [datagram_buffer_size](ClientCommon::datagram_buffer_size) in [ClientCommon].
+    pub fn datagram_buffer_size(mut self, size: usize) -> Self {
+        self.state.common.datagram_buffer_size(size);
+        self
+    }
+
+    /// See [cert_expiry_warning](ClientCommon::cert_expiry_warning) in [ClientCommon].
+    pub fn cert_expiry_warning<T: TryIntoU64>(mut self, window: T) -> Result<Self> {
+        self.state.common.cert_expiry_warning(window)?;
+        Ok(self)
+    }
+
+    /// See [on_cert_expiry](ClientCommon::on_cert_expiry) in [ClientCommon].
+    pub fn on_cert_expiry<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(SystemTime) + Send + Sync + 'static,
+    {
+        self.state.common.on_cert_expiry(handler);
+        self
+    }
+
+    /// See [server_name](ClientCommon::server_name) in [ClientCommon].
+    pub fn with_server_name(mut self, server_name: &str) -> Self {
+        self.state.common.server_name(server_name);
+        self
+    }
+
     /// Specifies the remote server address to connect to.
     pub fn endpoint(self, endpoint: &str) -> ClientBuilder<CustomWantsRootCert> {
         let next_state = CustomWantsRootCert::new(self.state, endpoint);
@@ -52,9 +116,94 @@ impl ClientBuilder<CustomWantsRootCert> {
         let next_state = CustomWantsCertAndKey::new(self.state, root_store);
         Ok(ClientBuilder { state: next_state })
     }
+
+    /// Attempts to load valid CA certificates from each of the provided files, and creates a
+    /// root cert store containing all of them to use with authenticating the QUIC connection.
+    ///
+    /// This allows the client to trust servers whose certificates were issued by any one of
+    /// several CAs, such as in multi-tenant deployments. Certificates can be encoded in either a
+    /// Base64 ASCII (.pem) or binary (.der) format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if no `ca_paths` are provided, or if any of the provided `ca_paths` does not
+    /// refer to a file containing a valid certificate.
+    pub fn with_certificate_authorities<T: AsRef<Path>>(
+        self,
+        ca_paths: &[T],
+    ) -> Result<ClientBuilder<CustomWantsCertAndKey>> {
+        let root_store = load_root_store_from_files(ca_paths)?;
+        Ok(self.with_root_store(root_store))
+    }
+
+    /// Authenticates the QUIC connection with a custom root cert store, e.g. one assembled from
+    /// certificates held in memory, rather than loaded from the filesystem.
+    pub fn with_root_store(
+        self,
+        root_store: RootCertStore,
+    ) -> ClientBuilder<CustomWantsCertAndKey> {
+        let next_state = CustomWantsCertAndKey::new(self.state, root_store);
+        ClientBuilder { state: next_state }
+    }
+
+    /// Loads the root certificates from the platform's trust store, and creates a root cert store
+    /// to use with authenticating the QUIC connection.
+    ///
+    /// This is useful when connecting to a server with a publicly trusted certificate, such as
+    /// one issued by Let's Encrypt, as no CA file needs to be distributed with the client.
+    /// Additional CAs can be trusted alongside the native roots with
+    /// [with_certificate_authority](ClientBuilder::<CustomWantsCertAndKey>::with_certificate_authority).
+    ///
+    /// **Note:** Trusting the native roots means that the client will accept a certificate for
+    /// the endpoint issued by _any_ of the CAs trusted by the platform, rather than only those
+    /// issued by your own CA, and that the platform's trust store can be altered without any
+    /// change to the client. Prefer
+    /// [with_certificate_authority](ClientBuilder::<CustomWantsRootCert>::with_certificate_authority)
+    /// for servers with a certificate issued by a private CA.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the platform's trust store can't be read, or contains no valid
+    /// certificates.
+    #[cfg(feature = "native-roots")]
+    pub fn with_native_roots(self) -> Result<ClientBuilder<CustomWantsCertAndKey>> {
+        let mut root_store = RootCertStore::empty();
+        add_native_roots(&mut root_store)?;
+        Ok(self.with_root_store(root_store))
+    }
 }
 
 impl ClientBuilder<CustomWantsCertAndKey> {
+    /// Attempts to load a valid CA certificate from the filesystem, and adds it to the root
+    /// cert store used to authenticate the QUIC connection, alongside any CAs already trusted.
+    ///
+    /// Certificates can be encoded in either a Base64 ASCII (.pem) or binary (.der) format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided `ca_path` argument does not refer to a file containing a
+    /// valid certificate.
+    pub fn with_certificate_authority<T: AsRef<Path>>(mut self, ca_path: T) -> Result<Self> {
+        add_certificate_authority(&mut self.state.root_store, ca_path)?;
+        Ok(self)
+    }
+
+    /// Adds the root certificates from the platform's trust store to the root cert store used to
+    /// authenticate the QUIC connection, alongside any CAs already trusted.
+    ///
+    /// See [with_native_roots](ClientBuilder::<CustomWantsRootCert>::with_native_roots) for the
+    /// security implications of trusting the native roots.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the platform's trust store can't be read, or contains no valid
+    /// certificates.
+    #[cfg(feature = "native-roots")]
+    pub fn with_native_roots(mut self) -> Result<Self> {
+        add_native_roots(&mut self.state.root_store)?;
+        Ok(self)
+    }
+
     /// Attempts to load a valid keypair from the filesystem to use with authenticating the QUIC connection.
     ///
     /// Keypairs can be encoded in either a Base64 ASCII (.pem) or binary (.der) format.
@@ -93,33 +242,90 @@ impl ClientBuilder<CustomWantsConnect> {
     ///
     /// - If the provided `addr` argument does not resolve to a valid
     /// [SocketAddr](std::net::SocketAddr).
+    /// - If the `keep_alive_interval` is more than half of the `idle_timeout`.
     /// - If the connection cannot be established.
     pub async fn connect(self) -> Result<Client> {
+        let mut clients = self.connect_clients(1).await?;
+        Ok(clients.remove(0))
+    }
+
+    /// Attempts to establish a [ClientPool] of `size` independent connections with the `Selium`
+    /// server corresponding to the provided `addr` argument. A `size` of 0 is treated as 1.
+    ///
+    /// See [ClientPool] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] under the same conditions as [connect](ClientBuilder::connect), if any of the
+    /// connections cannot be established.
+    pub async fn connect_pool(self, size: usize) -> Result<ClientPool> {
+        let clients = self.connect_clients(size.max(1)).await?;
+        Ok(ClientPool::from_clients(clients))
+    }
+
+    async fn connect_clients(self, size: usize) -> Result<Vec<Client>> {
         let CustomWantsConnect {
             common,
             certs,
             key,
             endpoint,
             root_store,
+            cert_expiry,
         } = self.state;
         if endpoint == SELIUM_CLOUD_REMOTE_URL {
             return Err(SeliumError::ConnectDirectToCloud);
         }
 
+        common.validate()?;
+        common.check_cert_expiry(cert_expiry);
+
         let ClientCommon {
-            keep_alive,
+            keep_alive_interval,
+            idle_timeout,
+            connect_timeout,
             backoff_strategy,
+            retry_initial_connect,
+            alpn,
+            datagram_buffer_size,
+            server_name,
+            ..
         } = common;
 
-        let options = ConnectionOptions::new(certs.as_slice(), key, root_store, keep_alive);
-        logging::connection::connect_to_address(&endpoint);
-        let connection = ClientConnection::connect(&endpoint, options).await?;
-        let connection = Arc::new(Mutex::new(connection));
-        logging::connection::successful_connection(&endpoint);
+        let options = ConnectionOptions::new(
+            certs.as_slice(),
+            key,
+            root_store,
+            keep_alive_interval,
+            idle_timeout,
+            connect_timeout,
+            alpn,
+        )
+        .with_datagram_buffer_size(datagram_buffer_size)
+        .with_server_name(&server_name);
+        let mut clients = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            let options = options.clone();
 
-        Ok(Client {
-            connection,
-            backoff_strategy,
-        })
+            logging::connection::connect_to_address(&endpoint);
+            let connection = if retry_initial_connect {
+                ClientConnection::connect_with_retry(&endpoint, options, backoff_strategy.clone())
+                    .await?
+            } else {
+                ClientConnection::connect(&endpoint, options).await?
+            };
+            let events = connection.events().clone();
+            let connection = Arc::new(Mutex::new(connection));
+            logging::connection::successful_connection(&endpoint);
+
+            clients.push(Client {
+                connection,
+                backoff_strategy: backoff_strategy.clone(),
+                events,
+                cert_expiry,
+            });
+        }
+
+        Ok(clients)
     }
 }
@@ -1,5 +1,7 @@
+use crate::crypto::cert::cert_expiry;
 use crate::ClientCommon;
 use rustls::{Certificate, PrivateKey, RootCertStore};
+use std::time::SystemTime;
 
 #[doc(hidden)]
 #[derive(Debug, Default)]
@@ -49,6 +51,7 @@ pub struct CustomWantsConnect {
     pub(crate) root_store: RootCertStore,
     pub(crate) certs: Vec<Certificate>,
     pub(crate) key: PrivateKey,
+    pub(crate) cert_expiry: Option<SystemTime>,
 }
 
 impl CustomWantsConnect {
@@ -59,6 +62,7 @@ impl CustomWantsConnect {
             root_store: prev.root_store,
             certs: certs.to_owned(),
             key,
+            cert_expiry: cert_expiry(certs),
         }
     }
 }
@@ -1,16 +1,34 @@
 mod builder;
 mod cloud;
 mod custom;
+mod pool;
 
 use crate::connection::SharedConnection;
-use crate::keep_alive::BackoffStrategy;
+use crate::keep_alive::{BackoffStrategy, ConnectionEvent, EventSender};
 use crate::pubsub::states::{PublisherWantsEncoder, SubscriberWantsDecoder};
 use crate::request_reply::states::{ReplierWantsRequestDecoder, RequestorWantsRequestEncoder};
+use crate::streams::{
+    handle_offsets_reply, handle_reply, handle_server_info_reply, handle_truncate_reply,
+    open_bistream, topic_events,
+};
 use crate::StreamBuilder;
+use futures::{SinkExt, Stream};
+use selium_protocol::{Frame, QueryOffsetsPayload, TopicName, TruncateTopicPayload};
+use selium_std::errors::Result;
+use std::time::{Duration, Instant, SystemTime};
 
 pub use builder::*;
 pub use cloud::*;
 pub use custom::*;
+pub use pool::*;
+
+/// The build and runtime details of a `Selium` server, as returned by
+/// [server_info](Client::server_info).
+pub use selium_protocol::ServerInfoPayload as ServerInfo;
+
+/// A topic being created or removed on a `Selium` server, as returned by
+/// [watch_topics](Client::watch_topics).
+pub use selium_protocol::TopicEvent;
 
 /// Constructs a Custom [ClientBuilder] in its initial state to prepare to connect to a self-hosted
 /// `Selium` server.
@@ -51,6 +69,8 @@ pub fn cloud() -> ClientBuilder<CloudWantsCertAndKey> {
 pub struct Client {
     pub(crate) connection: SharedConnection,
     pub(crate) backoff_strategy: BackoffStrategy,
+    pub(crate) events: EventSender,
+    pub(crate) cert_expiry: Option<SystemTime>,
 }
 
 impl Client {
@@ -77,4 +97,151 @@ impl Client {
     pub fn requestor(&self, endpoint: &str) -> StreamBuilder<RequestorWantsRequestEncoder> {
         StreamBuilder::new(self.clone(), RequestorWantsRequestEncoder::new(endpoint))
     }
+
+    /// Retrieves the offsets of the provided Pub/Sub topic, as a `(start, end)` tuple.
+    ///
+    /// `start` is the offset of the earliest message still retained in the topic, and `end` is
+    /// the offset that will be assigned to the next message published to the topic, so the topic
+    /// currently holds `end - start` messages. This is useful for tracking progress when
+    /// subscribing to a topic from the beginning, e.g. to backfill historical messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the topic name is invalid, if the topic doesn't exist or isn't a Pub/Sub
+    /// topic, or if the query fails to be sent to the `Selium` server.
+    pub async fn topic_offsets(&self, topic: &str) -> Result<(u64, u64)> {
+        let topic = TopicName::try_from(topic)?;
+
+        let connection = self.connection.lock().await;
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
+
+        let frame = Frame::QueryOffsets(QueryOffsetsPayload { topic });
+        stream.send(frame).await?;
+
+        let offsets = handle_offsets_reply(&mut stream, connect_timeout).await?;
+        Ok((offsets.start, offsets.end))
+    }
+
+    /// Removes the messages preceding `offset` from the provided Pub/Sub topic, returning the
+    /// topic's new offsets as a `(start, end)` tuple, as per [topic_offsets](Client::topic_offsets).
+    ///
+    /// The topic's log is truncated in whole segments, so messages sharing a segment with
+    /// `offset` are retained, and `start` may precede `offset`. Subscribers that are positioned
+    /// before the new `start` offset skip ahead to the oldest retained message.
+    ///
+    /// As truncation permanently removes messages, servers refuse it unless they've been started
+    /// with `--allow-topic-truncation`. Truncation requests are then authorized in the same way as
+    /// any other stream on the topic.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the topic name is invalid, if the server doesn't allow topic truncation,
+    /// if the topic doesn't exist or isn't a Pub/Sub topic, if the topic's log fails to be
+    /// truncated, or if the request fails to be sent to the `Selium` server.
+    pub async fn truncate_topic(&self, topic: &str, offset: u64) -> Result<(u64, u64)> {
+        let topic = TopicName::try_from(topic)?;
+
+        let connection = self.connection.lock().await;
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
+
+        let frame = Frame::TruncateTopic(TruncateTopicPayload { topic, offset });
+        stream.send(frame).await?;
+
+        let offsets = handle_truncate_reply(&mut stream, connect_timeout).await?;
+        Ok((offsets.start, offsets.end))
+    }
+
+    /// Sends a health check to the `Selium` server, returning the round-trip time once the server
+    /// has replied.
+    ///
+    /// The server answers health checks without opening a topic, so this is a cheap way to confirm
+    /// that the server is accepting streams, e.g. from a load balancer's liveness probe.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the health check fails to be sent, or the server doesn't reply within the
+    /// connection's timeout.
+    pub async fn ping(&self) -> Result<Duration> {
+        let connection = self.connection.lock().await;
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
+
+        let start = Instant::now();
+        stream.send(Frame::Health).await?;
+        handle_reply(&mut stream, connect_timeout).await?;
+
+        Ok(start.elapsed())
+    }
+
+    /// Retrieves the version, uptime and configured limits of the `Selium` server, e.g. for
+    /// keeping an inventory of a fleet of servers.
+    ///
+    /// Server info queries are authorized in the same way as any other stream, so servers that
+    /// only admit streams for specific topics may refuse the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the query is refused by the server, fails to be sent, or the server
+    /// doesn't reply within the connection's timeout.
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let connection = self.connection.lock().await;
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
+
+        stream.send(Frame::QueryServerInfo).await?;
+        handle_server_info_reply(&mut stream, connect_timeout).await
+    }
+
+    /// Watches the `Selium` server's topics, returning a stream of [TopicEvent]s as topics are
+    /// created by the first stream to use them, and removed once they're closed, e.g. after
+    /// being idle.
+    ///
+    /// Only events that occur after the call are sent, so external systems can combine this with
+    /// their own record of the server's topics to keep an up-to-date inventory. Topic watches are
+    /// authorized in the same way as any other stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the watch is refused by the server, fails to be sent, or the server
+    /// doesn't reply within the connection's timeout. The returned stream yields an [Err] if the
+    /// watcher falls too far behind the server's events, before ending.
+    pub async fn watch_topics(&self) -> Result<impl Stream<Item = Result<TopicEvent>> + Unpin> {
+        let connection = self.connection.lock().await;
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
+
+        stream.send(Frame::WatchTopics).await?;
+        handle_reply(&mut stream, connect_timeout).await?;
+
+        Ok(topic_events(stream))
+    }
+
+    /// Returns a stream of [ConnectionEvent]s, describing the lifecycle of the client's
+    /// connection to the `Selium` server, and of the streams opened on it.
+    ///
+    /// Each call returns an independent stream that receives every event emitted after the call,
+    /// allowing multiple consumers, such as dashboards and health checks, to observe the same
+    /// connection. Consumers that fall behind will skip the events they missed.
+    ///
+    /// ```no_run
+    /// # use futures::StreamExt;
+    /// # async fn run(client: selium::Client) {
+    /// let mut events = client.events();
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     println!("Connection event: {event:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn events(&self) -> impl Stream<Item = ConnectionEvent> + Unpin {
+        self.events.subscribe()
+    }
+
+    /// Returns the time at which the client's certificate expires, or [None] if the certificate
+    /// couldn't be parsed.
+    ///
+    /// The server refuses connections from clients with an expired certificate, so the
+    /// certificate must be rotated before then. See
+    /// [cert_expiry_warning](crate::ClientCommon::cert_expiry_warning) to be warned when
+    /// connecting with a certificate that's due to expire.
+    pub fn cert_expiry(&self) -> Option<SystemTime> {
+        self.cert_expiry
+    }
 }
@@ -0,0 +1,78 @@
+use super::{Client, CustomWantsConnect};
+use crate::pubsub::states::{PublisherWantsEncoder, SubscriberWantsDecoder};
+use crate::request_reply::states::{ReplierWantsRequestDecoder, RequestorWantsRequestEncoder};
+use crate::{ClientBuilder, StreamBuilder};
+use selium_std::errors::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A pool of [Client]s, each holding an independent connection to the same `Selium` server.
+///
+/// A single [Client] multiplexes every stream over one connection, so streams share a single
+/// congestion-controlled path, and opening streams is serialized on the connection. For highly
+/// concurrent workloads, such as many [Requestor](crate::request_reply::Requestor) streams, a
+/// `ClientPool` spreads streams across several connections by handing out clients in round-robin
+/// order.
+///
+/// Each pooled connection is kept alive independently, so a stream opened from the pool will only
+/// ever reconnect the connection it was opened on.
+pub struct ClientPool {
+    clients: Vec<Client>,
+    next: AtomicUsize,
+}
+
+impl ClientPool {
+    /// Attempts to establish a `ClientPool` of `size` independent connections with the `Selium`
+    /// server at `endpoint`, as configured by a [ClientBuilder]. A `size` of 0 is treated as 1.
+    ///
+    /// This is equivalent to calling [connect_pool](ClientBuilder::connect_pool) on the builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] under the same conditions as [connect](ClientBuilder::connect), if any of the
+    /// connections cannot be established.
+    pub async fn new(endpoint: ClientBuilder<CustomWantsConnect>, size: usize) -> Result<Self> {
+        endpoint.connect_pool(size).await
+    }
+
+    /// Constructs a `ClientPool` from already connected clients. `clients` must not be empty,
+    /// which `connect_pool` guarantees.
+    pub(crate) fn from_clients(clients: Vec<Client>) -> Self {
+        debug_assert!(!clients.is_empty());
+
+        Self {
+            clients,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the next [Client] in the pool, in round-robin order.
+    pub fn client(&self) -> &Client {
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        &self.clients[next % self.clients.len()]
+    }
+
+    /// Returns the number of connections in the pool.
+    pub fn size(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns a new `Subscriber` [StreamBuilder] for the next [Client] in the pool.
+    pub fn subscriber(&self, topic: &str) -> StreamBuilder<SubscriberWantsDecoder> {
+        self.client().subscriber(topic)
+    }
+
+    /// Returns a new `Publisher` [StreamBuilder] for the next [Client] in the pool.
+    pub fn publisher(&self, topic: &str) -> StreamBuilder<PublisherWantsEncoder> {
+        self.client().publisher(topic)
+    }
+
+    /// Returns a new `Replier` [StreamBuilder] for the next [Client] in the pool.
+    pub fn replier(&self, endpoint: &str) -> StreamBuilder<ReplierWantsRequestDecoder> {
+        self.client().replier(endpoint)
+    }
+
+    /// Returns a new `Requestor` [StreamBuilder] for the next [Client] in the pool.
+    pub fn requestor(&self, endpoint: &str) -> StreamBuilder<RequestorWantsRequestEncoder> {
+        self.client().requestor(endpoint)
+    }
+}
@@ -1,13 +1,22 @@
+use crate::constants::{DATAGRAM_BUFFER_SIZE_DEFAULT, SERVER_NAME_DEFAULT};
+use crate::keep_alive::helpers::{
+    connection_closed, is_recoverable_error, is_shutdown_connection_error,
+};
+use crate::keep_alive::{BackoffStrategy, ConnectionEvent, EventSender, NextAttempt};
+use crate::logging;
 use crate::utils::net::get_socket_addrs;
-use quinn::{ClientConfig, Connection, Endpoint, TransportConfig};
+use bytes::Bytes;
+use quinn::{
+    ClientConfig, Connection, ConnectionError, Endpoint, IdleTimeout, TransportConfig, VarInt,
+};
 use rustls::{Certificate, PrivateKey, RootCertStore};
-use selium_std::errors::{ParseEndpointAddressError, QuicError, Result};
-use std::sync::Arc;
+use selium_std::errors::{ParseEndpointAddressError, QuicError, Result, SeliumError};
+use std::sync::{Arc, Weak};
 use std::{net::SocketAddr, time::Duration};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex, Notify};
 
-const ALPN_QUIC_HTTP: &[&[u8]] = &[b"hq-29"];
 const ENDPOINT_ADDRESS: &str = "[::]:0";
+const DATAGRAM_CHANNEL_SIZE: usize = 1024;
 
 pub type SharedConnection = Arc<Mutex<ClientConnection>>;
 
@@ -16,7 +25,12 @@ pub struct ConnectionOptions {
     certs: Vec<Certificate>,
     key: PrivateKey,
     root_store: RootCertStore,
-    keep_alive: u64,
+    keep_alive_interval: u64,
+    idle_timeout: u64,
+    connect_timeout: u64,
+    alpn: String,
+    datagram_buffer_size: usize,
+    server_name: String,
 }
 
 impl ConnectionOptions {
@@ -24,51 +38,214 @@ impl ConnectionOptions {
         certs: &[Certificate],
         key: PrivateKey,
         root_store: RootCertStore,
-        keep_alive: u64,
+        keep_alive_interval: u64,
+        idle_timeout: u64,
+        connect_timeout: u64,
+        alpn: String,
     ) -> Self {
         Self {
             certs: certs.to_vec(),
             key,
             root_store,
-            keep_alive,
+            keep_alive_interval,
+            idle_timeout,
+            connect_timeout,
+            alpn,
+            datagram_buffer_size: DATAGRAM_BUFFER_SIZE_DEFAULT,
+            server_name: SERVER_NAME_DEFAULT.to_owned(),
         }
     }
+
+    /// Overrides the size of the buffer for incoming datagrams, where 0 disables datagrams.
+    pub fn with_datagram_buffer_size(mut self, size: usize) -> Self {
+        self.datagram_buffer_size = size;
+        self
+    }
+
+    /// Overrides the TLS server name (SNI) requested from the server.
+    pub fn with_server_name(mut self, server_name: &str) -> Self {
+        self.server_name = server_name.to_owned();
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ClientConnection {
     addr: SocketAddr,
+    server_name: String,
     connection: Connection,
     client_config: ClientConfig,
+    connect_timeout: Duration,
+    events: EventSender,
+    datagrams: Weak<DatagramReader>,
 }
 
 impl ClientConnection {
     pub async fn connect(addr: &str, options: ConnectionOptions) -> Result<Self> {
+        let connect_timeout = Duration::from_millis(options.connect_timeout);
+        let server_name = options.server_name.clone();
         let client_config = configure_client(options);
         let addr = get_socket_addrs(addr)?;
-        let connection = connect_to_endpoint(addr, client_config.clone()).await?;
+        let connection =
+            connect_to_endpoint(addr, &server_name, client_config.clone(), connect_timeout).await?;
 
         Ok(Self {
             addr,
+            server_name,
             connection,
             client_config,
+            connect_timeout,
+            events: EventSender::default(),
+            datagrams: Weak::new(),
         })
     }
 
+    /// Attempts to connect to the server, retrying recoverable failures according to the
+    /// provided backoff `strategy`.
+    pub async fn connect_with_retry(
+        addr: &str,
+        options: ConnectionOptions,
+        strategy: BackoffStrategy,
+    ) -> Result<Self> {
+        let mut attempts = strategy.into_iter();
+
+        loop {
+            match Self::connect(addr, options.clone()).await {
+                Ok(connection) => return Ok(connection),
+                Err(err) if is_recoverable_error(&err) => match attempts.next() {
+                    Some(NextAttempt {
+                        duration,
+                        attempt_num,
+                        max_attempts,
+                    }) => {
+                        logging::connection::retry_connect(&err, attempt_num, max_attempts);
+                        tokio::time::sleep(duration).await;
+                    }
+                    None => {
+                        logging::keep_alive::too_many_retries();
+                        return Err(err);
+                    }
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
     pub fn conn(&self) -> &Connection {
         &self.connection
     }
 
+    /// The maximum duration to wait for the connection to be established, or for the server to
+    /// reply when opening a stream.
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    pub(crate) fn events(&self) -> &EventSender {
+        &self.events
+    }
+
+    /// Subscribes to the datagrams received on the connection.
+    ///
+    /// A single task reads datagrams from the connection on behalf of every receiver, and stops
+    /// once all of its receivers have been dropped, or the connection has been replaced.
+    pub(crate) fn datagrams(&mut self) -> DatagramReceiver {
+        let connection_id = self.connection.stable_id();
+
+        let reader = match self.datagrams.upgrade() {
+            Some(reader) if reader.connection_id == connection_id => reader,
+            _ => {
+                let reader = Arc::new(DatagramReader::spawn(self.connection.clone()));
+                self.datagrams = Arc::downgrade(&reader);
+                reader
+            }
+        };
+
+        DatagramReceiver {
+            rx: reader.tx.subscribe(),
+            _reader: reader,
+        }
+    }
+
     pub async fn reconnect(&mut self) -> Result<()> {
-        if self.connection.close_reason().is_some() {
-            let connection = connect_to_endpoint(self.addr, self.client_config.clone()).await?;
+        if let Some(reason) = self.connection.close_reason() {
+            // Don't attempt to reconnect to a server that has intentionally shut down
+            match &reason {
+                ConnectionError::ApplicationClosed(close)
+                    if is_shutdown_connection_error(&reason) =>
+                {
+                    return Err(connection_closed(close))
+                }
+                _ => (),
+            }
+
+            let connection = connect_to_endpoint(
+                self.addr,
+                &self.server_name,
+                self.client_config.clone(),
+                self.connect_timeout,
+            )
+            .await?;
             self.connection = connection;
+            self.events.send(ConnectionEvent::Connected);
         }
 
         Ok(())
     }
 }
 
+/// Reads datagrams from a connection and broadcasts them to every [DatagramReceiver], until the
+/// reader is dropped.
+#[derive(Debug)]
+pub(crate) struct DatagramReader {
+    connection_id: usize,
+    tx: broadcast::Sender<Bytes>,
+    stop: Arc<Notify>,
+}
+
+impl DatagramReader {
+    fn spawn(connection: Connection) -> Self {
+        let (tx, _) = broadcast::channel(DATAGRAM_CHANNEL_SIZE);
+        let stop = Arc::new(Notify::new());
+        let connection_id = connection.stable_id();
+
+        let sender = tx.clone();
+        let stopped = stop.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = connection.read_datagram() => match result {
+                        // Datagrams received while nobody is listening are simply dropped
+                        Ok(bytes) => { let _ = sender.send(bytes); }
+                        Err(_) => break,
+                    },
+                    _ = stopped.notified() => break,
+                }
+            }
+        });
+
+        Self {
+            connection_id,
+            tx,
+            stop,
+        }
+    }
+}
+
+impl Drop for DatagramReader {
+    fn drop(&mut self) {
+        // Releases the reader's handle to the connection, so the connection can close
+        self.stop.notify_one();
+    }
+}
+
+/// Receives the datagrams read by a shared [DatagramReader], keeping the reader alive.
+pub(crate) struct DatagramReceiver {
+    pub(crate) rx: broadcast::Receiver<Bytes>,
+    _reader: Arc<DatagramReader>,
+}
+
 fn configure_client(options: ConnectionOptions) -> ClientConfig {
     let mut crypto = rustls::ClientConfig::builder()
         .with_safe_defaults()
@@ -76,29 +253,42 @@ fn configure_client(options: ConnectionOptions) -> ClientConfig {
         .with_client_auth_cert(options.certs, options.key)
         .unwrap();
 
-    crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+    crypto.alpn_protocols = vec![options.alpn.into_bytes()];
 
     let mut config = ClientConfig::new(Arc::new(crypto));
     let mut transport_config = TransportConfig::default();
-    let keep_alive = Duration::from_millis(options.keep_alive);
+    let keep_alive_interval = Duration::from_millis(options.keep_alive_interval);
+    let idle_timeout = VarInt::from_u64(options.idle_timeout).unwrap_or(VarInt::MAX);
 
-    transport_config.keep_alive_interval(Some(keep_alive));
+    transport_config.keep_alive_interval(Some(keep_alive_interval));
+    transport_config.max_idle_timeout(Some(IdleTimeout::from(idle_timeout)));
+    transport_config.datagram_receive_buffer_size(
+        (options.datagram_buffer_size > 0).then_some(options.datagram_buffer_size),
+    );
     config.transport_config(Arc::new(transport_config));
 
     config
 }
 
-async fn connect_to_endpoint(addr: SocketAddr, config: ClientConfig) -> Result<Connection> {
+async fn connect_to_endpoint(
+    addr: SocketAddr,
+    server_name: &str,
+    config: ClientConfig,
+    connect_timeout: Duration,
+) -> Result<Connection> {
     let endpoint_addr = ENDPOINT_ADDRESS
         .parse::<SocketAddr>()
         .map_err(ParseEndpointAddressError::InvalidAddress)?;
 
     let mut endpoint = Endpoint::client(endpoint_addr)?;
     endpoint.set_default_client_config(config);
-    let connection = endpoint
-        .connect(addr, "localhost")
-        .map_err(QuicError::ConnectError)?
+    let connecting = endpoint
+        .connect(addr, server_name)
+        .map_err(QuicError::ConnectError)?;
+
+    let connection = tokio::time::timeout(connect_timeout, connecting)
         .await
+        .map_err(|_| SeliumError::ConnectTimeout)?
         .map_err(QuicError::ConnectionError)?;
 
     Ok(connection)
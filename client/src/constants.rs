@@ -1,7 +1,30 @@
 //! Commonly used constants.
 
+/// The default `keep_alive_interval` for a client connection.
+pub const KEEP_ALIVE_INTERVAL_DEFAULT: u64 = 5_000;
 /// The default `keep_alive` interval for a client connection.
-pub const KEEP_ALIVE_DEFAULT: u64 = 5_000;
+#[deprecated(note = "renamed to `KEEP_ALIVE_INTERVAL_DEFAULT`")]
+pub const KEEP_ALIVE_DEFAULT: u64 = KEEP_ALIVE_INTERVAL_DEFAULT;
+/// The default `idle_timeout` for a client connection.
+pub const IDLE_TIMEOUT_DEFAULT: u64 = 15_000;
+/// The default `connect_timeout` for establishing a connection, or opening a stream, with the
+/// `Selium` server.
+pub const CONNECT_TIMEOUT_DEFAULT: u64 = 10_000;
+/// The default ALPN protocol identifier negotiated with the `Selium` server.
+pub const ALPN_DEFAULT: &str = selium_protocol::DEFAULT_ALPN;
+/// The default TLS server name requested from the `Selium` server, which must match a name in the
+/// server's certificate.
+pub const SERVER_NAME_DEFAULT: &str = "localhost";
+/// The default size in bytes of the buffer for datagrams received on a client connection.
+pub const DATAGRAM_BUFFER_SIZE_DEFAULT: usize = 1_250_000;
+/// The default limit on the number of bytes that a compressed message can decompress to - 64MiB,
+/// or 64 times the maximum frame size.
+pub const MAX_DECOMPRESSED_BYTES_DEFAULT: usize = 64 * 1024 * 1024;
+/// The default number of messages buffered for each receiver of a
+/// [SubscriberBroadcast](crate::pubsub::SubscriberBroadcast).
+pub const BROADCAST_CAPACITY_DEFAULT: usize = 1024;
+/// The default `cert_expiry_warning` window for a client certificate - 14 days.
+pub const CERT_EXPIRY_WARNING_DEFAULT: u64 = 1000 * 60 * 60 * 24 * 14;
 /// The default `retention_policy` setting for messages.
 pub const RETENTION_POLICY_DEFAULT: u64 = 1000 * 60 * 60 * 24;
 
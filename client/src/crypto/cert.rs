@@ -1,7 +1,12 @@
 use rustls::{Certificate, PrivateKey, RootCertStore};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use selium_std::errors::{CryptoError, Result};
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+use x509_parser::parse_x509_certificate;
 
 pub type KeyPair = (Vec<Certificate>, PrivateKey);
 
@@ -68,6 +73,95 @@ pub(crate) fn load_root_store(certs: &[Certificate]) -> Result<RootCertStore> {
     Ok(store)
 }
 
+/// Creates and returns a RootCertStore containing the certificates parsed from each of the
+/// provided Certificate Authority files.
+///
+/// This function will fail if any of the files can't be read, or if no certificates can be
+/// successfully parsed from any one of them, so that a misconfigured CA is never silently
+/// ignored.
+///
+/// # Arguments
+///
+/// * `ca_files` - The filepaths to the CA files.
+///
+pub(crate) fn load_root_store_from_files<T: AsRef<Path>>(ca_files: &[T]) -> Result<RootCertStore> {
+    let mut store = RootCertStore::empty();
+
+    for ca_file in ca_files {
+        add_certificate_authority(&mut store, ca_file)?;
+    }
+
+    if store.is_empty() {
+        return Err(CryptoError::InvalidRootCert.into());
+    }
+
+    Ok(store)
+}
+
+/// Adds the certificates parsed from the provided Certificate Authority file to an existing
+/// RootCertStore.
+///
+/// This function will fail if the file can't be read, or if no certificates can be successfully
+/// parsed from it.
+///
+/// # Arguments
+///
+/// * `store` - The RootCertStore to add the certificates to.
+/// * `ca_file` - The filepath to the CA file.
+///
+pub(crate) fn add_certificate_authority<T: AsRef<Path>>(
+    store: &mut RootCertStore,
+    ca_file: T,
+) -> Result<()> {
+    let ca_file = ca_file.as_ref();
+    let certs = load_certs(ca_file)?;
+    let (added, _) = store.add_parsable_certificates(&certs);
+
+    if added == 0 {
+        return Err(CryptoError::InvalidRootCertFile(ca_file.to_owned()).into());
+    }
+
+    Ok(())
+}
+
+/// Adds the root certificates from the platform's trust store to an existing RootCertStore.
+///
+/// Certificates in the trust store that can't be parsed are skipped, but this function will fail
+/// if the trust store can't be read, or if it doesn't contain any valid certificates.
+///
+/// # Arguments
+///
+/// * `store` - The RootCertStore to add the certificates to.
+///
+#[cfg(feature = "native-roots")]
+pub(crate) fn add_native_roots(store: &mut RootCertStore) -> Result<()> {
+    let certs = rustls_native_certs::load_native_certs()
+        .map_err(CryptoError::LoadNativeRootsError)?
+        .into_iter()
+        .map(|cert| cert.0)
+        .collect::<Vec<_>>();
+    let (added, _) = store.add_parsable_certificates(&certs);
+
+    if added == 0 {
+        return Err(CryptoError::NoNativeRootsFound.into());
+    }
+
+    Ok(())
+}
+
+/// Returns the time at which the leaf certificate of the provided chain expires, or [None] if the
+/// chain is empty, or the certificate can't be parsed.
+///
+/// # Arguments
+///
+/// * `certs` - The certificate chain, beginning with the leaf certificate.
+///
+pub(crate) fn cert_expiry(certs: &[Certificate]) -> Option<SystemTime> {
+    let (_, cert) = parse_x509_certificate(&certs.first()?.0).ok()?;
+    let not_after = u64::try_from(cert.validity().not_after.timestamp()).ok()?;
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(not_after))
+}
+
 /// Extracts a public/private key pair from the provided filepaths.
 ///
 /// This function will fail if no valid certificates or private key can be
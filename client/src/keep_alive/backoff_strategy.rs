@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Default max retry attempts.
 pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
@@ -29,6 +29,7 @@ impl Default for Strategy {
 #[derive(Debug, Clone)]
 struct BackoffStrategyState {
     max_duration: Option<Duration>,
+    max_elapsed: Option<Duration>,
     max_attempts: u32,
     step: Duration,
 }
@@ -37,6 +38,7 @@ impl Default for BackoffStrategyState {
     fn default() -> Self {
         Self {
             max_duration: None,
+            max_elapsed: None,
             max_attempts: DEFAULT_MAX_ATTEMPTS,
             step: DEFAULT_STEP,
         }
@@ -57,7 +59,7 @@ impl Default for BackoffStrategyState {
 /// ```
 ///
 /// The preset strategy can then be tweaked further by overriding the default settings provided for
-/// the max attempts, max retry duration, and duration step.
+/// the max attempts, max retry duration, max elapsed time, and duration step.
 ///
 /// ```
 /// use selium::keep_alive::BackoffStrategy;
@@ -66,6 +68,7 @@ impl Default for BackoffStrategyState {
 /// let tweaked_linear = BackoffStrategy::linear()
 ///     .with_max_attempts(5)
 ///     .with_max_duration(Duration::from_secs(3))
+///     .with_max_elapsed(Duration::from_secs(120))
 ///     .with_step(Duration::from_secs(1));
 /// ```
 #[derive(Default, Debug, Clone)]
@@ -178,6 +181,10 @@ impl BackoffStrategy {
         self
     }
 
+    pub(crate) fn max_attempts(&self) -> u32 {
+        self.state.max_attempts
+    }
+
     /// Specifies a max duration to clamp produced [Duration]s to a maximum value. Can be useful
     /// when creating an `exponential` strategy with a high amount of retries to keep the [Duration]
     /// values within reasonable boundaries.
@@ -213,6 +220,32 @@ impl BackoffStrategy {
         self
     }
 
+    /// Specifies a total time budget for all retry attempts. Once the cumulative elapsed time
+    /// (including the time spent sleeping between attempts) would exceed this budget, the iterator
+    /// stops producing [Duration] values, regardless of the remaining number of attempts.
+    ///
+    /// The budget starts counting when the first attempt is produced. The elapsed time is taken as
+    /// the greater of the wall-clock time since the first attempt, and the sum of all previously
+    /// produced [Duration] values.
+    ///
+    /// # Examples
+    ///
+    /// The following strategy will keep retrying every 5 seconds for up to 2 minutes, then give up.
+    ///
+    /// ```
+    /// # use selium::keep_alive::BackoffStrategy;
+    /// # use std::time::Duration;
+    /// #
+    /// BackoffStrategy::constant()
+    ///     .with_max_attempts(u32::MAX)
+    ///     .with_max_elapsed(Duration::from_secs(120))
+    ///     .with_step(Duration::from_secs(5));
+    /// ```
+    pub fn with_max_elapsed(mut self, max: Duration) -> Self {
+        self.state.max_elapsed = Some(max);
+        self
+    }
+
     /// Overrides the [default step](DEFAULT_STEP) to use for each strategy. Depending on the
     /// strategy, this will have different results.
     ///
@@ -262,6 +295,8 @@ impl IntoIterator for BackoffStrategy {
             strategy_type: self.strategy_type,
             current_attempt: 1,
             state: self.state,
+            started_at: None,
+            scheduled: Duration::ZERO,
         }
     }
 }
@@ -271,6 +306,8 @@ pub struct BackoffStrategyIter {
     strategy_type: Strategy,
     state: BackoffStrategyState,
     current_attempt: u32,
+    started_at: Option<Instant>,
+    scheduled: Duration,
 }
 
 impl Iterator for BackoffStrategyIter {
@@ -292,12 +329,22 @@ impl Iterator for BackoffStrategyIter {
             Strategy::Exponential(factor) => step.mul_f64(factor.pow(current_attempt - 1) as f64),
         };
 
-        self.current_attempt += 1;
-
         if let Some(max) = max_duration {
             next_duration = next_duration.min(max);
         }
 
+        if let Some(max_elapsed) = self.state.max_elapsed {
+            let started_at = *self.started_at.get_or_insert_with(Instant::now);
+            let elapsed = started_at.elapsed().max(self.scheduled);
+
+            if elapsed + next_duration > max_elapsed {
+                return None;
+            }
+        }
+
+        self.current_attempt += 1;
+        self.scheduled += next_duration;
+
         let next = NextAttempt {
             duration: next_duration,
             attempt_num: current_attempt,
@@ -427,4 +474,40 @@ mod tests {
         // We should have fully consumed the iterator in the previous step
         assert_eq!(strategy.next(), None);
     }
+
+    #[test]
+    fn iterator_is_exhausted_after_max_elapsed() {
+        let step = Duration::from_millis(10);
+
+        let mut strategy = BackoffStrategy::constant()
+            .with_max_attempts(u32::MAX)
+            .with_max_elapsed(Duration::from_millis(35))
+            .with_step(step)
+            .into_iter();
+
+        // Only three 10ms attempts fit within the 35ms budget
+        for attempt_num in 1..=3 {
+            let next = strategy.next().unwrap();
+            assert_eq!(next.duration, step);
+            assert_eq!(next.attempt_num, attempt_num);
+        }
+
+        assert_eq!(strategy.next(), None);
+    }
+
+    #[test]
+    fn max_elapsed_accounts_for_wall_clock_time() {
+        let mut strategy = BackoffStrategy::constant()
+            .with_max_attempts(u32::MAX)
+            .with_max_elapsed(Duration::from_millis(50))
+            .with_step(Duration::from_millis(1))
+            .into_iter();
+
+        assert!(strategy.next().is_some());
+
+        // Simulate a slow reconnection attempt that blows the budget
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert_eq!(strategy.next(), None);
+    }
 }
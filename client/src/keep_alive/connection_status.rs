@@ -1,11 +1,16 @@
-use super::{BackoffStrategy, NextAttempt};
+use super::{BackoffStrategy, KeepAliveState, NextAttempt};
 use futures::Future;
 use selium_protocol::BiStream;
 use selium_std::errors::Result;
+use std::any::Any;
 use std::pin::Pin;
+use tokio::time::Sleep;
 
 pub type AttemptsIterator = Box<dyn Iterator<Item = NextAttempt> + Send>;
-pub type AttemptFut = Pin<Box<dyn Future<Output = Result<BiStream>> + Send>>;
+pub type AttemptFut<S = BiStream> = Pin<Box<dyn Future<Output = Result<S>> + Send>>;
+/// The stream opened by a successful reconnection attempt, which is handed back to the stream
+/// that lost its connection, so that its type needn't be known to the `KeepAlive`.
+pub type ReconnectedStream = Box<dyn Any + Send>;
 
 pub enum ConnectionStatus {
     Connected,
@@ -24,20 +29,54 @@ impl ConnectionStatus {
         let reconnect_state = ReconnectState::from(backoff_strategy);
         ConnectionStatus::Disconnected(reconnect_state)
     }
+
+    pub fn state(&self) -> KeepAliveState {
+        match self {
+            Self::Connected => KeepAliveState::Connected,
+            Self::Disconnected(state) => KeepAliveState::Reconnecting {
+                attempt: state.attempt_num,
+                max: state.max_attempts,
+            },
+            Self::Exhausted => KeepAliveState::Failed,
+        }
+    }
 }
 
 pub struct ReconnectState {
     pub attempts: AttemptsIterator,
-    pub current_attempt: AttemptFut,
+    pub current_attempt: AttemptFut<ReconnectedStream>,
+    pub deadline: Option<Pin<Box<Sleep>>>,
+    pub attempt_num: u32,
+    pub max_attempts: u32,
 }
 
 impl From<BackoffStrategy> for ReconnectState {
     fn from(strategy: BackoffStrategy) -> Self {
+        let max_attempts = strategy.max_attempts();
         let attempts = Box::new(strategy.into_iter());
         let current_attempt = Box::pin(async { unreachable!() });
         Self {
             attempts,
             current_attempt,
+            deadline: None,
+            attempt_num: 0,
+            max_attempts,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_max_attempts_before_first_attempt() {
+        let strategy = BackoffStrategy::constant().with_max_attempts(3);
+        let status = ConnectionStatus::disconnected(strategy);
+
+        assert_eq!(
+            status.state(),
+            KeepAliveState::Reconnecting { attempt: 0, max: 3 }
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use futures::{stream, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+const EVENTS_CHANNEL_SIZE: usize = 64;
+
+/// A lifecycle event emitted by a [Client](crate::Client)'s connection to the `Selium` server.
+///
+/// Events are emitted by every stream opened on the client, so if multiple streams lose their
+/// connection at the same time, each stream will emit its own `Disconnected`, `Reconnecting`
+/// and `Reconnected` events.
+///
+/// See [Client::events](crate::Client::events) for more information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionEvent {
+    /// A connection to the `Selium` server has been established.
+    Connected,
+    /// A stream has lost its connection to the `Selium` server.
+    Disconnected,
+    /// A stream is attempting to reconnect to the `Selium` server.
+    Reconnecting { attempt: u32 },
+    /// A stream has successfully reconnected to the `Selium` server.
+    Reconnected,
+    /// A stream has given up reconnecting to the `Selium` server, either because it has exhausted
+    /// its [BackoffStrategy](crate::keep_alive::BackoffStrategy), or because it encountered an
+    /// unrecoverable error.
+    Failed,
+}
+
+/// Broadcasts [ConnectionEvent]s to every consumer of a client's event stream.
+#[derive(Debug, Clone)]
+pub(crate) struct EventSender(broadcast::Sender<ConnectionEvent>);
+
+impl Default for EventSender {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(EVENTS_CHANNEL_SIZE);
+        Self(tx)
+    }
+}
+
+impl EventSender {
+    pub fn send(&self, event: ConnectionEvent) {
+        // Events are discarded if there are no consumers
+        let _ = self.0.send(event);
+    }
+
+    pub fn subscribe(&self) -> impl Stream<Item = ConnectionEvent> + Unpin {
+        let events = stream::unfold(self.0.subscribe(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((event, rx)),
+                    // Slow consumers skip the events they missed, rather than ending the stream
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Box::pin(events)
+    }
+}
@@ -1,5 +1,6 @@
-use selium_protocol::error_codes::REPLIER_ALREADY_BOUND;
-use selium_std::errors::{QuicError, Result, SeliumError};
+use quinn::{ApplicationClose, ConnectionError, ReadError, VarInt, WriteError};
+use selium_protocol::error_codes::SHUTDOWN;
+use selium_std::errors::{ErrorCode, QuicError, Result, SeliumError};
 use std::{io, task::Poll};
 
 pub fn is_disconnect_error(err: &io::Error) -> bool {
@@ -9,15 +10,78 @@ pub fn is_disconnect_error(err: &io::Error) -> bool {
     )
 }
 
-pub fn is_bind_error(code: u32) -> bool {
-    code == REPLIER_ALREADY_BOUND
+pub fn is_bind_error(err: &SeliumError) -> bool {
+    matches!(err, SeliumError::ReplierAlreadyBound)
+}
+
+pub fn is_shutdown_connection_error(err: &ConnectionError) -> bool {
+    matches!(err, ConnectionError::ApplicationClosed(close) if close.error_code == VarInt::from_u32(SHUTDOWN))
+}
+
+/// Converts the code and reason the server closed a connection with into a
+/// [SeliumError::ConnectionClosed] error.
+pub fn connection_closed(close: &ApplicationClose) -> SeliumError {
+    // Selium only closes connections with codes that fit within a u32
+    let code = u32::try_from(close.error_code.into_inner()).unwrap_or(u32::MAX);
+
+    SeliumError::ConnectionClosed {
+        code: ErrorCode::from(code),
+        reason: String::from_utf8_lossy(&close.reason).into_owned(),
+    }
+}
+
+// Quinn converts stream errors into io::Errors, so the original error must be recovered to
+// determine why the connection was lost
+fn io_connection_error(err: &io::Error) -> Option<&ConnectionError> {
+    let inner = err.get_ref()?;
+
+    if let Some(ReadError::ConnectionLost(err)) = inner.downcast_ref::<ReadError>() {
+        return Some(err);
+    }
+
+    if let Some(WriteError::ConnectionLost(err)) = inner.downcast_ref::<WriteError>() {
+        return Some(err);
+    }
+
+    None
+}
+
+fn connection_error(err: &SeliumError) -> Option<&ConnectionError> {
+    match err {
+        SeliumError::IoError(err) => io_connection_error(err),
+        SeliumError::Quic(QuicError::ConnectionError(err))
+        | SeliumError::Quic(QuicError::WriteError(WriteError::ConnectionLost(err))) => Some(err),
+        _ => None,
+    }
+}
+
+pub fn is_shutdown_error(err: &SeliumError) -> bool {
+    match err {
+        SeliumError::ConnectionClosed { code, .. } => *code == ErrorCode::Shutdown,
+        err => connection_error(err).is_some_and(is_shutdown_connection_error),
+    }
+}
+
+/// Surfaces the code and reason the server closed the connection with, if `err` was caused by the
+/// server closing the connection.
+pub fn map_connection_closed(err: SeliumError) -> SeliumError {
+    match connection_error(&err) {
+        Some(ConnectionError::ApplicationClosed(close)) => connection_closed(close),
+        _ => err,
+    }
 }
 
 pub fn is_recoverable_error(err: &SeliumError) -> bool {
+    // The server closed the connection deliberately, so there is nothing to reconnect to.
+    if is_shutdown_error(err) {
+        return false;
+    }
+
     match err {
         SeliumError::IoError(err) => is_disconnect_error(err),
         SeliumError::Quic(QuicError::ConnectionError(_)) => true,
-        SeliumError::OpenStream(code, _) => is_bind_error(*code),
+        // The server may be temporarily unresponsive, so give it another chance
+        SeliumError::ConnectTimeout => true,
         _ => false,
     }
 }
@@ -29,3 +93,106 @@ pub fn is_stream_disconnected<Item>(result: &Result<Item>) -> bool {
 pub fn is_sink_disconnected(result: &Poll<Result<()>>) -> bool {
     matches!(result, Poll::Ready(Err(err)) if is_recoverable_error(err))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use quinn::ApplicationClose;
+    use selium_protocol::error_codes::{SHUTDOWN_IN_PROGRESS, STREAM_CLOSED_PREMATURELY};
+
+    fn application_closed(code: u32) -> SeliumError {
+        let close = ApplicationClose {
+            error_code: VarInt::from_u32(code),
+            reason: Bytes::new(),
+        };
+
+        QuicError::ConnectionError(ConnectionError::ApplicationClosed(close)).into()
+    }
+
+    #[test]
+    fn does_not_recover_from_shutdown() {
+        assert!(!is_recoverable_error(&application_closed(SHUTDOWN)));
+    }
+
+    #[test]
+    fn recovers_from_other_connection_errors() {
+        assert!(is_recoverable_error(&application_closed(
+            STREAM_CLOSED_PREMATURELY
+        )));
+        assert!(is_recoverable_error(
+            &QuicError::ConnectionError(ConnectionError::TimedOut).into()
+        ));
+    }
+
+    #[test]
+    fn recovers_from_connect_timeout() {
+        assert!(is_recoverable_error(&SeliumError::ConnectTimeout));
+    }
+
+    #[test]
+    fn does_not_recover_from_shutdown_while_reading() {
+        let close = ApplicationClose {
+            error_code: VarInt::from_u32(SHUTDOWN),
+            reason: Bytes::new(),
+        };
+        let read_err = ReadError::ConnectionLost(ConnectionError::ApplicationClosed(close));
+        let err = SeliumError::IoError(read_err.into());
+
+        assert!(!is_recoverable_error(&err));
+        assert!(matches!(
+            map_connection_closed(err),
+            SeliumError::ConnectionClosed {
+                code: ErrorCode::Shutdown,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn recovers_from_transient_disconnects_while_reading() {
+        let read_err = ReadError::ConnectionLost(ConnectionError::TimedOut);
+        let err = SeliumError::IoError(read_err.into());
+
+        assert!(is_recoverable_error(&err));
+        assert!(matches!(
+            map_connection_closed(err),
+            SeliumError::IoError(_)
+        ));
+    }
+
+    #[test]
+    fn surfaces_application_close_code_and_reason() {
+        let close = ApplicationClose {
+            error_code: VarInt::from_u32(STREAM_CLOSED_PREMATURELY),
+            reason: Bytes::from_static(b"Stream closed."),
+        };
+        let write_err = WriteError::ConnectionLost(ConnectionError::ApplicationClosed(close));
+        let err = QuicError::WriteError(write_err).into();
+
+        assert!(matches!(
+            map_connection_closed(err),
+            SeliumError::ConnectionClosed {
+                code: ErrorCode::StreamClosedPrematurely,
+                ref reason,
+            } if reason == "Stream closed."
+        ));
+    }
+
+    #[test]
+    fn does_not_recover_from_bind_error() {
+        let err = SeliumError::ReplierAlreadyBound;
+        assert!(is_bind_error(&err));
+        assert!(!is_recoverable_error(&err));
+    }
+
+    #[test]
+    fn does_not_recover_from_server_errors() {
+        let codes = [SHUTDOWN, SHUTDOWN_IN_PROGRESS, STREAM_CLOSED_PREMATURELY];
+
+        for code in codes {
+            let err = SeliumError::OpenStream(code.into(), "Closing".into());
+            assert!(!is_recoverable_error(&err));
+        }
+    }
+}
@@ -10,10 +10,18 @@
 
 mod backoff_strategy;
 mod connection_status;
-mod helpers;
+mod events;
+pub(crate) mod helpers;
+mod replay;
+mod state;
 
 pub mod pubsub;
 pub mod reqrep;
 
 pub use backoff_strategy::*;
 pub(crate) use connection_status::*;
+pub use events::ConnectionEvent;
+pub(crate) use events::EventSender;
+pub(crate) use replay::ReplayBuffer;
+pub use replay::ReplayConfig;
+pub use state::KeepAliveState;
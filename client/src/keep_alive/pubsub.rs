@@ -1,13 +1,22 @@
-use super::helpers::{is_recoverable_error, is_sink_disconnected, is_stream_disconnected};
-use super::{BackoffStrategy, ConnectionStatus};
+use super::helpers::{
+    is_recoverable_error, is_sink_disconnected, is_stream_disconnected, map_connection_closed,
+};
+use super::{
+    BackoffStrategy, ConnectionEvent, ConnectionStatus, EventSender, KeepAliveState,
+    ReconnectedStream,
+};
 use crate::keep_alive::NextAttempt;
 use crate::logging;
-use crate::pubsub::Publisher;
+use crate::pubsub::{check_reserved_headers, Publisher};
 use crate::traits::KeepAliveStream;
+use futures::future::poll_fn;
 use futures::{ready, FutureExt, Sink, SinkExt, Stream, StreamExt};
+use selium_protocol::{KEY_HEADER, PRIORITY_HEADER};
 use selium_std::errors::{QuicError, Result, SeliumError};
 use selium_std::traits::codec::MessageEncoder;
+use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
+use std::time::SystemTime;
 use std::{
     pin::Pin,
     task::{Context, Poll},
@@ -18,24 +27,40 @@ pub struct KeepAlive<T> {
     stream: T,
     backoff_strategy: BackoffStrategy,
     status: ConnectionStatus,
+    events: EventSender,
 }
 
 impl<T> KeepAlive<T>
 where
     T: KeepAliveStream + Send + Unpin,
 {
-    pub fn new(stream: T, backoff_strategy: BackoffStrategy) -> Self {
+    pub(crate) fn new(stream: T, backoff_strategy: BackoffStrategy, events: EventSender) -> Self {
         Self {
             stream,
             backoff_strategy,
             status: ConnectionStatus::Connected,
+            events,
         }
     }
 
+    /// Returns the current connection state of the stream.
+    pub fn state(&self) -> KeepAliveState {
+        self.status.state()
+    }
+
     fn on_disconnect(&mut self, cx: &mut Context<'_>) {
         if let ConnectionStatus::Connected = self.status {
             logging::keep_alive::connection_lost();
+            self.events.send(ConnectionEvent::Disconnected);
+            self.stream.on_disconnect();
             self.status = ConnectionStatus::disconnected(self.backoff_strategy.clone());
+
+            if let ConnectionStatus::Disconnected(ref mut state) = self.status {
+                state.deadline = self
+                    .stream
+                    .max_reconnect_gap()
+                    .map(|gap| Box::pin(tokio::time::sleep(gap)));
+            }
         }
 
         if let ConnectionStatus::Disconnected(ref mut state) = self.status {
@@ -47,19 +72,27 @@ where
                 Some(next) => next,
                 None => {
                     logging::keep_alive::too_many_retries();
+                    self.events.send(ConnectionEvent::Failed);
                     self.status = ConnectionStatus::Exhausted;
                     cx.waker().wake_by_ref();
                     return;
                 }
             };
 
+            state.attempt_num = attempt_num;
+            state.max_attempts = max_attempts;
+
             let connection = self.stream.get_connection();
             let headers = self.stream.get_headers();
             logging::keep_alive::reconnect_attempt(attempt_num, max_attempts);
+            self.events.send(ConnectionEvent::Reconnecting {
+                attempt: attempt_num,
+            });
 
             state.current_attempt = Box::pin(async move {
                 tokio::time::sleep(duration).await;
-                T::reestablish_connection(connection, headers).await
+                let stream = T::reestablish_connection(connection, headers).await?;
+                Ok(Box::new(stream) as ReconnectedStream)
             });
         } else {
             unreachable!();
@@ -70,11 +103,24 @@ where
 
     fn poll_reconnect(&mut self, cx: &mut Context<'_>) -> Result<()> {
         if let ConnectionStatus::Disconnected(ref mut state) = self.status {
+            if let Some(deadline) = state.deadline.as_mut() {
+                if deadline.poll_unpin(cx).is_ready() {
+                    logging::keep_alive::reconnect_gap_exceeded();
+                    self.events.send(ConnectionEvent::Failed);
+                    self.status = ConnectionStatus::Exhausted;
+                    return Err(SeliumError::ReconnectGapExceeded);
+                }
+            }
+
             match state.current_attempt.poll_unpin(cx) {
                 Poll::Ready(Ok(stream)) => {
+                    let stream = stream
+                        .downcast::<T::Stream>()
+                        .expect("Reconnection attempts are made by the stream itself");
                     self.status = ConnectionStatus::Connected;
-                    self.stream.on_reconnect(stream);
+                    self.stream.on_reconnect(*stream);
                     logging::keep_alive::successful_reconnection();
+                    self.events.send(ConnectionEvent::Reconnected);
                     cx.waker().wake_by_ref();
                 }
                 Poll::Ready(Err(err)) if is_recoverable_error(&err) => {
@@ -83,7 +129,9 @@ where
                 }
                 Poll::Ready(Err(err)) => {
                     logging::keep_alive::unrecoverable_error(&err);
-                    return Err(err);
+                    self.events.send(ConnectionEvent::Failed);
+                    self.status = ConnectionStatus::Exhausted;
+                    return Err(map_connection_closed(err));
                 }
                 _ => (),
             }
@@ -106,6 +154,82 @@ where
     pub async fn duplicate(&self) -> Result<Self> {
         self.stream.duplicate().await
     }
+
+    pub async fn send_all<I>(&mut self, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = E::Item>,
+    {
+        self.stream.send_all(items).await
+    }
+
+    pub async fn forward_from<S>(&mut self, source: S) -> Result<()>
+    where
+        S: Stream<Item = E::Item>,
+    {
+        self.stream.forward_from(source).await
+    }
+
+    pub async fn flush(&mut self) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        self.stream.flush_batch()?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    pub async fn send_with_event_time(
+        &mut self,
+        item: E::Item,
+        event_time: SystemTime,
+    ) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        self.stream.flush_batch()?;
+        poll_fn(|cx| Sink::<E::Item>::poll_ready(Pin::new(&mut *self), cx)).await?;
+        self.stream.start_send_with_event_time(item, event_time)?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    pub async fn send_with_headers(
+        &mut self,
+        item: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        check_reserved_headers(&headers)?;
+        self.send_headers(item, headers).await
+    }
+
+    // Sends a message with the provided headers without checking for reserved headers, so that
+    // Selium's own headers can be attached
+    async fn send_headers(&mut self, item: E::Item, headers: HashMap<String, String>) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        self.stream.flush_batch()?;
+        poll_fn(|cx| Sink::<E::Item>::poll_ready(Pin::new(&mut *self), cx)).await?;
+        self.stream.start_send_with_headers(item, headers)?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    pub async fn send_with_key(&mut self, item: E::Item, key: &str) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        let headers = HashMap::from([(KEY_HEADER.to_owned(), key.to_owned())]);
+        self.send_headers(item, headers).await
+    }
+
+    pub async fn send_with_priority(&mut self, item: E::Item, priority: u8) -> Result<()>
+    where
+        E::Item: Unpin + Send,
+    {
+        let headers = HashMap::from([(PRIORITY_HEADER.to_owned(), priority.to_string())]);
+        self.send_headers(item, headers).await
+    }
 }
 
 impl<T, Item> Sink<Item> for KeepAlive<T>
@@ -124,12 +248,18 @@ where
                     self.on_disconnect(cx);
                     Poll::Pending
                 } else {
-                    result
+                    result.map_err(map_connection_closed)
                 }
             }
             ConnectionStatus::Disconnected(_) => {
                 self.poll_reconnect(cx)?;
-                Poll::Pending
+
+                // Accept the item into the stream's buffer if the reconnection is still pending
+                if let ConnectionStatus::Disconnected(_) = self.status {
+                    self.stream.poll_buffer(cx)
+                } else {
+                    Poll::Pending
+                }
             }
             ConnectionStatus::Exhausted => Poll::Ready(Err(QuicError::TooManyRetries)?),
         }
@@ -148,7 +278,7 @@ where
                     self.on_disconnect(cx);
                     Poll::Pending
                 } else {
-                    result
+                    result.map_err(map_connection_closed)
                 }
             }
             ConnectionStatus::Disconnected(_) => {
@@ -193,7 +323,7 @@ where
                         self.on_disconnect(cx);
                         Poll::Pending
                     } else {
-                        Poll::Ready(Some(result))
+                        Poll::Ready(Some(result.map_err(map_connection_closed)))
                     }
                 } else {
                     self.on_disconnect(cx);
@@ -0,0 +1,113 @@
+use selium_protocol::Frame;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Configuration type used to buffer messages sent by a [Publisher](crate::pubsub::Publisher)
+/// while it is reconnecting to the `Selium` server.
+///
+/// By default, a publisher applies backpressure while it is disconnected, so any attempt to send
+/// a message will wait until the connection has been reestablished. With a `ReplayConfig`, the
+/// publisher instead accepts up to `max_messages` messages, or `max_bytes` bytes of encoded
+/// messages, while disconnected, and replays them in order once reconnected. Backpressure is only
+/// applied once the buffer is full, so short outages are transparent to the producer, while the
+/// memory used during a long outage is bounded.
+///
+/// To surface long outages as an error, rather than waiting for the backoff strategy to be
+/// exhausted, specify a `max_gap`. If the publisher hasn't reconnected within the gap, the next
+/// send or flush fails with [ReconnectGapExceeded](crate::std::errors::SeliumError::ReconnectGapExceeded),
+/// and any buffered messages are discarded.
+///
+/// # Delivery Guarantees
+///
+/// Buffered messages are delivered at most once. Messages that had already been sent over the
+/// wire when the connection was lost can't be recovered, so may be lost, as can buffered
+/// messages that are being replayed when the connection is lost again. Combine buffering with
+/// [deduplication](crate::StreamBuilder::with_deduplication) and your own retries if you
+/// require at-least-once delivery.
+///
+/// ```
+/// use selium::keep_alive::ReplayConfig;
+/// use std::time::Duration;
+///
+/// let config = ReplayConfig::new(1_000, 1024 * 1024).max_gap(Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub(crate) max_messages: usize,
+    pub(crate) max_bytes: usize,
+    pub(crate) max_gap: Option<Duration>,
+}
+
+impl ReplayConfig {
+    /// Constructs a new `ReplayConfig` instance with the provided `max_messages` and `max_bytes`
+    /// limits. If message batching is enabled, each batch counts as a single message.
+    pub fn new(max_messages: usize, max_bytes: usize) -> Self {
+        Self {
+            max_messages,
+            max_bytes,
+            max_gap: None,
+        }
+    }
+
+    /// Specifies the maximum duration that the publisher can remain disconnected before sending
+    /// fails with an error.
+    pub fn max_gap(mut self, gap: Duration) -> Self {
+        self.max_gap = Some(gap);
+        self
+    }
+}
+
+/// A bounded queue of message frames awaiting replay after a reconnection.
+#[derive(Debug)]
+pub(crate) struct ReplayBuffer {
+    config: ReplayConfig,
+    frames: VecDeque<Frame>,
+    bytes: usize,
+}
+
+impl ReplayBuffer {
+    pub fn config(&self) -> ReplayConfig {
+        self.config.clone()
+    }
+
+    pub fn max_gap(&self) -> Option<Duration> {
+        self.config.max_gap
+    }
+
+    pub fn has_capacity(&self) -> bool {
+        self.frames.len() < self.config.max_messages && self.bytes < self.config.max_bytes
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        self.bytes += frame_size(&frame);
+        self.frames.push_back(frame);
+    }
+
+    pub fn pop(&mut self) -> Option<Frame> {
+        let frame = self.frames.pop_front()?;
+        self.bytes -= frame_size(&frame);
+        Some(frame)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+impl From<ReplayConfig> for ReplayBuffer {
+    fn from(config: ReplayConfig) -> Self {
+        Self {
+            config,
+            frames: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+}
+
+fn frame_size(frame: &Frame) -> usize {
+    frame.message().map_or(0, <[u8]>::len)
+}
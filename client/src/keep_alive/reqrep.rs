@@ -1,33 +1,50 @@
 use super::backoff_strategy::*;
-use super::helpers::is_recoverable_error;
+use super::helpers::{is_bind_error, is_recoverable_error, map_connection_closed};
+use super::{ConnectionEvent, EventSender, KeepAliveState};
 use crate::logging;
-use crate::request_reply::{Replier, Requestor};
+use crate::request_reply::{CancellationToken, Replier, RequestHandler, Requestor};
 use crate::traits::KeepAliveStream;
 use futures::Future;
 use selium_std::errors::QuicError;
 use selium_std::errors::Result;
 use selium_std::traits::codec::{MessageDecoder, MessageEncoder};
+use std::collections::HashMap;
 use std::fmt::Debug;
 
 #[doc(hidden)]
 pub struct KeepAlive<T> {
     stream: T,
     backoff_strategy: BackoffStrategy,
+    events: EventSender,
+    state: KeepAliveState,
 }
 
 impl<T> KeepAlive<T>
 where
     T: KeepAliveStream,
 {
-    pub fn new(stream: T, backoff_strategy: BackoffStrategy) -> Self {
+    pub(crate) fn new(stream: T, backoff_strategy: BackoffStrategy, events: EventSender) -> Self {
         Self {
             stream,
             backoff_strategy,
+            events,
+            state: KeepAliveState::Connected,
         }
     }
 
+    /// Returns the current connection state of the stream.
+    pub fn state(&self) -> KeepAliveState {
+        self.state
+    }
+
+    fn on_failure(&mut self) {
+        self.events.send(ConnectionEvent::Failed);
+        self.state = KeepAliveState::Failed;
+    }
+
     async fn try_reconnect(&mut self, attempts: &mut BackoffStrategyIter) -> Result<()> {
         logging::keep_alive::connection_lost();
+        self.events.send(ConnectionEvent::Disconnected);
 
         loop {
             let NextAttempt {
@@ -38,6 +55,7 @@ where
                 Some(next) => next,
                 None => {
                     logging::keep_alive::too_many_retries();
+                    self.on_failure();
                     return Err(QuicError::TooManyRetries)?;
                 }
             };
@@ -46,20 +64,32 @@ where
             let headers = self.stream.get_headers();
 
             logging::keep_alive::reconnect_attempt(attempt_num, max_attempts);
+            self.state = KeepAliveState::Reconnecting {
+                attempt: attempt_num,
+                max: max_attempts,
+            };
+            self.events.send(ConnectionEvent::Reconnecting {
+                attempt: attempt_num,
+            });
             tokio::time::sleep(duration).await;
 
             match T::reestablish_connection(connection, headers).await {
                 Ok(stream) => {
                     logging::keep_alive::successful_reconnection();
+                    self.events.send(ConnectionEvent::Reconnected);
                     self.stream.on_reconnect(stream);
+                    self.state = KeepAliveState::Connected;
                     return Ok(());
                 }
-                Err(err) if is_recoverable_error(&err) => {
+                // The server may not have released this replier's previous binding yet, so keep
+                // retrying until it notices the old stream has gone
+                Err(err) if is_recoverable_error(&err) || is_bind_error(&err) => {
                     logging::keep_alive::reconnect_error(&err)
                 }
                 Err(err) => {
                     logging::keep_alive::unrecoverable_error(&err);
-                    return Err(err);
+                    self.on_failure();
+                    return Err(map_connection_closed(err));
                 }
             }
         }
@@ -75,6 +105,8 @@ where
         Self {
             stream: self.stream.clone(),
             backoff_strategy: self.backoff_strategy.clone(),
+            events: self.events.clone(),
+            state: self.state,
         }
     }
 }
@@ -84,16 +116,58 @@ where
     E: MessageEncoder + Send + Unpin + Clone,
     D: MessageDecoder + Send + Unpin + Clone,
 {
+    pub fn priority(&self) -> i32 {
+        self.stream.priority()
+    }
+
     pub async fn request(&mut self, req: E::Item) -> Result<D::Item> {
+        self.request_with_cancellation(req, CancellationToken::new())
+            .await
+    }
+
+    pub async fn request_with_cancellation(
+        &mut self,
+        req: E::Item,
+        token: CancellationToken,
+    ) -> Result<D::Item> {
         let mut attempts = self.backoff_strategy.clone().into_iter();
 
         loop {
-            match self.stream.request(req.clone()).await {
+            match self
+                .stream
+                .request_with_cancellation(req.clone(), token.clone())
+                .await
+            {
                 Ok(res) => return Ok(res),
                 Err(err) if is_recoverable_error(&err) => self.try_reconnect(&mut attempts).await?,
                 Err(err) => {
                     logging::keep_alive::unrecoverable_error(&err);
-                    return Err(err);
+                    self.on_failure();
+                    return Err(map_connection_closed(err));
+                }
+            };
+        }
+    }
+
+    pub async fn request_with_headers(
+        &mut self,
+        req: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<D::Item> {
+        let mut attempts = self.backoff_strategy.clone().into_iter();
+
+        loop {
+            match self
+                .stream
+                .request_with_headers(req.clone(), headers.clone())
+                .await
+            {
+                Ok(res) => return Ok(res),
+                Err(err) if is_recoverable_error(&err) => self.try_reconnect(&mut attempts).await?,
+                Err(err) => {
+                    logging::keep_alive::unrecoverable_error(&err);
+                    self.on_failure();
+                    return Err(map_connection_closed(err));
                 }
             };
         }
@@ -105,17 +179,28 @@ where
     D: MessageDecoder + Send + Unpin,
     E: MessageEncoder + Send + Unpin,
     Err: Debug,
-    F: FnMut(D::Item) -> Fut + Send + Unpin,
+    F: RequestHandler<D::Item, Future = Fut> + Send + Unpin,
     Fut: Future<Output = std::result::Result<E::Item, Err>>,
 {
+    pub fn priority(&self) -> i32 {
+        self.stream.priority()
+    }
+
     pub async fn listen(&mut self) -> Result<()> {
+        self.listen_with_cancellation(CancellationToken::new())
+            .await
+    }
+
+    pub async fn listen_with_cancellation(&mut self, token: CancellationToken) -> Result<()> {
         let mut attempts = self.backoff_strategy.clone().into_iter();
 
         loop {
-            match self.stream.listen().await {
+            match self.stream.listen_with_cancellation(token.clone()).await {
+                Ok(()) if token.is_cancelled() => return Ok(()),
                 Err(err) if !is_recoverable_error(&err) => {
                     logging::keep_alive::unrecoverable_error(&err);
-                    return Err(err);
+                    self.on_failure();
+                    return Err(map_connection_closed(err));
                 }
                 _ => self.try_reconnect(&mut attempts).await?,
             };
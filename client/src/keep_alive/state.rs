@@ -0,0 +1,17 @@
+/// The connection state of a stream wrapped in a `KeepAlive`, as returned by its `state` method.
+///
+/// Unlike [ConnectionEvent](crate::keep_alive::ConnectionEvent)s, which are broadcast for every
+/// stream opened on a client, the state describes a single stream, making it suitable for health
+/// checks and readiness probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepAliveState {
+    /// The stream is connected to the `Selium` server.
+    Connected,
+    /// The stream has lost its connection to the `Selium` server, and is making its `attempt`th
+    /// attempt to reconnect, out of a maximum of `max` attempts.
+    Reconnecting { attempt: u32, max: u32 },
+    /// The stream has given up reconnecting to the `Selium` server, either because it has
+    /// exhausted its [BackoffStrategy](crate::keep_alive::BackoffStrategy), or because it
+    /// encountered an unrecoverable error. The stream will not recover, and must be reopened.
+    Failed,
+}
@@ -1,6 +1,9 @@
 mod client;
 mod streams;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 pub mod batching;
 pub mod constants;
 pub mod keep_alive;
@@ -1,3 +1,6 @@
+use selium_std::errors::SeliumError;
+use std::time::SystemTime;
+
 pub fn get_cloud_endpoint() {
     tracing::info!("Retrieving Selium server endpoint from Selium Cloud.");
 }
@@ -9,3 +12,23 @@ pub fn connect_to_address(endpoint: &str) {
 pub fn successful_connection(endpoint: &str) {
     tracing::info!(endpoint, "Successfully connected to remote address.");
 }
+
+pub fn cert_expiring(expiry: SystemTime) {
+    let expires_in_secs = expiry
+        .duration_since(SystemTime::now())
+        .map_or(0, |remaining| remaining.as_secs());
+
+    tracing::warn!(
+        expires_in_secs,
+        "Client certificate is due to expire. Rotate the certificate to avoid failed connections."
+    );
+}
+
+pub fn retry_connect(err: &SeliumError, attempt_num: u32, max_attempts: u32) {
+    tracing::warn!(
+        error = err.to_string(),
+        attempt_num,
+        max_attempts,
+        "Failed to connect to remote address. Retrying..."
+    );
+}
@@ -30,3 +30,7 @@ pub fn unrecoverable_error(err: &SeliumError) {
 pub fn connection_lost() {
     tracing::error!("Client lost connection to the server.");
 }
+
+pub fn reconnect_gap_exceeded() {
+    tracing::error!("Failed to reconnect within the maximum reconnect gap. Aborting reconnection.");
+}
@@ -1,2 +1,4 @@
 pub mod connection;
 pub mod keep_alive;
+pub mod publisher;
+pub mod subscriber;
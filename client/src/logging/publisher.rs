@@ -0,0 +1,22 @@
+use selium_protocol::Frame;
+
+pub fn dropped_unsent_messages(count: usize) {
+    tracing::warn!(
+        count,
+        "Publisher dropped with unsent batched messages. Call `flush` or `finish` before dropping the publisher to send them."
+    );
+}
+
+pub fn dropped_buffered_messages(count: usize) {
+    tracing::warn!(
+        count,
+        "Publisher dropped with messages buffered during a disconnection. These messages will not be replayed."
+    );
+}
+
+pub fn unexpected_frame(frame: &Frame) {
+    tracing::debug!(
+        ?frame,
+        "Publisher received an unexpected frame. Ignoring the frame."
+    );
+}
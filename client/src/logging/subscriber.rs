@@ -0,0 +1,22 @@
+use selium_std::errors::SeliumError;
+
+pub fn receiver_lagged(skipped: u64) {
+    tracing::warn!(
+        skipped,
+        "Broadcast receiver fell behind. Dropping the oldest messages."
+    );
+}
+
+pub fn skipped_decode_error(err: &SeliumError) {
+    tracing::warn!(
+        error = err.to_string(),
+        "Subscriber failed to decode a message. Skipping the message."
+    );
+}
+
+pub fn broadcast_failed(err: &SeliumError) {
+    tracing::error!(
+        error = err.to_string(),
+        "Subscriber failed while broadcasting messages."
+    );
+}
@@ -1,35 +1,223 @@
 mod aliases;
 mod builder;
+pub(crate) mod transport;
 
 pub mod pubsub;
 pub mod request_reply;
+use crate::connection::ClientConnection;
 pub use builder::*;
-use futures::StreamExt;
+use futures::{future, Stream, StreamExt};
 use selium_protocol::{
-    error_codes::{STREAM_CLOSED_PREMATURELY, UNKNOWN_ERROR},
-    BiStream, Frame,
+    error_codes::{REPLIER_ALREADY_BOUND, STREAM_CLOSED_PREMATURELY, UNKNOWN_ERROR},
+    BiStream, ErrorPayload, Frame, OffsetsPayload, ServerInfoPayload, TopicEvent, WriteHalf,
 };
-use selium_std::errors::{Result, SeliumError};
+use selium_std::errors::{QuicError, Result, SeliumError};
+use std::time::Duration;
+use tokio::sync::MutexGuard;
 
-// Handle response from Selium server on opening a stream
-async fn handle_reply(stream: &mut BiStream) -> Result<()> {
-    match stream.next().await {
+// Open a new stream on the connection, returning it alongside the connection's timeout.
+//
+// Once the server's concurrent stream limit has been reached, opening a stream waits for the
+// server to grant more credit as other streams close. The lock is released first so that waiting
+// for credit doesn't block other streams from reconnecting, and the wait is bounded by the
+// connection's timeout, so this only fails if the connection is lost or no credit is granted in
+// time.
+pub(crate) async fn open_bistream(
+    connection: MutexGuard<'_, ClientConnection>,
+) -> Result<(BiStream, Duration)> {
+    let connect_timeout = connection.connect_timeout();
+    let conn = connection.conn().clone();
+    drop(connection);
+
+    let stream = tokio::time::timeout(connect_timeout, BiStream::try_from_connection(&conn))
+        .await
+        .map_err(|_| SeliumError::ConnectTimeout)??;
+
+    Ok((stream, connect_timeout))
+}
+
+// Open a new unidirectional stream on the connection, waiting for credit from the server in the
+// same manner as `open_bistream`.
+pub(crate) async fn open_unistream(
+    connection: MutexGuard<'_, ClientConnection>,
+) -> Result<WriteHalf> {
+    let connect_timeout = connection.connect_timeout();
+    let conn = connection.conn().clone();
+    drop(connection);
+
+    let stream = tokio::time::timeout(connect_timeout, conn.open_uni())
+        .await
+        .map_err(|_| SeliumError::ConnectTimeout)?
+        .map_err(QuicError::ConnectionError)?;
+
+    Ok(stream.into())
+}
+
+// Handle response from Selium server on opening a stream, giving up if the server doesn't reply
+// within the connection's timeout
+pub(crate) async fn handle_reply(stream: &mut BiStream, connect_timeout: Duration) -> Result<()> {
+    let reply = tokio::time::timeout(connect_timeout, stream.next())
+        .await
+        .map_err(|_| SeliumError::ConnectTimeout)?;
+
+    match reply {
         Some(Ok(Frame::Ok)) => Ok(()),
-        Some(Ok(Frame::Error(payload))) => match String::from_utf8(payload.message.to_vec()) {
-            Ok(s) => Err(SeliumError::OpenStream(payload.code, s)),
-            Err(_) => Err(SeliumError::OpenStream(
-                payload.code,
-                "Invalid UTF-8 error".into(),
-            )),
-        },
+        Some(Ok(Frame::Error(payload))) => Err(error_from_payload(payload)),
+        Some(Ok(_)) => Err(SeliumError::OpenStream(
+            UNKNOWN_ERROR.into(),
+            "Invalid frame returned from server".into(),
+        )),
+        Some(Err(e)) => Err(e),
+        None => Err(SeliumError::OpenStream(
+            STREAM_CLOSED_PREMATURELY.into(),
+            "Stream closed prematurely".into(),
+        )),
+    }
+}
+
+// Handle the response from Selium server to a topic offsets query
+pub(crate) async fn handle_offsets_reply(
+    stream: &mut BiStream,
+    connect_timeout: Duration,
+) -> Result<OffsetsPayload> {
+    handle_reply(stream, connect_timeout).await?;
+
+    match stream.next().await {
+        Some(Ok(Frame::Offsets(payload))) => Ok(payload),
+        Some(Ok(Frame::Error(payload))) => Err(error_from_payload(payload)),
         Some(Ok(_)) => Err(SeliumError::OpenStream(
-            UNKNOWN_ERROR,
+            UNKNOWN_ERROR.into(),
             "Invalid frame returned from server".into(),
         )),
         Some(Err(e)) => Err(e),
         None => Err(SeliumError::OpenStream(
-            STREAM_CLOSED_PREMATURELY,
+            STREAM_CLOSED_PREMATURELY.into(),
             "Stream closed prematurely".into(),
         )),
     }
 }
+
+// Handle the response from Selium server to a topic truncation, which is answered with a single
+// frame once the topic has been truncated, giving up if the server doesn't reply within the
+// connection's timeout
+pub(crate) async fn handle_truncate_reply(
+    stream: &mut BiStream,
+    connect_timeout: Duration,
+) -> Result<OffsetsPayload> {
+    let reply = tokio::time::timeout(connect_timeout, stream.next())
+        .await
+        .map_err(|_| SeliumError::ConnectTimeout)?;
+
+    match reply {
+        Some(Ok(Frame::Offsets(payload))) => Ok(payload),
+        Some(Ok(Frame::Error(payload))) => Err(error_from_payload(payload)),
+        Some(Ok(_)) => Err(SeliumError::OpenStream(
+            UNKNOWN_ERROR.into(),
+            "Invalid frame returned from server".into(),
+        )),
+        Some(Err(e)) => Err(e),
+        None => Err(SeliumError::OpenStream(
+            STREAM_CLOSED_PREMATURELY.into(),
+            "Stream closed prematurely".into(),
+        )),
+    }
+}
+
+// Handle the response from Selium server to a server info query, giving up if the server doesn't
+// reply within the connection's timeout
+pub(crate) async fn handle_server_info_reply(
+    stream: &mut BiStream,
+    connect_timeout: Duration,
+) -> Result<ServerInfoPayload> {
+    let reply = tokio::time::timeout(connect_timeout, stream.next())
+        .await
+        .map_err(|_| SeliumError::ConnectTimeout)?;
+
+    match reply {
+        Some(Ok(Frame::ServerInfo(payload))) => Ok(payload),
+        Some(Ok(Frame::Error(payload))) => Err(error_from_payload(payload)),
+        Some(Ok(_)) => Err(SeliumError::OpenStream(
+            UNKNOWN_ERROR.into(),
+            "Invalid frame returned from server".into(),
+        )),
+        Some(Err(e)) => Err(e),
+        None => Err(SeliumError::OpenStream(
+            STREAM_CLOSED_PREMATURELY.into(),
+            "Stream closed prematurely".into(),
+        )),
+    }
+}
+
+// Map the frames sent to a topic watcher into the topic events they carry, ending the stream with
+// an error if the server disconnects the watcher
+pub(crate) fn topic_events(stream: BiStream) -> impl Stream<Item = Result<TopicEvent>> + Unpin {
+    stream.filter_map(|frame| {
+        future::ready(match frame {
+            Ok(Frame::TopicEvent(event)) => Some(Ok(event)),
+            Ok(Frame::Error(payload)) => Some(Err(error_from_payload(payload))),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+    })
+}
+
+// Convert an error frame sent by the Selium server into a [SeliumError], retaining the error code
+// so that callers can react to it
+fn error_from_payload(payload: ErrorPayload) -> SeliumError {
+    if payload.code == REPLIER_ALREADY_BOUND {
+        return SeliumError::ReplierAlreadyBound;
+    }
+
+    match String::from_utf8(payload.message.to_vec()) {
+        Ok(s) => SeliumError::OpenStream(payload.code.into(), s),
+        Err(_) => SeliumError::OpenStream(payload.code.into(), "Invalid UTF-8 error".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use selium_protocol::error_codes::SHUTDOWN_IN_PROGRESS;
+    use selium_std::errors::ErrorCode;
+
+    #[test]
+    fn retains_error_code_from_payload() {
+        let codes = [SHUTDOWN_IN_PROGRESS, STREAM_CLOSED_PREMATURELY];
+
+        for code in codes {
+            let payload = ErrorPayload {
+                code,
+                message: "Oh no".into(),
+            };
+
+            let err = error_from_payload(payload);
+            assert!(
+                matches!(err, SeliumError::OpenStream(c, ref s) if u32::from(c) == code && s == "Oh no")
+            );
+        }
+    }
+
+    #[test]
+    fn maps_bind_error_to_dedicated_variant() {
+        let payload = ErrorPayload {
+            code: REPLIER_ALREADY_BOUND,
+            message: "Bound".into(),
+        };
+
+        let err = error_from_payload(payload);
+        assert!(matches!(err, SeliumError::ReplierAlreadyBound));
+    }
+
+    #[test]
+    fn replaces_invalid_utf8_message() {
+        let payload = ErrorPayload {
+            code: UNKNOWN_ERROR,
+            message: vec![0xff, 0xfe].into(),
+        };
+
+        let err = error_from_payload(payload);
+        assert!(
+            matches!(err, SeliumError::OpenStream(ErrorCode::Unknown(UNKNOWN_ERROR), ref s) if s == "Invalid UTF-8 error")
+        );
+    }
+}
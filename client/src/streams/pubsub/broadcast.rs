@@ -0,0 +1,198 @@
+use super::Subscriber;
+use crate::constants::BROADCAST_CAPACITY_DEFAULT;
+use crate::keep_alive::pubsub::KeepAlive;
+use crate::logging;
+use futures::{stream, Stream, StreamExt};
+use selium_std::errors::Result;
+use selium_std::traits::codec::MessageDecoder;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+/// Fans out the messages consumed by a single [Subscriber] to any number of receivers within the
+/// same process, without opening additional subscriptions on the `Selium` server.
+///
+/// Every [BroadcastReceiver] obtained via [subscribe](SubscriberBroadcast::subscribe) receives a
+/// copy of each decoded message, so the decoded items must implement [Clone].
+///
+/// Messages are buffered in a channel with a fixed capacity. If a receiver falls behind by more
+/// than the capacity, the oldest messages are dropped for that receiver, and a warning is logged.
+/// Other receivers are unaffected.
+///
+/// The broadcast ends once the underlying subscriber ends, or fails with an error, at which point
+/// each receiver will return `None` after draining any buffered messages.
+pub struct SubscriberBroadcast<T> {
+    receiver: broadcast::Receiver<T>,
+    task: JoinHandle<()>,
+}
+
+impl<T> SubscriberBroadcast<T>
+where
+    T: Clone + Send + 'static,
+{
+    fn spawn<S>(mut stream: S, capacity: usize) -> Self
+    where
+        S: Stream<Item = Result<T>> + Send + Unpin + 'static,
+    {
+        let (tx, receiver) = broadcast::channel(capacity);
+
+        let task = tokio::spawn(async move {
+            while let Some(result) = stream.next().await {
+                match result {
+                    Ok(item) => {
+                        // All receivers have been dropped, so there's nobody left to deliver to
+                        if tx.send(item).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        logging::subscriber::broadcast_failed(&err);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self { receiver, task }
+    }
+
+    /// Creates a new [BroadcastReceiver], which will receive every message consumed after this
+    /// call.
+    pub fn subscribe(&self) -> BroadcastReceiver<T> {
+        BroadcastReceiver {
+            receiver: self.receiver.resubscribe(),
+        }
+    }
+
+    /// Stops consuming messages from the underlying subscriber.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+}
+
+/// A receiver for messages fanned out by a [SubscriberBroadcast].
+///
+/// Cloning a receiver creates a new receiver that receives every message consumed after the
+/// clone, regardless of how far behind the original receiver is.
+pub struct BroadcastReceiver<T> {
+    receiver: broadcast::Receiver<T>,
+}
+
+impl<T> BroadcastReceiver<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Receives the next message, or `None` if the broadcast has ended.
+    ///
+    /// If this receiver has fallen behind, the messages that were dropped are skipped, and the
+    /// oldest message still buffered is returned instead.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(item) => return Some(item),
+                Err(RecvError::Lagged(skipped)) => logging::subscriber::receiver_lagged(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Converts this receiver into a [Stream] of messages.
+    pub fn into_stream(self) -> impl Stream<Item = T> + Send + Unpin {
+        Box::pin(stream::unfold(self, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        }))
+    }
+}
+
+impl<T> Clone for BroadcastReceiver<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            receiver: self.receiver.resubscribe(),
+        }
+    }
+}
+
+impl<D> Subscriber<D>
+where
+    D: MessageDecoder + Send + Unpin + 'static,
+    D::Item: Clone + Send + 'static,
+{
+    /// Consumes the subscriber to fan out its messages to multiple receivers, buffering up to
+    /// [BROADCAST_CAPACITY_DEFAULT] messages per receiver.
+    ///
+    /// See [SubscriberBroadcast] for more information.
+    pub fn broadcast(self) -> SubscriberBroadcast<D::Item> {
+        self.broadcast_with_capacity(BROADCAST_CAPACITY_DEFAULT)
+    }
+
+    /// Consumes the subscriber to fan out its messages to multiple receivers, buffering up to
+    /// `capacity` messages per receiver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn broadcast_with_capacity(self, capacity: usize) -> SubscriberBroadcast<D::Item> {
+        SubscriberBroadcast::spawn(self, capacity)
+    }
+}
+
+impl<D> KeepAlive<Subscriber<D>>
+where
+    D: MessageDecoder + Send + Unpin + 'static,
+    D::Item: Clone + Send + Unpin + 'static,
+{
+    /// See [broadcast](Subscriber::broadcast) in [Subscriber].
+    pub fn broadcast(self) -> SubscriberBroadcast<D::Item> {
+        self.broadcast_with_capacity(BROADCAST_CAPACITY_DEFAULT)
+    }
+
+    /// See [broadcast_with_capacity](Subscriber::broadcast_with_capacity) in [Subscriber].
+    pub fn broadcast_with_capacity(self, capacity: usize) -> SubscriberBroadcast<D::Item> {
+        SubscriberBroadcast::spawn(self, capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pubsub::in_memory;
+    use selium_std::codecs::StringCodec;
+
+    #[tokio::test]
+    async fn receivers_see_every_message() {
+        let (mut publisher, subscriber) = in_memory(StringCodec, StringCodec);
+        let broadcast = subscriber.broadcast();
+        let mut first = broadcast.subscribe();
+        let mut second = first.clone();
+        drop(broadcast);
+
+        let messages = vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()];
+        publisher.send_all(messages.clone()).await.unwrap();
+        publisher.finish().await.unwrap();
+
+        for receiver in [&mut first, &mut second] {
+            for message in &messages {
+                assert_eq!(receiver.recv().await.as_ref(), Some(message));
+            }
+
+            assert_eq!(receiver.recv().await, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn lagging_receiver_skips_oldest_messages() {
+        let (mut publisher, subscriber) = in_memory(StringCodec, StringCodec);
+        let broadcast = subscriber.broadcast_with_capacity(2);
+        let mut receiver = broadcast.subscribe();
+        drop(broadcast);
+
+        let messages = vec!["one".to_owned(), "two".to_owned(), "three".to_owned()];
+        publisher.send_all(messages).await.unwrap();
+        publisher.finish().await.unwrap();
+
+        assert_eq!(receiver.recv().await.as_deref(), Some("two"));
+        assert_eq!(receiver.recv().await.as_deref(), Some("three"));
+        assert_eq!(receiver.recv().await, None);
+    }
+}
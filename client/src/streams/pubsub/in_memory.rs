@@ -0,0 +1,97 @@
+use super::{Publisher, Subscriber};
+use crate::streams::transport::InMemoryStream;
+use selium_protocol::{Offset, PublisherPayload, SubscriberPayload, TopicName};
+use selium_std::traits::codec::{MessageDecoder, MessageEncoder};
+
+const IN_MEMORY_NAMESPACE: &str = "selium";
+const IN_MEMORY_TOPIC: &str = "in_memory";
+
+/// Constructs a connected [Publisher] and [Subscriber] pair that communicate over an in-memory
+/// channel, rather than via a `Selium` server.
+///
+/// Every message sent by the Publisher is received by the Subscriber, making the pair suitable for
+/// testing application code that produces or consumes messages, without the overhead of
+/// running a server. As there is no server, the Publisher won't receive log offset
+/// acknowledgements, and neither stream supports compression or message batching.
+///
+/// Calling [duplicate](Publisher::duplicate) on the Publisher creates another Publisher that sends
+/// messages to the same Subscriber. Once all Publishers have been dropped or finished, the
+/// Subscriber stream ends.
+///
+/// # Examples
+/// ```
+/// use futures::{SinkExt, StreamExt};
+/// use selium::pubsub;
+/// use selium::std::codecs::StringCodec;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (mut publisher, mut subscriber) = pubsub::in_memory(StringCodec, StringCodec);
+///
+/// publisher.send("Hello, world!".to_owned()).await?;
+/// assert_eq!(subscriber.next().await.unwrap()?, "Hello, world!");
+/// # Ok(())
+/// # }
+/// ```
+pub fn in_memory<E, D>(encoder: E, decoder: D) -> (Publisher<E>, Subscriber<D>)
+where
+    E: MessageEncoder + Clone + Send + Unpin,
+    D: MessageDecoder + Send + Unpin,
+{
+    let (publisher_stream, subscriber_stream) = InMemoryStream::pair();
+    let topic = TopicName::_create_unchecked(IN_MEMORY_NAMESPACE, IN_MEMORY_TOPIC);
+
+    let publisher_headers = PublisherPayload {
+        topic: topic.clone(),
+        retention_policy: 0,
+        operations: vec![],
+        compression: None,
+    };
+
+    let subscriber_headers = SubscriberPayload {
+        topic,
+        retention_policy: 0,
+        operations: vec![],
+        offset: Offset::FromBeginning(0),
+        compression: None,
+        filter_headers: None,
+        group: None,
+        prioritise: false,
+    };
+
+    let publisher = Publisher::in_memory(publisher_stream, publisher_headers, encoder);
+    let subscriber = Subscriber::in_memory(subscriber_stream, subscriber_headers, decoder);
+
+    (publisher, subscriber)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt, TryStreamExt};
+    use selium_std::codecs::StringCodec;
+
+    #[tokio::test]
+    async fn subscriber_receives_published_messages() {
+        let (mut publisher, subscriber) = in_memory(StringCodec, StringCodec);
+        let messages = vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()];
+
+        publisher.send_all(messages.clone()).await.unwrap();
+        publisher.finish().await.unwrap();
+
+        let received: Vec<_> = subscriber.try_collect().await.unwrap();
+        assert_eq!(received, messages);
+    }
+
+    #[tokio::test]
+    async fn duplicated_publishers_send_to_same_subscriber() {
+        let (mut publisher, mut subscriber) = in_memory(StringCodec, StringCodec);
+        let mut duplicate = publisher.duplicate().await.unwrap();
+
+        publisher.send("foo".to_owned()).await.unwrap();
+        duplicate.send("bar".to_owned()).await.unwrap();
+
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "foo");
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "bar");
+    }
+}
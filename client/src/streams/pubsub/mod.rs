@@ -1,8 +1,18 @@
 //! Asynchronous Pub/Sub streams.
 
+mod broadcast;
+mod in_memory;
 mod publisher;
+mod raw;
 mod subscriber;
+mod unreliable;
 
 pub(crate) mod states;
-pub use publisher::Publisher;
+pub use broadcast::{BroadcastReceiver, SubscriberBroadcast};
+pub use in_memory::in_memory;
+pub(crate) use publisher::check_reserved_headers;
+pub use publisher::{Publisher, ReservedWrite};
+pub use raw::RawDecoder;
+pub use selium_protocol::{Offset, Signal};
 pub use subscriber::Subscriber;
+pub use unreliable::UnreliableSubscriber;
@@ -1,24 +1,36 @@
 use super::states::{PublisherWantsEncoder, PublisherWantsOpen};
-use crate::batching::{BatchConfig, MessageBatch};
+use crate::batching::{BatchConfig, BatchStats, FlushTrigger, MessageBatch};
 use crate::connection::{ClientConnection, SharedConnection};
 use crate::keep_alive::pubsub::KeepAlive;
-use crate::keep_alive::AttemptFut;
+use crate::keep_alive::{AttemptFut, BackoffStrategy, EventSender, ReplayBuffer, ReplayConfig};
+use crate::logging;
 use crate::streams::aliases::Comp;
-use crate::streams::handle_reply;
+use crate::streams::transport::{InMemoryStream, Transport};
+use crate::streams::{handle_reply, open_bistream, open_unistream};
 use crate::traits::{KeepAliveStream, Open, Operations, Retain, TryIntoU64};
 use crate::{Client, StreamBuilder};
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::{Sink, SinkExt};
+use futures::future::{poll_fn, Either};
+use futures::{ready, Sink, SinkExt, Stream, StreamExt};
 use selium_protocol::utils::encode_message_batch;
-use selium_protocol::{BatchPayload, BiStream, Frame, MessagePayload, PublisherPayload, TopicName};
-use selium_std::errors::{CodecError, Result, SeliumError};
+use selium_protocol::{
+    split_message, AckPayload, BatchPayload, BiStream, ChunkPayload, Datagram, ErrorPayload, Frame,
+    MessagePayload, PublisherPayload, ReservationPayload, SequenceId, TopicName, WriteHalf,
+    COMPRESSED_HEADER, EVENT_TIME_HEADER, KEY_HEADER, MAX_CHUNK_SIZE, PRIORITY_HEADER,
+    RESERVED_HEADER_PREFIX,
+};
+use selium_std::encoding::BincodeConfig;
+use selium_std::errors::{CodecError, QuicError, Result, SeliumError};
 use selium_std::traits::codec::MessageEncoder;
 use selium_std::traits::compression::Compress;
-use std::pin::Pin;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::{pin, Pin};
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::MutexGuard;
 
 impl StreamBuilder<PublisherWantsEncoder> {
@@ -53,6 +65,18 @@ impl<E> StreamBuilder<PublisherWantsOpen<E>> {
         self
     }
 
+    /// Only compresses messages of at least `bytes` bytes once encoded, sending smaller messages
+    /// uncompressed, as compressing them wastes CPU and can even inflate them. Has no effect
+    /// unless compression is enabled via [with_compression](Self::with_compression).
+    ///
+    /// Uncompressed messages are marked via their headers, so that subscribers know not to
+    /// decompress them. Message batches, and messages large enough to be sent in chunks, are
+    /// always compressed.
+    pub fn with_compression_threshold(mut self, bytes: usize) -> Self {
+        self.state.compression_threshold = bytes;
+        self
+    }
+
     /// Enables message batching for a [Publisher] stream.
     ///
     /// Relies on the specified [BatchConfig](crate::batching::BatchConfig) to tune the batching
@@ -64,6 +88,88 @@ impl<E> StreamBuilder<PublisherWantsOpen<E>> {
         self.state.batch_config = Some(config);
         self
     }
+
+    /// Assigns a time-to-live to every message produced by a [Publisher] stream.
+    ///
+    /// Once a message's time-to-live has elapsed, the `Selium` server will stop delivering it to
+    /// [Subscriber](crate::streams::pubsub::Subscriber) streams, even if the topic's retention
+    /// policy would otherwise retain it. This allows ephemeral messages to share a topic with
+    /// long-lived messages. If message batching is enabled, the time-to-live applies to each batch
+    /// as a whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided time-to-live fails to be converted to a [u64].
+    pub fn with_message_ttl<T: TryIntoU64>(mut self, ttl: T) -> Result<Self> {
+        self.state.message_ttl = Some(ttl.try_into_u64()?);
+        Ok(self)
+    }
+
+    /// Enables idempotent publishing for a [Publisher] stream.
+    ///
+    /// Each message (or batch, if message batching is enabled) is tagged with the provided
+    /// `producer_id` and a monotonically increasing sequence, starting at `next_sequence`. The
+    /// `Selium` server discards any message whose `(producer_id, sequence)` pair it has already
+    /// written to the topic within its deduplication window.
+    ///
+    /// To avoid duplicates when retrying after an ambiguous failure, persist the value of
+    /// [Publisher::next_sequence] alongside your own checkpoints, and reopen the stream with the
+    /// same `producer_id` and the sequence of the first message that may not have been written.
+    ///
+    /// **Note:** Deduplication state is held in memory by the server, so duplicates sent after
+    /// the server has restarted will not be discarded.
+    pub fn with_deduplication(mut self, producer_id: &str, next_sequence: u64) -> Self {
+        self.state.sequence = Some(SequenceId {
+            producer_id: producer_id.to_owned(),
+            sequence: next_sequence,
+        });
+        self
+    }
+
+    /// Buffers messages sent while a [Publisher] stream is reconnecting to the `Selium` server,
+    /// and replays them once reconnected.
+    ///
+    /// See [ReplayConfig](crate::keep_alive::ReplayConfig) for more information, including the
+    /// delivery guarantees for buffered messages.
+    pub fn with_replay(mut self, config: ReplayConfig) -> Self {
+        self.state.replay_config = Some(config);
+        self
+    }
+
+    /// Sets the priority of messages sent by the [Publisher] stream, relative to the other
+    /// streams opened by the same [Client](crate::Client).
+    ///
+    /// Priorities are only meaningful in relation to each other: when several streams have data
+    /// waiting to be sent over the connection, data on higher priority streams is sent first,
+    /// while streams of equal priority share the connection fairly. Streams default to a priority
+    /// of 0, so a bulk publisher can be given a negative priority to avoid starving other
+    /// streams, without having to prioritize every other stream.
+    ///
+    /// Priorities only govern the order in which this client sends data, and don't affect how
+    /// the `Selium` server schedules the data it sends.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.state.priority = priority;
+        self
+    }
+
+    /// Registers the [Publisher] over a unidirectional QUIC stream, rather than a bidirectional
+    /// stream, for fire-and-forget publishing.
+    ///
+    /// One-way streams are cheaper for the `Selium` server to hold open, as it keeps no state for
+    /// sending to them, which suits topics with a large number of publishers. In exchange, the
+    /// server can't reply to the publisher: the stream is opened without waiting for the server to
+    /// accept it, messages aren't acknowledged, so [last_offset](Publisher::last_offset) is never
+    /// set, and messages the server refuses to write are discarded without an error.
+    /// [reserve](Publisher::reserve) always fails, as it waits for the server to reply.
+    ///
+    /// If the server refuses the stream, e.g. because the publisher isn't authorized, it stops
+    /// the stream, and the next message sent fails with an
+    /// [OpenStream](crate::std::errors::SeliumError::OpenStream) error carrying the reason. Any
+    /// messages sent before then are lost.
+    pub fn one_way(mut self) -> Self {
+        self.state.one_way = true;
+        self
+    }
 }
 
 impl<E> Retain for StreamBuilder<PublisherWantsOpen<E>> {
@@ -99,17 +205,28 @@ where
             topic,
             retention_policy: self.state.common.retention_policy,
             operations: self.state.common.operations,
+            compression: self
+                .state
+                .compression
+                .as_ref()
+                .map(|comp| comp.algorithm().to_owned()),
         };
 
-        let publisher = Publisher::spawn(
+        let mut publisher = Publisher::spawn(
             self.client,
-            headers,
+            (headers, self.state.one_way),
             self.state.encoder,
             self.state.compression,
             self.state.batch_config,
+            self.state.message_ttl,
+            self.state.sequence,
         )
         .await?;
 
+        publisher.set_replay(self.state.replay_config);
+        publisher.compression_threshold = self.state.compression_threshold;
+        publisher.set_priority(self.state.priority)?;
+
         Ok(publisher)
     }
 }
@@ -129,16 +246,39 @@ where
 /// to the [Subscriber](crate::streams::pubsub::Subscriber) streams. If you prefer synchronous messaging patterns like RPC,
 /// the [Request/Reply](crate::streams::request_reply) streams are an implementation of this pattern.
 ///
+/// Messages that are too large to be sent in a single frame are transparently split into chunks,
+/// which are reassembled by the [Subscriber](crate::streams::pubsub::Subscriber) before being
+/// decoded, so multi-megabyte messages can be published without any special handling. Each chunk
+/// is written to the topic's log as its own entry. Chunking only applies to messages sent
+/// without batching, and chunked messages are not tagged for deduplication.
+///
+/// Although the Publisher doesn't wait for delivery, the `Selium` server acknowledges the log
+/// offset assigned to each message it receives. The most recently acknowledged offset can be
+/// retrieved via [Publisher::last_offset], and can be correlated with the offsets read by
+/// [Subscriber](crate::streams::pubsub::Subscriber) streams.
+///
 /// **Note:** The Publisher struct is never constructed directly, but rather, via a
 /// [StreamBuilder](crate::StreamBuilder).
 pub struct Publisher<E> {
-    client: Client,
-    stream: BiStream,
+    client: Option<Client>,
+    /// The sender of the [ConnectionEvent](crate::keep_alive::ConnectionEvent)s reported by this
+    /// stream's [KeepAlive], shared with any duplicates of the stream.
+    events: EventSender,
+    stream: Transport,
     headers: PublisherPayload,
     encoder: E,
     compression: Option<Comp>,
+    compression_threshold: usize,
+    priority: i32,
+    one_way: bool,
     batch: Option<MessageBatch>,
     batch_config: Option<BatchConfig>,
+    message_ttl: Option<u64>,
+    sequence: Option<SequenceId>,
+    last_offset: Option<u64>,
+    replay: Option<ReplayBuffer>,
+    disconnected: bool,
+    next_message_id: u64,
 }
 
 impl<E> Publisher<E>
@@ -147,26 +287,44 @@ where
 {
     async fn spawn(
         client: Client,
-        headers: PublisherPayload,
+        (headers, one_way): (PublisherPayload, bool),
         encoder: E,
         compression: Option<Comp>,
         batch_config: Option<BatchConfig>,
+        message_ttl: Option<u64>,
+        sequence: Option<SequenceId>,
     ) -> Result<KeepAlive<Self>> {
         let batch = batch_config.as_ref().map(|c| MessageBatch::from(c.clone()));
         let lock = client.connection.lock().await;
-        let stream = Self::open_stream(lock, headers.clone()).await?;
+        let stream = Self::open_stream(lock, (headers.clone(), one_way))
+            .await?
+            .into();
 
         let publisher = Self {
-            client: client.clone(),
+            client: Some(client.clone()),
+            events: client.events.clone(),
             stream,
             headers,
             encoder,
             compression,
+            compression_threshold: 0,
+            priority: 0,
+            one_way,
             batch,
             batch_config,
+            message_ttl,
+            sequence,
+            last_offset: None,
+            replay: None,
+            disconnected: false,
+            next_message_id: random_message_id(),
         };
 
-        Ok(KeepAlive::new(publisher, client.backoff_strategy))
+        Ok(KeepAlive::new(
+            publisher,
+            client.backoff_strategy,
+            client.events,
+        ))
     }
 
     /// Spawns a new [Publisher] stream with the same configuration as the current stream, without
@@ -178,22 +336,367 @@ where
     ///
     /// See the included examples in the repository for more information.
     ///
+    /// Duplicated streams do not inherit the producer id assigned via
+    /// [with_deduplication](StreamBuilder::with_deduplication), as concurrent streams sharing a
+    /// producer id would produce conflicting sequences.
+    ///
     /// # Errors
     ///
     /// Returns [Err] if a new stream cannot be opened on the current client connection.
     pub async fn duplicate(&self) -> Result<KeepAlive<Self>> {
-        let publisher = Publisher::spawn(
-            self.client.clone(),
-            self.headers.clone(),
+        let client = match (&self.client, &self.stream) {
+            (Some(client), _) => client.clone(),
+            (None, Transport::InMemory(stream)) => {
+                let mut publisher = Self::in_memory(
+                    stream.duplicate(),
+                    self.headers.clone(),
+                    self.encoder.clone(),
+                );
+                publisher.events = self.events.clone();
+
+                // In-memory streams have no connection to re-establish
+                let backoff_strategy = BackoffStrategy::constant().with_max_attempts(0);
+                let events = publisher.events.clone();
+                return Ok(KeepAlive::new(publisher, backoff_strategy, events));
+            }
+            (None, _) => unreachable!(),
+        };
+
+        let mut publisher = Publisher::spawn(
+            client,
+            (self.headers.clone(), self.one_way),
             self.encoder.clone(),
             self.compression.clone(),
             self.batch_config.clone(),
+            self.message_ttl,
+            None,
         )
         .await?;
 
+        publisher.set_replay(self.replay.as_ref().map(ReplayBuffer::config));
+        publisher.compression_threshold = self.compression_threshold;
+        publisher.set_priority(self.priority)?;
+
         Ok(publisher)
     }
 
+    /// Sends every item yielded by `items` to the stream, and then flushes the stream once all
+    /// items have been sent.
+    ///
+    /// If message batching is enabled for the stream, items are pushed onto the current batch,
+    /// allowing the batcher to form optimally sized batches, and any partial batch remaining after
+    /// the final item will be sent before flushing. Backpressure is respected between each item.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if any item fails to be encoded or sent, or if the stream fails to flush.
+    /// If an error occurs mid-iteration, any items preceding the failed item may have already
+    /// been sent over the wire, while the remaining items will be discarded.
+    pub async fn send_all<I>(&mut self, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = E::Item>,
+    {
+        for item in items {
+            self.feed(item).await?;
+        }
+
+        self.flush().await
+    }
+
+    /// Sends every item yielded by the `source` stream, returning once the source has ended and
+    /// the stream has been flushed.
+    ///
+    /// This behaves in the same manner as [send_all](Publisher::send_all), but for asynchronous
+    /// sources such as another topic's [Subscriber](crate::pubsub::Subscriber). The source is only
+    /// polled for its next item once the stream is ready to accept it, so a slow connection
+    /// applies backpressure to the source.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] as soon as any item fails to be encoded or sent, or if the stream fails to
+    /// flush. The remainder of the source is left unconsumed.
+    pub async fn forward_from<S>(&mut self, source: S) -> Result<()>
+    where
+        S: Stream<Item = E::Item>,
+    {
+        let mut source = pin!(source);
+
+        while let Some(item) = source.next().await {
+            self.feed(item).await?;
+        }
+
+        self.flush().await
+    }
+
+    /// Forces any buffered messages out over the wire, without closing the stream.
+    ///
+    /// Unlike the [Sink](futures::Sink) implementation's `poll_flush`, if message batching is
+    /// enabled for the stream, `flush` will also send the current batch, even if the batch is not
+    /// yet full and the batching interval has not yet elapsed. This is useful for long-lived
+    /// publishers that need to deliver messages at the end of a logical unit of work.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the current batch fails to be sent, or if the stream fails to flush.
+    pub async fn flush(&mut self) -> Result<()> {
+        self.flush_batch()?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    /// Sends a message tagged with the time at which it was created, and then flushes the
+    /// stream.
+    ///
+    /// The event time is stored in the topic's log alongside the time the message was written,
+    /// and is used to expire the message and to resolve subscribers seeking by timestamp if the
+    /// server is configured to use event times, preserving the original timeline when the topic
+    /// is replayed.
+    ///
+    /// If message batching is enabled, the current batch is sent before the message, which is
+    /// sent in its own frame. Messages large enough to be sent in chunks are stored without an
+    /// event time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded or sent, or if the stream fails to flush.
+    pub async fn send_with_event_time(
+        &mut self,
+        item: E::Item,
+        event_time: SystemTime,
+    ) -> Result<()> {
+        self.flush_batch()?;
+        poll_fn(|cx| Sink::<E::Item>::poll_ready(Pin::new(&mut *self), cx)).await?;
+        self.start_send_with_event_time(item, event_time)?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    /// Sends a message with the provided headers, and then flushes the stream.
+    ///
+    /// Headers are stored in the topic's log alongside the message, so that subscribers can
+    /// [filter](crate::StreamBuilder::filter_header) messages by their headers. Headers beginning
+    /// with [RESERVED_HEADER_PREFIX] are reserved for Selium's own use.
+    ///
+    /// If message batching is enabled, the current batch is sent before the message, which is
+    /// sent in its own frame. Messages large enough to be sent in chunks are stored without
+    /// headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [SeliumError::ReservedHeaderError] if any of the headers are reserved. Otherwise,
+    /// returns [Err] if the message fails to be encoded or sent, or if the stream fails to flush.
+    pub async fn send_with_headers(
+        &mut self,
+        item: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<()> {
+        check_reserved_headers(&headers)?;
+        self.send_headers(item, headers).await
+    }
+
+    // Sends a message with the provided headers without checking for reserved headers, so that
+    // Selium's own headers can be attached
+    async fn send_headers(
+        &mut self,
+        item: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<()> {
+        self.flush_batch()?;
+        poll_fn(|cx| Sink::<E::Item>::poll_ready(Pin::new(&mut *self), cx)).await?;
+        self.start_send_with_headers(item, headers)?;
+        SinkExt::<E::Item>::flush(self).await
+    }
+
+    /// Sends a message tagged with the provided key, and then flushes the stream.
+    ///
+    /// Subscribers opened with [snapshot_then_live](crate::StreamBuilder::snapshot_then_live)
+    /// receive the latest message for each key when they connect. The key is stored as a header,
+    /// so is subject to the same limitations as
+    /// [send_with_headers](Publisher::send_with_headers).
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded or sent, or if the stream fails to flush.
+    pub async fn send_with_key(&mut self, item: E::Item, key: &str) -> Result<()> {
+        let headers = HashMap::from([(KEY_HEADER.to_owned(), key.to_owned())]);
+        self.send_headers(item, headers).await
+    }
+
+    /// Sends a message tagged with the provided priority, and then flushes the stream.
+    ///
+    /// Subscribers opened with [prioritise](crate::StreamBuilder::prioritise) receive higher
+    /// priority messages ahead of lower priority messages that were published before them, but
+    /// haven't been delivered yet. Messages without a priority have a priority of 0. The priority
+    /// is stored as a header, so is subject to the same limitations as
+    /// [send_with_headers](Publisher::send_with_headers).
+    ///
+    /// Unlike [with_priority](crate::StreamBuilder::with_priority), which prioritises the
+    /// publisher's stream over the client's other streams, this orders messages within the topic.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded or sent, or if the stream fails to flush.
+    pub async fn send_with_priority(&mut self, item: E::Item, priority: u8) -> Result<()> {
+        let headers = HashMap::from([(PRIORITY_HEADER.to_owned(), priority.to_string())]);
+        self.send_headers(item, headers).await
+    }
+
+    /// Writes a message to the topic's log in an uncommitted state, returning a [ReservedWrite]
+    /// once the server has assigned the message an offset.
+    ///
+    /// The reserved message isn't delivered to subscribers until it's committed via
+    /// [ReservedWrite::commit], and is discarded if it's aborted via [ReservedWrite::abort]. This
+    /// allows a message to be written before performing other work, such as publishing to other
+    /// topics, and only made visible once that work has succeeded.
+    ///
+    /// As messages are delivered in order, subscribers won't receive any messages written to the
+    /// topic after the reserved message until it has been committed or aborted, so reservations
+    /// should be short-lived. If the stream is closed or its connection is lost before the
+    /// message is committed, the server aborts the message.
+    ///
+    /// If message batching is enabled, the current batch is sent before the message is
+    /// reserved. Reserved messages are not tagged for deduplication, and are never buffered for
+    /// replay.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded or sent, if the server rejects the
+    /// message, or if the stream closes before the message has been reserved. Always returns
+    /// [Err] for [one_way](StreamBuilder::one_way) publishers.
+    pub async fn reserve(&mut self, item: E::Item) -> Result<ReservedWrite<'_, E>> {
+        if self.one_way {
+            return Err(SeliumError::OneWayStream);
+        }
+
+        self.flush_batch()?;
+
+        let (bytes, headers) = self.encode_message(item, HashMap::new())?;
+        let (bytes, headers) = self.compress_message(bytes, headers)?;
+
+        let frame = Frame::Reserve(MessagePayload {
+            headers,
+            message: bytes,
+            ttl: self.message_ttl,
+            offset: None,
+            sequence_id: None,
+        });
+        self.stream.send(frame).await?;
+
+        loop {
+            match self.stream.next().await {
+                Some(Ok(Frame::Reserved(ReservationPayload { offset }))) => {
+                    return Ok(ReservedWrite {
+                        publisher: self,
+                        offset,
+                    });
+                }
+                Some(Ok(Frame::Ack(AckPayload { offset }))) => {
+                    self.last_offset = Some(offset);
+                }
+                Some(Ok(Frame::Error(ErrorPayload { code, message }))) => {
+                    let message = String::from_utf8_lossy(&message).into_owned();
+                    return Err(SeliumError::MessageRejected(code.into(), message));
+                }
+                Some(Ok(_)) => (),
+                Some(Err(err)) => return Err(err),
+                None => return Err(SeliumError::TopicClosed),
+            }
+        }
+    }
+
+    /// Sends a message to the topic's [unreliable subscribers](crate::pubsub::UnreliableSubscriber)
+    /// as a QUIC datagram, bypassing the topic's log.
+    ///
+    /// Datagrams aren't retained, acknowledged or retransmitted, so the message is only received
+    /// by unreliable subscribers that are open when it arrives, and may be lost or delivered out
+    /// of order. Regular subscribers never receive the message. The message is sent immediately,
+    /// regardless of any message batching, and isn't tagged for deduplication or buffered for
+    /// replay.
+    ///
+    /// In-memory publishers send the message over their stream, like any other message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be encoded, if datagrams are disabled on either the
+    /// client or the server, or if the encoded message is larger than the connection's maximum
+    /// datagram size.
+    pub async fn send_unreliable(&mut self, item: E::Item) -> Result<()> {
+        let connection = match &self.client {
+            Some(client) => client.connection.clone(),
+            None => return SinkExt::send(self, item).await,
+        };
+
+        let mut bytes = self
+            .encoder
+            .encode(item)
+            .map_err(CodecError::EncodeFailure)?;
+
+        if let Some(comp) = &self.compression {
+            bytes = comp.compress(bytes).map_err(CodecError::CompressFailure)?;
+        }
+
+        let datagram = Datagram::new(self.headers.topic.clone(), bytes);
+        let bytes = datagram.to_bytes(&BincodeConfig::default())?;
+
+        connection
+            .lock()
+            .await
+            .conn()
+            .send_datagram(bytes)
+            .map_err(QuicError::SendDatagramError)?;
+
+        Ok(())
+    }
+
+    /// Returns the log offset of the most recent message acknowledged by the server, or
+    /// [None] if no messages have been acknowledged yet.
+    ///
+    /// Acknowledgements are received as the stream is polled, i.e. while sending or flushing
+    /// messages, so the returned offset may lag behind the most recently sent message.
+    ///
+    /// Messages from a single Publisher are written to the log in the order they are sent, so
+    /// offsets are monotonically increasing. When message batching is enabled, each batch is
+    /// written to the log as a single entry, meaning that every message in the batch shares the
+    /// same offset. Messages sent by [one_way](StreamBuilder::one_way) publishers are never
+    /// acknowledged.
+    pub fn last_offset(&self) -> Option<u64> {
+        self.last_offset
+    }
+
+    /// Returns the priority of the stream, as set by
+    /// [with_priority](StreamBuilder::with_priority) or [set_priority](Publisher::set_priority).
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Changes the priority of the stream, relative to the other streams opened by the same
+    /// [Client]. The new priority applies to any messages that are yet to be sent, and is kept
+    /// when the stream reconnects.
+    ///
+    /// See [with_priority](StreamBuilder::with_priority) for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream has already been closed.
+    pub fn set_priority(&mut self, priority: i32) -> Result<()> {
+        self.stream.set_priority(priority)?;
+        self.priority = priority;
+        Ok(())
+    }
+
+    /// Returns statistics describing the batches sent by the stream, or [None] if message
+    /// batching was not enabled via [with_batching](StreamBuilder::with_batching).
+    ///
+    /// See [BatchStats] for more information.
+    pub fn batch_stats(&self) -> Option<BatchStats> {
+        self.batch.as_ref().map(MessageBatch::stats)
+    }
+
+    /// Returns the sequence that will be assigned to the next message sent by an idempotent
+    /// publisher, or [None] if deduplication was not enabled via
+    /// [with_deduplication](StreamBuilder::with_deduplication).
+    pub fn next_sequence(&self) -> Option<u64> {
+        self.sequence.as_ref().map(|id| id.sequence)
+    }
+
     /// Gracefully closes the stream.
     ///
     /// It is highly recommended to invoke this method after no new messages will be
@@ -203,6 +706,10 @@ where
     /// Under the hood, `finish` calls the [finish](quinn::SendStream::finish) on the underlying
     /// [SendStream](quinn::SendStream).
     ///
+    /// If message batching is enabled, the current batch is sent before the stream is closed.
+    /// Dropping the publisher without calling `finish` or [flush](Publisher::flush) will discard
+    /// any messages in the current batch, and log a warning with the number of messages lost.
+    ///
     /// # Errors
     ///
     /// Returns [Err] if the stream fails to close gracefully.
@@ -211,36 +718,155 @@ where
         self.stream.finish().await
     }
 
+    pub(crate) fn in_memory(stream: InMemoryStream, headers: PublisherPayload, encoder: E) -> Self {
+        Self {
+            client: None,
+            events: EventSender::default(),
+            stream: Transport::InMemory(stream),
+            headers,
+            encoder,
+            compression: None,
+            compression_threshold: 0,
+            priority: 0,
+            one_way: false,
+            batch: None,
+            batch_config: None,
+            message_ttl: None,
+            sequence: None,
+            last_offset: None,
+            replay: None,
+            disconnected: false,
+            next_message_id: random_message_id(),
+        }
+    }
+
     async fn open_stream(
         connection: MutexGuard<'_, ClientConnection>,
-        headers: PublisherPayload,
-    ) -> Result<BiStream> {
-        let mut stream = BiStream::try_from_connection(connection.conn()).await?;
-        drop(connection);
-
+        (headers, one_way): (PublisherPayload, bool),
+    ) -> Result<Either<BiStream, WriteHalf>> {
         let frame = Frame::RegisterPublisher(headers);
+
+        // The server has no way to reply over a one-way stream, so there's nothing to wait for
+        if one_way {
+            let mut stream = open_unistream(connection).await?;
+            stream.send(frame).await?;
+            return Ok(Either::Right(stream));
+        }
+
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
         stream.send(frame).await?;
 
-        handle_reply(&mut stream).await?;
-        Ok(stream)
+        handle_reply(&mut stream, connect_timeout).await?;
+        Ok(Either::Left(stream))
     }
 
-    fn send_single(&mut self, mut bytes: Bytes) -> Result<()> {
-        if let Some(comp) = &self.compression {
-            bytes = comp.compress(bytes).map_err(CodecError::CompressFailure)?;
+    pub(crate) fn start_send_with_event_time(
+        &mut self,
+        item: E::Item,
+        event_time: SystemTime,
+    ) -> Result<()> {
+        let event_time = event_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let headers = HashMap::from([(EVENT_TIME_HEADER.to_owned(), event_time.to_string())]);
+
+        self.start_send_with_headers(item, headers)
+    }
+
+    pub(crate) fn start_send_with_headers(
+        &mut self,
+        item: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<()> {
+        let (bytes, headers) = self.encode_message(item, headers)?;
+        self.send_single(bytes, headers)
+    }
+
+    /// Encodes a message, along with any headers added by the encoder.
+    fn encode_message(
+        &self,
+        item: E::Item,
+        mut headers: HashMap<String, String>,
+    ) -> Result<(Bytes, Option<HashMap<String, String>>)> {
+        let bytes = self
+            .encoder
+            .encode_with_headers(item, &mut headers)
+            .map_err(CodecError::EncodeFailure)?;
+
+        Ok((bytes, Some(headers).filter(|headers| !headers.is_empty())))
+    }
+
+    fn send_single(
+        &mut self,
+        bytes: Bytes,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<()> {
+        let (bytes, headers) = self.compress_message(bytes, headers)?;
+
+        // In-memory streams don't encode frames, so they aren't subject to the frame size limit
+        if bytes.len() > MAX_CHUNK_SIZE && !matches!(self.stream, Transport::InMemory(_)) {
+            return self.send_chunks(bytes);
         }
 
         let frame = Frame::Message(MessagePayload {
-            headers: None,
+            headers,
             message: bytes,
+            ttl: self.message_ttl,
+            offset: None,
+            sequence_id: self.sequence.clone(),
         });
-        self.stream.start_send_unpin(frame)
+        self.send_frame(frame)?;
+        self.advance_sequence();
+
+        Ok(())
     }
 
-    fn send_batch(&mut self, now: Instant) -> Result<()> {
+    /// Compresses a single message if it meets the compression threshold, and otherwise marks it
+    /// as uncompressed in its headers.
+    fn compress_message(
+        &self,
+        bytes: Bytes,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<(Bytes, Option<HashMap<String, String>>)> {
+        let comp = match &self.compression {
+            Some(comp) => comp,
+            None => return Ok((bytes, headers)),
+        };
+
+        // Chunks don't carry headers, so messages that may need to be chunked are compressed
+        if bytes.len() >= self.compression_threshold || bytes.len() > MAX_CHUNK_SIZE {
+            let bytes = comp.compress(bytes).map_err(CodecError::CompressFailure)?;
+            return Ok((bytes, headers));
+        }
+
+        let mut headers = headers.unwrap_or_default();
+        headers.insert(COMPRESSED_HEADER.to_owned(), false.to_string());
+
+        Ok((bytes, Some(headers)))
+    }
+
+    fn send_chunks(&mut self, bytes: Bytes) -> Result<()> {
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        for (header, chunk) in split_message(message_id, bytes) {
+            let frame = Frame::MessageChunk(ChunkPayload {
+                header,
+                message: chunk,
+                ttl: self.message_ttl,
+                offset: None,
+            });
+            self.send_frame(frame)?;
+        }
+
+        Ok(())
+    }
+
+    fn send_batch(&mut self, now: Instant, trigger: FlushTrigger) -> Result<()> {
         let batch = self.batch.as_mut().unwrap();
 
-        let messages = batch.drain();
+        let messages = batch.drain(trigger);
         let batch_size = messages.len();
         let mut bytes = encode_message_batch(messages);
 
@@ -251,18 +877,77 @@ where
         let frame = Frame::BatchMessage(BatchPayload {
             size: batch_size as u32,
             message: bytes,
+            ttl: self.message_ttl,
+            offset: None,
+            first_offset: None,
+            sequence_id: self.sequence.clone(),
         });
 
-        self.stream.start_send_unpin(frame)?;
-        batch.update_last_run(now);
+        self.send_frame(frame)?;
+        self.batch.as_mut().unwrap().update_last_run(now);
+        self.advance_sequence();
 
         Ok(())
     }
 
-    fn flush_batch(&mut self) -> Result<()> {
+    fn set_replay(&mut self, config: Option<ReplayConfig>) {
+        self.replay = config.map(ReplayBuffer::from);
+    }
+
+    fn send_frame(&mut self, frame: Frame) -> Result<()> {
+        match self.replay.as_mut() {
+            Some(replay) if self.disconnected => {
+                replay.push(frame);
+                Ok(())
+            }
+            _ => self.stream.start_send_unpin(frame),
+        }
+    }
+
+    fn poll_replay(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if let Some(replay) = self.replay.as_mut() {
+            while !replay.is_empty() {
+                ready!(self.stream.poll_ready_unpin(cx))?;
+                let frame = replay.pop().unwrap();
+                self.stream.start_send_unpin(frame)?;
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn advance_sequence(&mut self) {
+        if let Some(id) = self.sequence.as_mut() {
+            id.sequence += 1;
+        }
+    }
+
+    // Polling for acknowledgements also surfaces a lost connection before any more messages are
+    // accepted, rather than only once they fail to be written.
+    fn poll_acks(&mut self, cx: &mut Context<'_>) -> Result<()> {
+        loop {
+            match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(Frame::Ack(AckPayload { offset })))) => {
+                    self.last_offset = Some(offset);
+                }
+                // The server refused to write a message, but the stream remains usable
+                Poll::Ready(Some(Ok(Frame::Error(ErrorPayload { code, message })))) => {
+                    let message = String::from_utf8_lossy(&message).into_owned();
+                    return Err(SeliumError::MessageRejected(code.into(), message));
+                }
+                Poll::Ready(Some(Ok(frame))) => logging::publisher::unexpected_frame(&frame),
+                Poll::Ready(Some(Err(err))) => return Err(err),
+                // The server has finished the stream, so no more messages will be accepted
+                Poll::Ready(None) => return Err(SeliumError::TopicClosed),
+                Poll::Pending => return Ok(()),
+            }
+        }
+    }
+
+    pub(crate) fn flush_batch(&mut self) -> Result<()> {
         if let Some(batch) = self.batch.as_ref() {
             if !batch.is_empty() {
-                self.send_batch(Instant::now())?;
+                self.send_batch(Instant::now(), FlushTrigger::Manual)?;
             }
         }
 
@@ -270,6 +955,95 @@ where
     }
 }
 
+/// Rejects headers under [RESERVED_HEADER_PREFIX], so that user headers can't be mistaken for
+/// Selium's own, such as a message's key, priority or event time.
+pub(crate) fn check_reserved_headers(headers: &HashMap<String, String>) -> Result<()> {
+    match headers
+        .keys()
+        .find(|key| key.starts_with(RESERVED_HEADER_PREFIX))
+    {
+        Some(key) => Err(SeliumError::ReservedHeaderError(key.clone())),
+        None => Ok(()),
+    }
+}
+
+// Chunks of messages from different publishers may be interleaved in the topic's log, so each
+// publisher starts numbering its chunked messages from a random id to keep them distinct
+fn random_message_id() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// A message reserved in a topic's log by [Publisher::reserve], which isn't delivered to
+/// subscribers until it has been committed.
+///
+/// The reservation holds on to its [Publisher] until it's resolved. Dropping a ReservedWrite
+/// without committing or aborting it leaves the message reserved until the publisher's stream is
+/// closed, at which point the server aborts the message.
+#[must_use = "reserved messages hold back delivery until they are committed or aborted"]
+pub struct ReservedWrite<'a, E> {
+    publisher: &'a mut Publisher<E>,
+    offset: u64,
+}
+
+impl<E> ReservedWrite<'_, E> {
+    /// The log offset reserved for the message.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Commits the message, making it visible to subscribers, once the server has acknowledged
+    /// the commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the commit fails to be sent, if the server rejects it, or if the stream
+    /// closes before the commit has been acknowledged. The server rejects the commit with
+    /// [ErrorCode::ReservationNotFound](selium_std::errors::ErrorCode::ReservationNotFound)
+    /// if the reservation has already been aborted, such as after the topic's reservation
+    /// timeout.
+    pub async fn commit(self) -> Result<()> {
+        let frame = Frame::Commit(ReservationPayload {
+            offset: self.offset,
+        });
+        self.resolve(frame).await
+    }
+
+    /// Aborts the message, so that it's never delivered to subscribers, once the server has
+    /// acknowledged the abort.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the abort fails to be sent, if the server rejects it, or if the stream
+    /// closes before the abort has been acknowledged.
+    pub async fn abort(self) -> Result<()> {
+        let frame = Frame::Abort(ReservationPayload {
+            offset: self.offset,
+        });
+        self.resolve(frame).await
+    }
+
+    async fn resolve(self, frame: Frame) -> Result<()> {
+        let publisher = self.publisher;
+        publisher.stream.send(frame).await?;
+
+        loop {
+            match publisher.stream.next().await {
+                Some(Ok(Frame::Ok)) => return Ok(()),
+                Some(Ok(Frame::Ack(AckPayload { offset }))) => {
+                    publisher.last_offset = Some(offset);
+                }
+                Some(Ok(Frame::Error(ErrorPayload { code, message }))) => {
+                    let message = String::from_utf8_lossy(&message).into_owned();
+                    return Err(SeliumError::MessageRejected(code.into(), message));
+                }
+                Some(Ok(_)) => (),
+                Some(Err(err)) => return Err(err),
+                None => return Err(SeliumError::TopicClosed),
+            }
+        }
+    }
+}
+
 impl<E> Sink<E::Item> for Publisher<E>
 where
     E: MessageEncoder + Clone + Send + Unpin,
@@ -277,11 +1051,20 @@ where
     type Error = SeliumError;
 
     fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_acks(cx)?;
+        ready!(self.poll_replay(cx))?;
+
         if let Some(batch) = self.batch.as_ref() {
             let now = Instant::now();
 
-            if batch.is_ready(now) {
-                self.send_batch(now)?;
+            if let Some(trigger) = batch.ready_trigger(now) {
+                // Bounded batches wait for the stream to become writable, applying backpressure
+                // to the producer rather than buffering frames without bound
+                if batch.is_bounded() {
+                    ready!(self.stream.poll_ready_unpin(cx))?;
+                }
+
+                self.send_batch(now, trigger)?;
             }
 
             return Poll::Ready(Ok(()));
@@ -291,20 +1074,25 @@ where
     }
 
     fn start_send(mut self: Pin<&mut Self>, item: E::Item) -> Result<(), Self::Error> {
-        let bytes = self
-            .encoder
-            .encode(item)
-            .map_err(CodecError::EncodeFailure)?;
+        let (bytes, headers) = self.encode_message(item, HashMap::new())?;
 
-        if let Some(batch) = self.batch.as_mut() {
-            batch.push(bytes);
-            Ok(())
-        } else {
-            self.send_single(bytes)
+        match self.batch.as_mut() {
+            Some(batch) if headers.is_none() => {
+                batch.push(bytes);
+                Ok(())
+            }
+            // Batches don't carry headers, so messages with headers from the encoder are sent on
+            // their own, after any messages that have already been batched
+            _ => {
+                self.flush_batch()?;
+                self.send_single(bytes, headers)
+            }
         }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_acks(cx)?;
+        ready!(self.poll_replay(cx))?;
         self.stream.poll_flush_unpin(cx)
     }
 
@@ -317,9 +1105,13 @@ impl<E> KeepAliveStream for Publisher<E>
 where
     E: MessageEncoder + Clone + Send + Unpin,
 {
-    type Headers = PublisherPayload;
+    type Headers = (PublisherPayload, bool);
+    type Stream = Either<BiStream, WriteHalf>;
 
-    fn reestablish_connection(connection: SharedConnection, headers: Self::Headers) -> AttemptFut {
+    fn reestablish_connection(
+        connection: SharedConnection,
+        headers: Self::Headers,
+    ) -> AttemptFut<Self::Stream> {
         Box::pin(async move {
             let mut connection = connection.lock().await;
             connection.reconnect().await?;
@@ -327,15 +1119,170 @@ where
         })
     }
 
-    fn on_reconnect(&mut self, stream: BiStream) {
+    fn on_reconnect(&mut self, stream: Self::Stream) {
+        let stream = Transport::from(stream);
+        // A failure means that the new stream has already closed, which will surface on the
+        // next send
+        let _ = stream.set_priority(self.priority);
         self.stream = stream;
+        self.disconnected = false;
     }
 
     fn get_connection(&self) -> SharedConnection {
-        self.client.connection.clone()
+        self.client
+            .as_ref()
+            .expect("In-memory streams never reconnect")
+            .connection
+            .clone()
     }
 
     fn get_headers(&self) -> Self::Headers {
-        self.headers.clone()
+        (self.headers.clone(), self.one_way)
+    }
+
+    fn on_disconnect(&mut self) {
+        self.disconnected = true;
+    }
+
+    fn poll_buffer(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.replay.is_none() {
+            return Poll::Pending;
+        }
+
+        // Move a ready batch into the buffer, so that batches can't grow without bound
+        if let Some(batch) = self.batch.as_ref() {
+            let now = Instant::now();
+
+            if let Some(trigger) = batch.ready_trigger(now) {
+                self.send_batch(now, trigger)?;
+            }
+        }
+
+        match self.replay.as_ref() {
+            Some(replay) if replay.has_capacity() => Poll::Ready(Ok(())),
+            _ => Poll::Pending,
+        }
+    }
+
+    fn max_reconnect_gap(&self) -> Option<Duration> {
+        self.replay.as_ref().and_then(ReplayBuffer::max_gap)
+    }
+}
+
+impl<E> Drop for Publisher<E> {
+    // Sending the pending batch requires polling the stream, which can't be done while dropping,
+    // so at least make the loss of any unsent messages visible.
+    fn drop(&mut self) {
+        if let Some(batch) = self.batch.as_ref() {
+            if !batch.is_empty() {
+                logging::publisher::dropped_unsent_messages(batch.len());
+            }
+        }
+
+        if let Some(replay) = self.replay.as_ref() {
+            if !replay.is_empty() {
+                logging::publisher::dropped_buffered_messages(replay.len());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::in_memory;
+    use super::*;
+    use selium_std::codecs::StringCodec;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn bounded_batches_throttle_producer_when_consumer_stalls() {
+        let (mut publisher, mut subscriber) = in_memory(StringCodec, StringCodec);
+        let config = BatchConfig::new(1_000, Duration::from_secs(60)).max_buffered_bytes(16);
+        publisher.batch = Some(MessageBatch::from(config.clone()));
+        publisher.batch_config = Some(config);
+
+        let messages = (0..1_000).map(|i| format!("message {i:03}"));
+        let sending =
+            tokio::time::timeout(Duration::from_millis(100), publisher.send_all(messages));
+
+        assert!(
+            sending.await.is_err(),
+            "producer should be throttled while the consumer is stalled"
+        );
+
+        // Once the consumer resumes, the producer can make progress again
+        tokio::spawn(async move { while subscriber.next().await.is_some() {} });
+        tokio::time::timeout(
+            Duration::from_millis(100),
+            publisher.send("message".to_owned()),
+        )
+        .await
+        .expect("producer should resume once the consumer catches up")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn batch_stats_reflect_sent_batches() {
+        let (mut publisher, mut subscriber) = in_memory(StringCodec, StringCodec);
+        let config = BatchConfig::new(3, Duration::from_secs(60));
+        publisher.batch = Some(MessageBatch::from(config.clone()));
+        publisher.batch_config = Some(config);
+
+        tokio::spawn(async move { while subscriber.next().await.is_some() {} });
+
+        let messages = (0..7).map(|i| format!("message {i}"));
+        publisher.send_all(messages).await.unwrap();
+
+        // Two full batches are sent as the batch size is reached, then the remainder is flushed
+        let stats = publisher.batch_stats().unwrap();
+        assert_eq!(stats.batches_sent(), 3);
+        assert_eq!(stats.messages_sent(), 7);
+        assert_eq!(stats.max_batch_size(), 3);
+        assert!((stats.average_batch_size() - 7.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(stats.flushes(FlushTrigger::BatchSize), 2);
+        assert_eq!(stats.flushes(FlushTrigger::Manual), 1);
+        assert_eq!(stats.flushes(FlushTrigger::Interval), 0);
+        assert_eq!(stats.flushes(FlushTrigger::BufferedBytes), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_reserved_headers() {
+        let (mut publisher, _subscriber) = in_memory(StringCodec, StringCodec);
+
+        for reserved in [
+            COMPRESSED_HEADER,
+            KEY_HEADER,
+            PRIORITY_HEADER,
+            EVENT_TIME_HEADER,
+        ] {
+            let headers = HashMap::from([(reserved.to_owned(), "1".to_owned())]);
+
+            let result = publisher
+                .send_with_headers("message".to_owned(), headers)
+                .await;
+
+            assert!(
+                matches!(result, Err(SeliumError::ReservedHeaderError(key)) if key == reserved)
+            );
+        }
+
+        // Selium's own headers are still attached by the dedicated methods
+        publisher
+            .send_with_key("message".to_owned(), "id")
+            .await
+            .unwrap();
+        publisher
+            .send_with_priority("message".to_owned(), 1)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn returns_topic_closed_once_stream_has_finished() {
+        let (mut publisher, subscriber) = in_memory(StringCodec, StringCodec);
+        drop(subscriber);
+
+        let result = publisher.send("message".to_owned()).await;
+        assert!(matches!(result, Err(SeliumError::TopicClosed)));
     }
 }
@@ -0,0 +1,27 @@
+use bytes::{Bytes, BytesMut};
+use selium_std::traits::codec::MessageDecoder;
+
+/// A decoder that leaves each message encoded exactly as it was published, for subscribers that
+/// forward messages to another system without needing to inspect them.
+///
+/// Batches are still split into their individual messages, and messages are still decompressed
+/// if the subscriber has been configured with a decompressor, so each item yielded by the
+/// [Subscriber](crate::pubsub::Subscriber) is the payload produced by the publisher's encoder.
+///
+/// See [raw](crate::StreamBuilder::raw) to open a [Subscriber](crate::pubsub::Subscriber) with
+/// this decoder.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawDecoder;
+
+/// Returns the payload as [Bytes](bytes::Bytes) without decoding it.
+///
+/// # Errors
+///
+/// Guaranteed not to error.
+impl MessageDecoder for RawDecoder {
+    type Item = Bytes;
+
+    fn decode(&self, buffer: &mut BytesMut) -> anyhow::Result<Self::Item> {
+        Ok(buffer.split().freeze())
+    }
+}
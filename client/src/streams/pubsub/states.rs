@@ -1,9 +1,11 @@
 use crate::{
     batching::BatchConfig,
-    streams::aliases::{Comp, Decomp},
+    constants::MAX_DECOMPRESSED_BYTES_DEFAULT,
+    keep_alive::ReplayConfig,
+    streams::aliases::{Comp, Decomp, SignalHandler},
     PubSubCommon,
 };
-use selium_protocol::Offset;
+use selium_protocol::{HeaderFilter, Offset, SequenceId, DEFAULT_MAX_REASSEMBLY_BYTES};
 
 #[doc(hidden)]
 pub struct SubscriberWantsDecoder {
@@ -23,7 +25,14 @@ pub struct SubscriberWantsOpen<D> {
     pub(crate) common: PubSubCommon,
     pub(crate) decoder: D,
     pub(crate) decompression: Option<Decomp>,
-    pub(crate) offset: Offset,
+    pub(crate) offset: Option<Offset>,
+    pub(crate) group: Option<String>,
+    pub(crate) signal_handler: Option<SignalHandler>,
+    pub(crate) max_reassembly_bytes: usize,
+    pub(crate) max_decompressed_bytes: usize,
+    pub(crate) header_filter: HeaderFilter,
+    pub(crate) skip_decode_errors: bool,
+    pub(crate) prioritise: bool,
 }
 
 impl<D> SubscriberWantsOpen<D> {
@@ -32,7 +41,14 @@ impl<D> SubscriberWantsOpen<D> {
             common: prev.common,
             decoder,
             decompression: None,
-            offset: Offset::default(),
+            offset: None,
+            group: None,
+            signal_handler: None,
+            max_reassembly_bytes: DEFAULT_MAX_REASSEMBLY_BYTES,
+            max_decompressed_bytes: MAX_DECOMPRESSED_BYTES_DEFAULT,
+            header_filter: HeaderFilter::default(),
+            skip_decode_errors: false,
+            prioritise: false,
         }
     }
 }
@@ -56,7 +72,13 @@ pub struct PublisherWantsOpen<E> {
     pub(crate) common: PubSubCommon,
     pub(crate) encoder: E,
     pub(crate) compression: Option<Comp>,
+    pub(crate) compression_threshold: usize,
     pub(crate) batch_config: Option<BatchConfig>,
+    pub(crate) message_ttl: Option<u64>,
+    pub(crate) sequence: Option<SequenceId>,
+    pub(crate) replay_config: Option<ReplayConfig>,
+    pub(crate) priority: i32,
+    pub(crate) one_way: bool,
 }
 
 impl<E> PublisherWantsOpen<E> {
@@ -65,7 +87,13 @@ impl<E> PublisherWantsOpen<E> {
             common: prev.common,
             encoder,
             compression: None,
+            compression_threshold: 0,
             batch_config: None,
+            message_ttl: None,
+            sequence: None,
+            replay_config: None,
+            priority: 0,
+            one_way: false,
         }
     }
 }
@@ -1,22 +1,32 @@
 use super::states::{SubscriberWantsDecoder, SubscriberWantsOpen};
+use super::RawDecoder;
 use crate::connection::{ClientConnection, SharedConnection};
+use crate::constants::MAX_DECOMPRESSED_BYTES_DEFAULT;
 use crate::keep_alive::pubsub::KeepAlive;
 use crate::keep_alive::AttemptFut;
-use crate::streams::aliases::Decomp;
-use crate::streams::handle_reply;
+use crate::logging;
+use crate::streams::aliases::{Decomp, SignalHandler};
+use crate::streams::transport::{InMemoryStream, Transport};
+use crate::streams::{error_from_payload, handle_reply, open_bistream};
 use crate::traits::{KeepAliveStream, Open, Operations, Retain, TryIntoU64};
 use crate::{Client, StreamBuilder};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
 use futures::{SinkExt, Stream, StreamExt};
+use selium_protocol::error_codes::TOPIC_CLOSED;
 use selium_protocol::utils::decode_message_batch;
-use selium_protocol::{BiStream, Frame, Offset, SubscriberPayload, TopicName};
-use selium_std::errors::{CodecError, Result};
+use selium_protocol::{
+    BiStream, ChunkAssembler, Frame, GroupOffsetPayload, Offset, Signal, SubscriberPayload,
+    TopicName,
+};
+use selium_std::errors::{CodecError, ErrorCode, Result, SeliumError};
 use selium_std::traits::codec::MessageDecoder;
 use selium_std::traits::compression::Decompress;
+use std::collections::{HashMap, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, Waker};
 use tokio::sync::MutexGuard;
 
 impl StreamBuilder<SubscriberWantsDecoder> {
@@ -32,6 +42,16 @@ impl StreamBuilder<SubscriberWantsDecoder> {
             client: self.client,
         }
     }
+
+    /// Opens a [Subscriber] that yields each message as the raw bytes produced by the
+    /// publisher's encoder, rather than decoding it.
+    ///
+    /// This avoids the cost of decoding and re-encoding messages that are only being forwarded
+    /// to another system. Offsets, seeking and decompression behave exactly as they would for
+    /// any other decoder.
+    pub fn raw(self) -> StreamBuilder<SubscriberWantsOpen<RawDecoder>> {
+        self.with_decoder(RawDecoder)
+    }
 }
 
 impl<D> StreamBuilder<SubscriberWantsOpen<D>> {
@@ -49,7 +69,144 @@ impl<D> StreamBuilder<SubscriberWantsOpen<D>> {
     }
 
     pub fn seek(mut self, offset: Offset) -> Self {
-        self.state.offset = offset;
+        self.state.offset = Some(offset);
+        self
+    }
+
+    /// Only delivers messages published after the [Subscriber] has been opened, without
+    /// replaying any messages already retained by the topic.
+    ///
+    /// Equivalent to seeking to [Offset::Latest]. The server resolves the offset before
+    /// acknowledging the stream, so any message published once `open` has returned will be
+    /// delivered.
+    pub fn live(self) -> Self {
+        self.seek(Offset::Latest)
+    }
+
+    /// Delivers the latest message for each key retained by the topic, followed by every message
+    /// published after the snapshot was taken, treating the topic as a table of keys and their
+    /// current values.
+    ///
+    /// Equivalent to seeking to [Offset::Snapshot]. Messages are keyed via
+    /// [send_with_key](crate::pubsub::Publisher::send_with_key), and messages without a key are
+    /// left out of the snapshot. The snapshot is delivered in the order its messages were written
+    /// to the topic, and once it has been delivered, the server sends a
+    /// [SnapshotComplete](Signal::SnapshotComplete) signal to the [on_signal](Self::on_signal)
+    /// handler. Every message that follows is delivered as it's published, including repeated
+    /// updates to the same key.
+    ///
+    /// If the subscriber reconnects before the snapshot is complete, the snapshot is taken again
+    /// from scratch, so some keys may be delivered twice.
+    pub fn snapshot_then_live(self) -> Self {
+        self.seek(Offset::Snapshot)
+    }
+
+    /// Joins the named consumer group, whose progress through the topic is stored by the server.
+    ///
+    /// Offsets are committed to the group via [commit](Subscriber::commit). Unless the
+    /// [Subscriber] is given an explicit offset via [seek](Self::seek), it resumes from the message
+    /// following the group's last committed offset, or from the end of the topic if the group
+    /// hasn't committed an offset yet. See [Offset::Committed].
+    ///
+    /// Every member of a group shares the same committed offset, which only ever moves forward,
+    /// so a commit behind an offset already committed by another member has no effect. Groups
+    /// don't divide messages between their members, so each member receives every message from
+    /// the offset it resumed from.
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.state.group = Some(group.to_owned());
+        self
+    }
+
+    /// Invokes `handler` with each [Signal] sent by the server, such as a
+    /// [SubscriberLagging](Signal::SubscriberLagging) warning once the subscriber has fallen too
+    /// far behind the topic.
+    ///
+    /// Signals are advisory, so they're never yielded by the [Subscriber] stream, and the stream
+    /// continues to deliver messages as usual. The handler is invoked while the stream is being
+    /// polled, so it should return promptly, e.g. by notifying another task.
+    pub fn on_signal<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Signal) + Send + Sync + 'static,
+    {
+        self.state.signal_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Limits the number of bytes the [Subscriber] buffers while reassembling messages that were
+    /// too large to be published in a single frame.
+    ///
+    /// Once the limit is reached, the oldest incomplete messages are discarded to make room, and
+    /// any single message exceeding the limit is discarded and yields an error.
+    ///
+    /// Defaults to 64MiB.
+    pub fn with_max_reassembly_bytes(mut self, max_bytes: usize) -> Self {
+        self.state.max_reassembly_bytes = max_bytes;
+        self
+    }
+
+    /// Limits the number of bytes that each message can decompress to, guarding against
+    /// payloads crafted to decompress to an excessive size.
+    ///
+    /// Decompression stops as soon as the limit is exceeded, and the message yields a
+    /// [DecompressFailure](CodecError::DecompressFailure) error. The limit applies to a batch of
+    /// messages as a whole, as batches are compressed together.
+    ///
+    /// Defaults to [MAX_DECOMPRESSED_BYTES_DEFAULT](crate::constants::MAX_DECOMPRESSED_BYTES_DEFAULT).
+    pub fn with_max_decompressed_bytes(mut self, max_bytes: usize) -> Self {
+        self.state.max_decompressed_bytes = max_bytes;
+        self
+    }
+
+    /// Only delivers messages whose `key` header is `value`. Messages are filtered by the server,
+    /// so those that don't match are never sent to the [Subscriber].
+    ///
+    /// Can be called multiple times for the same header to allow several values, or for several
+    /// headers, in which case a message must match every header. Messages lacking a filtered
+    /// header are excluded, unless [pass_through_missing_headers](Self::pass_through_missing_headers)
+    /// is called.
+    ///
+    /// Headers are attached to messages with
+    /// [send_with_headers](crate::pubsub::Publisher::send_with_headers). Batched and chunked
+    /// messages don't have headers.
+    pub fn filter_header(mut self, key: &str, value: &str) -> Self {
+        self.state.header_filter.allow(key, value);
+        self
+    }
+
+    /// Delivers messages lacking any of the headers filtered by
+    /// [filter_header](Self::filter_header), rather than excluding them.
+    pub fn pass_through_missing_headers(mut self) -> Self {
+        self.state.header_filter.pass_through_missing = true;
+        self
+    }
+
+    /// Skips messages that fail to be decoded, rather than yielding an error for them.
+    ///
+    /// Each message in a batch is decoded individually, so a corrupt message only costs the
+    /// [Subscriber] that message, and the rest of the batch is still delivered. Skipped messages
+    /// are counted by [decode_errors](Subscriber::decode_errors).
+    pub fn skip_decode_errors(mut self) -> Self {
+        self.state.skip_decode_errors = true;
+        self
+    }
+
+    /// Delivers higher priority messages ahead of lower priority messages that were published
+    /// before them, but haven't been delivered yet.
+    ///
+    /// Messages are tagged with a priority via
+    /// [send_with_priority](crate::pubsub::Publisher::send_with_priority). The topic still stores
+    /// its messages in a single log in the order they were published, so the server reorders the
+    /// messages it reads from the log for the [Subscriber] in windows of a fixed size, which may
+    /// be smaller if the server's read-ahead limit is. A message can only overtake those in its
+    /// own window, and never messages that have already been sent. Messages of the same priority
+    /// are delivered in the order they were published.
+    ///
+    /// After each window, the server sends a
+    /// [PriorityWindowComplete](Signal::PriorityWindowComplete) signal marking the offset that the
+    /// [Subscriber] resumes from if it reconnects. Messages delivered since the last signal may be
+    /// delivered again after reconnecting.
+    pub fn prioritise(mut self) -> Self {
+        self.state.prioritise = true;
         self
     }
 }
@@ -87,17 +244,34 @@ where
             topic,
             retention_policy: self.state.common.retention_policy,
             operations: self.state.common.operations,
-            offset: self.state.offset,
+            offset: self.state.offset.unwrap_or(match self.state.group {
+                Some(_) => Offset::Committed,
+                None => Offset::default(),
+            }),
+            compression: self
+                .state
+                .decompression
+                .as_ref()
+                .map(|decomp| decomp.algorithm().to_owned()),
+            filter_headers: Some(self.state.header_filter)
+                .filter(|filter| !filter.headers.is_empty()),
+            group: self.state.group,
+            prioritise: self.state.prioritise,
         };
 
-        let subscriber = Subscriber::spawn(
+        let mut subscriber = Subscriber::spawn(
             self.client,
             headers,
             self.state.decoder,
             self.state.decompression,
+            self.state.signal_handler,
+            self.state.max_reassembly_bytes,
+            self.state.max_decompressed_bytes,
         )
         .await?;
 
+        subscriber.skip_decode_errors = self.state.skip_decode_errors;
+
         Ok(subscriber)
     }
 }
@@ -108,15 +282,30 @@ where
 /// contexts as a [Stream](futures::Stream). Any messages polled on the stream will be decoded
 /// using the provided decoder.
 ///
+/// Consumption can be temporarily halted with [pause](Subscriber::pause), without closing the
+/// stream. While paused, messages are left unread on the stream, so the server will stop sending
+/// messages once the stream's buffers are full.
+///
 /// **Note:** The Subscriber struct is never constructed directly, but rather, via a
 /// [StreamBuilder](crate::StreamBuilder).
 pub struct Subscriber<D> {
-    client: Client,
-    stream: BiStream,
+    client: Option<Client>,
+    stream: Transport,
     headers: SubscriberPayload,
     decoder: D,
     decompression: Option<Decomp>,
-    message_batch: Option<Vec<Bytes>>,
+    max_decompressed_bytes: usize,
+    signal_handler: Option<SignalHandler>,
+    message_batch: Option<Vec<(Option<Bytes>, Option<u64>)>>,
+    /// Frames received while waiting for a commit to be confirmed, to be delivered before
+    /// reading from the stream again.
+    pending_frames: VecDeque<Frame>,
+    chunks: ChunkAssembler,
+    last_offset: Option<u64>,
+    paused: bool,
+    waker: Option<Waker>,
+    skip_decode_errors: bool,
+    decode_errors: u64,
 }
 
 impl<D> Subscriber<D>
@@ -128,45 +317,196 @@ where
         headers: SubscriberPayload,
         decoder: D,
         decompression: Option<Decomp>,
+        signal_handler: Option<SignalHandler>,
+        max_reassembly_bytes: usize,
+        max_decompressed_bytes: usize,
     ) -> Result<KeepAlive<Self>> {
         let lock = client.connection.lock().await;
         let stream = Self::open_stream(lock, headers.clone()).await?;
 
         let subscriber = Self {
-            client: client.clone(),
-            stream,
+            client: Some(client.clone()),
+            stream: stream.into(),
             headers,
             decoder,
             message_batch: None,
+            pending_frames: VecDeque::new(),
+            chunks: ChunkAssembler::new(max_reassembly_bytes),
+            last_offset: None,
             decompression,
+            max_decompressed_bytes,
+            signal_handler,
+            paused: false,
+            waker: None,
+            skip_decode_errors: false,
+            decode_errors: 0,
         };
 
-        Ok(KeepAlive::new(subscriber, client.backoff_strategy))
+        Ok(KeepAlive::new(
+            subscriber,
+            client.backoff_strategy,
+            client.events,
+        ))
+    }
+
+    pub(crate) fn in_memory(
+        stream: InMemoryStream,
+        headers: SubscriberPayload,
+        decoder: D,
+    ) -> Self {
+        Self {
+            client: None,
+            stream: Transport::InMemory(stream),
+            headers,
+            decoder,
+            decompression: None,
+            max_decompressed_bytes: MAX_DECOMPRESSED_BYTES_DEFAULT,
+            signal_handler: None,
+            message_batch: None,
+            pending_frames: VecDeque::new(),
+            chunks: ChunkAssembler::default(),
+            last_offset: None,
+            paused: false,
+            waker: None,
+            skip_decode_errors: false,
+            decode_errors: 0,
+        }
+    }
+
+    /// Stops delivering messages until [resume](Subscriber::resume) is called.
+    ///
+    /// The stream remains open while paused, and any messages that have already been received,
+    /// or are sent in the meantime, will be delivered once the subscriber is resumed.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes delivering messages from where the subscriber was paused.
+    pub fn resume(&mut self) {
+        self.paused = false;
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the subscriber has been paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns the log offset of the most recently received message, or [None] if no messages
+    /// have been received yet.
+    ///
+    /// The server assigns each message the next offset in the topic's log as it's written, so
+    /// offsets impose a total order on the messages from every publisher on the topic, including
+    /// [duplicated](crate::pubsub::Publisher::duplicate) publishers. This is the order in which
+    /// messages arrived at the server, rather than the order in which they were produced.
+    ///
+    /// Offsets strictly increase from one message to the next, except for messages published
+    /// together in a batch, which are written to the log as a single entry and share its offset.
+    pub fn last_offset(&self) -> Option<u64> {
+        self.last_offset
+    }
+
+    /// Returns the number of messages that have been skipped after failing to be decoded.
+    ///
+    /// Messages are only skipped if the subscriber was opened with
+    /// [skip_decode_errors](StreamBuilder::skip_decode_errors), so this is always 0 otherwise.
+    pub fn decode_errors(&self) -> u64 {
+        self.decode_errors
+    }
+
+    /// Commits `offset` to the subscriber's consumer group, marking every message up to and
+    /// including it as processed. Once committed, members of the group that resume from the
+    /// group's offset start from the following message.
+    ///
+    /// Messages published together in a batch share a single offset, so committing the offset of
+    /// any message in a batch commits the whole batch. Commits are ignored by the server if the
+    /// subscriber hasn't joined a group via [with_group](StreamBuilder::with_group).
+    ///
+    /// Waits for the server to confirm that the offset has been persisted. Any messages that
+    /// arrive in the meantime are buffered, and delivered by the stream as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the commit fails to be sent to the server, if the server fails to persist
+    /// the offset, or if the stream ends before the commit is confirmed.
+    pub async fn commit(&mut self, offset: u64) -> Result<()> {
+        let frame = Frame::CommitGroupOffset(GroupOffsetPayload { offset });
+        self.stream.send(frame).await?;
+
+        // In-memory streams have no server to confirm the commit
+        if let Transport::InMemory(_) = self.stream {
+            return Ok(());
+        }
+
+        loop {
+            match self.stream.next().await {
+                Some(Ok(Frame::Ack(_))) => return Ok(()),
+                // The topic has been closed, so leave the error for the stream to surface too
+                Some(Ok(Frame::Error(payload))) if payload.code == TOPIC_CLOSED => {
+                    self.pending_frames.push_back(Frame::Error(payload));
+                    return Err(SeliumError::TopicClosed);
+                }
+                Some(Ok(Frame::Error(payload))) => return Err(error_from_payload(payload)),
+                Some(Ok(frame)) => self.pending_frames.push_back(frame),
+                Some(Err(err)) => return Err(err),
+                None => return Err(SeliumError::TopicClosed),
+            }
+        }
     }
 
     async fn open_stream(
         connection: MutexGuard<'_, ClientConnection>,
         headers: SubscriberPayload,
     ) -> Result<BiStream> {
-        let mut stream = BiStream::try_from_connection(connection.conn()).await?;
-        drop(connection);
+        let (mut stream, connect_timeout) = open_bistream(connection).await?;
 
         let frame = Frame::RegisterSubscriber(headers);
         stream.send(frame).await?;
 
-        handle_reply(&mut stream).await?;
+        handle_reply(&mut stream, connect_timeout)
+            .await
+            .map_err(|err| match err {
+                // Surface the mismatch as a decompression error, as that's what the subscriber would
+                // otherwise encounter when decoding messages
+                SeliumError::OpenStream(ErrorCode::CompressionMismatch, msg) => {
+                    CodecError::DecompressFailure(anyhow!(msg)).into()
+                }
+                err => err,
+            })?;
         Ok(stream)
     }
 
-    fn decode_message(&mut self, bytes: Bytes) -> Poll<Option<Result<D::Item>>> {
-        let mut mut_bytes = BytesMut::with_capacity(bytes.len());
-        mut_bytes.extend_from_slice(&bytes);
-
-        let decoded = self
-            .decoder
-            .decode(&mut mut_bytes)
-            .map_err(CodecError::DecodeFailure)?;
-        Poll::Ready(Some(Ok(decoded)))
+    /// Decodes a message, returning [None] if it couldn't be decoded and decode errors are being
+    /// skipped.
+    fn decode_message(
+        &mut self,
+        bytes: Option<Bytes>,
+        offset: Option<u64>,
+        headers: Option<&HashMap<String, String>>,
+    ) -> Option<Result<D::Item>> {
+        self.last_offset = offset.or(self.last_offset);
+
+        // A truncated batch message is missing, so there is nothing to decode
+        let decoded = bytes
+            .ok_or_else(|| anyhow!("Batched message was truncated"))
+            .and_then(|bytes| {
+                let mut mut_bytes = BytesMut::with_capacity(bytes.len());
+                mut_bytes.extend_from_slice(&bytes);
+                self.decoder.decode_with_headers(&mut mut_bytes, headers)
+            })
+            .map_err(|err| SeliumError::from(CodecError::DecodeFailure(err)));
+
+        match decoded {
+            Err(err) if self.skip_decode_errors => {
+                self.decode_errors += 1;
+                logging::subscriber::skipped_decode_error(&err);
+                None
+            }
+            decoded => Some(decoded),
+        }
     }
 }
 
@@ -177,45 +517,137 @@ where
     type Item = Result<D::Item>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        // Attempt to pop a message off of the current batch, if available.
-        if let Some(bytes) = self.message_batch.as_mut().and_then(|b| b.pop()) {
-            return self.decode_message(bytes);
-        }
-
-        // Otherwise, poll a new frame from the stream
-        let frame = match futures::ready!(self.stream.poll_next_unpin(cx)) {
-            Some(Ok(frame)) => frame,
-            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
-            None => return Poll::Ready(None),
-        };
+        // Frames that don't yield a message are handled iteratively, rather than by polling
+        // again recursively, so that a long run of them can't overflow the stack
+        loop {
+            // Leave messages on the stream until the subscriber is resumed.
+            if self.paused {
+                self.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
 
-        match frame {
-            // If the frame is a standard, unbatched message, then decode and return it
-            // immediately.
-            Frame::Message(mut payload) => {
-                if let Some(decomp) = &self.decompression {
-                    payload.message = decomp
-                        .decompress(payload.message)
-                        .map_err(CodecError::DecompressFailure)?;
+            // Attempt to pop a message off of the current batch, if available.
+            if let Some((bytes, offset)) = self.message_batch.as_mut().and_then(|b| b.pop()) {
+                match self.decode_message(bytes, offset, None) {
+                    Some(decoded) => return Poll::Ready(Some(decoded)),
+                    None => continue,
                 }
+            }
 
-                self.decode_message(payload.message)
+            // Otherwise, take the next frame, starting with any received while committing
+            let frame = match self.pending_frames.pop_front() {
+                Some(frame) => frame,
+                None => match futures::ready!(self.stream.poll_next_unpin(cx)) {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                    None => return Poll::Ready(None),
+                },
+            };
+
+            // Track the last delivered offset, so that reconnecting resumes from the following
+            // message rather than replaying the stream from the original offset. Snapshots and
+            // prioritised messages aren't delivered in log order, so they're tracked via signals
+            // instead.
+            if let Some(offset) = frame
+                .offset()
+                .filter(|_| self.headers.offset != Offset::Snapshot && !self.headers.prioritise)
+            {
+                self.headers.offset = Offset::FromBeginning(offset + 1);
             }
-            // If the frame is a batched message, then set the current batch and call `poll_next`
-            // again to begin popping off messages.
-            Frame::BatchMessage(mut payload) => {
-                if let Some(decomp) = &self.decompression {
-                    payload.message = decomp
-                        .decompress(payload.message)
-                        .map_err(CodecError::DecompressFailure)?;
-                }
 
-                let batch = decode_message_batch(payload.message);
-                self.message_batch = Some(batch);
-                self.poll_next(cx)
+            match frame {
+                // If the frame is a standard, unbatched message, then decode and return it
+                // immediately.
+                Frame::Message(mut payload) => {
+                    let decomp = self
+                        .decompression
+                        .as_ref()
+                        .filter(|_| payload.is_compressed());
+
+                    if let Some(decomp) = decomp {
+                        payload.message = decomp
+                            .decompress_with_limit(payload.message, self.max_decompressed_bytes)
+                            .map_err(CodecError::DecompressFailure)?;
+                    }
+
+                    let headers = payload.headers.as_ref();
+
+                    if let Some(decoded) =
+                        self.decode_message(Some(payload.message), payload.offset, headers)
+                    {
+                        return Poll::Ready(Some(decoded));
+                    }
+                }
+                // If the frame is a batched message, then set the current batch and carry on
+                // polling to begin popping off messages.
+                Frame::BatchMessage(mut payload) => {
+                    if let Some(decomp) = &self.decompression {
+                        payload.message = decomp
+                            .decompress_with_limit(payload.message, self.max_decompressed_bytes)
+                            .map_err(CodecError::DecompressFailure)?;
+                    }
+
+                    // Reverse the batch so that popping messages preserves the order in which they
+                    // were published.
+                    // Messages coalesced by the server each have their own offset, whereas messages
+                    // published as a batch share the offset of the batch.
+                    let mut batch = decode_message_batch(payload.message)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, message)| {
+                            let offset = payload
+                                .first_offset
+                                .map(|first| first + i as u64)
+                                .or(payload.offset);
+                            (message, offset)
+                        })
+                        .collect::<Vec<_>>();
+                    batch.reverse();
+                    self.message_batch = Some(batch);
+                }
+                // If the frame is a chunk of a larger message, then buffer it until the final chunk
+                // has arrived, and decode the reassembled message.
+                Frame::MessageChunk(payload) => {
+                    let mut message = match self.chunks.push(payload.header, payload.message)? {
+                        Some(message) => message,
+                        None => continue,
+                    };
+
+                    if let Some(decomp) = &self.decompression {
+                        message = decomp
+                            .decompress_with_limit(message, self.max_decompressed_bytes)
+                            .map_err(CodecError::DecompressFailure)?;
+                    }
+
+                    if let Some(decoded) = self.decode_message(Some(message), payload.offset, None)
+                    {
+                        return Poll::Ready(Some(decoded));
+                    }
+                }
+                // Signals are advisory, so hand them to the handler and carry on polling
+                Frame::Signal(signal) => {
+                    if let Signal::SnapshotComplete { offset }
+                    | Signal::PriorityWindowComplete { offset } = signal
+                    {
+                        self.headers.offset = Offset::FromBeginning(offset);
+                    }
+
+                    if let Some(handler) = &self.signal_handler {
+                        handler(signal);
+                    }
+                }
+                // The server has closed the topic, rather than the connection having been lost, so
+                // there is nothing to resume.
+                Frame::Error(payload) if payload.code == TOPIC_CLOSED => {
+                    return Poll::Ready(Some(Err(SeliumError::TopicClosed)));
+                }
+                // If the server has sent an error, surface it so that the caller can react to it.
+                Frame::Error(payload) => {
+                    return Poll::Ready(Some(Err(error_from_payload(payload))));
+                }
+                // Otherwise, do nothing.
+                _ => return Poll::Ready(None),
             }
-            // Otherwise, do nothing.
-            _ => Poll::Ready(None),
         }
     }
 
@@ -229,8 +661,12 @@ where
     D: MessageDecoder + Send + Unpin,
 {
     type Headers = SubscriberPayload;
+    type Stream = BiStream;
 
-    fn reestablish_connection(connection: SharedConnection, headers: Self::Headers) -> AttemptFut {
+    fn reestablish_connection(
+        connection: SharedConnection,
+        headers: Self::Headers,
+    ) -> AttemptFut<Self::Stream> {
         Box::pin(async move {
             let mut lock = connection.lock().await;
             lock.reconnect().await?;
@@ -239,14 +675,151 @@ where
     }
 
     fn on_reconnect(&mut self, stream: BiStream) {
-        self.stream = stream;
+        self.stream = stream.into();
     }
 
     fn get_connection(&self) -> SharedConnection {
-        self.client.connection.clone()
+        self.client
+            .as_ref()
+            .expect("In-memory streams never reconnect")
+            .connection
+            .clone()
     }
 
     fn get_headers(&self) -> Self::Headers {
         self.headers.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use selium_protocol::utils::encode_message_batch;
+    use selium_protocol::{BatchPayload, MessagePayload};
+    use selium_std::codecs::StringCodec;
+
+    fn subscriber(skip_decode_errors: bool) -> (Transport, Subscriber<StringCodec>) {
+        let (left, right) = InMemoryStream::pair();
+        let headers = SubscriberPayload {
+            topic: TopicName::_create_unchecked("selium", "test"),
+            retention_policy: 0,
+            operations: vec![],
+            offset: Offset::FromBeginning(0),
+            compression: None,
+            filter_headers: None,
+            group: None,
+            prioritise: false,
+        };
+
+        let mut subscriber = Subscriber::in_memory(right, headers, StringCodec);
+        subscriber.skip_decode_errors = skip_decode_errors;
+
+        (Transport::InMemory(left), subscriber)
+    }
+
+    fn batch(message: Bytes) -> Frame {
+        Frame::BatchMessage(BatchPayload {
+            size: 3,
+            message,
+            ttl: None,
+            offset: Some(2),
+            first_offset: Some(0),
+            sequence_id: None,
+        })
+    }
+
+    fn corrupt_batch() -> Frame {
+        batch(encode_message_batch(vec![
+            Bytes::from("first"),
+            Bytes::from_static(&[0xff, 0xfe]),
+            Bytes::from("third"),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn yields_batched_messages_in_published_order() {
+        let (mut tx, mut subscriber) = subscriber(false);
+        tx.send(batch(encode_message_batch(vec![
+            Bytes::from("first"),
+            Bytes::from("second"),
+            Bytes::from("third"),
+        ])))
+        .await
+        .unwrap();
+        drop(tx);
+
+        for (expected, offset) in [("first", 0), ("second", 1), ("third", 2)] {
+            assert_eq!(subscriber.next().await.unwrap().unwrap(), expected);
+            assert_eq!(subscriber.last_offset(), Some(offset));
+        }
+
+        assert!(subscriber.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn skips_corrupt_message_within_batch() {
+        let (mut tx, mut subscriber) = subscriber(true);
+        tx.send(corrupt_batch()).await.unwrap();
+        drop(tx);
+
+        let mut received = vec![];
+
+        while let Some(message) = subscriber.next().await {
+            received.push(message.unwrap());
+        }
+
+        assert_eq!(received, ["first", "third"]);
+        assert_eq!(subscriber.decode_errors(), 1);
+        assert_eq!(subscriber.last_offset(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn skips_long_run_of_corrupt_messages() {
+        let (mut tx, mut subscriber) = subscriber(true);
+        let mut messages = vec![Bytes::from_static(&[0xff, 0xfe]); 100_000];
+        messages.push(Bytes::from("last"));
+        tx.send(batch(encode_message_batch(messages)))
+            .await
+            .unwrap();
+        drop(tx);
+
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "last");
+        assert_eq!(subscriber.decode_errors(), 100_000);
+    }
+
+    #[tokio::test]
+    async fn yields_error_for_corrupt_message_within_batch() {
+        let (mut tx, mut subscriber) = subscriber(false);
+        tx.send(corrupt_batch()).await.unwrap();
+        drop(tx);
+
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "first");
+        assert!(subscriber.next().await.unwrap().is_err());
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "third");
+        assert!(subscriber.next().await.is_none());
+        assert_eq!(subscriber.decode_errors(), 0);
+    }
+
+    #[tokio::test]
+    async fn skips_truncated_message_within_batch() {
+        let (mut tx, mut subscriber) = subscriber(true);
+        let message = encode_message_batch(vec![Bytes::from("first"), Bytes::from("second")]);
+        tx.send(batch(message.slice(..message.len() - 1)))
+            .await
+            .unwrap();
+        tx.send(Frame::Message(MessagePayload {
+            headers: None,
+            message: Bytes::from("next"),
+            ttl: None,
+            offset: Some(3),
+            sequence_id: None,
+        }))
+        .await
+        .unwrap();
+        drop(tx);
+
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "first");
+        assert_eq!(subscriber.next().await.unwrap().unwrap(), "next");
+        assert_eq!(subscriber.decode_errors(), 1);
+    }
+}
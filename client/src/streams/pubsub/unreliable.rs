@@ -0,0 +1,146 @@
+use super::states::SubscriberWantsOpen;
+use crate::connection::DatagramReceiver;
+use crate::streams::aliases::Decomp;
+use crate::streams::{handle_reply, open_bistream};
+use crate::StreamBuilder;
+use bytes::{Bytes, BytesMut};
+use futures::stream::BoxStream;
+use futures::{SinkExt, Stream, StreamExt};
+use selium_protocol::{BiStream, Datagram, DatagramSubscriberPayload, Frame, TopicName};
+use selium_std::encoding::BincodeConfig;
+use selium_std::errors::{CodecError, Result};
+use selium_std::traits::codec::MessageDecoder;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::broadcast::error::RecvError;
+
+impl<D> StreamBuilder<SubscriberWantsOpen<D>>
+where
+    D: MessageDecoder + Send + Unpin,
+{
+    /// Opens an [UnreliableSubscriber], which receives the messages sent to the topic with
+    /// [send_unreliable](crate::pubsub::Publisher::send_unreliable).
+    ///
+    /// Only the decoder and decompression settings apply to unreliable subscribers, as their
+    /// messages bypass the topic's log.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream cannot be opened, or if datagrams have been disabled on either
+    /// the client or the server.
+    pub async fn open_unreliable(self) -> Result<UnreliableSubscriber<D>> {
+        let topic = TopicName::try_from(self.state.common.topic.as_str())?;
+        let mut lock = self.client.connection.lock().await;
+
+        // Listen for datagrams before registering, so that none are missed once the server has
+        // acknowledged the stream
+        let datagrams = lock.datagrams();
+        let (mut stream, connect_timeout) = open_bistream(lock).await?;
+
+        let frame = Frame::RegisterDatagramSubscriber(DatagramSubscriberPayload {
+            topic: topic.clone(),
+        });
+        stream.send(frame).await?;
+        handle_reply(&mut stream, connect_timeout).await?;
+
+        Ok(UnreliableSubscriber {
+            stream,
+            datagrams: receive(datagrams),
+            topic,
+            decoder: self.state.decoder,
+            decompression: self.state.decompression,
+            max_decompressed_bytes: self.state.max_decompressed_bytes,
+            config: BincodeConfig::default(),
+        })
+    }
+}
+
+/// A subscriber stream that receives messages sent to a topic as QUIC datagrams.
+///
+/// Datagrams bypass the topic's log, so an UnreliableSubscriber only receives messages sent
+/// while it's open, and messages may be lost, duplicated or delivered out of order. In exchange,
+/// messages are delivered with as little latency as possible, as they are never retained or
+/// retransmitted. This suits data that is quickly superseded, such as sensor readings or
+/// positional updates.
+///
+/// The subscriber doesn't reconnect if its connection is lost, and ends along with the
+/// connection.
+///
+/// **Note:** The UnreliableSubscriber struct is never constructed directly, but rather, via
+/// [open_unreliable](StreamBuilder::open_unreliable).
+pub struct UnreliableSubscriber<D> {
+    // Keeps the subscription registered with the server for as long as it's open
+    stream: BiStream,
+    datagrams: BoxStream<'static, Bytes>,
+    topic: TopicName,
+    decoder: D,
+    decompression: Option<Decomp>,
+    max_decompressed_bytes: usize,
+    config: BincodeConfig,
+}
+
+impl<D> UnreliableSubscriber<D>
+where
+    D: MessageDecoder + Send + Unpin,
+{
+    fn decode_message(&self, datagram: Datagram) -> Result<D::Item> {
+        let mut message = datagram.message;
+
+        if let Some(decomp) = &self.decompression {
+            message = decomp
+                .decompress_with_limit(message, self.max_decompressed_bytes)
+                .map_err(CodecError::DecompressFailure)?;
+        }
+
+        let mut bytes = BytesMut::from(&message[..]);
+        let decoded = self
+            .decoder
+            .decode(&mut bytes)
+            .map_err(CodecError::DecodeFailure)?;
+
+        Ok(decoded)
+    }
+}
+
+impl<D> Stream for UnreliableSubscriber<D>
+where
+    D: MessageDecoder + Send + Unpin,
+{
+    type Item = Result<D::Item>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // The server closes the stream when the subscription ends, such as during shutdown
+        if let Poll::Ready(None | Some(Err(_))) = self.stream.poll_next_unpin(cx) {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let bytes = match futures::ready!(self.datagrams.poll_next_unpin(cx)) {
+                Some(bytes) => bytes,
+                None => return Poll::Ready(None),
+            };
+
+            // Datagrams for the connection's other subscriptions are ignored
+            match Datagram::from_bytes(&bytes, &self.config) {
+                Ok(datagram) if datagram.topic == self.topic => {
+                    return Poll::Ready(Some(self.decode_message(datagram)));
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+fn receive(receiver: DatagramReceiver) -> BoxStream<'static, Bytes> {
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.rx.recv().await {
+                Ok(bytes) => return Some((bytes, receiver)),
+                // Datagrams are best-effort, so skip any that were missed by a slow subscriber
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .boxed()
+}
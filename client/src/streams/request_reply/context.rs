@@ -0,0 +1,76 @@
+use selium_protocol::PEER_IDENTITY_HEADER;
+use std::collections::HashMap;
+
+/// The details of a request received by a [Replier](crate::streams::request_reply::Replier),
+/// passed to handlers registered via
+/// [with_handler_ctx](crate::StreamBuilder::with_handler_ctx).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestContext {
+    headers: HashMap<String, String>,
+}
+
+impl RequestContext {
+    pub(crate) fn new(headers: Option<&HashMap<String, String>>) -> Self {
+        Self {
+            headers: headers.cloned().unwrap_or_default(),
+        }
+    }
+
+    /// The headers attached to the request, including those used by `Selium` to route the reply
+    /// back to the [Requestor](crate::streams::request_reply::Requestor).
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    /// Returns the value of the request header named `key`, if it was attached to the request.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).map(String::as_str)
+    }
+
+    /// The identity of the requestor's client certificate, as a hex-encoded SHA-512
+    /// fingerprint, or [None] if the server doesn't know the requestor's identity.
+    ///
+    /// The identity is attached by the `Selium` server, so it can't be forged by the requestor.
+    pub fn peer_identity(&self) -> Option<&str> {
+        self.header(PEER_IDENTITY_HEADER)
+    }
+}
+
+/// A handler for the requests received by a [Replier](crate::streams::request_reply::Replier).
+///
+/// Implemented for every closure or function of the form `FnMut(Req) -> Fut`, as registered via
+/// [with_handler](crate::StreamBuilder::with_handler), and for [WithContext], as registered via
+/// [with_handler_ctx](crate::StreamBuilder::with_handler_ctx).
+pub trait RequestHandler<Req> {
+    /// The future returned by the handler, which resolves to the reply.
+    type Future;
+
+    /// Handles a request, returning a future that resolves to the reply.
+    fn handle(&mut self, req: Req, context: RequestContext) -> Self::Future;
+}
+
+impl<Req, Fut, F> RequestHandler<Req> for F
+where
+    F: FnMut(Req) -> Fut,
+{
+    type Future = Fut;
+
+    fn handle(&mut self, req: Req, _context: RequestContext) -> Fut {
+        self(req)
+    }
+}
+
+/// A handler of the form `FnMut(Req, RequestContext) -> Fut`, which receives the
+/// [RequestContext] of each request alongside the request itself.
+pub struct WithContext<F>(pub(crate) F);
+
+impl<Req, Fut, F> RequestHandler<Req> for WithContext<F>
+where
+    F: FnMut(Req, RequestContext) -> Fut,
+{
+    type Future = Fut;
+
+    fn handle(&mut self, req: Req, context: RequestContext) -> Fut {
+        (self.0)(req, context)
+    }
+}
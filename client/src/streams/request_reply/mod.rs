@@ -1,8 +1,11 @@
 //! Synchronous Request/Reply streams.
 
+mod context;
 mod replier;
 mod requestor;
 
 pub(crate) mod states;
+pub use context::{RequestContext, RequestHandler, WithContext};
 pub use replier::Replier;
 pub use requestor::Requestor;
+pub use tokio_util::sync::CancellationToken;
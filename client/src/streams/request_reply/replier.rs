@@ -1,22 +1,28 @@
 use super::states::*;
+use super::{RequestContext, RequestHandler, WithContext};
 use crate::connection::{ClientConnection, SharedConnection};
 use crate::keep_alive::reqrep::KeepAlive;
 use crate::keep_alive::AttemptFut;
 use crate::streams::aliases::{Comp, Decomp};
-use crate::streams::handle_reply;
+use crate::streams::{error_from_payload, handle_reply, open_bistream};
 use crate::traits::{KeepAliveStream, Open};
 use crate::{Client, StreamBuilder};
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
-use futures::{Future, SinkExt, StreamExt};
+use futures::future::{AbortHandle, Abortable};
+use futures::stream::FuturesUnordered;
+use futures::{Future, FutureExt, SinkExt, StreamExt};
 use selium_protocol::error_codes::UNKNOWN_ERROR;
-use selium_protocol::{BiStream, Frame, MessagePayload, ReplierPayload, TopicName};
+use selium_protocol::{BiStream, CancelPayload, Frame, MessagePayload, ReplierPayload, TopicName};
 use selium_std::errors::{CodecError, Result, SeliumError};
 use selium_std::traits::codec::{MessageDecoder, MessageEncoder};
 use selium_std::traits::compression::{Compress, Decompress};
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::{pin::Pin, sync::Arc};
+use tokio::select;
 use tokio::sync::MutexGuard;
+use tokio_util::sync::CancellationToken;
 
 impl StreamBuilder<ReplierWantsRequestDecoder> {
     /// Specifies the decoder a [Replier] uses for decoding
@@ -98,6 +104,83 @@ impl<D, E> StreamBuilder<ReplierWantsHandler<D, E>> {
             client: self.client,
         }
     }
+
+    /// Specifies the callback to invoke when handling incoming requests, in the same manner as
+    /// [with_handler](StreamBuilder::with_handler).
+    ///
+    /// The closure will receive the decoded request, along with a [RequestContext] containing the
+    /// request's headers and the identity of the requestor, for handlers whose replies depend on
+    /// who is asking.
+    ///
+    /// # Errors
+    ///
+    /// The handler function must return a [Result] to account for failures when processing
+    /// requests.
+    pub fn with_handler_ctx<Err, F, Fut>(
+        self,
+        handler: F,
+    ) -> StreamBuilder<ReplierWantsOpen<D, E, WithContext<F>>>
+    where
+        D: MessageDecoder + Send + Unpin,
+        E: MessageEncoder + Send + Unpin,
+        F: FnMut(D::Item, RequestContext) -> Fut,
+        Fut: Future<Output = std::result::Result<E::Item, Err>>,
+    {
+        let next_state = ReplierWantsOpen::new(self.state, WithContext(handler));
+
+        StreamBuilder {
+            state: next_state,
+            client: self.client,
+        }
+    }
+}
+
+impl<D, E, F> StreamBuilder<ReplierWantsOpen<D, E, F>> {
+    /// Limits the number of requests that the [Replier] handles at once.
+    ///
+    /// While fewer than `max` handlers are running, each incoming request is handled as soon as
+    /// it's received, rather than waiting for earlier requests to be handled, so a slow request
+    /// doesn't hold up the requests behind it. Replies are sent as each handler completes, and
+    /// are correlated with their request by the [Requestor](crate::streams::request_reply::Requestor).
+    /// Once `max` handlers are running, further requests are queued until one completes.
+    ///
+    /// By default, requests are handled one at a time, in the order they were received.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is 0.
+    pub fn with_max_concurrency(mut self, max: usize) -> Self {
+        assert!(max > 0, "Max concurrency must be greater than 0");
+        self.state.max_concurrency = max;
+        self
+    }
+
+    /// Limits the number of bytes that each request payload can decompress to, guarding against
+    /// payloads crafted to decompress to an excessive size.
+    ///
+    /// Decompression stops as soon as the limit is exceeded, and the request fails with a
+    /// [DecompressFailure](CodecError::DecompressFailure) error.
+    ///
+    /// Defaults to [MAX_DECOMPRESSED_BYTES_DEFAULT](crate::constants::MAX_DECOMPRESSED_BYTES_DEFAULT).
+    pub fn with_max_request_decompressed_bytes(mut self, max_bytes: usize) -> Self {
+        self.state.max_decompressed_bytes = max_bytes;
+        self
+    }
+
+    /// Sets the priority of replies sent by the [Replier] stream, relative to the other streams
+    /// opened by the same [Client].
+    ///
+    /// Priorities are only meaningful in relation to each other: when several streams have data
+    /// waiting to be sent over the connection, data on higher priority streams is sent first,
+    /// while streams of equal priority share the connection fairly. Streams default to a priority
+    /// of 0.
+    ///
+    /// Priorities only govern the order in which this client sends data, and don't affect how
+    /// the `Selium` server schedules the requests it forwards.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.state.priority = priority;
+        self
+    }
 }
 
 #[async_trait]
@@ -106,7 +189,7 @@ where
     D: MessageDecoder + Send + Unpin,
     E: MessageEncoder + Send + Unpin,
     Err: Debug,
-    F: FnMut(D::Item) -> Fut + Send + Unpin,
+    F: RequestHandler<D::Item, Future = Fut> + Send + Unpin,
     Fut: Future<Output = std::result::Result<E::Item, Err>>,
 {
     type Output = KeepAlive<Replier<E, D, F>>;
@@ -116,16 +199,7 @@ where
 
         let headers = ReplierPayload { topic };
 
-        let replier = Replier::spawn(
-            self.client,
-            headers,
-            self.state.encoder,
-            self.state.decoder,
-            self.state.compression,
-            self.state.decompression,
-            self.state.handler,
-        )
-        .await?;
+        let replier = Replier::spawn(self.client, headers, self.state).await?;
 
         Ok(replier)
     }
@@ -139,7 +213,8 @@ where
 ///
 /// When a Replier stream is spawned, it will bind to the specified topic. A consequence of this is
 /// that only one active stream can bind to a namespace/topic combination at any given time. Trying
-/// to bind to an already occupied topic will result in a runtime error.
+/// to bind to an already occupied topic will fail with
+/// [SeliumError::ReplierAlreadyBound](crate::std::errors::SeliumError::ReplierAlreadyBound).
 pub struct Replier<E, D, F> {
     client: Client,
     stream: BiStream,
@@ -148,7 +223,11 @@ pub struct Replier<E, D, F> {
     decoder: D,
     compression: Option<Comp>,
     decompression: Option<Decomp>,
+    max_decompressed_bytes: usize,
     handler: Pin<Box<F>>,
+    max_concurrency: usize,
+    backlog: VecDeque<MessagePayload>,
+    priority: i32,
 }
 
 impl<D, E, Err, F, Fut> Replier<E, D, F>
@@ -156,53 +235,57 @@ where
     D: MessageDecoder + Send + Unpin,
     E: MessageEncoder + Send + Unpin,
     Err: Debug,
-    F: FnMut(D::Item) -> Fut + Send + Unpin,
+    F: RequestHandler<D::Item, Future = Fut> + Send + Unpin,
     Fut: Future<Output = std::result::Result<E::Item, Err>>,
 {
     async fn spawn(
         client: Client,
         headers: ReplierPayload,
-        encoder: E,
-        decoder: D,
-        compression: Option<Comp>,
-        decompression: Option<Decomp>,
-        handler: Pin<Box<F>>,
+        state: ReplierWantsOpen<D, E, F>,
     ) -> Result<KeepAlive<Self>> {
         let lock = client.connection.lock().await;
         let stream = Self::open_stream(lock, headers.clone()).await?;
+        stream.set_priority(state.priority)?;
 
         let replier = Self {
             client: client.clone(),
             stream,
             headers,
-            encoder,
-            decoder,
-            compression,
-            decompression,
-            handler,
+            encoder: state.encoder,
+            decoder: state.decoder,
+            compression: state.compression,
+            decompression: state.decompression,
+            max_decompressed_bytes: state.max_decompressed_bytes,
+            handler: state.handler,
+            max_concurrency: state.max_concurrency,
+            backlog: VecDeque::new(),
+            priority: state.priority,
         };
 
-        Ok(KeepAlive::new(replier, client.backoff_strategy))
+        Ok(KeepAlive::new(
+            replier,
+            client.backoff_strategy,
+            client.events,
+        ))
     }
 
     async fn open_stream(
         lock: MutexGuard<'_, ClientConnection>,
         headers: ReplierPayload,
     ) -> Result<BiStream> {
-        let mut stream = BiStream::try_from_connection(lock.conn()).await?;
-        drop(lock);
+        let (mut stream, connect_timeout) = open_bistream(lock).await?;
 
         let frame = Frame::RegisterReplier(headers);
         stream.send(frame).await?;
 
-        handle_reply(&mut stream).await?;
+        handle_reply(&mut stream, connect_timeout).await?;
         Ok(stream)
     }
 
     fn decode_message(&mut self, mut bytes: Bytes) -> Result<D::Item> {
         if let Some(decomp) = self.decompression.as_ref() {
             bytes = decomp
-                .decompress(bytes)
+                .decompress_with_limit(bytes, self.max_decompressed_bytes)
                 .map_err(CodecError::DecompressFailure)?;
         }
 
@@ -230,16 +313,21 @@ where
         Ok(encoded)
     }
 
-    async fn handle_request(&mut self, req_payload: MessagePayload) -> Result<()> {
-        let decoded = self.decode_message(req_payload.message)?;
-        let response = (self.handler)(decoded)
-            .await
-            .map_err(|e| SeliumError::RequestHandlerFailure(format!("{e:?}")))?;
+    async fn send_reply(
+        &mut self,
+        headers: Option<HashMap<String, String>>,
+        response: std::result::Result<E::Item, Err>,
+    ) -> Result<()> {
+        let response =
+            response.map_err(|e| SeliumError::RequestHandlerFailure(format!("{e:?}")))?;
         let encoded = self.encode_message(response)?;
 
         let res_payload = MessagePayload {
-            headers: req_payload.headers,
+            headers,
             message: encoded,
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         };
 
         let frame = Frame::Message(res_payload);
@@ -248,28 +336,125 @@ where
         Ok(())
     }
 
+    /// Returns the priority of the stream, as set by
+    /// [with_priority](StreamBuilder::with_priority).
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
     /// Prepares a [Replier] stream to begin processing incoming messages.
     /// This method will block the current task until the stream has been exhausted.
+    ///
+    /// Up to the configured [max concurrency](StreamBuilder::with_max_concurrency) requests are
+    /// handled at once, and each reply is sent as soon as its handler completes, so replies may be
+    /// sent in a different order to the requests they answer. Up to the same number of further
+    /// requests are queued until a handler completes, after which no more requests are read from
+    /// the stream until the queue drains.
+    ///
+    /// If the stream ends or the server reports an error, no further requests are started, but
+    /// any requests that are already being handled are allowed to complete before returning.
+    /// Their replies are discarded, as the stream can no longer be written to.
+    ///
+    /// If a requestor cancels a request while it's being handled, the future returned by the
+    /// handler is dropped, and no reply is sent.
     pub async fn listen(&mut self) -> Result<()> {
-        while let Some(frame) = self.stream.next().await {
-            self.handle_frame(frame).await?;
+        self.listen_with_cancellation(CancellationToken::new())
+            .await
+    }
+
+    /// Processes incoming requests in the same manner as [listen](Replier::listen), until the
+    /// provided `token` is cancelled.
+    ///
+    /// Once the token is cancelled, no further requests are read or started. Any requests that
+    /// are already being handled are allowed to complete and are replied to, after which the
+    /// stream is finished and this method returns `Ok`. Requests that were queued but not yet
+    /// started are not replied to.
+    pub async fn listen_with_cancellation(&mut self, token: CancellationToken) -> Result<()> {
+        let mut handlers = FuturesUnordered::new();
+        // The headers and abort handle of each running handler, keyed by the order in which the
+        // requests were started.
+        let mut running: HashMap<u64, (Option<HashMap<String, String>>, AbortHandle)> =
+            HashMap::new();
+        let mut next_id = 0u64;
+        let mut cancelled = false;
+        // The result to return once the running handlers have completed, if the stream has ended
+        let mut ended = None;
+
+        loop {
+            if handlers.is_empty() {
+                if let Some(result) = ended.take() {
+                    return result;
+                }
+
+                if cancelled {
+                    self.stream.finish().await?;
+                    return Ok(());
+                }
+            }
+
+            let reading = !cancelled && ended.is_none();
+
+            while reading && handlers.len() < self.max_concurrency {
+                let req = match self.backlog.pop_front() {
+                    Some(req) => req,
+                    None => break,
+                };
+
+                let decoded = self.decode_message(req.message)?;
+                let context = RequestContext::new(req.headers.as_ref());
+                let handler = self.handler.handle(decoded, context);
+                let (abort, registration) = AbortHandle::new_pair();
+                let id = next_id;
+                next_id += 1;
+
+                running.insert(id, (req.headers, abort));
+                handlers.push(Abortable::new(handler, registration).map(move |res| (id, res)));
+            }
+
+            // Stop reading requests once the backlog is full, so that requestors sending faster
+            // than the handlers can keep up are held back by the stream's flow control
+            let backlog_full = self.backlog.len() >= self.max_concurrency;
+
+            // Keep reading from the stream while handlers run, so that requests can be aborted
+            // if their requestor cancels them. The stream is only written to from this loop, so
+            // replies from concurrent handlers are never interleaved.
+            select! {
+                Some((id, response)) = handlers.next(), if !handlers.is_empty() => {
+                    let (headers, _) = running.remove(&id).expect("handler should be running");
+
+                    // Handlers that were aborted have been cancelled by their requestor
+                    if let (Ok(response), None) = (response, &ended) {
+                        self.send_reply(headers, response).await?;
+                    }
+                }
+                _ = token.cancelled(), if reading => cancelled = true,
+                frame = self.stream.next(), if reading && !backlog_full => match frame {
+                    Some(Ok(Frame::Message(req))) => self.backlog.push_back(req),
+                    Some(Ok(Frame::Cancel(payload))) => {
+                        running
+                            .values()
+                            .filter(|(headers, _)| is_same_request(headers.as_ref(), &payload))
+                            .for_each(|(_, abort)| abort.abort());
+
+                        self.cancel_queued_request(&payload);
+                    }
+                    Some(frame) => ended = Some(self.handle_control_frame(frame)),
+                    None => ended = Some(Ok(())),
+                }
+            }
         }
+    }
 
-        Ok(())
+    fn cancel_queued_request(&mut self, cancel: &CancelPayload) {
+        self.backlog
+            .retain(|req| !is_same_request(req.headers.as_ref(), cancel));
     }
 
-    async fn handle_frame(&mut self, frame: Result<Frame>) -> Result<()> {
+    fn handle_control_frame(&mut self, frame: Result<Frame>) -> Result<()> {
         match frame {
-            Ok(Frame::Message(req)) => Ok(self.handle_request(req).await?),
-            Ok(Frame::Error(payload)) => match String::from_utf8(payload.message.to_vec()) {
-                Ok(s) => Err(SeliumError::OpenStream(payload.code, s)),
-                Err(_) => Err(SeliumError::OpenStream(
-                    payload.code,
-                    "Invalid UTF-8 error".into(),
-                )),
-            },
+            Ok(Frame::Error(payload)) => Err(error_from_payload(payload)),
             Ok(_) => Err(SeliumError::OpenStream(
-                UNKNOWN_ERROR,
+                UNKNOWN_ERROR.into(),
                 "Invalid frame returned from server".into(),
             )),
             Err(err) => Err(err),
@@ -277,15 +462,27 @@ where
     }
 }
 
+// Requests are identified by the requestor's request id, along with the id the server assigns to
+// the requestor's stream.
+fn is_same_request(headers: Option<&HashMap<String, String>>, cancel: &CancelPayload) -> bool {
+    match headers {
+        Some(headers) => ["req_id", "cid"]
+            .iter()
+            .all(|key| headers.get(*key) == cancel.headers.get(*key)),
+        None => false,
+    }
+}
+
 impl<D, E, Err, F, Fut> KeepAliveStream for Replier<E, D, F>
 where
     D: MessageDecoder + Send + Unpin,
     E: MessageEncoder + Send + Unpin,
     Err: Debug,
-    F: FnMut(D::Item) -> Fut + Send + Unpin,
+    F: RequestHandler<D::Item, Future = Fut> + Send + Unpin,
     Fut: Future<Output = std::result::Result<E::Item, Err>>,
 {
     type Headers = ReplierPayload;
+    type Stream = BiStream;
 
     fn reestablish_connection(connection: SharedConnection, headers: Self::Headers) -> AttemptFut {
         Box::pin(async move {
@@ -296,6 +493,9 @@ where
     }
 
     fn on_reconnect(&mut self, stream: BiStream) {
+        // A failure means that the new stream has already closed, which will surface on the
+        // next read
+        let _ = stream.set_priority(self.priority);
         self.stream = stream;
     }
 
@@ -3,14 +3,15 @@ use crate::connection::{ClientConnection, SharedConnection};
 use crate::keep_alive::reqrep::KeepAlive;
 use crate::keep_alive::AttemptFut;
 use crate::streams::aliases::{Comp, Decomp};
-use crate::streams::handle_reply;
+use crate::streams::{handle_reply, open_bistream};
 use crate::traits::{KeepAliveStream, Open, TryIntoU64};
 use crate::{Client, StreamBuilder};
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
 use futures::{SinkExt, StreamExt};
 use selium_protocol::{
-    BiStream, Frame, MessagePayload, ReadHalf, RequestId, RequestorPayload, TopicName, WriteHalf,
+    BiStream, CancelPayload, Frame, MessagePayload, ReadHalf, RequestId, RequestorPayload,
+    TopicName, WriteHalf,
 };
 use selium_std::errors::Result;
 use selium_std::errors::{CodecError, SeliumError};
@@ -19,8 +20,10 @@ use selium_std::traits::compression::{Compress, Decompress};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::select;
 use tokio::sync::oneshot::{self, Receiver, Sender};
-use tokio::sync::{Mutex, MutexGuard};
+use tokio::sync::{Mutex, MutexGuard, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 type SharedPendingRequests = Arc<Mutex<HashMap<u32, Sender<Bytes>>>>;
 type SharedReadHalf = Arc<Mutex<ReadHalf>>;
@@ -104,6 +107,53 @@ impl<E, D> StreamBuilder<RequestorWantsOpen<E, D>> {
         self.state.request_timeout = Duration::from_millis(millis);
         Ok(self)
     }
+
+    /// Limits the number of requests that the [Requestor] can have awaiting a reply at once,
+    /// across all of its clones.
+    ///
+    /// Once `max` requests are outstanding, further calls to [request](Requestor::request) wait
+    /// for a reply, timeout or cancellation to free a slot before dispatching their request. This
+    /// applies backpressure when the replier is stalled, rather than queuing requests without
+    /// bound. The request timeout only starts once the request has been dispatched.
+    ///
+    /// By default, the number of outstanding requests is unbounded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is 0.
+    pub fn with_max_inflight(mut self, max: usize) -> Self {
+        assert!(max > 0, "Max in-flight requests must be greater than 0");
+        self.state.max_inflight = Some(max);
+        self
+    }
+
+    /// Limits the number of bytes that each reply payload can decompress to, guarding against
+    /// payloads crafted to decompress to an excessive size.
+    ///
+    /// Decompression stops as soon as the limit is exceeded, and the request fails with a
+    /// [DecompressFailure](CodecError::DecompressFailure) error.
+    ///
+    /// Defaults to [MAX_DECOMPRESSED_BYTES_DEFAULT](crate::constants::MAX_DECOMPRESSED_BYTES_DEFAULT).
+    pub fn with_max_reply_decompressed_bytes(mut self, max_bytes: usize) -> Self {
+        self.state.max_decompressed_bytes = max_bytes;
+        self
+    }
+
+    /// Sets the priority of requests sent by the [Requestor] stream, relative to the other
+    /// streams opened by the same [Client].
+    ///
+    /// Priorities are only meaningful in relation to each other: when several streams have data
+    /// waiting to be sent over the connection, data on higher priority streams is sent first,
+    /// while streams of equal priority share the connection fairly. Streams default to a priority
+    /// of 0, so a latency-sensitive requestor can be given a positive priority to keep its
+    /// requests from queuing behind bulk publishers on the same connection.
+    ///
+    /// Priorities only govern the order in which this client sends data, and don't affect how
+    /// the `Selium` server schedules the replies it sends.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.state.priority = priority;
+        self
+    }
 }
 
 #[async_trait()]
@@ -119,16 +169,7 @@ where
 
         let headers = RequestorPayload { topic };
 
-        let requestor = Requestor::spawn(
-            self.client,
-            headers,
-            self.state.encoder,
-            self.state.decoder,
-            self.state.compression,
-            self.state.decompression,
-            self.state.request_timeout,
-        )
-        .await?;
+        let requestor = Requestor::spawn(self.client, headers, self.state).await?;
 
         Ok(requestor)
     }
@@ -160,8 +201,11 @@ pub struct Requestor<E, D> {
     decoder: D,
     compression: Option<Comp>,
     decompression: Option<Decomp>,
+    max_decompressed_bytes: usize,
     request_timeout: Duration,
     pending_requests: SharedPendingRequests,
+    inflight: Option<Arc<Semaphore>>,
+    priority: i32,
 }
 
 impl<E, D> Requestor<E, D>
@@ -172,15 +216,12 @@ where
     async fn spawn(
         client: Client,
         headers: RequestorPayload,
-        encoder: E,
-        decoder: D,
-        compression: Option<Comp>,
-        decompression: Option<Decomp>,
-        request_timeout: Duration,
+        state: RequestorWantsOpen<E, D>,
     ) -> Result<KeepAlive<Self>> {
         let lock = client.connection.lock().await;
 
         let stream = Self::open_stream(lock, headers.clone()).await?;
+        stream.set_priority(state.priority)?;
         let (write_half, read_half) = Self::split_stream(stream);
 
         let pending_requests = Arc::new(Mutex::new(HashMap::new()));
@@ -194,28 +235,34 @@ where
             read_half,
             write_half,
             headers,
-            encoder,
-            decoder,
-            compression,
-            decompression,
-            request_timeout,
+            encoder: state.encoder,
+            decoder: state.decoder,
+            compression: state.compression,
+            decompression: state.decompression,
+            max_decompressed_bytes: state.max_decompressed_bytes,
+            request_timeout: state.request_timeout,
             pending_requests,
+            inflight: state.max_inflight.map(|max| Arc::new(Semaphore::new(max))),
+            priority: state.priority,
         };
 
-        Ok(KeepAlive::new(requestor, client.backoff_strategy))
+        Ok(KeepAlive::new(
+            requestor,
+            client.backoff_strategy,
+            client.events,
+        ))
     }
 
     async fn open_stream(
         lock: MutexGuard<'_, ClientConnection>,
         headers: RequestorPayload,
     ) -> Result<BiStream> {
-        let mut stream = BiStream::try_from_connection(lock.conn()).await?;
-        drop(lock);
+        let (mut stream, connect_timeout) = open_bistream(lock).await?;
 
         let frame = Frame::RegisterRequestor(headers);
         stream.send(frame).await?;
 
-        handle_reply(&mut stream).await?;
+        handle_reply(&mut stream, connect_timeout).await?;
 
         Ok(stream)
     }
@@ -246,7 +293,7 @@ where
     fn decode_response(&mut self, mut bytes: Bytes) -> Result<D::Item> {
         if let Some(decomp) = self.decompression.as_ref() {
             bytes = decomp
-                .decompress(bytes)
+                .decompress_with_limit(bytes, self.max_decompressed_bytes)
                 .map_err(CodecError::DecompressFailure)?;
         }
 
@@ -259,6 +306,23 @@ where
             .map_err(CodecError::DecodeFailure)?)
     }
 
+    /// Waits for a free in-flight slot, if the number of outstanding requests is limited. The
+    /// slot is freed when the returned permit is dropped.
+    async fn acquire_slot(&self) -> Result<Option<OwnedSemaphorePermit>> {
+        match &self.inflight {
+            Some(inflight) => {
+                let permit = inflight
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| SeliumError::RequestFailed)?;
+
+                Ok(Some(permit))
+            }
+            None => Ok(None),
+        }
+    }
+
     async fn queue_request(&mut self) -> (u32, Receiver<Bytes>) {
         let (tx, rx) = oneshot::channel();
         let mut lock = self.pending_requests.lock().await;
@@ -269,6 +333,25 @@ where
         (req_id, rx)
     }
 
+    /// Abandons a pending request, and notifies the replier so that it can stop processing it.
+    async fn cancel_request(&mut self, req_id: u32) {
+        self.pending_requests.lock().await.remove(&req_id);
+
+        let frame = Frame::Cancel(CancelPayload {
+            headers: request_headers(req_id),
+        });
+
+        // The replier can't be notified if the stream has disconnected, but as the reply would
+        // be discarded anyway, there's nothing else to do
+        let _ = self.write_half.lock().await.send(frame).await;
+    }
+
+    /// Returns the priority of the stream, as set by
+    /// [with_priority](StreamBuilder::with_priority).
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
     /// Dispatches a request and blocks the current task while waiting for a response, or a request
     /// timeout.
     ///
@@ -281,24 +364,92 @@ where
     /// - The request times out.
     /// - The reply fails to be decoded.
     pub async fn request(&mut self, req: E::Item) -> Result<D::Item> {
+        self.request_with_cancellation(req, CancellationToken::new())
+            .await
+    }
+
+    /// Dispatches a request in the same manner as [request](Requestor::request), which can also
+    /// be cancelled via the provided `token`.
+    ///
+    /// Cancelling the token returns [SeliumError::RequestCancelled] immediately, and notifies the
+    /// [Replier](crate::streams::request_reply::Replier) so that it can stop handling the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` under the same conditions as [request](Requestor::request), or if the
+    /// request is cancelled.
+    pub async fn request_with_cancellation(
+        &mut self,
+        req: E::Item,
+        token: CancellationToken,
+    ) -> Result<D::Item> {
+        self.dispatch(req, HashMap::new(), token).await
+    }
+
+    /// Dispatches a request in the same manner as [request](Requestor::request), attaching the
+    /// provided headers to the request, e.g. a trace id, which the
+    /// [Replier](crate::streams::request_reply::Replier) can read via its
+    /// [RequestContext](crate::streams::request_reply::RequestContext).
+    ///
+    /// Headers used by `Selium` to route the request and its reply take precedence over any
+    /// headers of the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` under the same conditions as [request](Requestor::request).
+    pub async fn request_with_headers(
+        &mut self,
+        req: E::Item,
+        headers: HashMap<String, String>,
+    ) -> Result<D::Item> {
+        self.dispatch(req, headers, CancellationToken::new()).await
+    }
+
+    async fn dispatch(
+        &mut self,
+        req: E::Item,
+        mut headers: HashMap<String, String>,
+        token: CancellationToken,
+    ) -> Result<D::Item> {
         let encoded = self.encode_request(req)?;
+
+        // Held until this function returns, so that the slot is freed by a reply, timeout or
+        // cancellation alike
+        let _slot = select! {
+            slot = self.acquire_slot() => slot?,
+            _ = token.cancelled() => return Err(SeliumError::RequestCancelled),
+        };
+
         let (req_id, rx) = self.queue_request().await;
 
-        let mut headers = HashMap::new();
-        headers.insert("req_id".to_owned(), req_id.to_string());
+        headers.extend(request_headers(req_id));
 
         let req_payload = MessagePayload {
             headers: Some(headers),
             message: encoded,
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         };
 
         let frame = Frame::Message(req_payload);
         self.write_half.lock().await.send(frame).await?;
 
-        let response = tokio::time::timeout(self.request_timeout, rx)
-            .await
-            .map_err(|_| SeliumError::RequestTimeout)?
-            .map_err(|_| SeliumError::RequestFailed)?;
+        let response = select! {
+            response = tokio::time::timeout(self.request_timeout, rx) => response,
+            _ = token.cancelled() => {
+                self.cancel_request(req_id).await;
+                return Err(SeliumError::RequestCancelled);
+            }
+        };
+
+        let response = match response {
+            Ok(response) => response.map_err(|_| SeliumError::RequestFailed)?,
+            Err(_) => {
+                self.cancel_request(req_id).await;
+                return Err(SeliumError::RequestTimeout);
+            }
+        };
 
         let decoded = self.decode_response(response)?;
 
@@ -306,6 +457,12 @@ where
     }
 }
 
+fn request_headers(req_id: u32) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    headers.insert("req_id".to_owned(), req_id.to_string());
+    headers
+}
+
 fn poll_replies(read_half: SharedReadHalf, pending_requests: SharedPendingRequests) {
     tokio::spawn(async move {
         let mut read_half = read_half.lock().await;
@@ -332,6 +489,7 @@ where
     D: MessageDecoder + Send + Unpin,
 {
     type Headers = RequestorPayload;
+    type Stream = BiStream;
 
     fn reestablish_connection(connection: SharedConnection, headers: Self::Headers) -> AttemptFut {
         Box::pin(async move {
@@ -342,6 +500,9 @@ where
     }
 
     fn on_reconnect(&mut self, stream: BiStream) {
+        // A failure means that the new stream has already closed, which will surface on the
+        // next request
+        let _ = stream.set_priority(self.priority);
         let (write_half, read_half) = Self::split_stream(stream);
         self.write_half = write_half;
         self.read_half = read_half;
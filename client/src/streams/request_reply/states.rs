@@ -1,3 +1,4 @@
+use crate::constants::MAX_DECOMPRESSED_BYTES_DEFAULT;
 use crate::streams::aliases::{Comp, Decomp};
 use std::{pin::Pin, time::Duration};
 
@@ -38,7 +39,10 @@ pub struct RequestorWantsOpen<E, D> {
     pub(crate) compression: Option<Comp>,
     pub(crate) decoder: D,
     pub(crate) decompression: Option<Decomp>,
+    pub(crate) max_decompressed_bytes: usize,
     pub(crate) request_timeout: Duration,
+    pub(crate) max_inflight: Option<usize>,
+    pub(crate) priority: i32,
 }
 
 impl<E, D> RequestorWantsOpen<E, D> {
@@ -49,7 +53,10 @@ impl<E, D> RequestorWantsOpen<E, D> {
             compression: prev.compression,
             decoder,
             decompression: None,
+            max_decompressed_bytes: MAX_DECOMPRESSED_BYTES_DEFAULT,
             request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_inflight: None,
+            priority: 0,
         }
     }
 }
@@ -113,6 +120,9 @@ pub struct ReplierWantsOpen<D, E, F> {
     pub(crate) encoder: E,
     pub(crate) compression: Option<Comp>,
     pub(crate) handler: Pin<Box<F>>,
+    pub(crate) max_concurrency: usize,
+    pub(crate) max_decompressed_bytes: usize,
+    pub(crate) priority: i32,
 }
 
 impl<D, E, F> ReplierWantsOpen<D, E, F> {
@@ -124,6 +134,9 @@ impl<D, E, F> ReplierWantsOpen<D, E, F> {
             encoder: prev.encoder,
             compression: prev.compression,
             handler: Box::pin(handler),
+            max_concurrency: 1,
+            max_decompressed_bytes: MAX_DECOMPRESSED_BYTES_DEFAULT,
+            priority: 0,
         }
     }
 }
@@ -0,0 +1,195 @@
+use futures::channel::mpsc;
+use futures::future::Either;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use quinn::{VarInt, WriteError};
+use selium_protocol::error_codes::SHUTDOWN_IN_PROGRESS;
+use selium_protocol::{BiStream, Frame, WriteHalf};
+use selium_std::errors::{QuicError, Result, SeliumError};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const IN_MEMORY_CHANNEL_SIZE: usize = 100;
+
+/// The underlying transport used by a stream to send and receive frames.
+///
+/// Streams opened via a [Client](crate::Client) are backed by a QUIC stream connected to the
+/// `Selium` server, whereas in-memory streams are backed by a channel, allowing them to be used
+/// in tests without any networking.
+///
+/// One-way streams are backed by a unidirectional QUIC stream, so never receive any frames. The
+/// server refuses a one-way stream by stopping it, which surfaces as an error the next time a
+/// frame is sent.
+pub(crate) enum Transport {
+    Network(BiStream),
+    OneWay(WriteHalf),
+    InMemory(InMemoryStream),
+}
+
+impl Transport {
+    pub async fn finish(&mut self) -> Result<()> {
+        match self {
+            Self::Network(stream) => stream.finish().await,
+            Self::OneWay(stream) => {
+                stream
+                    .finish()
+                    .await
+                    .map_err(|err| refused(QuicError::WriteError(err).into()))?;
+                Ok(())
+            }
+            Self::InMemory(stream) => {
+                stream.tx.close_channel();
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets the priority of frames sent on the stream. In-memory streams have no connection to
+    /// share, so their priority is ignored.
+    pub fn set_priority(&self, priority: i32) -> Result<()> {
+        match self {
+            Self::Network(stream) => stream.set_priority(priority),
+            Self::OneWay(stream) => {
+                stream
+                    .set_priority(priority)
+                    .map_err(QuicError::UnknownStream)?;
+                Ok(())
+            }
+            Self::InMemory(_) => Ok(()),
+        }
+    }
+}
+
+impl From<BiStream> for Transport {
+    fn from(stream: BiStream) -> Self {
+        Self::Network(stream)
+    }
+}
+
+impl From<Either<BiStream, WriteHalf>> for Transport {
+    fn from(stream: Either<BiStream, WriteHalf>) -> Self {
+        match stream {
+            Either::Left(stream) => Self::Network(stream),
+            Either::Right(stream) => Self::OneWay(stream),
+        }
+    }
+}
+
+impl Sink<Frame> for Transport {
+    type Error = SeliumError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.get_mut() {
+            Self::Network(stream) => stream.poll_ready_unpin(cx),
+            Self::OneWay(stream) => stream.poll_ready_unpin(cx).map_err(refused),
+            Self::InMemory(stream) => stream.tx.poll_ready_unpin(cx).map_err(channel_closed),
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Frame) -> Result<(), Self::Error> {
+        match self.get_mut() {
+            Self::Network(stream) => stream.start_send_unpin(item),
+            Self::OneWay(stream) => stream.start_send_unpin(item).map_err(refused),
+            Self::InMemory(stream) => stream.tx.start_send_unpin(item).map_err(channel_closed),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.get_mut() {
+            Self::Network(stream) => stream.poll_flush_unpin(cx),
+            Self::OneWay(stream) => stream.poll_flush_unpin(cx).map_err(refused),
+            Self::InMemory(stream) => stream.tx.poll_flush_unpin(cx).map_err(channel_closed),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.get_mut() {
+            Self::Network(stream) => stream.poll_close_unpin(cx),
+            Self::OneWay(stream) => stream.poll_close_unpin(cx).map_err(refused),
+            Self::InMemory(stream) => stream.tx.poll_close_unpin(cx).map_err(channel_closed),
+        }
+    }
+}
+
+impl Stream for Transport {
+    type Item = Result<Frame>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            Self::Network(stream) => stream.poll_next_unpin(cx),
+            Self::OneWay(_) => Poll::Pending,
+            Self::InMemory(stream) => match stream.rx.as_mut() {
+                Some(rx) => rx.poll_next_unpin(cx).map(|frame| frame.map(Ok)),
+                None => Poll::Pending,
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Network(stream) => stream.size_hint(),
+            Self::OneWay(_) => (0, None),
+            Self::InMemory(stream) => stream.rx.as_ref().map_or((0, None), |rx| rx.size_hint()),
+        }
+    }
+}
+
+/// A channel-backed stream, where frames sent on one end of the pair are received by the other.
+pub(crate) struct InMemoryStream {
+    tx: mpsc::Sender<Frame>,
+    // Duplicated streams have no receiver, and never receive any frames
+    rx: Option<mpsc::Receiver<Frame>>,
+}
+
+impl InMemoryStream {
+    pub fn pair() -> (Self, Self) {
+        let (left_tx, left_rx) = mpsc::channel(IN_MEMORY_CHANNEL_SIZE);
+        let (right_tx, right_rx) = mpsc::channel(IN_MEMORY_CHANNEL_SIZE);
+
+        let left = Self {
+            tx: left_tx,
+            rx: Some(right_rx),
+        };
+
+        let right = Self {
+            tx: right_tx,
+            rx: Some(left_rx),
+        };
+
+        (left, right)
+    }
+
+    /// Creates a new stream that sends frames to the same receiver as this stream, but never
+    /// receives any frames itself.
+    pub fn duplicate(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            rx: None,
+        }
+    }
+}
+
+// The server stops a one-way stream to refuse it, as there is no other way to reply, so the code
+// it stopped the stream with is surfaced as the reason. Streams stopped because the server is
+// shutting down are left as lost connections, so that they reconnect.
+fn refused(err: SeliumError) -> SeliumError {
+    let write_err = match &err {
+        SeliumError::IoError(err) => err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<WriteError>()),
+        SeliumError::Quic(QuicError::WriteError(err)) => Some(err),
+        _ => None,
+    };
+
+    match write_err {
+        Some(WriteError::Stopped(code)) if *code != VarInt::from_u32(SHUTDOWN_IN_PROGRESS) => {
+            let code = u32::try_from(code.into_inner()).unwrap_or(u32::MAX);
+            SeliumError::OpenStream(code.into(), "The server refused the stream".to_owned())
+        }
+        _ => err,
+    }
+}
+
+fn channel_closed(err: mpsc::SendError) -> SeliumError {
+    io::Error::new(io::ErrorKind::BrokenPipe, err).into()
+}
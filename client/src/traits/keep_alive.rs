@@ -1,20 +1,41 @@
 use crate::connection::SharedConnection;
 use crate::keep_alive::AttemptFut;
-use selium_protocol::BiStream;
+use selium_std::errors::Result;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 /// Provides methods to adapt a stream into a `KeepAlive` compatible stream.
 pub trait KeepAliveStream {
     type Headers: Sized + Clone + Unpin + Send + 'static;
+    /// The stream opened by each attempt to reconnect, which replaces the lost stream.
+    type Stream: Send + 'static;
 
     /// Callback that is invoked to attempt to reconnect to the `Selium` server.
-    fn reestablish_connection(connection: SharedConnection, headers: Self::Headers) -> AttemptFut;
+    fn reestablish_connection(
+        connection: SharedConnection,
+        headers: Self::Headers,
+    ) -> AttemptFut<Self::Stream>;
 
     /// Callback that is invoked upon successful reconnection.
-    fn on_reconnect(&mut self, stream: BiStream);
+    fn on_reconnect(&mut self, stream: Self::Stream);
 
     /// Retrieves the shared selium client connection.
     fn get_connection(&self) -> SharedConnection;
 
     /// Retrieves the headers used to register the stream with the `Selium` server.
     fn get_headers(&self) -> Self::Headers;
+
+    /// Callback that is invoked when the connection is lost, before attempting to reconnect.
+    fn on_disconnect(&mut self) {}
+
+    /// Polls whether the stream can accept another item while it is disconnected, to be replayed
+    /// once reconnected. Streams that don't buffer items are never ready.
+    fn poll_buffer(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Pending
+    }
+
+    /// The maximum duration the stream can remain disconnected before failing, if any.
+    fn max_reconnect_gap(&self) -> Option<Duration> {
+        None
+    }
 }
@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use selium_log::{
     config::{FlushPolicy, LogConfig},
     message::Message,
@@ -11,6 +11,7 @@ use tempfile::tempdir;
 const ONE_DAY: u64 = 86_400;
 const NUM_OF_MESSAGES: u64 = 1_000_000;
 const MAX_ENTRIES_PER_SEGMENT: u32 = 50_000;
+const READ_AHEAD_SIZES: [u64; 3] = [100, 1_000, 10_000];
 
 fn get_log_config(path: impl AsRef<Path>) -> LogConfig {
     LogConfig::from_path(path)
@@ -34,13 +35,13 @@ async fn write_records(path: impl AsRef<Path>) {
     log.flush().await.unwrap();
 }
 
-async fn read_records(path: impl AsRef<Path>) {
+async fn read_records(path: impl AsRef<Path>, limit: Option<u64>) {
     let config = get_log_config(path);
     let log = MessageLog::open(Arc::new(config)).await.unwrap();
     let mut offset = 0;
 
     loop {
-        let slice = log.read_slice(offset, None).await.unwrap();
+        let slice = log.read_slice(offset, limit).await.unwrap();
         offset = slice.end_offset();
 
         match slice.messages().as_mut() {
@@ -65,8 +66,20 @@ pub fn benchmark(c: &mut Criterion) {
 
     c.bench_function("read 1_000_000 records", |b| {
         let runtime = tokio::runtime::Runtime::new().expect("Failed to construct executor");
-        b.to_async(runtime).iter(move || read_records(path));
+        b.to_async(runtime).iter(move || read_records(path, None));
     });
+
+    let mut group = c.benchmark_group("read 1_000_000 records with read-ahead");
+
+    for size in READ_AHEAD_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to construct executor");
+            b.to_async(runtime)
+                .iter(move || read_records(path, Some(size)));
+        });
+    }
+
+    group.finish();
 }
 
 criterion_group! {
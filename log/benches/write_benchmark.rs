@@ -5,12 +5,16 @@ use selium_log::{
     message::Message,
     MessageLog,
 };
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tempfile::tempdir;
 
 const ONE_DAY: u64 = 86_400;
 const NUM_OF_MESSAGES: u64 = 1_000_000;
 const MAX_ENTRIES_PER_SEGMENT: u32 = 50_000;
+const ROLL_OVER_ENTRIES_PER_SEGMENT: u32 = 1_000;
 
 fn get_log_config() -> LogConfig {
     let tempdir = tempdir().unwrap();
@@ -35,11 +39,53 @@ async fn log_task() {
     log.flush().await.unwrap();
 }
 
+// Measures only the writes that fill the hot segment, and therefore roll over to a new segment.
+async fn roll_over_task(preallocate: bool, iters: u64) -> Duration {
+    let tempdir = tempdir().unwrap();
+    let mut config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(ROLL_OVER_ENTRIES_PER_SEGMENT)
+        .retention_period(Duration::from_secs(ONE_DAY))
+        .cleaner_interval(Duration::from_secs(ONE_DAY));
+
+    if !preallocate {
+        config = config.disable_preallocation();
+    }
+
+    let log = MessageLog::open(Arc::new(config)).await.unwrap();
+    let mut elapsed = Duration::ZERO;
+
+    for i in 1..=iters * ROLL_OVER_ENTRIES_PER_SEGMENT as u64 {
+        let batch = Bytes::copy_from_slice(&[1; 32]);
+        let message = Message::single(&batch, 1);
+
+        let start = Instant::now();
+        log.write(message).await.unwrap();
+
+        if i % ROLL_OVER_ENTRIES_PER_SEGMENT as u64 == 0 {
+            elapsed += start.elapsed();
+        }
+    }
+
+    elapsed
+}
+
 pub fn benchmark(c: &mut Criterion) {
     c.bench_function("write 1_000_000 records", |b| {
         let runtime = tokio::runtime::Runtime::new().expect("Failed to construct executor");
         b.to_async(runtime).iter(log_task);
     });
+
+    c.bench_function("roll over to inline segment", |b| {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to construct executor");
+        b.to_async(runtime)
+            .iter_custom(|iters| roll_over_task(false, iters));
+    });
+
+    c.bench_function("roll over to preallocated segment", |b| {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to construct executor");
+        b.to_async(runtime)
+            .iter_custom(|iters| roll_over_task(true, iters));
+    });
 }
 
 criterion_group! {
@@ -0,0 +1,119 @@
+use crate::error::{LogError, Result};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use std::{fmt, path::Path};
+
+/// The byte length of an XChaCha20-Poly1305 key.
+pub const KEY_SIZE: usize = 32;
+
+/// The byte length of the nonce prepended to each encrypted records batch.
+pub const NONCE_SIZE: usize = 24;
+
+/// A key used to encrypt the records of each message written to the log with
+/// XChaCha20-Poly1305.
+///
+/// Each records batch is encrypted with a random nonce, which is stored alongside the ciphertext,
+/// so that the key is the only secret required to read the log. Nonces are 192 bits, so they can
+/// be chosen at random for every batch written under the same key without risk of reuse. As records are encrypted before
+/// the message's CRC is calculated, the CRC covers the ciphertext. Indexes are left unencrypted,
+/// as they only contain offsets and timestamps.
+///
+/// A log must always be opened with the key it was written with, as records encrypted with a
+/// different key, or written without a key, will fail to decrypt.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    cipher: XChaCha20Poly1305,
+}
+
+impl EncryptionKey {
+    /// Constructs an EncryptionKey from raw key bytes.
+    pub fn new(key: [u8; KEY_SIZE]) -> Self {
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+        Self { cipher }
+    }
+
+    /// Constructs an EncryptionKey from a hex-encoded key, ignoring surrounding whitespace.
+    ///
+    /// # Errors
+    /// - Returns [LogError::InvalidEncryptionKey] if the key isn't a 64 character hex string.
+    pub fn from_hex(key: &str) -> Result<Self> {
+        let key = key.trim();
+
+        if key.len() != KEY_SIZE * 2 {
+            return Err(LogError::InvalidEncryptionKey);
+        }
+
+        let mut bytes = [0; KEY_SIZE];
+
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let digits = key
+                .get(i * 2..i * 2 + 2)
+                .ok_or(LogError::InvalidEncryptionKey)?;
+            *byte = u8::from_str_radix(digits, 16).map_err(|_| LogError::InvalidEncryptionKey)?;
+        }
+
+        Ok(Self::new(bytes))
+    }
+
+    /// Constructs an EncryptionKey from a hex-encoded key stored in the provided environment
+    /// variable.
+    ///
+    /// # Errors
+    /// - Returns [LogError::LoadEncryptionKey] if the environment variable isn't set.
+    /// - Returns [LogError::InvalidEncryptionKey] if the key isn't a 64 character hex string.
+    pub fn from_env(var: &str) -> Result<Self> {
+        let key = std::env::var(var).map_err(|e| {
+            LogError::LoadEncryptionKey(std::io::Error::new(std::io::ErrorKind::NotFound, e))
+        })?;
+
+        Self::from_hex(&key)
+    }
+
+    /// Constructs an EncryptionKey from a hex-encoded key stored in the file at the provided
+    /// path.
+    ///
+    /// # Errors
+    /// - Returns [LogError::LoadEncryptionKey] if the file cannot be read.
+    /// - Returns [LogError::InvalidEncryptionKey] if the key isn't a 64 character hex string.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let key = std::fs::read_to_string(path).map_err(LogError::LoadEncryptionKey)?;
+        Self::from_hex(&key)
+    }
+
+    /// Encrypts the provided records, returning the nonce followed by the ciphertext.
+    pub(crate) fn encrypt(&self, records: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, records)
+            .map_err(|_| LogError::Encrypt)?;
+
+        let mut encrypted = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&ciphertext);
+
+        Ok(encrypted)
+    }
+
+    /// Decrypts records previously encrypted by [encrypt](EncryptionKey::encrypt).
+    pub(crate) fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>> {
+        if encrypted.len() < NONCE_SIZE {
+            return Err(LogError::Decrypt);
+        }
+
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_SIZE);
+
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| LogError::Decrypt)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the key itself
+        f.write_str("EncryptionKey(..)")
+    }
+}
@@ -1,13 +1,25 @@
+use std::future::Future;
+use std::io;
 use std::time::Duration;
+use tokio::fs::File;
 
 /// Defines the flushing policy for a message log.
-/// Flushing is triggered by a defined interval, and optionally, a write threshold.
+/// Flushing is triggered by a defined interval, and optionally, a write-count or byte threshold.
+///
+/// Messages that have been written but not yet flushed are held in memory, and will be lost if the
+/// process terminates unexpectedly. Each threshold therefore places an upper bound on the data at
+/// risk, with a flush being triggered as soon as any one of them is reached.
 #[derive(Debug, Clone)]
 pub struct FlushPolicy {
     /// An optional write-count threshold. When the threshold is exceeded, a flush will be triggered.
     pub(crate) number_of_writes: Option<u64>,
+    /// An optional byte threshold. When the number of unflushed bytes exceeds the threshold, a flush
+    /// will be triggered.
+    pub(crate) max_unflushed_bytes: Option<u64>,
     /// The flushing interval for the log. Triggers a flush when the interval elapses.
     pub(crate) interval: Duration,
+    /// Whether flushed writes are synced to durable storage, or left to the OS page cache.
+    pub(crate) sync_mode: SyncMode,
 }
 
 impl Default for FlushPolicy {
@@ -16,7 +28,9 @@ impl Default for FlushPolicy {
     fn default() -> Self {
         Self {
             number_of_writes: None,
+            max_unflushed_bytes: None,
             interval: Duration::from_secs(3),
+            sync_mode: SyncMode::default(),
         }
     }
 }
@@ -29,6 +43,9 @@ impl FlushPolicy {
 
     /// Opts-in to flushing based on a write-count threshold, and specifies that the flush should be
     /// triggered on every write.
+    ///
+    /// This offers the strongest durability guarantee, as every write is committed to the
+    /// filesystem before it is acknowledged, at the cost of throughput.
     pub fn every_write(mut self) -> Self {
         self.number_of_writes = Some(1);
         self
@@ -36,14 +53,91 @@ impl FlushPolicy {
 
     /// Opts-in to flushing based on a write-count threshhold, and specifies that the flush should be
     /// triggered after the provided number of writes.
+    ///
+    /// At most `num - 1` messages can be lost if the process terminates unexpectedly.
     pub fn number_of_writes(mut self, num: u64) -> Self {
         self.number_of_writes = Some(num);
         self
     }
 
+    /// Opts-in to flushing based on a byte threshold, and specifies that the flush should be
+    /// triggered once the encoded size of the unflushed messages reaches the provided number of
+    /// bytes.
+    ///
+    /// This ensures that a burst of large writes is flushed promptly, bounding the amount of data
+    /// that can be lost to roughly `bytes`, regardless of how many messages it spans.
+    pub fn max_unflushed_bytes(mut self, bytes: u64) -> Self {
+        self.max_unflushed_bytes = Some(bytes);
+        self
+    }
+
     /// Overrides the default flushing interval.
+    ///
+    /// The interval is measured on the wall clock, independent of write activity, so a message will
+    /// never remain unflushed for longer than the interval, even on a topic with sparse writes.
     pub fn interval(mut self, interval: Duration) -> Self {
         self.interval = interval;
         self
     }
+
+    /// Overrides the default [SyncMode], which determines whether each flush is synced to durable
+    /// storage.
+    pub fn sync_mode(mut self, mode: SyncMode) -> Self {
+        self.sync_mode = mode;
+        self
+    }
+}
+
+/// Determines whether a flush waits for the segment's data and index files to reach durable
+/// storage.
+///
+/// A flush always hands the buffered messages to the operating system, so they survive the
+/// process terminating unexpectedly. However, they are held in the OS page cache until the kernel
+/// writes them back, and can still be lost if the machine itself crashes or loses power. Syncing
+/// closes that window, at the cost of blocking each flush on the underlying storage device, which
+/// can reduce throughput considerably.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Leaves flushed writes to the OS page cache, offering the highest throughput. The index
+    /// memory map is still written back to its file on each flush, but the file isn't synced.
+    #[default]
+    None,
+    /// Syncs the contents of the segment files on each flush, via [File::sync_data]. Metadata that
+    /// isn't needed to read the data back, such as the file's modification time, may not be synced.
+    Data,
+    /// Syncs the contents and all metadata of the segment files on each flush, via
+    /// [File::sync_all]. This offers the strongest durability guarantee.
+    DataAndMetadata,
+}
+
+impl SyncMode {
+    /// Syncs the provided file to durable storage, as required by this mode.
+    ///
+    /// # Errors
+    /// - Returns Err if the file fails to sync.
+    pub async fn sync(&self, file: &impl SyncFile) -> io::Result<()> {
+        match self {
+            Self::None => Ok(()),
+            Self::Data => file.sync_data().await,
+            Self::DataAndMetadata => file.sync_all().await,
+        }
+    }
+}
+
+/// A file that can be synced to durable storage by a [SyncMode].
+pub trait SyncFile {
+    /// Syncs the file's contents, and any metadata needed to read them back.
+    fn sync_data(&self) -> impl Future<Output = io::Result<()>> + Send;
+    /// Syncs the file's contents and all of its metadata.
+    fn sync_all(&self) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+impl SyncFile for File {
+    fn sync_data(&self) -> impl Future<Output = io::Result<()>> + Send {
+        File::sync_data(self)
+    }
+
+    fn sync_all(&self) -> impl Future<Output = io::Result<()>> + Send {
+        File::sync_all(self)
+    }
 }
@@ -1,8 +1,11 @@
 //! Configuration settings for an individual message log.
 
+mod encryption;
 mod flush_policy;
 
-pub use flush_policy::FlushPolicy;
+use crate::error::{LogError, Result};
+pub use encryption::EncryptionKey;
+pub use flush_policy::{FlushPolicy, SyncFile, SyncMode};
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
@@ -12,6 +15,13 @@ use std::{
 /// The default maximum entries for log segment.
 pub const MAX_INDEX_ENTRIES_DEFAULT: u32 = 100_000;
 
+/// The default maximum size in bytes of a segment's data file.
+pub const SEGMENT_MAX_BYTES_DEFAULT: u64 = 1024 * 1024 * 1024;
+
+/// The default fraction of a hot segment's capacity that must be used before the next segment is
+/// preallocated.
+pub const PREALLOCATE_WATERMARK_DEFAULT: f64 = 0.8;
+
 /// The default log retention period.
 pub const RETENTION_PERIOD_DEFAULT: Duration = Duration::from_secs(60 * 60 * 24 * 7);
 
@@ -20,13 +30,36 @@ pub const CLEANER_INTERVAL_DEFAULT: Duration = Duration::from_secs(60 * 5);
 
 pub type SharedLogConfig = Arc<LogConfig>;
 
+/// The timestamp used to order messages in the log when seeking by timestamp, and to determine
+/// when segments have expired.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampSource {
+    /// The time each message was appended to the log.
+    #[default]
+    Ingest,
+    /// The time each message was created by its producer, falling back to the time it was
+    /// appended to the log if the producer didn't attach an event time.
+    ///
+    /// Event times are subject to clock skew between producers, and may arrive out of order.
+    Event,
+}
+
 /// The LogConfig struct groups preferences for the log's behaviour, such as settings related to
 /// message retention, frequency of flushes, etc, and is shared across each component of the log.
 #[derive(Debug, Clone)]
 pub struct LogConfig {
     /// Indicates the maximum amount of entries a segment index will retain before a new segment
-    /// is created.
+    /// is created. Offsets across the log as a whole are 64-bit, so this only bounds the size of
+    /// each segment.
     pub max_index_entries: u32,
+    /// Indicates the maximum size in bytes that a segment's data file will grow to before a new
+    /// segment is created. A new segment is created when either this threshold or
+    /// `max_index_entries` is reached, whichever comes first.
+    pub segment_max_bytes: u64,
+    /// The fraction of the hot segment's capacity, between 0 and 1, at which the next segment
+    /// will be created in the background, so that rolling over to a new segment doesn't stall
+    /// writes. Preallocation is disabled if set to `None`.
+    pub preallocate_watermark: Option<f64>,
     /// The path to the directory containing the segment index/data files.
     pub segments_path: PathBuf,
     /// The retention period for each individual segment. Determines when a segment is stale/expired,
@@ -35,8 +68,15 @@ pub struct LogConfig {
     /// The desired interval to poll the cleaner task to discover stale/expired segments.
     pub cleaner_interval: Duration,
     /// The desired flush policy for the log. The flush policy dictates the frequency of flushing based
-    /// on the number of writes, and/or a defined interval.
+    /// on the number of writes, and/or a defined interval, and whether flushes are synced to durable
+    /// storage.
     pub flush_policy: FlushPolicy,
+    /// The key used to encrypt message records written to the log's data files. Records are
+    /// stored in plaintext if set to `None`.
+    pub encryption_key: Option<EncryptionKey>,
+    /// The timestamp used for seeking by timestamp and for retention. Changing the source for an
+    /// existing log only affects messages written after the change.
+    pub timestamp_source: TimestampSource,
 }
 
 impl LogConfig {
@@ -45,10 +85,14 @@ impl LogConfig {
     pub fn from_path(path: impl AsRef<Path>) -> Self {
         Self {
             max_index_entries: MAX_INDEX_ENTRIES_DEFAULT,
+            segment_max_bytes: SEGMENT_MAX_BYTES_DEFAULT,
+            preallocate_watermark: Some(PREALLOCATE_WATERMARK_DEFAULT),
             segments_path: path.as_ref().to_owned(),
             retention_period: RETENTION_PERIOD_DEFAULT,
             cleaner_interval: CLEANER_INTERVAL_DEFAULT,
             flush_policy: FlushPolicy::default(),
+            encryption_key: None,
+            timestamp_source: TimestampSource::default(),
         }
     }
 
@@ -58,6 +102,40 @@ impl LogConfig {
         self
     }
 
+    /// Checks that the configuration can be used to open a log.
+    ///
+    /// # Errors
+    /// - Returns [LogError::InvalidConfig] if `max_index_entries` is 0, as no segment could ever
+    ///   hold a message.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_index_entries == 0 {
+            return Err(LogError::InvalidConfig(
+                "max_index_entries must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Overrides the default `segment_max_bytes` field.
+    pub fn segment_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.segment_max_bytes = max_bytes;
+        self
+    }
+
+    /// Overrides the default `preallocate_watermark` field.
+    pub fn preallocate_watermark(mut self, watermark: f64) -> Self {
+        self.preallocate_watermark = Some(watermark);
+        self
+    }
+
+    /// Disables preallocation of segments, so that new segments are only created once the hot
+    /// segment is full.
+    pub fn disable_preallocation(mut self) -> Self {
+        self.preallocate_watermark = None;
+        self
+    }
+
     /// Overrides the default `retention_period` field.
     pub fn retention_period(mut self, period: Duration) -> Self {
         self.retention_period = period;
@@ -75,4 +153,16 @@ impl LogConfig {
         self.flush_policy = policy;
         self
     }
+
+    /// Encrypts message records at rest with the provided key.
+    pub fn encryption_key(mut self, key: EncryptionKey) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Overrides the default `timestamp_source` field.
+    pub fn timestamp_source(mut self, source: TimestampSource) -> Self {
+        self.timestamp_source = source;
+        self
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    error::Result,
-    message::{Headers, Message, CRC_SIZE, HEADERS_SIZE},
+    config::EncryptionKey,
+    error::{LogError, Result},
+    message::{Headers, Message, MessageState, CRC_SIZE, HEADERS_PREFIX_SIZE, LEN_MARKER_SIZE},
 };
 use tokio::{
     fs::File,
@@ -15,37 +16,106 @@ use tokio::{
 #[derive(Debug)]
 pub struct LogIterator {
     reader: BufReader<File>,
+    offset: u64,
     cursor: u64,
     end_position: u64,
+    encryption_key: Option<EncryptionKey>,
+    recovered_offset: u64,
 }
 
 impl LogIterator {
-    /// Constructs a new LogIterator instance.
-    pub fn new(reader: BufReader<File>, cursor: u64, end_position: u64) -> Self {
+    /// Constructs a new LogIterator instance, where `offset` is the log offset of the message at
+    /// the `cursor` position.
+    pub fn new(reader: BufReader<File>, offset: u64, cursor: u64, end_position: u64) -> Self {
         Self {
             reader,
+            offset,
             cursor,
             end_position,
+            encryption_key: None,
+            recovered_offset: 0,
         }
     }
 
+    /// Decrypts the records of each message with the provided key, if the log's records are
+    /// encrypted at rest.
+    pub fn with_encryption_key(mut self, key: Option<EncryptionKey>) -> Self {
+        self.encryption_key = key;
+        self
+    }
+
+    /// Treats messages preceding `offset` that are still uncommitted as aborted, as they were left
+    /// uncommitted when the log was last closed, and can no longer be committed.
+    pub fn with_recovered_offset(mut self, offset: u64) -> Self {
+        self.recovered_offset = offset;
+        self
+    }
+
+    /// The log offset of the next message to be decoded, including any expired messages that
+    /// will be skipped.
+    ///
+    /// Once a message has been returned by [next](LogIterator::next), its offset is
+    /// `next_offset() - 1`.
+    pub fn next_offset(&self) -> u64 {
+        self.offset
+    }
+
     /// Attempts to decode and retrieve the next message from the `reader`.
     /// Returns [Option::None] if there are no more messages to decode.
     ///
+    /// Messages whose time-to-live has elapsed are skipped, so that expired messages are never
+    /// returned, even if the segment containing them has not yet been cleaned up. Aborted
+    /// messages are skipped too, while uncommitted messages are returned, so that readers can
+    /// wait for them to be committed or aborted.
+    ///
     /// # Errors
     /// Returns std::io::ErrorKind::UnexpectedEof if the an unexpected end-of-file
     /// is encountered due to a partially committed or corrupted message.
     pub async fn next(&mut self) -> Result<Option<Message>> {
+        while let Some(message) = self.next_message().await? {
+            let headers = message.headers();
+            let offset = self.offset - 1;
+
+            let aborted = match headers.state() {
+                MessageState::Committed => false,
+                MessageState::Uncommitted => offset < self.recovered_offset,
+                MessageState::Aborted => true,
+            };
+
+            if !aborted && !headers.is_expired() {
+                return Ok(Some(message));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn next_message(&mut self) -> Result<Option<Message>> {
         if self.cursor >= self.end_position {
             return Ok(None);
         }
 
-        let mut headers = vec![0; HEADERS_SIZE];
+        // The length and version begin every layout, and the version identifies the layout of
+        // the rest of the headers
+        let mut headers = vec![0; HEADERS_PREFIX_SIZE];
         self.reader.read_exact(&mut headers).await?;
 
+        let mut length = [0; LEN_MARKER_SIZE];
+        length.copy_from_slice(&headers[..LEN_MARKER_SIZE]);
+        let combined_len = u64::from_be_bytes(length) as usize;
+        let mut version = [0; HEADERS_PREFIX_SIZE - LEN_MARKER_SIZE];
+        version.copy_from_slice(&headers[LEN_MARKER_SIZE..]);
+        let version = u32::from_be_bytes(version);
+        let headers_len =
+            Headers::encoded_len(version).ok_or(LogError::UnknownMessageLayout(version))?;
+
+        headers.resize(headers_len, 0);
+        self.reader
+            .read_exact(&mut headers[HEADERS_PREFIX_SIZE..])
+            .await?;
+
         let headers = Headers::decode(&headers);
-        let remainder_len = headers.length() as usize - HEADERS_SIZE;
-        let combined_len = HEADERS_SIZE + remainder_len;
+        let remainder_len = combined_len - headers_len;
         let records_len = remainder_len - CRC_SIZE;
 
         let mut remainder = vec![0; remainder_len];
@@ -55,9 +125,15 @@ impl LogIterator {
         let mut crc = [0; CRC_SIZE];
         crc.copy_from_slice(&remainder[records_len..]);
         let crc = u32::from_be_bytes(crc);
-        let message = Message::new(headers, records, crc);
+        let mut message = Message::new(headers, records, crc);
+
+        if let Some(key) = &self.encryption_key {
+            let records = key.decrypt(message.records())?;
+            message = message.with_records(records);
+        }
 
         self.cursor += combined_len as u64;
+        self.offset += 1;
 
         Ok(Some(message))
     }
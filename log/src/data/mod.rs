@@ -2,16 +2,18 @@
 
 mod iterator;
 
-use crate::error::Result;
-use crate::message::Message;
+use crate::config::SyncMode;
+use crate::error::{LogError, Result};
+use crate::message::{has_state, Message, MessageState, LEN_MARKER_SIZE, STATE_POSITION};
 use bytes::BytesMut;
 pub use iterator::LogIterator;
 use std::io::SeekFrom;
+use std::mem::size_of;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::fs;
 use tokio::fs::{File, OpenOptions};
-use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
 
 /// Wrapper type for a data file belonging to a log segment.
 ///
@@ -29,11 +31,36 @@ pub struct Data {
 impl Data {
     /// Creates a new Data instance by opening an existing data file.
     ///
+    /// The file is opened in append mode, so that new messages are written after those already
+    /// in the file, rather than over them from the start of the file.
+    ///
     /// # Errors
     /// - Returns Err if the existing data file cannot be opened.
     pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
-        let file = OpenOptions::new().read(true).write(true).open(path).await?;
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(path)
+            .await?;
+        let metadata = file.metadata().await?;
+        let position = metadata.len();
+
+        Ok(Self {
+            path: path.to_owned(),
+            file,
+            buffer: BytesMut::new(),
+            position,
+        })
+    }
+
+    /// Creates a new Data instance by opening an existing data file without write access.
+    ///
+    /// # Errors
+    /// - Returns Err if the existing data file cannot be opened.
+    pub async fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new().read(true).open(path).await?;
         let metadata = file.metadata().await?;
         let position = metadata.len();
 
@@ -74,6 +101,7 @@ impl Data {
     /// to iterate over.
     ///
     /// # Params
+    /// * `start_offset`   - The log offset of the message beginning at `start_position`.
     /// * `start_position` - The starting byte offset in the data file.
     /// * `end_position`   - An optional end_position to limit the amount of messages to read.
     ///                      If not provided, messages will be read from the entire segment, beginning
@@ -84,6 +112,7 @@ impl Data {
     /// - Returns Err if the buffered reader cannot seek to the `start_position`.
     pub async fn read_messages(
         &self,
+        start_offset: u64,
         start_position: u64,
         end_position: Option<u64>,
     ) -> Result<LogIterator> {
@@ -92,11 +121,28 @@ impl Data {
         reader.seek(SeekFrom::Start(start_position)).await?;
 
         let end_position = end_position.unwrap_or(self.position);
-        let log_slice = LogIterator::new(reader, start_position, end_position);
+        let log_slice = LogIterator::new(reader, start_offset, start_position, end_position);
 
         Ok(log_slice)
     }
 
+    /// Reads the version field of the first message in the data file, which records the layout
+    /// that the segment was first written with, or returns [None] if the file is empty.
+    ///
+    /// # Errors
+    /// - Returns Err if the file fails to be opened or read.
+    pub async fn first_message_version(&self) -> Result<Option<u32>> {
+        if self.position < (LEN_MARKER_SIZE + size_of::<u32>()) as u64 {
+            return Ok(None);
+        }
+
+        let mut file = File::open(&self.path).await?;
+        file.seek(SeekFrom::Start(LEN_MARKER_SIZE as u64)).await?;
+        let version = file.read_u32().await?;
+
+        Ok(Some(version))
+    }
+
     /// Returns true if the data file's last modified time falls outside of the
     /// provided `stale_duration`.
     ///
@@ -125,18 +171,82 @@ impl Data {
         self.position += length;
     }
 
-    /// Flushes the write buffer to the data file.
+    /// Updates the [MessageState] of the message beginning at the provided byte `position`.
+    ///
+    /// The state is updated in the write buffer if the message has not yet been flushed, and in
+    /// the data file otherwise.
+    ///
+    /// # Errors
+    /// - Returns [LogError::Write] if the data file cannot be opened for writing, or fails to be
+    ///   written to.
+    /// - Returns [LogError::LegacyMessageLayout] if the message was written in a layout without a
+    ///   state.
+    pub async fn set_state(&mut self, position: u64, state: MessageState) -> Result<()> {
+        let flushed = self.position - self.buffer.len() as u64;
+
+        if position >= flushed {
+            // Buffered messages are always written in the current layout
+            let position = position + STATE_POSITION as u64;
+            self.buffer[(position - flushed) as usize] = state.into();
+        } else {
+            // The data file may have been opened in append mode, so write via a separate handle
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&self.path)
+                .await
+                .map_err(LogError::Write)?;
+
+            // Messages written in earlier layouts may not store a state to update
+            file.seek(SeekFrom::Start(position + LEN_MARKER_SIZE as u64))
+                .await
+                .map_err(LogError::Write)?;
+            let version = file.read_u32().await.map_err(LogError::Write)?;
+
+            if !has_state(version) {
+                return Err(LogError::LegacyMessageLayout);
+            }
+
+            let position = position + STATE_POSITION as u64;
+            file.seek(SeekFrom::Start(position))
+                .await
+                .map_err(LogError::Write)?;
+            file.write_all(&[state.into()])
+                .await
+                .map_err(LogError::Write)?;
+            file.flush().await.map_err(LogError::Write)?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes the write buffer to the data file, and syncs the file as required by the provided
+    /// `sync_mode`.
+    ///
+    /// The write buffer is only cleared once it has been written to the data file, so that the
+    /// flush can be retried following a failure.
     ///
     /// # Errors
-    /// - Returns Err if writing the buffer to the data file fails.
-    /// - Returns Err if flushing the data file fails.
-    pub async fn flush(&mut self) -> Result<()> {
-        let buffer = std::mem::replace(&mut self.buffer, BytesMut::new());
-        self.file.write_all(&buffer).await?;
-        self.file.flush().await?;
+    /// - Returns [LogError::Flush] if writing the buffer to the data file fails.
+    /// - Returns [LogError::Flush] if flushing or syncing the data file fails.
+    pub async fn flush(&mut self, sync_mode: SyncMode) -> Result<()> {
+        if let Err(err) = self.write_buffer().await {
+            // Discard any partial write, so that retrying the flush doesn't duplicate it
+            let flushed = self.position - self.buffer.len() as u64;
+            let _ = self.file.set_len(flushed).await;
+            return Err(LogError::Flush(err));
+        }
+
+        self.buffer.clear();
+        sync_mode.sync(&self.file).await.map_err(LogError::Flush)?;
         Ok(())
     }
 
+    async fn write_buffer(&mut self) -> std::io::Result<()> {
+        self.file.write_all(&self.buffer).await?;
+        self.file.flush().await
+    }
+
     /// Removes the data file from the filesystem.
     ///
     /// This method is typically only called by the log cleaner task to remove data files
@@ -149,6 +259,18 @@ impl Data {
         Ok(())
     }
 
+    /// Renames the data file.
+    /// This method is used to assign a base offset to a preallocated segment.
+    ///
+    /// # Errors
+    /// - Returns Err if the data file fails to be renamed.
+    pub async fn rename(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::rename(&self.path, path).await?;
+        self.path = path.to_owned();
+        Ok(())
+    }
+
     /// The current byte position in the data file.
     pub fn position(&self) -> u64 {
         self.position
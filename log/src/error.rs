@@ -1,5 +1,6 @@
 //! Type aliases and Enums relating to Selium Log errors.
 
+use std::io::ErrorKind;
 use thiserror::Error;
 
 /// Result type alias for [LogError].
@@ -12,6 +13,15 @@ pub enum LogError {
     #[error("Failed to create log directory.")]
     CreateLogsDirectory(#[source] std::io::Error),
 
+    /// Returned when opening a log with an invalid [LogConfig](crate::config::LogConfig).
+    #[error("Invalid log configuration: {0}")]
+    InvalidConfig(&'static str),
+
+    /// Returned when a segment's [Index](crate::index::Index) has no room for another entry. The
+    /// message has not been written to the log.
+    #[error("Segment index is at full capacity.")]
+    IndexFull,
+
     /// Returned when a [std::io::Error] error occurs while provisioning a [SegmentList](crate::segment::SegmentList).
     #[error("Failed to load segment offsets from log directory.")]
     LoadSegments(#[source] std::io::Error),
@@ -20,11 +30,78 @@ pub enum LogError {
     #[error("Cannot find a hot segment to write to.")]
     SegmentListEmpty,
 
+    /// Returned when attempting to write to or flush a log that was opened in read-only mode.
+    #[error("Cannot modify a log opened in read-only mode.")]
+    ReadOnly,
+
+    /// Returned when a message fails to be written to a segment's data file. The message has
+    /// not been written to the log.
+    #[error("Failed to write to segment data file.")]
+    Write(#[source] std::io::Error),
+
+    /// Returned when a segment fails to be flushed to the filesystem. Unflushed messages are
+    /// retained, and flushed again on the next attempt.
+    #[error("Failed to flush segment to the filesystem.")]
+    Flush(#[source] std::io::Error),
+
+    /// Returned when a segment's [Index](crate::index::Index) file fails to be created or loaded.
+    #[error("Failed to create or load segment index file.")]
+    Index(#[source] std::io::Error),
+
     /// Returned when the [Index](crate::index::Index) file fails to map to the memory map buffer.
     #[error("Failed to map segment index file to memory.")]
     MemoryMapIndex(#[source] std::io::Error),
 
+    /// Returned when an [EncryptionKey](crate::config::EncryptionKey) cannot be read from its
+    /// source.
+    #[error("Failed to load log encryption key.")]
+    LoadEncryptionKey(#[source] std::io::Error),
+
+    /// Returned when an [EncryptionKey](crate::config::EncryptionKey) is not a valid hex-encoded
+    /// 256-bit key.
+    #[error("Log encryption key must be a 64 character hex string.")]
+    InvalidEncryptionKey,
+
+    /// Returned when attempting to update the state of a message that isn't in the log.
+    #[error("Cannot find a message at offset {0}.")]
+    MessageNotFound(u64),
+
+    /// Returned when a message's headers were written in a layout that this version of Selium Log
+    /// doesn't recognise, e.g. by a newer version.
+    #[error("Cannot decode message headers with unknown version {0:#x}.")]
+    UnknownMessageLayout(u32),
+
+    /// Returned when attempting to update the state of a message written before message states
+    /// were stored in the log.
+    #[error("Cannot update the state of a message written in a legacy layout.")]
+    LegacyMessageLayout,
+
+    /// Returned when a message's records fail to be encrypted.
+    #[error("Failed to encrypt message records.")]
+    Encrypt,
+
+    /// Returned when a message's records fail to be decrypted, e.g. because the log was written
+    /// with a different key.
+    #[error("Failed to decrypt message records.")]
+    Decrypt,
+
     /// Any generic [std::io::Error] errors that aren't classified.
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
+
+impl LogError {
+    /// Returns true if the error was caused by an I/O failure that may succeed if retried, such
+    /// as an interrupted system call.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Write(err) | Self::Flush(err) | Self::Index(err) | Self::IoError(err) => {
+                matches!(
+                    err.kind(),
+                    ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+                )
+            }
+            _ => false,
+        }
+    }
+}
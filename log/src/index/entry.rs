@@ -1,9 +1,15 @@
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 
+/// The size in bytes of an encoded [IndexEntry]: a 32-bit relative offset, followed by a 64-bit
+/// timestamp and a 64-bit physical position.
 pub const SIZE_OF_INDEX_ENTRY: usize =
     std::mem::size_of::<u32>() + std::mem::size_of::<u64>() + std::mem::size_of::<u64>();
 
 /// Represents an entry in a corresponding index file.
+///
+/// Relative offsets are 32-bit, as they only need to address the entries in a single segment,
+/// which is bounded by [LogConfig::max_index_entries](crate::config::LogConfig::max_index_entries).
+/// Physical positions are 64-bit, so data files can grow beyond 4GiB.
 #[derive(Debug)]
 pub struct IndexEntry {
     relative_offset: u32,
@@ -49,6 +55,12 @@ impl IndexEntry {
         self.relative_offset
     }
 
+    /// The highest timestamp of any message in the segment, up to and including the corresponding
+    /// record, in the [TimestampUnit](super::TimestampUnit) of the index.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
     /// The physical position of the corresponding record in the log file.
     pub fn physical_position(&self) -> u64 {
         self.physical_position
@@ -1,6 +1,6 @@
 use super::entry::SIZE_OF_INDEX_ENTRY;
 use crate::{
-    config::SharedLogConfig,
+    config::{SharedLogConfig, SyncMode},
     error::{LogError, Result},
     index::IndexEntry,
 };
@@ -9,15 +9,24 @@ use std::{
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
 };
-use tokio::fs::{self, OpenOptions};
+use tokio::fs::{self, File, OpenOptions};
 
 /// Wrapper type for a file-backed memory-map.
+///
+/// The memory map is either mutable, or read-only when loaded via [Mmap::load_read_only].
 #[derive(Debug)]
 pub struct Mmap {
-    mmap: memmap2::MmapMut,
+    mmap: Buffer,
+    file: File,
     path: PathBuf,
 }
 
+#[derive(Debug)]
+enum Buffer {
+    Mutable(memmap2::MmapMut),
+    ReadOnly(memmap2::Mmap),
+}
+
 impl Mmap {
     /// Creates an index file and maps it to a mutable memory mapped buffer.
     ///
@@ -25,8 +34,8 @@ impl Mmap {
     /// multiplied by the byte size of an index entry, and then zeroed.
     ///
     /// # Errors
-    /// - Returns Err if the underlying file cannot be created.
-    /// - Returns Err if the file length cannot be expanded.
+    /// - Returns [LogError::Index] if the underlying file cannot be created.
+    /// - Returns [LogError::Index] if the file length cannot be expanded.
     /// - Returns Err if the memory map system call fails.
     pub async fn create(path: impl AsRef<Path>, config: SharedLogConfig) -> Result<Self> {
         let path = path.as_ref();
@@ -38,10 +47,11 @@ impl Mmap {
             .truncate(true)
             .mode(0o600)
             .open(path)
-            .await?;
+            .await
+            .map_err(LogError::Index)?;
 
         let length = config.max_index_entries as u64 * SIZE_OF_INDEX_ENTRY as u64;
-        file.set_len(length).await?;
+        file.set_len(length).await.map_err(LogError::Index)?;
 
         // Safety: https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html#safety
         // Our usage is safe, as reads/writes/flushes are performed atomically, and the appropriate filesystem
@@ -50,7 +60,8 @@ impl Mmap {
         let mmap = unsafe { memmap2::MmapMut::map_mut(&file).map_err(LogError::MemoryMapIndex)? };
 
         Ok(Self {
-            mmap,
+            mmap: Buffer::Mutable(mmap),
+            file,
             path: path.to_owned(),
         })
     }
@@ -58,21 +69,72 @@ impl Mmap {
     /// Loads an index file and maps it to a mutable memory mapped buffer.
     ///
     /// # Errors
-    /// - Returns Err if the underlying file cannot be loaded.
+    /// - Returns [LogError::Index] if the underlying file cannot be loaded.
     /// - Returns Err if the memory map system call fails.
     pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
-        let file = OpenOptions::new().write(true).read(true).open(path).await?;
+        let file = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .open(path)
+            .await
+            .map_err(LogError::Index)?;
 
         // Safety: See the Mmap::create method for explanation.
         let mmap = unsafe { memmap2::MmapMut::map_mut(&file).map_err(LogError::MemoryMapIndex)? };
 
         Ok(Self {
-            mmap,
+            mmap: Buffer::Mutable(mmap),
+            file,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Loads an index file and maps it to a read-only memory mapped buffer.
+    ///
+    /// Entries appended to the file by another process after it has been loaded may be visible
+    /// through the buffer, but the buffer itself can never be written to.
+    ///
+    /// # Errors
+    /// - Returns [LogError::Index] if the underlying file cannot be loaded.
+    /// - Returns Err if the memory map system call fails.
+    pub async fn load_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .open(path)
+            .await
+            .map_err(LogError::Index)?;
+
+        // Safety: See the Mmap::create method for explanation.
+        let mmap = unsafe { memmap2::Mmap::map(&file).map_err(LogError::MemoryMapIndex)? };
+
+        Ok(Self {
+            mmap: Buffer::ReadOnly(mmap),
+            file,
             path: path.to_owned(),
         })
     }
 
+    /// Flushes outstanding modifications to the underlying file, and syncs the file as required by
+    /// the provided `sync_mode`.
+    ///
+    /// The memory map is always flushed synchronously, and the file is then synced as well with
+    /// the stronger modes.
+    /// This is a no-op for read-only memory maps.
+    ///
+    /// # Errors
+    /// - Returns [LogError::Flush] if the memory map fails to flush.
+    /// - Returns [LogError::Flush] if the underlying file fails to sync.
+    pub async fn flush(&self, sync_mode: SyncMode) -> Result<()> {
+        if let Buffer::Mutable(mmap) = &self.mmap {
+            mmap.flush().map_err(LogError::Flush)?;
+            sync_mode.sync(&self.file).await.map_err(LogError::Flush)?;
+        }
+
+        Ok(())
+    }
+
     /// Removes the underlying file from the filesystem.
     ///
     /// # Errors
@@ -82,15 +144,59 @@ impl Mmap {
         Ok(())
     }
 
+    /// Renames the underlying file, retaining the existing memory map.
+    ///
+    /// # Errors
+    /// - Returns Err if the file cannot be renamed.
+    pub async fn rename(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::rename(&self.path, path).await?;
+        self.path = path.to_owned();
+        Ok(())
+    }
+
     /// Pushes the provided [IndexEntry] to the memory map buffer.
     ///
-    /// # Panics
-    /// This method will panic if superflous pushes are attempted when the buffer is
-    /// at full capacity.
-    pub fn push(&mut self, entry: IndexEntry) {
-        let slice_start = (entry.relative_offset() - 1) as usize * SIZE_OF_INDEX_ENTRY;
+    /// # Errors
+    /// - Returns [LogError::IndexFull] if the entry's slot is beyond the capacity of the buffer.
+    /// - Returns [LogError::ReadOnly] if the buffer is read-only.
+    pub fn push(&mut self, entry: IndexEntry) -> Result<()> {
+        let slice_start = (entry.relative_offset() as usize)
+            .checked_sub(1)
+            .ok_or(LogError::IndexFull)?
+            * SIZE_OF_INDEX_ENTRY;
         let slice_end = slice_start + SIZE_OF_INDEX_ENTRY;
-        self.mmap[slice_start..slice_end].copy_from_slice(&entry.into_slice());
+
+        if slice_end > self.len() {
+            return Err(LogError::IndexFull);
+        }
+
+        match &mut self.mmap {
+            Buffer::Mutable(mmap) => {
+                mmap[slice_start..slice_end].copy_from_slice(&entry.into_slice());
+                Ok(())
+            }
+            Buffer::ReadOnly(_) => Err(LogError::ReadOnly),
+        }
+    }
+
+    /// The number of entries that the buffer has room for.
+    pub fn capacity(&self) -> u32 {
+        (self.len() / SIZE_OF_INDEX_ENTRY) as u32
+    }
+
+    /// Decodes the [IndexEntry] in the slot for the provided relative offset, without checking
+    /// whether the entry has been pushed.
+    ///
+    /// Returns [Option::None] if the relative offset is 0, or is beyond the capacity of the buffer.
+    pub fn get(&self, relative_offset: u32) -> Option<IndexEntry> {
+        let index_pos = (relative_offset as usize).checked_sub(1)? * SIZE_OF_INDEX_ENTRY;
+
+        if index_pos + SIZE_OF_INDEX_ENTRY > self.len() {
+            return None;
+        }
+
+        Some(IndexEntry::from_slice(self.get_entry_slice(index_pos)))
     }
 
     /// Performs a binary search to locate an [IndexEntry] in the memory map buffer.
@@ -139,31 +245,39 @@ impl Mmap {
     }
 
     fn get_last_offset(&self) -> u32 {
-        let range_start = self.mmap.len() - SIZE_OF_INDEX_ENTRY;
-        let mut slice = &self.mmap[range_start..];
+        let range_start = self.len() - SIZE_OF_INDEX_ENTRY;
+        let mut slice = &self[range_start..];
         slice.get_u32()
     }
 
     fn get_entry_slice(&self, offset: usize) -> &[u8] {
         let length = offset + SIZE_OF_INDEX_ENTRY;
-        &self.mmap[offset..length]
+        &self[offset..length]
     }
 
     fn get_offset_range(&self) -> std::ops::Range<usize> {
-        0..self.mmap.len() / SIZE_OF_INDEX_ENTRY
+        0..self.len() / SIZE_OF_INDEX_ENTRY
     }
 }
 
 impl Deref for Mmap {
-    type Target = memmap2::MmapMut;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
-        &self.mmap
+        match &self.mmap {
+            Buffer::Mutable(mmap) => mmap,
+            Buffer::ReadOnly(mmap) => mmap,
+        }
     }
 }
 
 impl DerefMut for Mmap {
+    /// # Panics
+    /// This method will panic if the memory map is read-only.
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.mmap
+        match &mut self.mmap {
+            Buffer::Mutable(mmap) => mmap,
+            Buffer::ReadOnly(_) => panic!("Cannot write to a read-only memory map"),
+        }
     }
 }
@@ -3,7 +3,10 @@
 mod entry;
 mod mmap;
 
-use crate::{config::SharedLogConfig, error::Result};
+use crate::{
+    config::SharedLogConfig,
+    error::{LogError, Result},
+};
 pub use entry::IndexEntry;
 pub use mmap::Mmap;
 use std::path::Path;
@@ -14,23 +17,76 @@ use std::path::Path;
 ///
 /// The index lists where any messages in the log can be located via byte offset. Given a relative offset,
 /// a fast lookup of a byte offset in the data file can be performed via a binary search.
+///
+/// Each entry records the highest timestamp of any message in the segment up to and including
+/// its own, rather than the timestamp of its message. Entries therefore remain ordered by
+/// timestamp even when messages aren't, e.g. when producers attach skewed or out-of-order event
+/// times, so the index can still be binary searched by timestamp.
+///
+/// Entries record timestamps in the index's [TimestampUnit], though timestamps are always passed
+/// to and returned from the index in milliseconds.
 #[derive(Debug)]
 pub struct Index {
     mmap: Mmap,
     current_offset: u32,
+    max_timestamp: u64,
+    unit: TimestampUnit,
     config: SharedLogConfig,
 }
 
+/// The unit of the timestamps recorded by the entries of an [Index].
+///
+/// Indexes record timestamps in milliseconds, but segments written by earlier versions of the log
+/// recorded them in seconds, which must be preserved so that every entry in an index shares the
+/// same unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampUnit {
+    Seconds,
+    #[default]
+    Millis,
+}
+
+impl TimestampUnit {
+    /// Converts a timestamp in milliseconds to this unit.
+    fn encode(self, timestamp: u64) -> u64 {
+        match self {
+            Self::Seconds => timestamp / 1000,
+            Self::Millis => timestamp,
+        }
+    }
+
+    /// Converts a timestamp in this unit to milliseconds.
+    fn decode(self, timestamp: u64) -> u64 {
+        match self {
+            Self::Seconds => timestamp.saturating_mul(1000),
+            Self::Millis => timestamp,
+        }
+    }
+}
+
 impl Index {
     /// Constructs a new Index instance.
     pub fn new(mmap: Mmap, current_offset: u32, config: SharedLogConfig) -> Self {
+        let max_timestamp = mmap
+            .get(current_offset)
+            .map_or(0, |entry| entry.timestamp());
+
         Self {
             mmap,
             current_offset,
+            max_timestamp,
+            unit: TimestampUnit::default(),
             config,
         }
     }
 
+    /// Sets the unit of the timestamps recorded by the index's entries.
+    pub fn with_timestamp_unit(mut self, unit: TimestampUnit) -> Self {
+        self.max_timestamp = unit.decode(self.unit.encode(self.max_timestamp));
+        self.unit = unit;
+        self
+    }
+
     /// Constructs an Index instance from an existing index file.
     ///
     /// # Errors
@@ -41,6 +97,17 @@ impl Index {
         Ok(Self::new(mmap, next_offset, config))
     }
 
+    /// Constructs an Index instance from an existing index file, which is mapped to memory in
+    /// read-only mode.
+    ///
+    /// # Errors
+    /// - Returns Err if a failure occurs while opening the existing index file.
+    pub async fn open_read_only(path: impl AsRef<Path>, config: SharedLogConfig) -> Result<Self> {
+        let mmap = Mmap::load_read_only(path).await?;
+        let next_offset = mmap.get_current_offset();
+        Ok(Self::new(mmap, next_offset, config))
+    }
+
     /// Constructs an Index instance, and creates the underlying memory-mapped index file.
     ///
     /// # Errors
@@ -54,25 +121,37 @@ impl Index {
     /// This method is called after appending an encoded message to the segment's data file.
     ///
     /// # Params
-    /// * `timestamp` - The UNIX timestamp corresponding to when the message was appended the data file.
+    /// * `timestamp` - The UNIX timestamp in milliseconds of the message, taken from the log's
+    ///   [TimestampSource](crate::config::TimestampSource).
     /// * `file_position` - The byte offset in the data file for the appended message.
-    pub fn append(&mut self, timestamp: u64, file_position: u64) {
-        if self.current_offset <= self.config.max_index_entries {
-            let next_offset = self.current_offset + 1;
-            let entry = IndexEntry::new(next_offset, timestamp, file_position);
-            self.mmap.push(entry);
-            self.current_offset = next_offset;
+    ///
+    /// # Errors
+    /// - Returns [LogError::IndexFull] if the index is at capacity.
+    /// - Returns [LogError::ReadOnly] if the index was opened in read-only mode.
+    pub fn append(&mut self, timestamp: u64, file_position: u64) -> Result<()> {
+        if self.is_full() {
+            return Err(LogError::IndexFull);
         }
+
+        let next_offset = self.current_offset + 1;
+        let max_timestamp = self.max_timestamp.max(timestamp);
+        let entry = IndexEntry::new(next_offset, self.unit.encode(max_timestamp), file_position);
+        self.mmap.push(entry)?;
+        self.max_timestamp = max_timestamp;
+        self.current_offset = next_offset;
+
+        Ok(())
     }
 
-    /// Flushes the memory map to the underlying file.
+    /// Flushes the memory map to the underlying file, syncing it as required by the log's
+    /// [SyncMode](crate::config::SyncMode).
     /// This operation can have negative performance impacts, so should be used sparingly until
     /// replication has been implemented.
     ///
     /// # Errors
     /// - Returns Err if the memory map fails to flush to the underlying file.
-    pub fn flush(&mut self) -> Result<()> {
-        self.mmap.flush()?;
+    pub async fn flush(&mut self) -> Result<()> {
+        self.mmap.flush(self.config.flush_policy.sync_mode).await?;
         Ok(())
     }
 
@@ -89,6 +168,39 @@ impl Index {
             .find(|entry| entry.relative_offset() == relative_offset)
     }
 
+    /// Performs a binary search for the first entry whose timestamp is greater than or equal to
+    /// `timestamp`, and returns its relative offset.
+    ///
+    /// As entries record the highest timestamp seen so far, no message preceding the returned
+    /// entry has a timestamp at or after `timestamp`, though some following it may have earlier
+    /// timestamps.
+    ///
+    /// Returns [Option::None] if every entry in the index precedes `timestamp`.
+    pub fn find_by_timestamp(&self, timestamp: u64) -> Option<u32> {
+        if self.current_offset == 0 || self.max_timestamp < timestamp {
+            return None;
+        }
+
+        let (mut low, mut high) = (1, self.current_offset);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+
+            let entry_timestamp = self
+                .mmap
+                .get(mid)
+                .map_or(u64::MAX, |entry| self.unit.decode(entry.timestamp()));
+
+            if entry_timestamp < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        Some(low)
+    }
+
     /// Removes the underlying index file.
     /// This method is typically only called when a segment is being removed by the log cleaner task.
     ///
@@ -99,14 +211,34 @@ impl Index {
         Ok(())
     }
 
+    /// Renames the underlying index file.
+    /// This method is used to assign a base offset to a preallocated segment.
+    ///
+    /// # Errors
+    /// - Returns Err if the underlying file cannot be renamed.
+    pub async fn rename(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.mmap.rename(path).await?;
+        Ok(())
+    }
+
     /// The current relative offset in the index.
     pub fn current_offset(&self) -> u32 {
         self.current_offset
     }
 
+    /// The highest timestamp in milliseconds of any message in the index, or 0 if the index is
+    /// empty.
+    pub fn max_timestamp(&self) -> u64 {
+        self.max_timestamp
+    }
+
     /// Returns true if the index is at capacity, based on the provided `max_index_entries` option
     /// in the shared log configuration.
+    ///
+    /// An index file created with a lower `max_index_entries` than the current configuration is
+    /// also full once the file itself is at capacity.
     pub fn is_full(&self) -> bool {
-        self.current_offset == self.config.max_index_entries
+        let capacity = self.config.max_index_entries.min(self.mmap.capacity());
+        self.current_offset >= capacity
     }
 }
@@ -29,16 +29,18 @@ pub mod segment;
 
 use crate::{
     config::SharedLogConfig,
+    data::LogIterator,
     error::{LogError, Result},
-    message::{Message, MessageSlice},
+    message::{Message, MessageSlice, MessageState},
     segment::SegmentList,
     tasks::{CleanerTask, FlusherTask},
 };
+use futures::{stream, Stream};
 use segment::SharedSegmentList;
-use std::{ffi::OsStr, path::Path, sync::Arc};
+use std::{ffi::OsStr, io::ErrorKind, path::Path, sync::Arc};
 use tokio::{
     fs,
-    sync::{mpsc, RwLock},
+    sync::{futures::Notified, mpsc, Notify, RwLock},
 };
 
 /// The entry point to Selium Log.
@@ -49,6 +51,9 @@ use tokio::{
 /// The MessageLog also creates the Flusher and Cleaner asynchronous tasks, and takes ownership of them to
 /// assure that the tasks are gracefully terminated when the MessageLog instance is destroyed.
 ///
+/// A log can also be opened via [MessageLog::open_read_only], for tools that only need to read
+/// historical data, in which case no tasks are created and writes are rejected.
+///
 /// # Examples
 /// ```
 /// use anyhow::Result;
@@ -79,9 +84,16 @@ use tokio::{
 pub struct MessageLog {
     segments: SharedSegmentList,
     config: SharedLogConfig,
+    tasks: Option<LogTasks>,
+    flushed: Arc<Notify>,
+}
+
+/// The background tasks owned by a log opened with write access.
+#[derive(Debug)]
+struct LogTasks {
     flush_interrupt: mpsc::Sender<()>,
-    _flusher: Arc<FlusherTask>,
-    _cleaner: Arc<CleanerTask>,
+    flusher: Arc<FlusherTask>,
+    cleaner: Arc<CleanerTask>,
 }
 
 impl MessageLog {
@@ -97,41 +109,159 @@ impl MessageLog {
     /// The Flusher and Cleaner asynchronous tasks will also be started, and will run in the background.
     ///
     /// # Errors
+    /// - Returns [LogError::InvalidConfig] if the provided `config` fails validation.
     /// - Returns [LogError::CreateLogsDirectory] if an error occurs while creating the log directory.
     /// - Returns Err if an error occurs while constructing the [SegmentList].
     pub async fn open(config: SharedLogConfig) -> Result<Self> {
+        config.validate()?;
+
         fs::create_dir_all(&config.segments_path)
             .await
             .map_err(LogError::CreateLogsDirectory)?;
 
         let segments = load_segments(config.clone()).await?;
-        let (_flusher, flush_interrupt) = FlusherTask::start(config.clone(), segments.clone());
-        let _cleaner = CleanerTask::start(config.clone(), segments.clone());
+        let flushed = segments.read().await.flush_notify();
+        let (flusher, flush_interrupt) = FlusherTask::start(config.clone(), segments.clone());
+        let cleaner = CleanerTask::start(config.clone(), segments.clone());
+
+        let tasks = LogTasks {
+            flush_interrupt,
+            flusher,
+            cleaner,
+        };
 
         Ok(Self {
             segments,
             config,
-            flush_interrupt,
-            _flusher,
-            _cleaner,
+            tasks: Some(tasks),
+            flushed,
+        })
+    }
+
+    /// Opens an existing message log at the segments directory configured in the provided
+    /// `config` argument, for reading only.
+    ///
+    /// Index files are mapped to memory in read-only mode, and the Flusher and Cleaner tasks are
+    /// not started, so the log can safely be read while another process has it open for writing.
+    /// Only messages that have been flushed by the writer will be visible.
+    ///
+    /// Calls to [write](MessageLog::write) and [flush](MessageLog::flush) will return
+    /// [LogError::ReadOnly].
+    ///
+    /// # Errors
+    /// - Returns [LogError::InvalidConfig] if the provided `config` fails validation.
+    /// - Returns [LogError::LoadSegments] if the log directory cannot be read.
+    /// - Returns Err if an error occurs while constructing the [SegmentList].
+    pub async fn open_read_only(config: SharedLogConfig) -> Result<Self> {
+        config.validate()?;
+        let offsets = get_offsets(&config.segments_path).await?;
+        let segments = SegmentList::from_offsets_read_only(&offsets, config.clone()).await?;
+        let flushed = segments.flush_notify();
+
+        Ok(Self {
+            segments: Arc::new(RwLock::new(segments)),
+            config,
+            tasks: None,
+            flushed,
         })
     }
 
-    /// Writes the provided [Message] to the current hot segment.
+    /// Writes the provided [Message] to the current hot segment, returning the offset assigned to
+    /// the message.
     ///
     /// If the hot segment is at full capacity following the write, the current hot segment
     /// will be flushed, and a new segment will be created and designated as the hot segment in
     /// its place. Otherwise, the `writes_since_last_flush` field is incremented by 1.
     ///
+    /// A flush that fails after the message has been written doesn't fail the write. The
+    /// unflushed messages are retained, and the flush is retried by the Flusher task and before
+    /// the next write.
+    ///
     /// # Errors
+    /// Errors are only returned if the message wasn't written.
+    /// - Returns [LogError::ReadOnly] if the log was opened in read-only mode.
     /// - Returns [LogError::SegmentListEmpty] if there are no segments in the list yet.
+    /// - Returns [LogError::Flush] if a previous flush failed, and fails again.
     /// - Returns Err if writing to the hot segment fails.
-    /// - Returns Err if the segment is full, and the current hot segment fails to flush.
-    /// - Returns Err if the segment is full, and the new hot segment fails to be created.
-    pub async fn write(&self, message: Message) -> Result<()> {
-        self.segments.write().await.write(message).await?;
-        self.try_flush().await?;
-        Ok(())
+    /// - Returns Err if a previous rollover to a new hot segment failed, and fails again.
+    pub async fn write(&self, message: Message) -> Result<u64> {
+        let offsets = self.write_many([message]).await?;
+        offsets.first().copied().ok_or(LogError::SegmentListEmpty)
+    }
+
+    /// Writes each of the provided messages to the hot segment in order, returning the offsets
+    /// assigned to them.
+    ///
+    /// Behaves like a series of calls to [write](MessageLog::write), except that the segment
+    /// list's lock is only acquired once, and the [FlushPolicy](crate::config::FlushPolicy) is
+    /// only checked once all of the messages have been written, so a burst of messages is
+    /// committed by a single flush. Each message is still appended to the segment individually,
+    /// so this isn't an atomic append: a failure part way through leaves the earlier messages
+    /// written.
+    ///
+    /// If writing a message fails once earlier messages have been written, the offsets of the
+    /// written messages are returned and the remaining messages are discarded, so fewer offsets
+    /// than messages may be returned. Writing the first unwritten message again will surface the
+    /// error.
+    ///
+    /// # Errors
+    /// Errors are only returned if no messages were written, for the same reasons as
+    /// [write](MessageLog::write).
+    pub async fn write_many(
+        &self,
+        messages: impl IntoIterator<Item = Message>,
+    ) -> Result<Vec<u64>> {
+        let tasks = self.tasks.as_ref().ok_or(LogError::ReadOnly)?;
+        let mut segments = self.segments.write().await;
+        // Retry a failed flush before writing, so that the failure is surfaced to the writer
+        let retried = segments.flush_failed();
+
+        if retried {
+            segments.flush().await?;
+        }
+
+        let mut offsets = Vec::new();
+
+        for message in messages {
+            match segments.write(message).await {
+                Ok(offset) => offsets.push(offset),
+                Err(e) if offsets.is_empty() => return Err(e),
+                Err(_) => break,
+            }
+        }
+
+        // Flush before releasing the lock, so that readers never observe entries that have not
+        // yet been committed to the data file.
+        let flushed = self.try_flush(&mut segments).await.unwrap_or(false);
+        drop(segments);
+
+        if retried || flushed {
+            let _ = tasks.flush_interrupt.send(()).await;
+        }
+
+        Ok(offsets)
+    }
+
+    /// Commits a message previously written with the [Uncommitted](MessageState::Uncommitted)
+    /// state, making it visible to readers.
+    ///
+    /// # Errors
+    /// - Returns [LogError::ReadOnly] if the log was opened in read-only mode.
+    /// - Returns [LogError::MessageNotFound] if the log doesn't contain the offset.
+    /// - Returns Err if the state fails to be written to the log.
+    pub async fn commit(&self, offset: u64) -> Result<()> {
+        self.set_state(offset, MessageState::Committed).await
+    }
+
+    /// Aborts a message previously written with the [Uncommitted](MessageState::Uncommitted)
+    /// state, so that it's skipped by readers.
+    ///
+    /// # Errors
+    /// - Returns [LogError::ReadOnly] if the log was opened in read-only mode.
+    /// - Returns [LogError::MessageNotFound] if the log doesn't contain the offset.
+    /// - Returns Err if the state fails to be written to the log.
+    pub async fn abort(&self, offset: u64) -> Result<()> {
+        self.set_state(offset, MessageState::Aborted).await
     }
 
     /// Reads a range of messages from a segment identified by the provided offset.
@@ -146,41 +276,225 @@ impl MessageLog {
         self.segments.read().await.read_slice(offset, limit).await
     }
 
+    /// Reads the messages in the range `[start, end)`, crossing segment boundaries as required.
+    ///
+    /// If `end` exceeds the tail of the log, the range is clamped to the last committed message.
+    /// If `start` precedes the oldest retained segment, the messages that have already been
+    /// cleaned are skipped, and the range begins from the oldest message still in the log.
+    ///
+    /// # Params
+    /// * `start` - The inclusive offset to start reading from.
+    /// * `end` - The exclusive offset to stop reading at.
+    ///
+    /// # Errors
+    /// - Returns Err if reading from any of the segments in the range fails.
+    pub async fn read_range(&self, start: u64, end: u64) -> Result<Vec<Message>> {
+        let segments = self.segments.read().await;
+        let mut offset = start.max(segments.start_offset());
+        let end = end.min(segments.number_of_entries());
+        let mut messages = Vec::with_capacity(end.saturating_sub(offset) as usize);
+
+        while offset < end {
+            let slice = segments.read_slice(offset, Some(end - offset)).await?;
+            let end_offset = slice.end_offset();
+
+            if let Some(mut iter) = slice.messages() {
+                loop {
+                    match iter.next().await {
+                        Ok(Some(message)) => messages.push(message),
+                        Ok(None) => break,
+                        // A partially committed message marks the end of the readable log.
+                        Err(LogError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                            return Ok(messages)
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+
+            if end_offset <= offset {
+                break;
+            }
+
+            offset = end_offset;
+        }
+
+        Ok(messages)
+    }
+
+    /// Returns a stream over every message in the log, starting from the provided offset.
+    ///
+    /// Unlike a subscriber, the stream does not wait for new messages to be written, and will
+    /// complete once it reaches the tail of the log. Segment boundaries are crossed transparently,
+    /// and if the provided offset precedes the oldest retained segment, the stream will begin
+    /// from the oldest message still in the log.
+    ///
+    /// Messages that have been written but not yet flushed to the filesystem are considered to
+    /// be beyond the tail of the log.
+    ///
+    /// # Params
+    /// * `offset` - The offset to start reading from.
+    pub fn iter_from(&self, offset: u64) -> impl Stream<Item = Result<Message>> + Send + 'static {
+        let state = IterState {
+            segments: self.segments.clone(),
+            offset,
+            messages: None,
+        };
+
+        stream::try_unfold(state, |mut state| async move {
+            loop {
+                if let Some(messages) = state.messages.as_mut() {
+                    match messages.next().await {
+                        Ok(Some(message)) => return Ok(Some((message, state))),
+                        Ok(None) => state.messages = None,
+                        // A partially committed message marks the end of the readable log.
+                        Err(LogError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                            return Ok(None)
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                let slice = state
+                    .segments
+                    .read()
+                    .await
+                    .read_slice(state.offset, None)
+                    .await?;
+
+                if slice.end_offset() <= state.offset {
+                    return Ok(None);
+                }
+
+                state.offset = slice.end_offset();
+                state.messages = slice.messages();
+            }
+        })
+    }
+
+    /// Removes every segment whose messages all precede the provided offset, without waiting for
+    /// the Cleaner task to expire them, and returns the offset of the oldest message still
+    /// retained in the log.
+    ///
+    /// The hot segment is flushed first, so that any unflushed messages below the offset are
+    /// removed too. Only whole segments are removed, so the new start offset may precede the
+    /// provided offset. Reads from an offset below the new start offset skip ahead to the oldest
+    /// retained message, as they do once the Cleaner task has removed a segment.
+    ///
+    /// # Errors
+    /// - Returns [LogError::ReadOnly] if the log was opened in read-only mode.
+    /// - Returns Err if the hot segment fails to flush.
+    /// - Returns Err if any of the truncated segments fail to be removed.
+    pub async fn truncate_to(&self, offset: u64) -> Result<u64> {
+        self.tasks.as_ref().ok_or(LogError::ReadOnly)?;
+        let mut segments = self.segments.write().await;
+        segments.flush().await?;
+        segments.truncate_to(offset).await
+    }
+
+    /// Returns a future that completes the next time the hot segment is flushed, whether by a
+    /// write reaching the [FlushPolicy](crate::config::FlushPolicy) thresholds, the Flusher task,
+    /// or a call to [flush](MessageLog::flush).
+    ///
+    /// Written messages are only visible to readers once they've been flushed, so readers waiting
+    /// for new messages should wait on this future, rather than on the write itself. The future
+    /// only observes flushes that happen after it's been polled or
+    /// [enabled](tokio::sync::futures::Notified::enable).
+    pub fn flushed(&self) -> Notified<'_> {
+        self.flushed.notified()
+    }
+
     /// Flushes the hot segment to the filesystem.
     /// The Flusher task interval will also be interrupted and reset.
     ///
     /// # Errors
+    /// - Returns [LogError::ReadOnly] if the log was opened in read-only mode.
     /// - Returns Err if the hot segment fails to flush.
     pub async fn flush(&self) -> Result<()> {
+        let tasks = self.tasks.as_ref().ok_or(LogError::ReadOnly)?;
         self.segments.write().await.flush().await?;
-        let _ = self.flush_interrupt.send(()).await;
+        let _ = tasks.flush_interrupt.send(()).await;
+        Ok(())
+    }
+
+    /// Flushes the hot segment to the filesystem, and stops the Flusher and Cleaner tasks.
+    ///
+    /// The log's resources, such as open segment files and memory-mapped indexes, are released
+    /// once the log is dropped. The log should not be written to after it has been closed, as
+    /// subsequent writes will not be flushed by the Flusher task.
+    ///
+    /// Closing a log opened in read-only mode is a no-op.
+    ///
+    /// # Errors
+    /// - Returns Err if the hot segment fails to flush.
+    pub async fn close(&self) -> Result<()> {
+        if let Some(tasks) = &self.tasks {
+            self.segments.write().await.flush().await?;
+            tasks.flusher.stop();
+            tasks.cleaner.stop();
+        }
+
         Ok(())
     }
 
+    /// Retrieves the offset of the earliest message still retained in the log.
+    ///
+    /// If all segments have been removed by the cleaner, this will be equal to
+    /// [number_of_entries](MessageLog::number_of_entries).
+    pub async fn start_offset(&self) -> u64 {
+        self.segments.read().await.start_offset()
+    }
+
+    /// Retrieves the offset to begin reading from in order to receive every message with a
+    /// timestamp at or after the provided UNIX timestamp in milliseconds.
+    ///
+    /// Timestamps are taken from the log's [TimestampSource](crate::config::TimestampSource).
+    /// Event times may be skewed or out of order, so rather than failing to find messages, the
+    /// returned offset never skips a message at or after the timestamp, though messages
+    /// preceding the timestamp may follow it. If every message precedes the timestamp, the next
+    /// offset to be written is returned.
+    pub async fn offset_for_timestamp(&self, timestamp: u64) -> u64 {
+        self.segments.read().await.offset_for_timestamp(timestamp)
+    }
+
     /// Retrieves the total number of entries in the log, based on the `end_offset` in the current
     /// hot segment.
     pub async fn number_of_entries(&self) -> u64 {
         self.segments.read().await.number_of_entries()
     }
 
-    async fn try_flush(&self) -> Result<()> {
-        let segments = self.segments.read().await;
+    async fn set_state(&self, offset: u64, state: MessageState) -> Result<()> {
+        self.tasks.as_ref().ok_or(LogError::ReadOnly)?;
+        self.segments.write().await.set_state(offset, state).await
+    }
 
-        let should_flush = match self.config.flush_policy.number_of_writes {
-            Some(number_of_writes) => segments.writes_since_last_flush() >= number_of_writes,
-            None => false,
-        };
+    async fn try_flush(&self, segments: &mut SegmentList) -> Result<bool> {
+        let policy = &self.config.flush_policy;
 
-        drop(segments);
+        let writes_exceeded = policy
+            .number_of_writes
+            .is_some_and(|number_of_writes| segments.writes_since_last_flush() >= number_of_writes);
+
+        let bytes_exceeded = policy
+            .max_unflushed_bytes
+            .is_some_and(|max_bytes| segments.bytes_since_last_flush() >= max_bytes);
+
+        let should_flush = writes_exceeded || bytes_exceeded;
 
         if should_flush {
-            self.flush().await?;
+            segments.flush().await?;
         }
 
-        Ok(())
+        Ok(should_flush)
     }
 }
 
+struct IterState {
+    segments: SharedSegmentList,
+    offset: u64,
+    messages: Option<LogIterator>,
+}
+
 fn is_index_file(path: &Path) -> bool {
     path.is_file() && path.extension() == Some("index".as_ref())
 }
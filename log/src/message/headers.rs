@@ -1,6 +1,77 @@
-use super::{CRC_SIZE, HEADERS_SIZE};
+use super::{CRC_SIZE, HEADERS_PREFIX_SIZE, HEADERS_SIZE};
+use crate::config::TimestampSource;
+use crate::index::TimestampUnit;
 use bytes::{Buf, BufMut};
 use chrono::Utc;
+use std::mem::size_of;
+use std::time::Duration;
+
+/// The encoded version field holds the layout of the headers in its upper byte, leaving the lower
+/// 24 bits for the version assigned by the writer.
+const LAYOUT_SHIFT: u32 = 24;
+const VERSION_MASK: u32 = (1 << LAYOUT_SHIFT) - 1;
+
+/// The original layout: length, version, batch size and timestamp. Messages written before
+/// layouts were versioned have a layout of 0, as their writers only used the lower bits.
+const LAYOUT_BASE: u8 = 0;
+/// Adds the expiry time.
+const LAYOUT_EXPIRY: u8 = 1;
+/// Adds the message state.
+const LAYOUT_STATE: u8 = 2;
+/// Adds the event time, preceding the message state.
+const LAYOUT_EVENT_TIME: u8 = 3;
+/// Stores the timestamp in milliseconds, rather than seconds.
+const LAYOUT_MILLIS: u8 = 4;
+/// The layout that messages are written with.
+const CURRENT_LAYOUT: u8 = LAYOUT_MILLIS;
+
+/// Sentinel value for [Headers::expires_at], indicating that the message never expires.
+const NO_EXPIRY: u64 = 0;
+
+/// Sentinel value for [Headers::event_time], indicating that the producer didn't attach an event
+/// time to the message.
+const NO_EVENT_TIME: u64 = 0;
+
+const COMMITTED: u8 = 0;
+const UNCOMMITTED: u8 = 1;
+const ABORTED: u8 = 2;
+
+/// The transaction state of a [Message](crate::message::Message).
+///
+/// Messages are committed when written, unless they are reserved as
+/// [Uncommitted](MessageState::Uncommitted), in which case their state is updated in place once
+/// they are committed or aborted via [MessageLog::commit](crate::MessageLog::commit) or
+/// [MessageLog::abort](crate::MessageLog::abort).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageState {
+    /// The message is visible to readers.
+    #[default]
+    Committed,
+    /// The message has been reserved, but is not yet visible to readers.
+    Uncommitted,
+    /// The message has been discarded, and will never be visible to readers.
+    Aborted,
+}
+
+impl From<u8> for MessageState {
+    fn from(value: u8) -> Self {
+        match value {
+            UNCOMMITTED => Self::Uncommitted,
+            ABORTED => Self::Aborted,
+            _ => Self::Committed,
+        }
+    }
+}
+
+impl From<MessageState> for u8 {
+    fn from(value: MessageState) -> Self {
+        match value {
+            MessageState::Committed => COMMITTED,
+            MessageState::Uncommitted => UNCOMMITTED,
+            MessageState::Aborted => ABORTED,
+        }
+    }
+}
 
 /// Headers corresponding to a [Message](crate::message::Message), containing information about the message records batch.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,46 +80,131 @@ pub struct Headers {
     version: u32,
     batch_size: u32,
     timestamp: u64,
+    expires_at: u64,
+    event_time: u64,
+    state: MessageState,
 }
 
 impl Headers {
     /// Constructs a new headers instance.
+    ///
+    /// Only the lower 24 bits of `version` are stored, as the upper byte of the encoded version
+    /// records the layout of the headers.
     pub fn new(batch_len: usize, batch_size: u32, version: u32) -> Self {
         let length = (batch_len + HEADERS_SIZE + CRC_SIZE) as u64;
-        let timestamp = Utc::now().timestamp() as u64;
+        let timestamp = Utc::now().timestamp_millis() as u64;
 
         Self {
             length,
-            version,
+            version: version & VERSION_MASK,
             batch_size,
             timestamp,
+            expires_at: NO_EXPIRY,
+            event_time: NO_EVENT_TIME,
+            state: MessageState::Committed,
         }
     }
 
+    /// Updates the message length to account for a records batch of `batch_len` bytes, e.g. after
+    /// the records have been encrypted or decrypted.
+    pub(crate) fn with_batch_len(mut self, batch_len: usize) -> Self {
+        self.length = (batch_len + HEADERS_SIZE + CRC_SIZE) as u64;
+        self
+    }
+
+    /// Assigns a time-to-live to the message, measured from the current time.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        let now = Utc::now().timestamp_millis() as u64;
+        self.expires_at = now.saturating_add(ttl.as_millis() as u64);
+        self
+    }
+
+    /// Assigns the time at which the message's producer created it, as a UNIX timestamp in
+    /// milliseconds.
+    pub fn with_event_time(mut self, event_time: u64) -> Self {
+        self.event_time = event_time;
+        self
+    }
+
+    /// Assigns a transaction state to the message.
+    pub fn with_state(mut self, state: MessageState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Returns the byte length of headers encoded with the provided version field, which follows
+    /// the length marker, or [None] if the headers were written in an unknown layout.
+    pub fn encoded_len(version: u32) -> Option<usize> {
+        let fields = match (version >> LAYOUT_SHIFT) as u8 {
+            LAYOUT_BASE => size_of::<u64>(),
+            LAYOUT_EXPIRY => size_of::<u64>() * 2,
+            LAYOUT_STATE => size_of::<u64>() * 2 + size_of::<u8>(),
+            LAYOUT_EVENT_TIME | LAYOUT_MILLIS => size_of::<u64>() * 3 + size_of::<u8>(),
+            _ => return None,
+        };
+
+        Some(HEADERS_PREFIX_SIZE + size_of::<u32>() + fields)
+    }
+
     /// Decodes a Headers instance from the provided bytes source.
     ///
+    /// Headers written in earlier layouts are decoded with the fields that they lack left unset,
+    /// and their length adjusted to that of the current layout, as they're re-encoded in the
+    /// current layout. Timestamps stored in seconds are converted to milliseconds.
+    ///
     /// # Panics
-    /// Will panic if the the bytes source is not large enough.
+    /// Will panic if the the bytes source is not large enough for the layout identified by
+    /// [encoded_len](Headers::encoded_len), or if the layout is unknown.
     pub fn decode(mut src: &[u8]) -> Self {
         let length = src.get_u64();
         let version = src.get_u32();
+        let layout = (version >> LAYOUT_SHIFT) as u8;
+        let encoded_len = Self::encoded_len(version).expect("unknown message header layout");
         let batch_size = src.get_u32();
-        let timestamp = src.get_u64();
+        let timestamp = if layout >= LAYOUT_MILLIS {
+            src.get_u64()
+        } else {
+            src.get_u64().saturating_mul(1000)
+        };
+
+        let expires_at = if layout >= LAYOUT_EXPIRY {
+            src.get_u64()
+        } else {
+            NO_EXPIRY
+        };
+
+        let event_time = if layout >= LAYOUT_EVENT_TIME {
+            src.get_u64()
+        } else {
+            NO_EVENT_TIME
+        };
+
+        let state = if layout >= LAYOUT_STATE {
+            src.get_u8().into()
+        } else {
+            MessageState::Committed
+        };
 
         Self {
-            length,
-            version,
+            length: length - encoded_len as u64 + HEADERS_SIZE as u64,
+            version: version & VERSION_MASK,
             batch_size,
             timestamp,
+            expires_at,
+            event_time,
+            state,
         }
     }
 
-    /// Encodes this Headers instance into the provided buffer.
+    /// Encodes this Headers instance into the provided buffer, using the current layout.
     pub fn encode<T: BufMut>(&self, buffer: &mut T) {
         buffer.put_u64(self.length);
-        buffer.put_u32(self.version);
+        buffer.put_u32((u32::from(CURRENT_LAYOUT) << LAYOUT_SHIFT) | self.version);
         buffer.put_u32(self.batch_size);
         buffer.put_u64(self.timestamp);
+        buffer.put_u64(self.expires_at);
+        buffer.put_u64(self.event_time);
+        buffer.put_u8(self.state.into());
     }
 
     /// The byte length of the encoded batch.
@@ -56,7 +212,7 @@ impl Headers {
         self.length
     }
 
-    /// The message frame version.
+    /// The message frame version assigned by the writer, excluding the layout of the headers.
     pub fn version(&self) -> u32 {
         self.version
     }
@@ -66,8 +222,62 @@ impl Headers {
         self.batch_size
     }
 
-    /// A UNIX timestamp representing the time the message was appended to the log.
+    /// A UNIX timestamp in milliseconds representing the time the message was appended to the
+    /// log. Messages written in layouts that stored the time in seconds are truncated to the
+    /// second.
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+
+    /// A UNIX timestamp in milliseconds, after which the message is considered expired, or [None]
+    /// if the message has no time-to-live.
+    pub fn expires_at(&self) -> Option<u64> {
+        (self.expires_at != NO_EXPIRY).then_some(self.expires_at)
+    }
+
+    /// A UNIX timestamp in milliseconds representing the time the message's producer created it,
+    /// or [None] if the producer didn't attach an event time.
+    pub fn event_time(&self) -> Option<u64> {
+        (self.event_time != NO_EVENT_TIME).then_some(self.event_time)
+    }
+
+    /// The timestamp of the message in milliseconds, taken from the provided [TimestampSource].
+    ///
+    /// Messages without an event time fall back to the time they were appended to the log.
+    pub fn timestamp_millis(&self, source: TimestampSource) -> u64 {
+        match source {
+            TimestampSource::Ingest => self.timestamp,
+            TimestampSource::Event => self.event_time().unwrap_or(self.timestamp),
+        }
+    }
+
+    /// Returns true if the message has a time-to-live, and it has elapsed.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at()
+            .is_some_and(|expires_at| expires_at <= Utc::now().timestamp_millis() as u64)
+    }
+
+    /// The transaction state of the message.
+    pub fn state(&self) -> MessageState {
+        self.state
+    }
+}
+
+/// Returns true if headers encoded with the provided version field include the message state, so
+/// that it can be updated in place.
+pub(crate) fn has_state(version: u32) -> bool {
+    (version >> LAYOUT_SHIFT) as u8 >= LAYOUT_STATE
+}
+
+/// Returns the unit of the timestamps in the index of a segment whose first message was encoded
+/// with the provided version field.
+///
+/// Segments indexed messages by their timestamp in seconds until the event time was added, after
+/// which they were indexed in milliseconds.
+pub(crate) fn index_timestamp_unit(version: u32) -> TimestampUnit {
+    if (version >> LAYOUT_SHIFT) as u8 >= LAYOUT_EVENT_TIME {
+        TimestampUnit::Millis
+    } else {
+        TimestampUnit::Seconds
+    }
 }
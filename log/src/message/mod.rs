@@ -4,10 +4,11 @@ mod headers;
 mod slice;
 
 use bytes::{BufMut, Bytes};
-use crc32c::crc32c;
-pub use headers::Headers;
+use crc32c::{crc32c, crc32c_append};
+pub(crate) use headers::{has_state, index_timestamp_unit};
+pub use headers::{Headers, MessageState};
 pub use slice::MessageSlice;
-use std::mem::size_of;
+use std::{mem::size_of, time::Duration};
 
 /// The byte length of the [Headers] length marker
 pub const LEN_MARKER_SIZE: usize = size_of::<u64>();
@@ -15,9 +16,23 @@ pub const LEN_MARKER_SIZE: usize = size_of::<u64>();
 /// The byte length of the CRC.
 pub const CRC_SIZE: usize = size_of::<u32>();
 
-/// The combined byte length of the message headers.
-pub const HEADERS_SIZE: usize =
-    size_of::<u64>() + size_of::<u32>() + size_of::<u32>() + size_of::<u64>();
+/// The byte length of the length marker and version field, which begin the headers in every
+/// layout.
+pub const HEADERS_PREFIX_SIZE: usize = LEN_MARKER_SIZE + size_of::<u32>();
+
+/// The combined byte length of the message headers, in the layout that messages are written with.
+/// Messages written in earlier layouts have shorter headers, as given by [Headers::encoded_len].
+pub const HEADERS_SIZE: usize = size_of::<u64>()
+    + size_of::<u32>()
+    + size_of::<u32>()
+    + size_of::<u64>()
+    + size_of::<u64>()
+    + size_of::<u64>()
+    + size_of::<u8>();
+
+/// The byte position of the [MessageState] within an encoded message, which is the last field of
+/// the headers.
+pub(crate) const STATE_POSITION: usize = HEADERS_SIZE - size_of::<u8>();
 
 /// The Message frame contains information required to parse the message, a calculated CRC used to
 /// verify message integrity, and the encoded records.
@@ -56,11 +71,53 @@ impl Message {
         Self::new(headers, records, 0)
     }
 
+    /// Assigns a time-to-live to this Message, measured from the time the message was constructed.
+    ///
+    /// Once expired, the message will be skipped when reading from the log, even if the segment
+    /// containing it is still retained.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.headers = self.headers.with_ttl(ttl);
+        self
+    }
+
+    /// Assigns the time at which the message's producer created it, as a UNIX timestamp in
+    /// milliseconds.
+    ///
+    /// The event time is stored alongside the time the message was appended to the log, and is
+    /// used for retention and seeking by timestamp if the log is configured with
+    /// [TimestampSource::Event](crate::config::TimestampSource::Event).
+    pub fn with_event_time(mut self, event_time: u64) -> Self {
+        self.headers = self.headers.with_event_time(event_time);
+        self
+    }
+
+    /// Assigns a transaction state to this Message.
+    ///
+    /// Messages written with the [Uncommitted](MessageState::Uncommitted) state are not visible
+    /// to readers until they are committed.
+    pub fn with_state(mut self, state: MessageState) -> Self {
+        self.headers = self.headers.with_state(state);
+        self
+    }
+
+    /// Replaces the records batch, updating the message length to match.
+    pub(crate) fn with_records(mut self, records: Vec<u8>) -> Self {
+        self.headers = self.headers.with_batch_len(records.len());
+        self.records = records.into();
+        self
+    }
+
     /// Encodes this Message instance into the provided buffer.
+    ///
+    /// The [MessageState] is excluded from the CRC, as it's updated in place once a reserved
+    /// message is committed or aborted.
     pub fn encode(&self, buffer: &mut Vec<u8>) {
         self.headers.encode(buffer);
         buffer.put_slice(&self.records);
-        let crc = crc32c(buffer);
+        let crc = crc32c_append(
+            crc32c(&buffer[..STATE_POSITION]),
+            &buffer[STATE_POSITION + 1..],
+        );
         buffer.put_u32(crc);
     }
 
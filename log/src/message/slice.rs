@@ -26,12 +26,22 @@ impl MessageSlice {
         }
     }
 
+    /// Treats messages preceding `offset` that are still uncommitted as aborted.
+    ///
+    /// See [LogIterator::with_recovered_offset].
+    pub(crate) fn with_recovered_offset(mut self, offset: u64) -> Self {
+        self.messages = self
+            .messages
+            .map(|messages| messages.with_recovered_offset(offset));
+        self
+    }
+
     /// An iterator over the log segment.
     pub fn messages(self) -> Option<LogIterator> {
         self.messages
     }
 
-    /// The last segment in this slice.
+    /// The offset following the last message in this slice.
     ///
     /// Used to determine the next offset to request from the log after processing all messages
     /// in this slice.
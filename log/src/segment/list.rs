@@ -1,12 +1,13 @@
 use super::Segment;
 use crate::config::SharedLogConfig;
 use crate::error::{LogError, Result};
-use crate::message::{Message, MessageSlice};
+use crate::message::{Message, MessageSlice, MessageState};
 use futures::future::try_join_all;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+use tokio::task::JoinHandle;
 
 pub type SharedSegmentList = Arc<RwLock<SegmentList>>;
 
@@ -18,14 +19,22 @@ pub type SharedSegmentList = Arc<RwLock<SegmentList>>;
 /// Upon construction, the SegmentList will contain at least one "hot" segment.
 ///
 /// Segments will eventually become full, depending on the configured [LogConfig::max_index_entries](crate::config::LogConfig::max_index_entries)
-/// setting, so new segments will be created to keep write buffers at reasonable sizes, allow efficient seeking, and
-/// reduce overhead when synchronizing the memory-mapped index with the filesystem.
+/// and [LogConfig::segment_max_bytes](crate::config::LogConfig::segment_max_bytes) settings, so new segments
+/// will be created to keep write buffers at reasonable sizes, allow efficient seeking, and reduce overhead when synchronizing the memory-mapped index with the filesystem.
+///
+/// Once the hot segment passes the configured [LogConfig::preallocate_watermark](crate::config::LogConfig::preallocate_watermark),
+/// the next segment is created in a background task, so that rolling over to it doesn't stall the write path.
 #[derive(Debug)]
 pub struct SegmentList {
     config: SharedLogConfig,
     segments: BTreeMap<u64, Segment>,
+    preallocated: Option<JoinHandle<Result<Segment>>>,
     number_of_entries: u64,
     writes_since_last_flush: u64,
+    bytes_since_last_flush: u64,
+    recovered_offset: u64,
+    flush_failed: bool,
+    flushed: Arc<Notify>,
 }
 
 impl SegmentList {
@@ -39,8 +48,13 @@ impl SegmentList {
         Self {
             segments,
             config,
+            preallocated: None,
             number_of_entries,
             writes_since_last_flush: 0,
+            bytes_since_last_flush: 0,
+            recovered_offset: 0,
+            flush_failed: false,
+            flushed: Arc::new(Notify::new()),
         }
     }
 
@@ -58,6 +72,9 @@ impl SegmentList {
     /// Constructs a SegmentList instance, opening each index/data file pair from the provided
     /// slice of base offsets.
     ///
+    /// Any messages that were left uncommitted when the segments were last written can never be
+    /// committed, so they are treated as aborted when read.
+    ///
     /// # Errors
     /// - Returns Err if any segments fail to open.
     pub async fn from_offsets(offsets: &[u64], config: SharedLogConfig) -> Result<Self> {
@@ -68,6 +85,25 @@ impl SegmentList {
             segments.insert(*offset, segment);
         }
 
+        let mut list = Self::new(segments, config);
+        list.recovered_offset = list.number_of_entries;
+
+        Ok(list)
+    }
+
+    /// Constructs a SegmentList instance, opening each index/data file pair from the provided
+    /// slice of base offsets without write access.
+    ///
+    /// # Errors
+    /// - Returns Err if any segments fail to open.
+    pub async fn from_offsets_read_only(offsets: &[u64], config: SharedLogConfig) -> Result<Self> {
+        let mut segments = BTreeMap::new();
+
+        for offset in offsets {
+            let segment = Segment::open_read_only(*offset, config.clone()).await?;
+            segments.insert(*offset, segment);
+        }
+
         Ok(Self::new(segments, config))
     }
 
@@ -92,50 +128,121 @@ impl SegmentList {
 
         if let Some((_, segment)) = found {
             let slice = segment.read_slice(offset, limit).await?;
-            Ok(slice)
+            Ok(slice.with_recovered_offset(self.recovered_offset))
+        } else if let Some((&base_offset, segment)) = self.segments.iter().next() {
+            // The requested offset belongs to a segment that has since been cleaned, so skip ahead
+            // to the oldest remaining segment.
+            let slice = segment.read_slice(base_offset, limit).await?;
+            Ok(slice.with_recovered_offset(self.recovered_offset))
         } else {
-            Ok(MessageSlice::default())
+            Ok(MessageSlice::empty(offset))
         }
     }
 
-    /// Writes the provided [Message] to the current hot segment.
+    /// Writes the provided [Message] to the current hot segment, returning the offset assigned to
+    /// the message.
     ///
     /// If the hot segment is at full capacity following the write, the current hot segment
     /// will be flushed, and a new segment will be created and designated as the hot segment in
-    /// its place. Otherwise, the `writes_since_last_flush` field is incremented by 1.
+    /// its place. Otherwise, the `writes_since_last_flush` field is incremented by 1, and the
+    /// `bytes_since_last_flush` field by the encoded size of the message.
+    ///
+    /// The new segment is taken from the preallocated segment if one has been created, and is
+    /// created inline otherwise. If rolling over to the new segment fails, the message is still
+    /// written, and the rollover is retried before the next write.
     ///
     /// # Errors
+    /// Errors are only returned if the message wasn't written.
     /// - Returns [LogError::SegmentListEmpty] if there are no segments in the list yet.
     /// - Returns Err if writing to the hot segment fails.
-    /// - Returns Err if the segment is full, and the current hot segment fails to flush.
-    /// - Returns Err if the segment is full, and the new hot segment fails to be created.
-    pub async fn write(&mut self, message: Message) -> Result<()> {
+    /// - Returns Err if a previous rollover failed, and the full hot segment fails to flush
+    ///   again, or the new hot segment fails to be created again.
+    pub async fn write(&mut self, message: Message) -> Result<u64> {
+        // All segments may have been removed by the cleaner, so resume from the current offset
         if self.segments.is_empty() {
-            let hot_segment = Segment::create(0, self.config.clone()).await?;
-            self.segments.insert(0, hot_segment);
+            let base_offset = self.number_of_entries;
+            let hot_segment = self.next_segment(base_offset).await?;
+            self.segments.insert(base_offset, hot_segment);
         }
 
+        self.roll_over_if_full().await?;
+
         let (_, segment) = self
             .segments
             .iter_mut()
             .last()
             .ok_or(LogError::SegmentListEmpty)?;
 
-        segment.write(message).await;
+        let bytes_written = segment.write(message).await?;
+        let offset = segment.end_offset() - 1;
+        let is_full = segment.is_full();
+        let capacity_used = segment.capacity_used();
+        self.writes_since_last_flush += 1;
+        self.bytes_since_last_flush += bytes_written;
 
-        if segment.is_full() {
-            segment.flush().await?;
-            let new_offset = segment.end_offset() + 1;
-            let new_segment = Segment::create(new_offset, self.config.clone()).await?;
-            self.segments.insert(new_offset, new_segment);
-            self.on_flush();
-        } else {
-            self.writes_since_last_flush += 1;
+        if is_full {
+            // The message has already been written, so a failed rollover is left to be retried
+            // by the next write
+            let _ = self.roll_over_if_full().await;
+        } else if self.preallocated.is_none()
+            && self
+                .config
+                .preallocate_watermark
+                .is_some_and(|watermark| capacity_used >= watermark)
+        {
+            let task = Segment::preallocate(self.config.clone());
+            self.preallocated = Some(tokio::spawn(task));
         }
 
+        Ok(offset)
+    }
+
+    /// Flushes the hot segment and rolls over to a new hot segment if the current one is full.
+    async fn roll_over_if_full(&mut self) -> Result<()> {
+        let segment = match self.segments.iter_mut().last() {
+            Some((_, segment)) if segment.is_full() => segment,
+            _ => return Ok(()),
+        };
+
+        segment.flush().await?;
+        let new_offset = segment.end_offset();
+        self.on_flush();
+
+        let new_segment = self.next_segment(new_offset).await?;
+        self.segments.insert(new_offset, new_segment);
+
         Ok(())
     }
 
+    async fn next_segment(&mut self, base_offset: u64) -> Result<Segment> {
+        // If preallocation failed, fall back to creating the segment inline.
+        if let Some(task) = self.preallocated.take() {
+            if let Ok(Ok(mut segment)) = task.await {
+                segment.assign_base_offset(base_offset).await?;
+                return Ok(segment);
+            }
+        }
+
+        Segment::create(base_offset, self.config.clone()).await
+    }
+
+    /// Updates the [MessageState] of the message at the provided offset, in whichever segment
+    /// contains it.
+    ///
+    /// # Errors
+    /// - Returns [LogError::MessageNotFound] if no retained segment contains the offset.
+    /// - Returns Err if the state fails to be written to the segment.
+    pub async fn set_state(&mut self, offset: u64, state: MessageState) -> Result<()> {
+        let (_, segment) = self
+            .segments
+            .iter_mut()
+            .rev()
+            .find(|(&base_offset, _)| offset >= base_offset)
+            .ok_or(LogError::MessageNotFound(offset))?;
+
+        segment.set_state(offset, state).await
+    }
+
     /// Flushes the hot segment to the filesystem.
     /// This function is a no-op if no writes have occurred prior to calling flush.
     ///
@@ -144,7 +251,9 @@ impl SegmentList {
     pub async fn flush(&mut self) -> Result<()> {
         if self.writes_since_last_flush > 0 {
             if let Some((_, segment)) = self.segments.iter_mut().last() {
-                segment.flush().await?;
+                let result = segment.flush().await;
+                self.flush_failed = result.is_err();
+                result?;
                 self.on_flush();
             }
         }
@@ -152,6 +261,17 @@ impl SegmentList {
         Ok(())
     }
 
+    /// Returns the [Notify] that wakes its waiters each time the hot segment is flushed, making
+    /// newly written messages visible to readers.
+    pub fn flush_notify(&self) -> Arc<Notify> {
+        self.flushed.clone()
+    }
+
+    /// Whether the last attempt to flush the hot segment failed.
+    pub fn flush_failed(&self) -> bool {
+        self.flush_failed
+    }
+
     /// Identifies any stale segments in the list, and returns a [Vec] of base offsets corresponding
     /// to those segments.
     ///
@@ -187,6 +307,59 @@ impl SegmentList {
         Ok(())
     }
 
+    /// Removes every segment whose messages all precede the provided offset, returning the
+    /// offset of the oldest message still retained in the log.
+    ///
+    /// Only whole segments are removed, so messages preceding the offset may still be retained
+    /// if they share a segment with the offset. The hot segment is also removed if it's entirely
+    /// below the offset, in which case a new hot segment is created on the next write.
+    ///
+    /// # Errors
+    /// - Returns Err if any of the identified segments fail to be removed.
+    pub async fn truncate_to(&mut self, offset: u64) -> Result<u64> {
+        let truncated: Vec<u64> = self
+            .segments
+            .iter()
+            .filter(|(_, segment)| segment.end_offset() <= offset)
+            .map(|(&base_offset, _)| base_offset)
+            .collect();
+
+        self.remove_segments(&truncated).await?;
+
+        Ok(self.start_offset())
+    }
+
+    /// Returns the offset of the first message from which every message with a timestamp at or
+    /// after the provided `timestamp` can be read, or the next offset to be written if every
+    /// message in the log precedes `timestamp`.
+    ///
+    /// Timestamps are UNIX timestamps in milliseconds, taken from the log's
+    /// [TimestampSource](crate::config::TimestampSource). If the timestamps of messages are out
+    /// of order, messages following the returned offset may still precede `timestamp`.
+    pub fn offset_for_timestamp(&self, timestamp: u64) -> u64 {
+        self.segments
+            .values()
+            .find_map(|segment| segment.offset_for_timestamp(timestamp))
+            .unwrap_or_else(|| {
+                self.segments
+                    .values()
+                    .last()
+                    .map_or(self.number_of_entries, |segment| segment.end_offset())
+            })
+    }
+
+    /// The offset of the oldest message retained in the log.
+    ///
+    /// If all segments have been removed by the cleaner, this will be the next offset to be
+    /// written.
+    pub fn start_offset(&self) -> u64 {
+        self.segments
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(self.number_of_entries)
+    }
+
     /// The total number of entries in the log, summed across all segments.
     pub fn number_of_entries(&self) -> u64 {
         self.number_of_entries
@@ -199,8 +372,17 @@ impl SegmentList {
         self.writes_since_last_flush
     }
 
+    /// The number of bytes written since the last flush.
+    /// Used for determining whether a hot segment should be flushed based on a provided
+    /// [FlushPolicy](crate::config::FlushPolicy).
+    pub fn bytes_since_last_flush(&self) -> u64 {
+        self.bytes_since_last_flush
+    }
+
     fn on_flush(&mut self) {
         self.number_of_entries += self.writes_since_last_flush;
         self.writes_since_last_flush = 0;
+        self.bytes_since_last_flush = 0;
+        self.flushed.notify_waiters();
     }
 }
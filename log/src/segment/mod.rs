@@ -2,11 +2,12 @@
 
 mod list;
 
-use crate::config::SharedLogConfig;
+use crate::config::{SharedLogConfig, TimestampSource};
 use crate::data::Data;
-use crate::error::Result;
-use crate::index::Index;
-use crate::message::{Message, MessageSlice};
+use crate::error::{LogError, Result};
+use crate::index::{Index, TimestampUnit};
+use crate::message::{index_timestamp_unit, Message, MessageSlice, MessageState};
+use chrono::Utc;
 pub use list::{SegmentList, SharedSegmentList};
 use std::cmp;
 use std::path::{Path, PathBuf};
@@ -26,6 +27,7 @@ pub struct Segment {
     data: Data,
     base_offset: u64,
     end_offset: u64,
+    config: SharedLogConfig,
 }
 
 impl Segment {
@@ -38,8 +40,10 @@ impl Segment {
     pub async fn open(base_offset: u64, config: SharedLogConfig) -> Result<Self> {
         let path = &config.segments_path;
         let (index_path, data_path) = get_segment_paths(path, base_offset);
-        let index = Index::open(index_path, config).await?;
         let data = Data::open(data_path).await?;
+        let index = Index::open(index_path, config.clone())
+            .await?
+            .with_timestamp_unit(timestamp_unit(&data).await?);
         let end_offset = base_offset + index.current_offset() as u64;
 
         Ok(Self {
@@ -47,6 +51,31 @@ impl Segment {
             data,
             base_offset,
             end_offset,
+            config,
+        })
+    }
+
+    /// Constructs a Segment instance, opening the index/data file pair for the corresponding
+    /// `base_offset` without write access.
+    ///
+    /// # Errors
+    /// - Returns Err if an error occurs while loading the Index portion.
+    /// - Returns Err if an error occurs while loading the Data portion.
+    pub async fn open_read_only(base_offset: u64, config: SharedLogConfig) -> Result<Self> {
+        let path = &config.segments_path;
+        let (index_path, data_path) = get_segment_paths(path, base_offset);
+        let data = Data::open_read_only(data_path).await?;
+        let index = Index::open_read_only(index_path, config.clone())
+            .await?
+            .with_timestamp_unit(timestamp_unit(&data).await?);
+        let end_offset = base_offset + index.current_offset() as u64;
+
+        Ok(Self {
+            index,
+            data,
+            base_offset,
+            end_offset,
+            config,
         })
     }
 
@@ -59,7 +88,7 @@ impl Segment {
     pub async fn create(base_offset: u64, config: SharedLogConfig) -> Result<Self> {
         let path = &config.segments_path;
         let (index_path, data_path) = get_segment_paths(path, base_offset);
-        let index = Index::create(index_path, config).await?;
+        let index = Index::create(index_path, config.clone()).await?;
         let data = Data::create(data_path).await?;
 
         Ok(Self {
@@ -67,11 +96,58 @@ impl Segment {
             data,
             base_offset,
             end_offset: base_offset,
+            config,
         })
     }
 
+    /// Constructs a Segment instance without a base offset, creating the accompanying index/data
+    /// file pair at a temporary location.
+    ///
+    /// The preallocated segment must be assigned a base offset via
+    /// [assign_base_offset](Segment::assign_base_offset) before it is written to.
+    ///
+    /// # Errors
+    /// - Returns Err if an error occurs while creating the Index portion.
+    /// - Returns Err if an error occurs while creating the Data portion.
+    pub async fn preallocate(config: SharedLogConfig) -> Result<Self> {
+        let path = &config.segments_path;
+        let (index_path, data_path) = get_preallocated_paths(path);
+        let index = Index::create(index_path, config.clone()).await?;
+        let data = Data::create(data_path).await?;
+
+        Ok(Self {
+            index,
+            data,
+            base_offset: 0,
+            end_offset: 0,
+            config,
+        })
+    }
+
+    /// Assigns a base offset to a preallocated segment, moving the index/data file pair to
+    /// their permanent location.
+    ///
+    /// The data file is moved first, as segments are discovered via their index file when the
+    /// log is opened.
+    ///
+    /// # Errors
+    /// - Returns Err if either file fails to be renamed.
+    pub async fn assign_base_offset(&mut self, base_offset: u64) -> Result<()> {
+        let path = &self.config.segments_path;
+        let (index_path, data_path) = get_segment_paths(path, base_offset);
+        self.data.rename(data_path).await?;
+        self.index.rename(index_path).await?;
+        self.base_offset = base_offset;
+        self.end_offset = base_offset;
+        Ok(())
+    }
+
     /// Reads a range of messages from this segment, starting from the provided offset.
     ///
+    /// The slice holds at most `limit` messages, and its end offset is the offset following its
+    /// last message, so that it can be passed straight to the next read without skipping or
+    /// repeating a message.
+    ///
     /// Returns an empty [MessageSlice] if the provided offset is greater than the total amount of
     /// entries in the log.
     ///
@@ -82,23 +158,34 @@ impl Segment {
     /// # Errors
     /// - Returns Err if an error occurs while reading from the data file.
     pub async fn read_slice(&self, offset: u64, limit: Option<u64>) -> Result<MessageSlice> {
-        let end_offset = limit.map_or(self.end_offset, |e| {
-            cmp::min(offset + e + 1, self.end_offset)
-        });
-        let relative_start_offset = offset - self.base_offset + 1;
-        let relative_end_offset = end_offset - self.base_offset;
+        let end_offset = limit.map_or(self.end_offset, |e| cmp::min(offset + e, self.end_offset));
+        let start_entry = self
+            .relative_offset(offset)
+            .and_then(|relative_offset| self.index.lookup(relative_offset));
 
-        if let Some(start_entry) = self.index.lookup(relative_start_offset as u32) {
+        if let Some(start_entry) = start_entry {
             let start_pos = start_entry.physical_position();
 
             if end_offset == self.end_offset {
-                let messages = self.data.read_messages(start_pos, None).await?;
-                return Ok(MessageSlice::new(messages, end_offset + 1));
+                let messages = self
+                    .data
+                    .read_messages(offset, start_pos, None)
+                    .await?
+                    .with_encryption_key(self.config.encryption_key.clone());
+                return Ok(MessageSlice::new(messages, end_offset));
             }
 
-            if let Some(end_entry) = self.index.lookup(relative_end_offset as u32) {
+            let end_entry = self
+                .relative_offset(end_offset)
+                .and_then(|relative_offset| self.index.lookup(relative_offset));
+
+            if let Some(end_entry) = end_entry {
                 let end_pos = end_entry.physical_position();
-                let messages = self.data.read_messages(start_pos, Some(end_pos)).await?;
+                let messages = self
+                    .data
+                    .read_messages(offset, start_pos, Some(end_pos))
+                    .await?
+                    .with_encryption_key(self.config.encryption_key.clone());
                 return Ok(MessageSlice::new(messages, end_offset));
             }
         }
@@ -108,22 +195,63 @@ impl Segment {
 
     /// Writes the provided [Message] to the write buffer, and then appends a new
     /// [IndexEntry](crate::index::IndexEntry) to the index memory-map.
-    pub async fn write(&mut self, message: Message) {
+    ///
+    /// The message's records are encrypted first if the log has been configured with an
+    /// [EncryptionKey](crate::config::EncryptionKey).
+    ///
+    /// # Errors
+    /// - Returns [LogError::IndexFull] if the segment's index is at capacity, in which case the
+    ///   message isn't written.
+    /// - Returns Err if the message's records fail to be encrypted.
+    pub async fn write(&mut self, mut message: Message) -> Result<u64> {
+        // Check the index first, so that a message is never written without an index entry
+        if self.index.is_full() {
+            return Err(LogError::IndexFull);
+        }
+
+        if let Some(key) = &self.config.encryption_key {
+            let records = key.encrypt(message.records())?;
+            message = message.with_records(records);
+        }
+
         let position = self.data.position();
-        let timestamp = message.headers().timestamp();
+        let timestamp = message
+            .headers()
+            .timestamp_millis(self.config.timestamp_source);
 
         self.data.write(message).await;
-        self.index.append(timestamp, position);
+        self.index.append(timestamp, position)?;
         self.end_offset += 1;
+
+        Ok(self.data.position() - position)
+    }
+
+    /// Updates the [MessageState] of the message at the provided offset.
+    ///
+    /// # Errors
+    /// - Returns [LogError::MessageNotFound] if the offset doesn't belong to this segment.
+    /// - Returns Err if the state fails to be written to the data file.
+    pub async fn set_state(&mut self, offset: u64, state: MessageState) -> Result<()> {
+        if offset < self.base_offset || offset >= self.end_offset {
+            return Err(LogError::MessageNotFound(offset));
+        }
+
+        let entry = self
+            .relative_offset(offset)
+            .and_then(|relative_offset| self.index.lookup(relative_offset))
+            .ok_or(LogError::MessageNotFound(offset))?;
+
+        self.data.set_state(entry.physical_position(), state).await
     }
 
-    /// Flushes the write buffer to the data file and the index memory-map to the filesystem.
+    /// Flushes the write buffer to the data file and the index memory-map to the filesystem,
+    /// syncing both files as required by the log's [SyncMode](crate::config::SyncMode).
     ///
     /// # Errors
     /// - Returns Err if an error occurs while flushing either file.
     pub async fn flush(&mut self) -> Result<()> {
-        self.index.flush()?;
-        self.data.flush().await?;
+        self.index.flush().await?;
+        self.data.flush(self.config.flush_policy.sync_mode).await?;
         Ok(())
     }
 
@@ -138,21 +266,55 @@ impl Segment {
         Ok(())
     }
 
-    /// Returns true if the data file's last modified time falls outside of the
-    /// provided `stale_duration`.
+    /// Returns true if the segment's most recent message falls outside of the provided
+    /// `stale_duration`.
+    ///
+    /// With [TimestampSource::Ingest], the data file's last modified time is used as the time of
+    /// the most recent message. With [TimestampSource::Event], the latest event time in the
+    /// segment is used instead, so a segment is retained until all of its events have expired.
     ///
     /// This method is used to determine which segments can be cleaned by the cleaner task.
     ///
     /// # Errors
     /// - Returns Err if the data file's metadata cannot be accessed.
     pub async fn is_stale(&self, stale_duration: Duration) -> Result<bool> {
-        self.data.is_stale(stale_duration).await
+        match self.config.timestamp_source {
+            TimestampSource::Event if self.index.current_offset() > 0 => {
+                let now = Utc::now().timestamp_millis() as u64;
+                let expires_at = self
+                    .index
+                    .max_timestamp()
+                    .saturating_add(stale_duration.as_millis() as u64);
+
+                Ok(expires_at < now)
+            }
+            _ => self.data.is_stale(stale_duration).await,
+        }
     }
 
-    /// Returns true if the index is at capacity, based on the provided `max_index_entries` option
-    /// in the shared log configuration.
+    /// Returns the offset of the first message in this segment from which every message with a
+    /// timestamp at or after the provided `timestamp` can be read, or [None] if every message in
+    /// the segment precedes `timestamp`.
+    ///
+    /// Timestamps are UNIX timestamps in milliseconds, taken from the log's [TimestampSource].
+    pub fn offset_for_timestamp(&self, timestamp: u64) -> Option<u64> {
+        self.index
+            .find_by_timestamp(timestamp)
+            .map(|relative_offset| self.base_offset + relative_offset as u64 - 1)
+    }
+
+    /// Returns true if the segment is at capacity, based on either the provided `max_index_entries`
+    /// or `segment_max_bytes` options in the shared log configuration, whichever is reached first.
     pub fn is_full(&self) -> bool {
-        self.index.is_full()
+        self.index.is_full() || self.data.position() >= self.config.segment_max_bytes
+    }
+
+    /// The fraction of the segment's capacity that has been used, based on whichever of the
+    /// `max_index_entries` or `segment_max_bytes` options is closest to being reached.
+    pub fn capacity_used(&self) -> f64 {
+        let entries = self.index.current_offset() as f64 / self.config.max_index_entries as f64;
+        let bytes = self.data.position() as f64 / self.config.segment_max_bytes as f64;
+        entries.max(bytes)
     }
 
     /// The next offset to write in this segment, or the maximum offset if the segment is at
@@ -160,6 +322,24 @@ impl Segment {
     pub fn end_offset(&self) -> u64 {
         self.end_offset
     }
+
+    // Converts an offset to its relative offset in the segment's index, or None if the offset
+    // precedes the segment, or is too far beyond it to be represented by a relative offset.
+    fn relative_offset(&self, offset: u64) -> Option<u32> {
+        let relative_offset = offset.checked_sub(self.base_offset)? + 1;
+        u32::try_from(relative_offset).ok()
+    }
+}
+
+/// Returns the unit of the timestamps in a segment's index, which depends on the layout that the
+/// segment's data file was first written with.
+async fn timestamp_unit(data: &Data) -> Result<TimestampUnit> {
+    let unit = data
+        .first_message_version()
+        .await?
+        .map_or(TimestampUnit::default(), index_timestamp_unit);
+
+    Ok(unit)
 }
 
 fn get_segment_paths(path: impl AsRef<Path>, base_offset: u64) -> (PathBuf, PathBuf) {
@@ -168,3 +348,10 @@ fn get_segment_paths(path: impl AsRef<Path>, base_offset: u64) -> (PathBuf, Path
     let data_path = path.join(format!("{base_offset}.data"));
     (index_path, data_path)
 }
+
+fn get_preallocated_paths(path: impl AsRef<Path>) -> (PathBuf, PathBuf) {
+    let path = path.as_ref();
+    let index_path = path.join("next.index.prealloc");
+    let data_path = path.join("next.data.prealloc");
+    (index_path, data_path)
+}
@@ -34,6 +34,11 @@ impl CleanerTask {
         cleaner
     }
 
+    /// Dispatches a cancel signal to gracefully terminate the background task.
+    pub fn stop(&self) {
+        self.cancellation_token.cancel();
+    }
+
     async fn run(&self) -> Result<()> {
         loop {
             tokio::select! {
@@ -1,12 +1,16 @@
-use crate::{config::SharedLogConfig, error::Result, segment::SharedSegmentList};
+use crate::{config::SharedLogConfig, segment::SharedSegmentList};
 use std::sync::Arc;
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::time::{self, MissedTickBehavior};
 use tokio_util::sync::CancellationToken;
 
 /// Task container for the asynchronous flusher task.
 ///
 /// The FlusherTask container spawns an asynchronous background task that polls the flushing interval
 /// of the log's FlushPolicy and triggers a flush once elapsed.
+///
+/// The interval ticks on the wall clock, regardless of write activity, and is only restarted when
+/// the log is flushed by other means, such as a write threshold being reached.
 #[derive(Debug)]
 pub struct FlusherTask {
     segments: SharedSegmentList,
@@ -29,24 +33,35 @@ impl FlusherTask {
         tokio::spawn({
             let task = task.clone();
             async move {
-                task.run(rx).await.unwrap();
+                task.run(rx).await;
             }
         });
 
         (task, tx)
     }
 
-    async fn run(&self, mut rx: Receiver<()>) -> Result<()> {
+    /// Dispatches a cancel signal to gracefully terminate the background task.
+    pub fn stop(&self) {
+        self.cancellation_token.cancel();
+    }
+
+    async fn run(&self, mut rx: Receiver<()>) {
+        let period = self.config.flush_policy.interval;
+        let mut interval = time::interval_at(time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         loop {
             tokio::select! {
-                _ = tokio::time::sleep(self.config.flush_policy.interval) => {
-                    self.segments.write().await.flush().await?;
+                _ = interval.tick() => {
+                    // Unflushed messages are retained if the flush fails, so it's retried on the
+                    // next tick, and surfaced to writers by the log's next write
+                    let _ = self.segments.write().await.flush().await;
                 },
-                _ = rx.recv() => {
-                    continue;
+                Some(_) = rx.recv() => {
+                    interval.reset();
                 }
                 _ = self.cancellation_token.cancelled() => {
-                    break Ok(());
+                    break;
                 }
             }
         }
@@ -1,12 +1,18 @@
 use bytes::Bytes;
 use fake::Fake;
+use futures::TryStreamExt;
 use selium_log::{
-    config::{LogConfig, SharedLogConfig},
-    message::Message,
+    config::{LogConfig, SharedLogConfig, SyncFile},
+    error::Result,
+    message::{Message, MessageState},
     MessageLog,
 };
-use std::sync::Arc;
-use tokio::fs;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{sync::Arc, time::Duration};
+use tokio::fs::{self, File};
 
 fn generate_dummy_message() -> String {
     (16..32).fake::<String>()
@@ -31,6 +37,30 @@ impl TestWrapper {
         Self { log, config }
     }
 
+    pub async fn reopen(self) -> Self {
+        self.log.close().await.unwrap();
+        drop(self.log);
+        let log = MessageLog::open(self.config.clone()).await.unwrap();
+
+        Self {
+            log,
+            config: self.config,
+        }
+    }
+
+    pub async fn reopen_with(self, config: LogConfig) -> Self {
+        self.log.close().await.unwrap();
+        drop(self.log);
+        Self::build(config).await
+    }
+
+    pub async fn open_read_only(&self) -> Self {
+        let config = self.config.clone();
+        let log = MessageLog::open_read_only(config.clone()).await.unwrap();
+
+        Self { log, config }
+    }
+
     pub async fn number_of_segments(&self) -> u64 {
         let mut segments_count = 0;
         let mut dir = fs::read_dir(&self.config.segments_path).await.unwrap();
@@ -46,10 +76,28 @@ impl TestWrapper {
         segments_count
     }
 
-    pub async fn write_records(&mut self, records: &[String]) {
+    pub async fn has_preallocated_segment(&self) -> bool {
+        let path = self.config.segments_path.join("next.index.prealloc");
+        fs::try_exists(path).await.unwrap()
+    }
+
+    pub async fn write_records(&mut self, records: &[String]) -> Vec<u64> {
+        let mut offsets = Vec::with_capacity(records.len());
+
         for record in records {
-            self.write(record).await;
+            offsets.push(self.write(record).await);
         }
+
+        offsets
+    }
+
+    pub async fn write_many(&mut self, records: &[String]) -> Vec<u64> {
+        let messages = records.iter().map(|record| {
+            let batch = Bytes::from(record.to_owned());
+            Message::single(&batch, 1)
+        });
+
+        self.log.write_many(messages).await.unwrap()
     }
 
     pub async fn write_dummy_records(&mut self, count: usize) {
@@ -78,13 +126,129 @@ impl TestWrapper {
         messages
     }
 
+    pub async fn read_all_records(&mut self, mut offset: u64) -> Vec<String> {
+        let mut messages = vec![];
+
+        loop {
+            let end_offset = self.read_end_offset(offset, None).await;
+
+            if end_offset <= offset {
+                break;
+            }
+
+            messages.extend(self.read_records(offset, None).await);
+            offset = end_offset;
+        }
+
+        messages
+    }
+
+    pub async fn read_range(&self, start: u64, end: u64) -> Vec<String> {
+        self.try_read_range(start, end).await.unwrap()
+    }
+
+    pub async fn try_read_range(&self, start: u64, end: u64) -> Result<Vec<String>> {
+        let messages = self.log.read_range(start, end).await?;
+
+        Ok(messages
+            .iter()
+            .map(|message| String::from_utf8(message.records().to_vec()).unwrap())
+            .collect())
+    }
+
+    pub async fn iter_records(&self, offset: u64) -> Vec<String> {
+        self.log
+            .iter_from(offset)
+            .map_ok(|message| String::from_utf8(message.records().to_vec()).unwrap())
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    pub async fn read_end_offset(&mut self, offset: u64, limit: Option<u64>) -> u64 {
+        self.log
+            .read_slice(offset, limit)
+            .await
+            .unwrap()
+            .end_offset()
+    }
+
     pub async fn flush(&mut self) {
         self.log.flush().await.unwrap();
     }
 
-    async fn write(&mut self, message: &str) {
+    pub async fn truncate_to(&mut self, offset: u64) -> u64 {
+        self.log.truncate_to(offset).await.unwrap()
+    }
+
+    pub async fn write_with_ttl(&mut self, message: &str, ttl: Duration) -> u64 {
+        let batch = Bytes::from(message.to_owned());
+        let message = Message::single(&batch, 1).with_ttl(ttl);
+        self.log.write(message).await.unwrap()
+    }
+
+    pub async fn write_with_event_time(&mut self, message: &str, event_time: u64) -> u64 {
+        let batch = Bytes::from(message.to_owned());
+        let message = Message::single(&batch, 1).with_event_time(event_time);
+        self.log.write(message).await.unwrap()
+    }
+
+    pub async fn offset_for_timestamp(&self, timestamp: u64) -> u64 {
+        self.log.offset_for_timestamp(timestamp).await
+    }
+
+    pub async fn write_uncommitted(&mut self, message: &str) -> u64 {
+        let batch = Bytes::from(message.to_owned());
+        let message = Message::single(&batch, 1).with_state(MessageState::Uncommitted);
+        self.log.write(message).await.unwrap()
+    }
+
+    pub async fn commit(&mut self, offset: u64) -> Result<()> {
+        self.log.commit(offset).await
+    }
+
+    pub async fn abort(&mut self, offset: u64) -> Result<()> {
+        self.log.abort(offset).await
+    }
+
+    pub async fn try_write(&mut self, message: &str) -> Result<u64> {
         let batch = Bytes::from(message.to_owned());
         let message = Message::single(&batch, 1);
-        self.log.write(message).await.unwrap();
+        self.log.write(message).await
+    }
+
+    async fn write(&mut self, message: &str) -> u64 {
+        self.try_write(message).await.unwrap()
+    }
+}
+
+/// Wraps a [File], counting each call made to sync it.
+pub struct SyncCounter {
+    file: File,
+    pub data_syncs: AtomicUsize,
+    pub full_syncs: AtomicUsize,
+}
+
+impl SyncCounter {
+    pub async fn create(path: impl AsRef<Path>) -> Self {
+        let file = File::create(path).await.unwrap();
+
+        Self {
+            file,
+            data_syncs: AtomicUsize::new(0),
+            full_syncs: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl SyncFile for SyncCounter {
+    fn sync_data(&self) -> impl Future<Output = io::Result<()>> + Send {
+        self.data_syncs.fetch_add(1, Ordering::SeqCst);
+        self.file.sync_data()
+    }
+
+    fn sync_all(&self) -> impl Future<Output = io::Result<()>> + Send {
+        self.full_syncs.fetch_add(1, Ordering::SeqCst);
+        self.file.sync_all()
     }
 }
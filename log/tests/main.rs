@@ -1,8 +1,15 @@
 mod helpers;
 
+use chrono::Utc;
 use helpers::generate_dummy_messages;
-use helpers::TestWrapper;
-use selium_log::config::{FlushPolicy, LogConfig};
+use helpers::{SyncCounter, TestWrapper};
+use selium_log::config::{EncryptionKey, FlushPolicy, LogConfig, SyncMode, TimestampSource};
+use selium_log::error::LogError;
+use selium_log::index::{Index, TimestampUnit};
+use selium_log::message::{Message, MessageState};
+use selium_log::MessageLog;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::{ops::Add, time::Duration};
 use tempfile::TempDir;
 
@@ -37,6 +44,59 @@ async fn writes_producer_messages_in_order() {
     assert_eq!(messages, read_messages);
 }
 
+#[tokio::test]
+async fn returns_assigned_offset_on_write() {
+    let total_messages = 100;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let offsets = wrapper.write_records(messages.as_slice()).await;
+    let expected = (0..total_messages as u64).collect::<Vec<_>>();
+    assert_eq!(offsets, expected);
+
+    wrapper.flush().await;
+
+    let read_messages = wrapper.read_records(offsets[50], None).await;
+    assert_eq!(read_messages, messages[50..]);
+}
+
+#[tokio::test]
+async fn batched_writes_match_individual_writes() {
+    let max_index_entries = 1_000;
+    let messages = generate_dummy_messages(2_500);
+
+    let individual_dir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(individual_dir.path()).max_index_entries(max_index_entries);
+    let mut individual = TestWrapper::build(config).await;
+    let individual_offsets = individual.write_records(messages.as_slice()).await;
+    individual.flush().await;
+
+    let batched_dir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(batched_dir.path()).max_index_entries(max_index_entries);
+    let mut batched = TestWrapper::build(config).await;
+    let mut batched_offsets = vec![];
+
+    for chunk in messages.chunks(300) {
+        batched_offsets.extend(batched.write_many(chunk).await);
+    }
+
+    batched.flush().await;
+
+    assert_eq!(batched_offsets, individual_offsets);
+    assert_eq!(
+        batched.read_all_records(0).await,
+        individual.read_all_records(0).await
+    );
+    assert_eq!(batched.read_all_records(0).await, messages);
+    assert_eq!(
+        batched.number_of_segments().await,
+        individual.number_of_segments().await
+    );
+}
+
 #[tokio::test]
 async fn splits_log_into_segments() {
     let max_index_entries = 10_000;
@@ -54,6 +114,361 @@ async fn splits_log_into_segments() {
     assert_eq!(actual_number_of_segments, number_of_segments as u64);
 }
 
+#[tokio::test]
+async fn splits_log_into_segments_by_size() {
+    let total_messages = 9;
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).segment_max_bytes(1);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_dummy_records(total_messages).await;
+    wrapper.flush().await;
+
+    // Every write exceeds the size threshold, so each message is followed by a new hot segment.
+    let actual_number_of_segments = wrapper.number_of_segments().await;
+    assert_eq!(actual_number_of_segments, total_messages as u64 + 1);
+}
+
+#[tokio::test]
+async fn preallocates_next_segment_past_watermark() {
+    let max_index_entries = 100;
+    let messages = generate_dummy_messages(max_index_entries as usize + 1);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(max_index_entries)
+        .preallocate_watermark(0.5);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(&messages[..50]).await;
+
+    // Preallocation happens in the background, so give the task a chance to complete.
+    for _ in 0..100 {
+        if wrapper.has_preallocated_segment().await {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert!(wrapper.has_preallocated_segment().await);
+    assert_eq!(wrapper.number_of_segments().await, 1);
+
+    wrapper.write_records(&messages[50..]).await;
+    wrapper.flush().await;
+
+    assert!(!wrapper.has_preallocated_segment().await);
+    assert_eq!(wrapper.number_of_segments().await, 2);
+
+    let read_messages = wrapper.read_all_records(0).await;
+    assert_eq!(read_messages, messages);
+}
+
+#[tokio::test]
+async fn rolls_over_when_index_file_is_at_capacity() {
+    let max_index_entries = 10;
+    let messages = generate_dummy_messages(35);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(max_index_entries);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    // Leave the hot segment's index file, sized for 10 entries, partially filled.
+    wrapper.write_records(&messages[..15]).await;
+    wrapper.flush().await;
+
+    // Reopening with a larger limit must not overrun the existing index file.
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(max_index_entries * 2);
+    let mut wrapper = wrapper.reopen_with(config).await;
+
+    let offsets = wrapper.write_records(&messages[15..]).await;
+    assert_eq!(offsets, (15..35).collect::<Vec<_>>());
+    wrapper.flush().await;
+
+    // 10 entries, 10 entries, then 15 entries in a hot segment sized for 20.
+    assert_eq!(wrapper.number_of_segments().await, 3);
+
+    let read_messages = wrapper.read_all_records(0).await;
+    assert_eq!(read_messages, messages);
+}
+
+#[tokio::test]
+async fn rejects_zero_max_index_entries() {
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(0);
+    let result = MessageLog::open(Arc::new(config)).await;
+
+    assert!(matches!(result, Err(LogError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn reads_messages_across_segments() {
+    let total_messages = 1_000;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(100)
+        .segment_max_bytes(2048);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let offsets = wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let expected_offsets = (0..total_messages as u64).collect::<Vec<_>>();
+    assert_eq!(offsets, expected_offsets);
+
+    let read_messages = wrapper.read_all_records(0).await;
+    assert_eq!(read_messages, messages);
+}
+
+#[tokio::test]
+async fn iterates_over_entire_log() {
+    let total_messages = 1_000;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(100);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let all_messages = wrapper.iter_records(0).await;
+    assert_eq!(all_messages, messages);
+
+    let from_offset = wrapper.iter_records(550).await;
+    assert_eq!(from_offset, messages[550..]);
+
+    let beyond_tail = wrapper.iter_records(total_messages as u64).await;
+    assert!(beyond_tail.is_empty());
+}
+
+#[tokio::test]
+async fn reads_populated_log_in_read_only_mode() {
+    let total_messages = 1_000;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(100);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    // The log remains open for writing while it is being read.
+    let mut read_only = wrapper.open_read_only().await;
+
+    let all_messages = read_only.iter_records(0).await;
+    assert_eq!(all_messages, messages);
+
+    let range = read_only.read_range(250, 750).await;
+    assert_eq!(range, messages[250..750]);
+
+    let result = read_only.try_write("Hello, world!").await;
+    assert!(matches!(result, Err(LogError::ReadOnly)));
+}
+
+#[tokio::test]
+async fn reads_exact_range_across_segments() {
+    let total_messages = 1_000;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(100);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let range = wrapper.read_range(150, 475).await;
+    assert_eq!(range, messages[150..475]);
+
+    let empty_range = wrapper.read_range(200, 200).await;
+    assert!(empty_range.is_empty());
+}
+
+#[tokio::test]
+async fn clamps_range_to_tail_of_log() {
+    let total_messages = 100;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let range = wrapper.read_range(90, 1_000).await;
+    assert_eq!(range, messages[90..]);
+
+    let beyond_tail = wrapper.read_range(500, 1_000).await;
+    assert!(beyond_tail.is_empty());
+}
+
+#[tokio::test]
+async fn skips_cleaned_segments_in_range() {
+    let max_index_entries = 100;
+    let retention_period = Duration::from_secs(2);
+    let cleaner_interval = Duration::from_millis(500);
+    let stale_messages = generate_dummy_messages(max_index_entries as usize);
+    let fresh_messages = generate_dummy_messages(50);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(max_index_entries)
+        .retention_period(retention_period)
+        .cleaner_interval(cleaner_interval);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(stale_messages.as_slice()).await;
+    wrapper.flush().await;
+
+    tokio::time::sleep(retention_period.add(Duration::from_secs(1))).await;
+
+    wrapper.write_records(fresh_messages.as_slice()).await;
+    wrapper.flush().await;
+
+    // The first segment is stale and has been removed, so reading from offset 0 should begin at
+    // the oldest retained message.
+    let range = wrapper.read_range(0, 125).await;
+    assert_eq!(range, fresh_messages[..25]);
+}
+
+#[tokio::test]
+async fn truncates_whole_segments_below_offset() {
+    let total_messages = 1_000;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).max_index_entries(100);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    // Offset 450 falls within the segment starting at 400, which must be retained in full.
+    let start_offset = wrapper.truncate_to(450).await;
+    assert_eq!(start_offset, 400);
+    assert_eq!(wrapper.number_of_segments().await, 7);
+
+    // Reads below the new start offset skip ahead to the oldest retained message.
+    let range = wrapper.read_range(0, 500).await;
+    assert_eq!(range, messages[400..500]);
+
+    let all_messages = wrapper.iter_records(0).await;
+    assert_eq!(all_messages, messages[400..]);
+
+    // Truncating past the tail removes every segment, but writing resumes from the tail.
+    let start_offset = wrapper.truncate_to(total_messages as u64).await;
+    assert_eq!(start_offset, total_messages as u64);
+    assert_eq!(wrapper.number_of_segments().await, 0);
+
+    let offset = wrapper.try_write("Hello, world!").await.unwrap();
+    assert_eq!(offset, total_messages as u64);
+}
+
+#[tokio::test]
+async fn seeks_by_event_time_with_out_of_order_timestamps() {
+    // Producers with skewed clocks may write events out of order
+    let event_times = [1_000, 3_000, 2_000, 5_000, 4_000, 6_000, 2_500, 7_000];
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(3)
+        .timestamp_source(TimestampSource::Event);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    for (i, event_time) in event_times.iter().enumerate() {
+        wrapper
+            .write_with_event_time(&format!("Message {i}"), *event_time)
+            .await;
+    }
+
+    wrapper.flush().await;
+
+    for timestamp in [0, 1_000, 2_000, 2_500, 4_500, 6_500, 7_000] {
+        let offset = wrapper.offset_for_timestamp(timestamp).await;
+
+        // No message at or after the timestamp may be skipped by the seek
+        let first_match = event_times.iter().position(|t| *t >= timestamp).unwrap();
+        assert_eq!(offset, first_match as u64, "seeking to {timestamp}");
+    }
+
+    assert_eq!(wrapper.offset_for_timestamp(8_000).await, 8);
+
+    // Messages following the seek offset may still precede the timestamp
+    let offset = wrapper.offset_for_timestamp(4_500).await;
+    let messages = wrapper.read_range(offset, 8).await;
+    assert_eq!(
+        messages,
+        [
+            "Message 3",
+            "Message 4",
+            "Message 5",
+            "Message 6",
+            "Message 7"
+        ]
+    );
+
+    // The index is rebuilt with the same ordering when the log is reopened
+    let wrapper = wrapper.reopen().await;
+    assert_eq!(wrapper.offset_for_timestamp(2_500).await, 1);
+    assert_eq!(wrapper.offset_for_timestamp(6_500).await, 7);
+}
+
+#[tokio::test]
+async fn seeks_by_ingest_time_by_default() {
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let started = Utc::now().timestamp_millis() as u64;
+
+    // Event times are ignored when seeking by ingest time
+    wrapper.write_with_event_time("Old event", 1_000).await;
+    wrapper.flush().await;
+
+    assert_eq!(wrapper.offset_for_timestamp(0).await, 0);
+    assert_eq!(wrapper.offset_for_timestamp(started - 1_000).await, 0);
+    assert_eq!(wrapper.offset_for_timestamp(started + 60_000).await, 1);
+}
+
+#[tokio::test]
+async fn removes_segments_with_expired_event_times() {
+    let max_index_entries = 10;
+    let now = Utc::now().timestamp_millis() as u64;
+    let hour_ago = now - 60 * 60 * 1_000;
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path())
+        .max_index_entries(max_index_entries)
+        .retention_period(Duration::from_secs(60))
+        .cleaner_interval(Duration::from_millis(500))
+        .timestamp_source(TimestampSource::Event);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    // The first segment only contains events from an hour ago, so it has expired even though it
+    // was only just written
+    for i in 0..max_index_entries {
+        wrapper
+            .write_with_event_time(&format!("Old {i}"), hour_ago)
+            .await;
+    }
+
+    wrapper.write_with_event_time("New", now).await;
+    wrapper.flush().await;
+
+    assert_eq!(wrapper.number_of_segments().await, 2);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(wrapper.number_of_segments().await, 1);
+
+    let messages = wrapper.read_range(0, 11).await;
+    assert_eq!(messages, ["New"]);
+}
+
 #[tokio::test]
 async fn flushes_log_based_on_interval() {
     let total_messages = 10_000;
@@ -76,6 +491,75 @@ async fn flushes_log_based_on_interval() {
     assert!(!messages.is_empty());
 }
 
+#[tokio::test]
+async fn flushes_single_write_within_interval() {
+    let flush_interval = Duration::from_secs(1);
+    let flush_policy = FlushPolicy::default().interval(flush_interval);
+    let tempdir = TempDir::new().unwrap();
+
+    let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    // Wait part of the way through the interval, so the write doesn't line up with a tick.
+    tokio::time::sleep(flush_interval / 2).await;
+    let message = generate_dummy_messages(1);
+    wrapper.write_records(&message).await;
+
+    let messages = wrapper.read_records(0, None).await;
+    assert!(messages.is_empty());
+
+    // No further writes occur, but the message should still be flushed once the interval elapses.
+    tokio::time::sleep(flush_interval).await;
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, message);
+}
+
+#[tokio::test]
+async fn notifies_readers_once_writes_are_flushed() {
+    let flush_interval = Duration::from_millis(500);
+    let flush_policy = FlushPolicy::default().interval(flush_interval);
+    let tempdir = TempDir::new().unwrap();
+
+    let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+    let log = MessageLog::open(Arc::new(config)).await.unwrap();
+    let flushed = log.flushed();
+    tokio::pin!(flushed);
+    flushed.as_mut().enable();
+
+    log.write(Message::single(b"foo", 1)).await.unwrap();
+
+    // The write is only readable once the Flusher task has flushed it
+    assert_eq!(log.read_range(0, 1).await.unwrap().len(), 0);
+    tokio::time::timeout(flush_interval * 2, flushed)
+        .await
+        .unwrap();
+    assert_eq!(log.read_range(0, 1).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn flushes_log_based_on_unflushed_bytes() {
+    let flush_policy = FlushPolicy::default()
+        .interval(Duration::from_secs(60))
+        .max_unflushed_bytes(4096);
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let small_message = generate_dummy_messages(1);
+    wrapper.write_records(&small_message).await;
+
+    // The byte threshold has not been reached, so the record shouldn't be flushed yet.
+    let messages = wrapper.read_records(0, None).await;
+    assert!(messages.is_empty());
+
+    // A single large write should exceed the byte threshold and trigger a flush.
+    let large_message = vec!["x".repeat(4096)];
+    wrapper.write_records(&large_message).await;
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, [small_message, large_message].concat());
+}
+
 #[tokio::test]
 async fn flushes_log_based_on_writes() {
     let number_of_writes_before_flush = 10_000;
@@ -99,6 +583,90 @@ async fn flushes_log_based_on_writes() {
     assert!(!messages.is_empty());
 }
 
+#[tokio::test]
+async fn skips_expired_messages() {
+    let ttl = Duration::from_millis(500);
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_with_ttl("ephemeral", ttl).await;
+    wrapper.write_records(&["durable".to_owned()]).await;
+    wrapper.flush().await;
+
+    // Both messages are in the same segment, and neither has expired yet.
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["ephemeral", "durable"]);
+
+    tokio::time::sleep(ttl).await;
+
+    // The expired message should be skipped, while the fresh message is still delivered.
+    assert_eq!(wrapper.number_of_segments().await, 1);
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["durable"]);
+}
+
+#[tokio::test]
+async fn commits_and_aborts_uncommitted_messages() {
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let committed = wrapper.write_uncommitted("committed").await;
+    let aborted = wrapper.write_uncommitted("aborted").await;
+    wrapper.flush().await;
+
+    // Update the state of flushed messages in the data file, and unflushed messages in the buffer
+    let buffered = wrapper.write_uncommitted("buffered").await;
+    wrapper.write_records(&["durable".to_owned()]).await;
+    wrapper.commit(committed).await.unwrap();
+    wrapper.abort(aborted).await.unwrap();
+    wrapper.abort(buffered).await.unwrap();
+    wrapper.flush().await;
+
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["committed", "durable"]);
+
+    let result = wrapper.commit(10).await;
+    assert!(matches!(result, Err(LogError::MessageNotFound(10))));
+}
+
+#[tokio::test]
+async fn aborts_uncommitted_messages_after_reopening() {
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_uncommitted("abandoned").await;
+    wrapper.write_records(&["durable".to_owned()]).await;
+    wrapper.flush().await;
+
+    // Uncommitted messages are returned, so that readers can wait for them to be resolved
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["abandoned", "durable"]);
+
+    let mut wrapper = wrapper.reopen().await;
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["durable"]);
+}
+
+#[tokio::test]
+async fn appends_to_existing_segment_after_reopening() {
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(&["first".to_owned()]).await;
+    wrapper.flush().await;
+
+    let mut wrapper = wrapper.reopen().await;
+    wrapper.write_records(&["second".to_owned()]).await;
+    wrapper.flush().await;
+
+    let messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, ["first", "second"]);
+}
+
 #[tokio::test]
 async fn removes_stale_logs() {
     let max_index_entries = 10_000;
@@ -220,3 +788,332 @@ async fn fetches_messages_from_offset_with_limit() {
 
     assert_eq!(read_messages.len(), offset_messages.len());
 }
+
+#[tokio::test]
+async fn returns_next_unread_offset_in_slice() {
+    let total_messages = 10;
+    let limit = 4;
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path());
+    let mut wrapper = TestWrapper::build(config).await;
+
+    let messages = generate_dummy_messages(total_messages);
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let end_offset = wrapper.read_end_offset(0, Some(limit)).await;
+    assert_eq!(end_offset, limit);
+
+    let read_messages = wrapper.read_records(end_offset, None).await;
+    assert_eq!(read_messages, messages[limit as usize..]);
+
+    let end_offset = wrapper.read_end_offset(0, None).await;
+    assert_eq!(end_offset, total_messages as u64);
+}
+
+#[tokio::test]
+async fn syncs_files_according_to_sync_mode() {
+    let tempdir = TempDir::new().unwrap();
+    let file = SyncCounter::create(tempdir.path().join("file")).await;
+
+    SyncMode::None.sync(&file).await.unwrap();
+    assert_eq!(file.data_syncs.load(Ordering::SeqCst), 0);
+    assert_eq!(file.full_syncs.load(Ordering::SeqCst), 0);
+
+    SyncMode::Data.sync(&file).await.unwrap();
+    assert_eq!(file.data_syncs.load(Ordering::SeqCst), 1);
+    assert_eq!(file.full_syncs.load(Ordering::SeqCst), 0);
+
+    SyncMode::DataAndMetadata.sync(&file).await.unwrap();
+    assert_eq!(file.data_syncs.load(Ordering::SeqCst), 1);
+    assert_eq!(file.full_syncs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn flushes_log_with_each_sync_mode() {
+    for sync_mode in [SyncMode::None, SyncMode::Data, SyncMode::DataAndMetadata] {
+        let messages = generate_dummy_messages(10);
+        let tempdir = TempDir::new().unwrap();
+        let flush_policy = FlushPolicy::default().sync_mode(sync_mode);
+        let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+        let mut wrapper = TestWrapper::build(config).await;
+
+        wrapper.write_records(&messages).await;
+        wrapper.flush().await;
+
+        // A separate reader only sees what the flush wrote to the segment files
+        let mut reader = wrapper.open_read_only().await;
+        assert_eq!(
+            reader.read_records(0, None).await,
+            messages,
+            "{sync_mode:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn writes_to_log_with_strongest_sync_mode() {
+    let total_messages = 100;
+    let messages = generate_dummy_messages(total_messages);
+
+    let tempdir = TempDir::new().unwrap();
+    let flush_policy = FlushPolicy::default().sync_mode(SyncMode::DataAndMetadata);
+    let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let read_messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, read_messages);
+}
+
+#[tokio::test]
+async fn encrypts_records_at_rest() {
+    let total_messages = 100;
+    let messages = generate_dummy_messages(total_messages);
+    let key = EncryptionKey::from_hex(&"2a".repeat(32)).unwrap();
+
+    let tempdir = TempDir::new().unwrap();
+    let config = LogConfig::from_path(tempdir.path()).encryption_key(key);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_records(messages.as_slice()).await;
+    wrapper.flush().await;
+
+    let data = std::fs::read(tempdir.path().join("0.data")).unwrap();
+    let data = String::from_utf8_lossy(&data);
+
+    for message in &messages {
+        assert!(!data.contains(message.as_str()));
+    }
+
+    let read_messages = wrapper.read_records(0, None).await;
+    assert_eq!(messages, read_messages);
+}
+
+#[tokio::test]
+async fn fails_to_read_records_with_different_key() {
+    let tempdir = TempDir::new().unwrap();
+    let key = EncryptionKey::from_hex(&"2a".repeat(32)).unwrap();
+    let config = LogConfig::from_path(tempdir.path()).encryption_key(key);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    wrapper.write_dummy_records(1).await;
+    wrapper.flush().await;
+    drop(wrapper);
+
+    let key = EncryptionKey::from_hex(&"2b".repeat(32)).unwrap();
+    let config = LogConfig::from_path(tempdir.path()).encryption_key(key);
+    let wrapper = TestWrapper::build(config).await;
+
+    let result = wrapper.try_read_range(0, 1).await;
+    assert!(matches!(result, Err(LogError::Decrypt)));
+}
+
+#[test]
+fn rejects_malformed_encryption_keys() {
+    for key in ["", "2a", &"zz".repeat(32), &"2a".repeat(33)] {
+        assert!(matches!(
+            EncryptionKey::from_hex(key),
+            Err(LogError::InvalidEncryptionKey)
+        ));
+    }
+}
+
+#[tokio::test]
+async fn surfaces_failed_flush_on_next_write() {
+    let tempdir = TempDir::new().unwrap();
+    // Every write to /dev/full fails, as if the disk were full
+    std::os::unix::fs::symlink("/dev/full", tempdir.path().join("0.data")).unwrap();
+
+    let flush_policy = FlushPolicy::default().number_of_writes(1);
+    let config = LogConfig::from_path(tempdir.path()).flush_policy(flush_policy);
+    let mut wrapper = TestWrapper::build(config).await;
+
+    // The message is written regardless, and the failed flush is retried before the next write
+    assert_eq!(wrapper.try_write("first").await.unwrap(), 0);
+
+    let result = wrapper.try_write("second").await;
+    assert!(matches!(result, Err(LogError::Flush(_))));
+    assert!(!result.unwrap_err().is_transient());
+}
+
+#[tokio::test]
+async fn index_stores_positions_beyond_32_bits() {
+    let tempdir = TempDir::new().unwrap();
+    let config = Arc::new(LogConfig::from_path(tempdir.path()).max_index_entries(3));
+    let path = tempdir.path().join("0.index");
+
+    let positions = [
+        u32::MAX as u64 - 1,
+        u32::MAX as u64 + 1,
+        (u32::MAX as u64 + 1) * 16 + 7,
+    ];
+
+    let mut index = Index::create(&path, config.clone()).await.unwrap();
+
+    for (timestamp, position) in positions.iter().enumerate() {
+        index.append(timestamp as u64, *position).unwrap();
+    }
+
+    assert!(index.is_full());
+    index.flush().await.unwrap();
+    drop(index);
+
+    let index = Index::open(&path, config).await.unwrap();
+    assert_eq!(index.current_offset(), 3);
+
+    for (relative_offset, position) in (1..).zip(positions) {
+        let entry = index.lookup(relative_offset).unwrap();
+        assert_eq!(entry.relative_offset(), relative_offset);
+        assert_eq!(entry.physical_position(), position);
+    }
+
+    // Appending to a full index is rejected, rather than writing past the end of the file
+    let mut index = index;
+    let result = index.append(3, u64::MAX);
+    assert!(matches!(result, Err(LogError::IndexFull)));
+    assert_eq!(index.current_offset(), 3);
+    assert!(index.lookup(4).is_none());
+}
+
+#[tokio::test]
+async fn reads_segment_written_with_legacy_message_layout() {
+    let tempdir = TempDir::new().unwrap();
+    let config = Arc::new(LogConfig::from_path(tempdir.path()));
+    let records = ["foo", "bar"];
+
+    // Headers were originally a length, version, batch size and timestamp, followed by the
+    // records and CRC
+    let mut data = Vec::new();
+    let mut index = Index::create(tempdir.path().join("0.index"), config.clone())
+        .await
+        .unwrap();
+
+    for record in records {
+        index.append(0, data.len() as u64).unwrap();
+        let length = (8 + 4 + 4 + 8 + record.len() + 4) as u64;
+        data.extend_from_slice(&length.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&Utc::now().timestamp().to_be_bytes());
+        data.extend_from_slice(record.as_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+    }
+
+    index.flush().await.unwrap();
+    drop(index);
+    std::fs::write(tempdir.path().join("0.data"), data).unwrap();
+
+    let log = MessageLog::open(config).await.unwrap();
+    let message = Message::single(b"baz", 1).with_state(MessageState::Uncommitted);
+    let offset = log.write(message).await.unwrap();
+    log.flush().await.unwrap();
+
+    // Legacy messages are read alongside those written in the current layout
+    let messages = log.read_range(0, 3).await.unwrap();
+    let read: Vec<_> = messages.iter().map(|m| m.records()).collect();
+    assert_eq!(read, [b"foo".as_slice(), b"bar", b"baz"]);
+    assert!(messages.iter().all(|m| m.headers().version() == 1));
+    assert_eq!(messages[0].headers().state(), MessageState::Committed);
+    assert_eq!(messages[2].headers().state(), MessageState::Uncommitted);
+
+    // Legacy messages have no state to update, unlike messages in the current layout
+    let result = log.commit(0).await;
+    assert!(matches!(result, Err(LogError::LegacyMessageLayout)));
+    log.commit(offset).await.unwrap();
+}
+
+#[tokio::test]
+async fn reads_segment_written_before_message_states() {
+    let tempdir = TempDir::new().unwrap();
+    let config = Arc::new(LogConfig::from_path(tempdir.path()));
+
+    // Messages carried an expiry time before they carried a state, which is marked by a layout
+    // of 1 in the upper byte of the version
+    let mut data = Vec::new();
+    let mut index = Index::create(tempdir.path().join("0.index"), config.clone())
+        .await
+        .unwrap();
+
+    index.append(0, 0).unwrap();
+    let record = b"foo";
+    let length = (8 + 4 + 4 + 8 + 8 + record.len() + 4) as u64;
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(&(1u32 << 24 | 1).to_be_bytes());
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&Utc::now().timestamp().to_be_bytes());
+    data.extend_from_slice(&0u64.to_be_bytes());
+    data.extend_from_slice(record);
+    data.extend_from_slice(&0u32.to_be_bytes());
+
+    index.flush().await.unwrap();
+    drop(index);
+    std::fs::write(tempdir.path().join("0.data"), data).unwrap();
+
+    let log = MessageLog::open(config).await.unwrap();
+    log.write(Message::single(b"bar", 1)).await.unwrap();
+    log.flush().await.unwrap();
+
+    // The message is read as committed, rather than its records being mistaken for a state
+    let messages = log.read_range(0, 2).await.unwrap();
+    let read: Vec<_> = messages.iter().map(|m| m.records()).collect();
+    assert_eq!(read, [b"foo".as_slice(), b"bar"]);
+    assert_eq!(messages[0].headers().version(), 1);
+    assert_eq!(messages[0].headers().expires_at(), None);
+    assert_eq!(messages[0].headers().state(), MessageState::Committed);
+    assert!(matches!(
+        log.commit(0).await,
+        Err(LogError::LegacyMessageLayout)
+    ));
+}
+
+#[tokio::test]
+async fn seeks_by_ingest_time_in_segment_indexed_in_seconds() {
+    let tempdir = TempDir::new().unwrap();
+    let config = Arc::new(LogConfig::from_path(tempdir.path()));
+    let now = Utc::now().timestamp() as u64;
+    let timestamps = [now - 100, now - 50];
+
+    // Segments were originally indexed by the time each message was appended in seconds, which
+    // was also stored in the message headers
+    let mut data = Vec::new();
+    let mut index = Index::create(tempdir.path().join("0.index"), config.clone())
+        .await
+        .unwrap()
+        .with_timestamp_unit(TimestampUnit::Seconds);
+
+    for (record, timestamp) in ["foo", "bar"].iter().zip(timestamps) {
+        index.append(timestamp * 1000, data.len() as u64).unwrap();
+        let length = (8 + 4 + 4 + 8 + record.len() + 4) as u64;
+        data.extend_from_slice(&length.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&timestamp.to_be_bytes());
+        data.extend_from_slice(record.as_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+    }
+
+    index.flush().await.unwrap();
+    drop(index);
+    std::fs::write(tempdir.path().join("0.data"), data).unwrap();
+
+    let log = MessageLog::open(config).await.unwrap();
+    log.write(Message::single(b"baz", 1)).await.unwrap();
+    log.flush().await.unwrap();
+
+    // Ingest times are reported in milliseconds, whichever layout they were written in
+    let messages = log.read_range(0, 3).await.unwrap();
+    assert_eq!(messages[0].headers().timestamp(), timestamps[0] * 1000);
+    assert_eq!(messages[1].headers().timestamp(), timestamps[1] * 1000);
+    assert!(messages[2].headers().timestamp() >= now * 1000);
+
+    // The segment is still searched correctly after messages in the current layout are appended
+    assert_eq!(log.offset_for_timestamp(0).await, 0);
+    assert_eq!(log.offset_for_timestamp(timestamps[0] * 1000).await, 0);
+    assert_eq!(log.offset_for_timestamp(timestamps[1] * 1000).await, 1);
+    assert_eq!(log.offset_for_timestamp(timestamps[1] * 1000 + 1).await, 2);
+    assert_eq!(log.offset_for_timestamp((now + 60) * 1000).await, 3);
+}
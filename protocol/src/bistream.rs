@@ -1,10 +1,13 @@
 use crate::traits::{ShutdownSink, ShutdownStream};
-use crate::{error_codes, Frame, MessageCodec};
+use crate::{error_codes, Frame, MessageCodec, StreamType, TopicName};
 use futures::{Sink, SinkExt, Stream, StreamExt};
 use quinn::VarInt;
 use quinn::{Connection, RecvStream, SendStream, StreamId};
+use selium_std::encoding::BincodeConfig;
 use selium_std::errors::{QuicError, Result, SeliumError};
 use std::{
+    error::Error,
+    fmt,
     ops::{Deref, DerefMut},
     pin::Pin,
     task::{Context, Poll},
@@ -20,6 +23,73 @@ pub struct BiStream {
     read: ReadHalf,
 }
 
+/// Error returned by [BiStream::reunite] when the halves were split from different streams.
+///
+/// Both halves are handed back, so that they can continue to be used separately.
+pub struct ReuniteError(pub WriteHalf, pub ReadHalf);
+
+impl WriteHalf {
+    /// Frames `send`, serializing frame payloads with the provided [BincodeConfig]. The peer
+    /// must decode the stream with the same configuration.
+    pub fn new(send: SendStream, config: BincodeConfig) -> Self {
+        Self(FramedWrite::new(send, MessageCodec::new(config)))
+    }
+
+    /// The type of stream registered by the header frame sent or received on the stream that
+    /// this half was split from, or [None] if the stream hasn't been registered yet.
+    pub fn stream_type(&self) -> Option<StreamType> {
+        self.0.encoder().stream_type()
+    }
+
+    /// The topic declared by the header frame sent or received on the stream that this half was
+    /// split from, or [None] if no header frame has been sent or received yet.
+    pub fn get_path(&self) -> Option<&TopicName> {
+        self.0.encoder().get_path()
+    }
+}
+
+impl ReadHalf {
+    /// Frames `recv`, deserializing frame payloads with the provided [BincodeConfig]. The peer
+    /// must encode the stream with the same configuration.
+    pub fn new(recv: RecvStream, config: BincodeConfig) -> Self {
+        Self(FramedRead::new(recv, MessageCodec::new(config)))
+    }
+
+    /// The type of stream registered by the header frame sent or received on the stream that
+    /// this half was split from, or [None] if the stream hasn't been registered yet.
+    pub fn stream_type(&self) -> Option<StreamType> {
+        self.0.decoder().stream_type()
+    }
+
+    /// The topic declared by the header frame sent or received on the stream that this half was
+    /// split from, or [None] if no header frame has been sent or received yet.
+    pub fn get_path(&self) -> Option<&TopicName> {
+        self.0.decoder().get_path()
+    }
+}
+
+impl fmt::Debug for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReuniteError")
+            .field(&self.0.id())
+            .field(&self.1.id())
+            .finish()
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tried to reunite halves of different streams ({} and {})",
+            self.0.id(),
+            self.1.id()
+        )
+    }
+}
+
+impl Error for ReuniteError {}
+
 impl Sink<Frame> for WriteHalf {
     type Error = SeliumError;
 
@@ -51,7 +121,7 @@ impl ShutdownSink for WriteHalf {
 
 impl From<SendStream> for WriteHalf {
     fn from(send: SendStream) -> Self {
-        Self(FramedWrite::new(send, MessageCodec))
+        Self::new(send, BincodeConfig::default())
     }
 }
 
@@ -92,7 +162,7 @@ impl ShutdownStream for ReadHalf {
 
 impl From<RecvStream> for ReadHalf {
     fn from(recv: RecvStream) -> Self {
-        Self(FramedRead::new(recv, MessageCodec))
+        Self::new(recv, BincodeConfig::default())
     }
 }
 
@@ -111,16 +181,63 @@ impl DerefMut for ReadHalf {
 }
 
 impl BiStream {
+    /// Frames a bidirectional stream, using the provided [BincodeConfig] for frame payloads in
+    /// both directions. The peer must frame the stream with the same configuration.
+    pub fn new(send: SendStream, recv: RecvStream, config: BincodeConfig) -> Self {
+        Self {
+            write: WriteHalf::new(send, config),
+            read: ReadHalf::new(recv, config),
+        }
+    }
+
     pub async fn try_from_connection(connection: &Connection) -> Result<Self> {
-        let stream = connection
+        Self::try_from_connection_with_config(connection, BincodeConfig::default()).await
+    }
+
+    /// Opens a new stream on `connection`, framed with the provided [BincodeConfig], as per
+    /// [new](BiStream::new).
+    pub async fn try_from_connection_with_config(
+        connection: &Connection,
+        config: BincodeConfig,
+    ) -> Result<Self> {
+        let (send, recv) = connection
             .open_bi()
             .await
             .map_err(QuicError::ConnectionError)?;
-        Ok(Self::from(stream))
+        Ok(Self::new(send, recv, config))
     }
 
+    /// Splits the stream into halves that can be sent and received on independently.
+    ///
+    /// Each half frames its own direction of the stream, but both halves retain the header
+    /// recorded by the stream, so either half can report the stream's type and topic, regardless
+    /// of which direction the header was sent in.
     pub fn split(self) -> (WriteHalf, ReadHalf) {
-        (self.write, self.read)
+        let Self {
+            mut write,
+            mut read,
+        } = self;
+
+        write.0.encoder_mut().inherit_header(read.0.decoder());
+        read.0.decoder_mut().inherit_header(write.0.encoder());
+
+        (write, read)
+    }
+
+    /// Recombines halves previously returned by [split](BiStream::split).
+    ///
+    /// Any frames buffered by either half are retained, so the reunited stream picks up exactly
+    /// where the halves left off.
+    ///
+    /// # Errors
+    ///
+    /// Returns [ReuniteError] if the halves were split from different streams.
+    pub fn reunite(write: WriteHalf, read: ReadHalf) -> Result<Self, Box<ReuniteError>> {
+        if write.id() != read.id() {
+            return Err(Box::new(ReuniteError(write, read)));
+        }
+
+        Ok(Self { write, read })
     }
 
     pub fn get_recv_stream_id(&self) -> StreamId {
@@ -131,6 +248,53 @@ impl BiStream {
         self.write.id()
     }
 
+    /// The type of stream registered by the header frame sent or received on this stream, or
+    /// [None] if the stream hasn't been registered yet.
+    pub fn stream_type(&self) -> Option<StreamType> {
+        self.read
+            .0
+            .decoder()
+            .stream_type()
+            .or_else(|| self.write.0.encoder().stream_type())
+    }
+
+    /// The topic declared by the header frame sent or received on this stream, or [None] if no
+    /// header frame has been sent or received yet.
+    pub fn get_path(&self) -> Option<&TopicName> {
+        self.read
+            .0
+            .decoder()
+            .get_path()
+            .or_else(|| self.write.0.encoder().get_path())
+    }
+
+    /// Sets the priority of data sent on this stream, relative to the other streams on the same
+    /// connection.
+    ///
+    /// When several streams have data ready to send, data on higher priority streams is sent
+    /// first, while streams of equal priority share the connection fairly. Streams default to a
+    /// priority of 0, and negative priorities are lower than the default.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream has already been closed.
+    pub fn set_priority(&self, priority: i32) -> Result<()> {
+        self.write
+            .set_priority(priority)
+            .map_err(QuicError::UnknownStream)?;
+        Ok(())
+    }
+
+    /// The priority of data sent on this stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream has already been closed.
+    pub fn priority(&self) -> Result<i32> {
+        let priority = self.write.priority().map_err(QuicError::UnknownStream)?;
+        Ok(priority)
+    }
+
     pub fn read(&mut self) -> &mut RecvStream {
         &mut self.read
     }
@@ -147,10 +311,7 @@ impl BiStream {
 
 impl From<(SendStream, RecvStream)> for BiStream {
     fn from((send, recv): (SendStream, RecvStream)) -> Self {
-        Self {
-            write: send.into(),
-            read: recv.into(),
-        }
+        Self::new(send, recv, BincodeConfig::default())
     }
 }
 
@@ -201,3 +362,144 @@ impl ShutdownStream for BiStream {
             .stop(VarInt::from_u32(error_codes::SHUTDOWN_IN_PROGRESS));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessagePayload, RequestorPayload};
+    use bytes::Bytes;
+    use quinn::{ClientConfig, Endpoint, ServerConfig};
+
+    async fn connect() -> (Connection, Connection) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let key = rustls::PrivateKey(cert.serialize_private_key_der());
+        let cert = rustls::Certificate(cert.serialize_der().unwrap());
+
+        let server_config = ServerConfig::with_single_cert(vec![cert.clone()], key).unwrap();
+        let server = Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&cert).unwrap();
+        let mut client = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        client.set_default_client_config(ClientConfig::with_root_certificates(roots));
+
+        let connecting = client
+            .connect(server.local_addr().unwrap(), "localhost")
+            .unwrap();
+        let (client, server) =
+            tokio::join!(connecting, async { server.accept().await.unwrap().await });
+
+        (client.unwrap(), server.unwrap())
+    }
+
+    fn message(message: &'static str) -> Frame {
+        Frame::Message(MessagePayload {
+            headers: None,
+            message: Bytes::from_static(message.as_bytes()),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn interleaves_requests_and_replies_across_split_halves() {
+        let (client, server) = connect().await;
+        let topic = TopicName::_create_unchecked("namespace", "topic");
+
+        let mut stream = BiStream::try_from_connection(&client).await.unwrap();
+        stream
+            .send(Frame::RegisterRequestor(RequestorPayload {
+                topic: topic.clone(),
+            }))
+            .await
+            .unwrap();
+
+        // Echo each request back to the requestor
+        let replier = tokio::spawn(async move {
+            let mut stream = BiStream::from(server.accept_bi().await.unwrap());
+            let header = stream.next().await.unwrap().unwrap();
+            assert!(matches!(header, Frame::RegisterRequestor(_)));
+
+            let (mut write, mut read) = stream.split();
+            assert_eq!(write.stream_type(), Some(StreamType::Requestor));
+
+            while let Some(Ok(frame)) = read.next().await {
+                write.send(frame).await.unwrap();
+            }
+        });
+
+        let (mut write, mut read) = stream.split();
+        assert_eq!(read.stream_type(), Some(StreamType::Requestor));
+        assert_eq!(read.get_path(), Some(&topic));
+
+        for request in ["foo", "bar", "baz"] {
+            write.send(message(request)).await.unwrap();
+            assert_eq!(read.next().await.unwrap().unwrap(), message(request));
+        }
+
+        // Send a request on one half while waiting for its reply on the other
+        let (sent, received) = tokio::join!(write.send(message("qux")), read.next());
+        sent.unwrap();
+        assert_eq!(received.unwrap().unwrap(), message("qux"));
+
+        let mut stream = BiStream::reunite(write, read).unwrap();
+        assert_eq!(stream.stream_type(), Some(StreamType::Requestor));
+
+        stream.send(message("quux")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), message("quux"));
+
+        stream.finish().await.unwrap();
+        replier.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn frames_streams_with_provided_config() {
+        let (client, server) = connect().await;
+        let config = BincodeConfig::varint();
+
+        let mut stream = BiStream::try_from_connection_with_config(&client, config)
+            .await
+            .unwrap();
+        stream.send(message("foo")).await.unwrap();
+
+        let (send, recv) = server.accept_bi().await.unwrap();
+        let mut stream = BiStream::new(send, recv, config);
+        assert_eq!(stream.next().await.unwrap().unwrap(), message("foo"));
+    }
+
+    #[tokio::test]
+    async fn sets_priority_of_send_stream() {
+        let (client, _server) = connect().await;
+
+        let stream = BiStream::try_from_connection(&client).await.unwrap();
+        assert_eq!(stream.priority().unwrap(), 0);
+
+        stream.set_priority(-5).unwrap();
+        assert_eq!(stream.priority().unwrap(), -5);
+
+        let (write, _) = stream.split();
+        assert_eq!(write.priority().unwrap(), -5);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_reunite_halves_of_different_streams() {
+        let (client, _server) = connect().await;
+
+        let (write, _) = BiStream::try_from_connection(&client)
+            .await
+            .unwrap()
+            .split();
+        let (_, read) = BiStream::try_from_connection(&client)
+            .await
+            .unwrap()
+            .split();
+
+        let Err(err) = BiStream::reunite(write, read) else {
+            panic!("Reunited halves of different streams");
+        };
+
+        let ReuniteError(write, read) = *err;
+        assert_ne!(write.id(), read.id());
+    }
+}
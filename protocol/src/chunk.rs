@@ -0,0 +1,298 @@
+use crate::MAX_MESSAGE_SIZE;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use selium_std::errors::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+
+/// Allows for the fields of a [ChunkPayload](crate::ChunkPayload) other than the chunk itself,
+/// which are far smaller than this under any [BincodeConfig](selium_std::encoding::BincodeConfig).
+const CHUNK_PAYLOAD_OVERHEAD: usize = 64;
+
+/// The maximum number of bytes of a message carried by a single chunk, so that each chunk fits
+/// within a single frame.
+pub const MAX_CHUNK_SIZE: usize = MAX_MESSAGE_SIZE as usize - CHUNK_PAYLOAD_OVERHEAD;
+
+/// The default limit on the number of bytes buffered by a [ChunkAssembler] - 64MiB.
+pub const DEFAULT_MAX_REASSEMBLY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Identifies a chunk of a message that was too large to be sent in a single frame.
+///
+/// Chunks of different messages may be interleaved when multiple publishers write to the same
+/// topic, so each chunk records the message it belongs to, alongside its position in that
+/// message.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChunkHeader {
+    /// Shared by every chunk of the same message.
+    pub message_id: u64,
+    /// The position of the chunk within the message, starting from 0.
+    pub index: u32,
+    /// The number of chunks that the message was split into.
+    pub total: u32,
+}
+
+impl ChunkHeader {
+    /// The size of an encoded header, in bytes.
+    pub const SIZE: usize = size_of::<u64>() + size_of::<u32>() + size_of::<u32>();
+
+    pub fn encode(&self, dst: &mut impl BufMut) {
+        dst.put_u64(self.message_id);
+        dst.put_u32(self.index);
+        dst.put_u32(self.total);
+    }
+
+    /// Decodes a header from the start of `src`, or returns [None] if `src` is too short.
+    pub fn decode(src: &mut impl Buf) -> Option<Self> {
+        if src.remaining() < Self::SIZE {
+            return None;
+        }
+
+        Some(Self {
+            message_id: src.get_u64(),
+            index: src.get_u32(),
+            total: src.get_u32(),
+        })
+    }
+}
+
+/// Splits `message` into chunks of at most [MAX_CHUNK_SIZE] bytes, in the order that they must
+/// be sent.
+pub fn split_message(message_id: u64, message: Bytes) -> Vec<(ChunkHeader, Bytes)> {
+    let total = message.len().div_ceil(MAX_CHUNK_SIZE) as u32;
+
+    (0..total)
+        .map(|index| {
+            let start = index as usize * MAX_CHUNK_SIZE;
+            let end = (start + MAX_CHUNK_SIZE).min(message.len());
+            let header = ChunkHeader {
+                message_id,
+                index,
+                total,
+            };
+
+            (header, message.slice(start..end))
+        })
+        .collect()
+}
+
+/// Reassembles chunked messages, returning each message once its final chunk has been received.
+///
+/// The chunks of each message must be received in order, though chunks of different messages
+/// may be interleaved. A chunk that doesn't begin a message is discarded if the start of its
+/// message was never received, e.g. when reading from an offset partway through the message.
+///
+/// To prevent an unbounded amount of memory from being consumed by incomplete messages, the
+/// number of buffered bytes is limited. Once the limit is reached, the oldest incomplete
+/// messages are discarded to make room for newer ones.
+#[derive(Debug)]
+pub struct ChunkAssembler {
+    pending: HashMap<u64, PendingMessage>,
+    // The order in which incomplete messages were started, so that the oldest can be discarded
+    order: VecDeque<u64>,
+    buffered_bytes: usize,
+    max_buffered_bytes: usize,
+}
+
+#[derive(Debug)]
+struct PendingMessage {
+    total: u32,
+    received: u32,
+    bytes: BytesMut,
+}
+
+impl ChunkAssembler {
+    pub fn new(max_buffered_bytes: usize) -> Self {
+        Self {
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+            buffered_bytes: 0,
+            max_buffered_bytes,
+        }
+    }
+
+    /// Adds a chunk to its message, returning the reassembled message if this was its final
+    /// chunk.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [ProtocolError::ChunkOutOfSequence] if the chunk doesn't follow the previous
+    ///   chunk received for its message, in which case the message is discarded.
+    /// - Returns [ProtocolError::ChunkedMessageTooLarge] if the message alone exceeds the
+    ///   buffer limit, in which case the message is discarded.
+    pub fn push(&mut self, header: ChunkHeader, chunk: Bytes) -> Result<Option<Bytes>> {
+        let id = header.message_id;
+
+        if header.index == 0 && header.total == 1 {
+            return Ok(Some(chunk));
+        }
+
+        let expected = match self.pending.get(&id) {
+            Some(message) if message.total == header.total => message.received,
+            Some(_) => u32::MAX,
+            None if header.index == 0 => 0,
+            // The start of the message was never received, so it can't be reassembled
+            None => return Ok(None),
+        };
+
+        if header.index != expected || header.index >= header.total {
+            self.discard(id);
+            Err(ProtocolError::ChunkOutOfSequence(id, header.index))?;
+        }
+
+        let buffered = self
+            .pending
+            .get(&id)
+            .map_or(0, |message| message.bytes.len());
+
+        if buffered + chunk.len() > self.max_buffered_bytes {
+            self.discard(id);
+            Err(ProtocolError::ChunkedMessageTooLarge(
+                id,
+                self.max_buffered_bytes,
+            ))?;
+        }
+
+        // Make room by discarding the oldest incomplete messages
+        while self.buffered_bytes + chunk.len() > self.max_buffered_bytes {
+            match self.order.iter().copied().find(|&oldest| oldest != id) {
+                Some(oldest) => self.discard(oldest),
+                None => break,
+            }
+        }
+
+        let message = self.pending.entry(id).or_insert_with(|| {
+            self.order.push_back(id);
+
+            PendingMessage {
+                total: header.total,
+                received: 0,
+                bytes: BytesMut::new(),
+            }
+        });
+
+        message.bytes.extend_from_slice(&chunk);
+        message.received += 1;
+        self.buffered_bytes += chunk.len();
+
+        if message.received < message.total {
+            return Ok(None);
+        }
+
+        let message = self.pending.remove(&id).unwrap();
+        self.order.retain(|&pending| pending != id);
+        self.buffered_bytes -= message.bytes.len();
+
+        Ok(Some(message.bytes.freeze()))
+    }
+
+    fn discard(&mut self, id: u64) {
+        if let Some(message) = self.pending.remove(&id) {
+            self.order.retain(|&pending| pending != id);
+            self.buffered_bytes -= message.bytes.len();
+        }
+    }
+}
+
+impl Default for ChunkAssembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_REASSEMBLY_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(message_id: u64, message: &[u8]) -> Vec<(ChunkHeader, Bytes)> {
+        split_message(message_id, Bytes::copy_from_slice(message))
+    }
+
+    #[test]
+    fn splits_message_into_bounded_chunks() {
+        let message = vec![7u8; MAX_CHUNK_SIZE * 2 + 1];
+        let chunks = chunks(1, &message);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|(header, _)| header.total == 3));
+        assert_eq!(chunks[0].1.len(), MAX_CHUNK_SIZE);
+        assert_eq!(chunks[2].1.len(), 1);
+    }
+
+    #[test]
+    fn round_trips_chunk_header() {
+        let header = ChunkHeader {
+            message_id: 42,
+            index: 1,
+            total: 3,
+        };
+        let mut bytes = BytesMut::new();
+        header.encode(&mut bytes);
+
+        assert_eq!(bytes.len(), ChunkHeader::SIZE);
+        assert_eq!(ChunkHeader::decode(&mut bytes.clone()), Some(header));
+        assert_eq!(ChunkHeader::decode(&mut &bytes[1..]), None);
+    }
+
+    #[test]
+    fn reassembles_interleaved_messages() {
+        let first = vec![1u8; MAX_CHUNK_SIZE + 10];
+        let second = vec![2u8; MAX_CHUNK_SIZE + 20];
+        let mut assembler = ChunkAssembler::default();
+
+        let mut first_chunks = chunks(1, &first).into_iter();
+        let mut second_chunks = chunks(2, &second).into_iter();
+
+        let (header, chunk) = first_chunks.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+        let (header, chunk) = second_chunks.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+        let (header, chunk) = second_chunks.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap().unwrap(), second);
+        let (header, chunk) = first_chunks.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap().unwrap(), first);
+    }
+
+    #[test]
+    fn rejects_chunks_out_of_sequence() {
+        let message = vec![0u8; MAX_CHUNK_SIZE * 2 + 1];
+        let chunks = chunks(1, &message);
+        let mut assembler = ChunkAssembler::default();
+
+        // Chunks without the start of their message are discarded
+        let (header, chunk) = chunks[1].clone();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+
+        let (header, chunk) = chunks[0].clone();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+        let (header, chunk) = chunks[2].clone();
+        let result = assembler.push(header, chunk);
+
+        assert!(matches!(
+            result,
+            Err(selium_std::errors::SeliumError::Protocol(
+                ProtocolError::ChunkOutOfSequence(1, 2)
+            ))
+        ));
+    }
+
+    #[test]
+    fn bounds_buffered_bytes() {
+        let message = vec![0u8; MAX_CHUNK_SIZE + 1];
+        let mut assembler = ChunkAssembler::new(MAX_CHUNK_SIZE);
+
+        let mut stale = chunks(1, &message).into_iter();
+        let (header, chunk) = stale.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+
+        // Starting a new message discards the stale message to make room
+        let mut fresh = chunks(2, &message).into_iter();
+        let (header, chunk) = fresh.next().unwrap();
+        assert_eq!(assembler.push(header, chunk).unwrap(), None);
+        assert_eq!(assembler.buffered_bytes, MAX_CHUNK_SIZE);
+
+        // The fresh message alone exceeds the limit
+        let (header, chunk) = fresh.next().unwrap();
+        assert!(assembler.push(header, chunk).is_err());
+        assert_eq!(assembler.buffered_bytes, 0);
+    }
+}
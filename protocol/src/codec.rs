@@ -1,31 +1,85 @@
-use crate::Frame;
+use crate::{Frame, StreamType, TopicName};
 use anyhow::Result;
 use bytes::{Buf, BufMut, BytesMut};
+use selium_std::encoding::BincodeConfig;
 use selium_std::errors::{ProtocolError, SeliumError};
 use std::mem::size_of;
 use tokio_util::codec::{Decoder, Encoder};
 
-const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+/// The maximum size of a single frame's payload, in bytes.
+pub const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 const LEN_MARKER_SIZE: usize = size_of::<u64>();
 const TYPE_MARKER_SIZE: usize = size_of::<u8>();
 const RESERVED_SIZE: usize = LEN_MARKER_SIZE + TYPE_MARKER_SIZE;
 
+/// Encodes and decodes [Frame]s sent between the client and server.
+///
+/// Frame payloads are serialized with [bincode], using the provided [BincodeConfig]. The
+/// configuration is part of the wire format, so the client and server must use the same
+/// configuration to communicate. Defaults to fixed-width, little-endian integers.
+///
+/// The codec also records the [StreamType] and topic declared by the first header frame that
+/// passes through it, so that the stream can be routed without re-parsing the header.
 #[derive(Debug, Default)]
-pub struct MessageCodec;
+pub struct MessageCodec {
+    config: BincodeConfig,
+    stream_type: Option<StreamType>,
+    // Boxed to avoid bloating every stream, as the path is seldom accessed
+    path: Option<Box<TopicName>>,
+}
+
+impl MessageCodec {
+    pub fn new(config: BincodeConfig) -> Self {
+        Self {
+            config,
+            stream_type: None,
+            path: None,
+        }
+    }
+
+    /// The type of stream registered by the header frame, or [None] if no registration frame
+    /// has been encoded or decoded.
+    pub fn stream_type(&self) -> Option<StreamType> {
+        self.stream_type
+    }
+
+    /// The topic declared by the header frame, or [None] if no header frame has been encoded or
+    /// decoded.
+    pub fn get_path(&self) -> Option<&TopicName> {
+        self.path.as_deref()
+    }
+
+    /// Adopts the header recorded by `other`, unless this codec has already recorded a header,
+    /// so that both halves of a split stream agree on how the stream was registered.
+    pub(crate) fn inherit_header(&mut self, other: &Self) {
+        if self.path.is_none() {
+            self.path = other.path.clone();
+            self.stream_type = other.stream_type;
+        }
+    }
+
+    fn record_header(&mut self, frame: &Frame) {
+        if self.path.is_none() {
+            self.path = frame.get_topic().cloned().map(Box::new);
+            self.stream_type = StreamType::from_frame(frame);
+        }
+    }
+}
 
 impl Encoder<Frame> for MessageCodec {
     type Error = SeliumError;
 
     fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let length = item.get_length()?;
+        let length = item.get_length(&self.config)?;
         validate_payload_length(length)?;
 
         let message_type = item.get_type();
+        self.record_header(&item);
 
         dst.reserve(RESERVED_SIZE + length as usize);
         dst.put_u64(length);
         dst.put_u8(message_type);
-        item.write_to_bytes(dst)?;
+        item.write_to_bytes(dst, &self.config)?;
 
         Ok(())
     }
@@ -44,12 +98,20 @@ impl Decoder for MessageCodec {
         length_bytes.copy_from_slice(&src[..LEN_MARKER_SIZE]);
 
         let length = u64::from_be_bytes(length_bytes);
-        validate_payload_length(length)?;
 
-        let bytes_read = src.len() - RESERVED_SIZE;
+        // Discard the buffered bytes, as there's no way to resynchronise with the stream once the
+        // length marker can't be trusted
+        if let Err(e) = validate_payload_length(length) {
+            src.clear();
+            return Err(e);
+        }
 
-        if bytes_read < length as usize {
-            src.reserve(bytes_read);
+        let frame_size = RESERVED_SIZE + length as usize;
+
+        // Only reserve enough space for the remainder of this frame, which is bounded by
+        // MAX_MESSAGE_SIZE
+        if src.len() < frame_size {
+            src.reserve(frame_size - src.len());
             return Ok(None);
         }
 
@@ -57,7 +119,8 @@ impl Decoder for MessageCodec {
 
         let message_type = src.get_u8();
         let bytes = src.split_to(length as usize);
-        let frame = Frame::try_from((message_type, bytes))?;
+        let frame = Frame::from_bytes(message_type, bytes, &self.config)?;
+        self.record_header(&frame);
 
         Ok(Some(frame))
     }
@@ -79,8 +142,10 @@ mod tests {
     use crate::error_codes::UNKNOWN_ERROR;
     use crate::utils::encode_message_batch;
     use crate::{
-        BatchPayload, ErrorPayload, MessagePayload, Offset, Operation, PublisherPayload,
-        SubscriberPayload, TopicName,
+        AckPayload, BatchPayload, CancelPayload, ChunkHeader, ChunkPayload, ErrorPayload,
+        GroupOffsetPayload, MessagePayload, Offset, OffsetsPayload, Operation, PublisherPayload,
+        QueryOffsetsPayload, ReservationPayload, ServerInfoPayload, Signal, SubscriberPayload,
+        TopicEvent, TopicName, TruncateTopicPayload, MAX_CHUNK_SIZE,
     };
     use bytes::Bytes;
 
@@ -97,11 +162,15 @@ mod tests {
                 Operation::Filter("third/module.wasm".into()),
             ],
             offset: Offset::default(),
+            compression: None,
+            filter_headers: None,
+            group: None,
+            prioritise: false,
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x92\x01\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\x01\0\0\0\0\0\0\0\0\0\0\0");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x96\x01\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -120,11 +189,12 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            compression: None,
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x86\0\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x87\0\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\0");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -139,11 +209,14 @@ mod tests {
         let frame = Frame::Message(MessagePayload {
             headers: Some(h),
             message: Bytes::from("Hello world"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from_static(b"\0\0\0\0\0\0\x006\x04\x01\x01\0\0\0\0\0\0\0\x04\0\0\0\0\0\0\0test\x06\0\0\0\0\0\0\0header\x0b\0\0\0\0\0\0\0Hello world");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\x009\x04\x01\x01\0\0\0\0\0\0\0\x04\0\0\0\0\0\0\0test\x06\0\0\0\0\0\0\0header\x0b\0\0\0\0\0\0\0Hello world\0\0\0");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -155,11 +228,14 @@ mod tests {
         let frame = Frame::Message(MessagePayload {
             headers: None,
             message: Bytes::from("Hello world"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from("\0\0\0\0\0\0\0\x14\x04\0\x0b\0\0\0\0\0\0\0Hello world");
+        let expected = Bytes::from("\0\0\0\0\0\0\0\x17\x04\0\x0b\0\0\0\0\0\0\0Hello world\0\0\0");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -177,12 +253,16 @@ mod tests {
         let payload = BatchPayload {
             message: batch,
             size: 3,
+            ttl: None,
+            offset: None,
+            first_offset: None,
+            sequence_id: None,
         };
 
         let frame = Frame::BatchMessage(payload);
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0T\x05H\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\rFirst message\0\0\0\0\0\0\0\x0eSecond message\0\0\0\0\0\0\0\rThird message\x03\0\0\0");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0X\x05H\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\rFirst message\0\0\0\0\0\0\0\x0eSecond message\0\0\0\0\0\0\0\rThird message\x03\0\0\0\0\0\0\0");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -196,7 +276,7 @@ mod tests {
             message: "This is an error".into(),
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
         let expected =
             Bytes::from_static(b"\0\0\0\0\0\0\0\x1c\x06\0\0\0\0\x10\0\0\0\0\0\0\0This is an error");
@@ -210,7 +290,7 @@ mod tests {
     fn encodes_ok_frame() {
         let frame = Frame::Ok;
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
         let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\0\x07");
 
@@ -219,6 +299,34 @@ mod tests {
         assert_eq!(buffer, expected);
     }
 
+    #[test]
+    fn encodes_health_frame() {
+        let frame = Frame::Health;
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\0\x0C");
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+        assert_eq!(codec.stream_type(), None);
+        assert_eq!(codec.get_path(), None);
+    }
+
+    #[test]
+    fn encodes_ack_frame() {
+        let frame = Frame::Ack(AckPayload { offset: 42 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x08\x08*\0\0\0\0\0\0\0");
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
     #[test]
     fn fails_to_encode_if_payload_too_large() {
         const PAYLOAD: [u8; MAX_MESSAGE_SIZE as usize + 1] = [0u8; MAX_MESSAGE_SIZE as usize + 1];
@@ -226,8 +334,11 @@ mod tests {
         let frame = Frame::Message(MessagePayload {
             headers: None,
             message: Bytes::from_static(&PAYLOAD),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         });
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
 
         assert!(codec.encode(frame, &mut buffer).is_err());
@@ -235,8 +346,8 @@ mod tests {
 
     #[test]
     fn decodes_register_subscriber_frame() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x92\x01\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\x01\0\0\0\0\0\0\0\0\0\0\0"[..]);
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x96\x01\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0"[..]);
         let topic = TopicName::try_from("/namespace/topic").unwrap();
 
         let expected = Frame::RegisterSubscriber(SubscriberPayload {
@@ -248,6 +359,10 @@ mod tests {
                 Operation::Filter("third/module.wasm".into()),
             ],
             offset: Offset::default(),
+            compression: None,
+            filter_headers: None,
+            group: None,
+            prioritise: false,
         });
 
         let result = codec.decode(&mut src).unwrap().unwrap();
@@ -257,8 +372,8 @@ mod tests {
 
     #[test]
     fn decodes_register_publisher_frame() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x86\0\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm"[..]);
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x87\0\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\0"[..]);
         let topic = TopicName::try_from("/namespace/topic").unwrap();
 
         let expected = Frame::RegisterPublisher(PublisherPayload {
@@ -269,6 +384,7 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            compression: None,
         });
 
         let result = codec.decode(&mut src).unwrap().unwrap();
@@ -278,8 +394,8 @@ mod tests {
 
     #[test]
     fn decodes_message_frame_with_header() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from("\0\0\0\0\0\0\x006\x04\x01\x01\0\0\0\0\0\0\0\x04\0\0\0\0\0\0\0test\x06\0\0\0\0\0\0\0header\x0b\0\0\0\0\0\0\0Hello world");
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\x009\x04\x01\x01\0\0\0\0\0\0\0\x04\0\0\0\0\0\0\0test\x06\0\0\0\0\0\0\0header\x0b\0\0\0\0\0\0\0Hello world\0\0\0");
 
         let mut h = HashMap::new();
         h.insert("test".to_owned(), "header".to_owned());
@@ -287,6 +403,9 @@ mod tests {
         let expected = Frame::Message(MessagePayload {
             headers: Some(h),
             message: Bytes::from("Hello world"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         });
         let result = codec.decode(&mut src).unwrap().unwrap();
 
@@ -295,12 +414,15 @@ mod tests {
 
     #[test]
     fn decodes_message_frame_without_header() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x14\x04\0\x0b\0\0\0\0\0\0\0Hello world");
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x17\x04\0\x0b\0\0\0\0\0\0\0Hello world\0\0\0");
 
         let expected = Frame::Message(MessagePayload {
             headers: None,
             message: Bytes::from("Hello world"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
         });
         let result = codec.decode(&mut src).unwrap().unwrap();
 
@@ -309,8 +431,8 @@ mod tests {
 
     #[test]
     fn decodes_batch_message_frame() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from("\0\0\0\0\0\0\0T\x05H\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\rFirst message\0\0\0\0\0\0\0\x0eSecond message\0\0\0\0\0\0\0\rThird message\x03\0\0\0");
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0X\x05H\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\rFirst message\0\0\0\0\0\0\0\x0eSecond message\0\0\0\0\0\0\0\rThird message\x03\0\0\0\0\0\0\0");
 
         let batch = encode_message_batch(vec![
             Bytes::from("First message"),
@@ -321,6 +443,10 @@ mod tests {
         let expected = Frame::BatchMessage(BatchPayload {
             message: batch,
             size: 3,
+            ttl: None,
+            offset: None,
+            first_offset: None,
+            sequence_id: None,
         });
         let result = codec.decode(&mut src).unwrap().unwrap();
 
@@ -329,7 +455,7 @@ mod tests {
 
     #[test]
     fn decodes_error_frame() {
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut src =
             BytesMut::from("\0\0\0\0\0\0\0\x1c\x06\0\0\0\0\x10\0\0\0\0\0\0\0This is an error");
 
@@ -345,7 +471,7 @@ mod tests {
 
     #[test]
     fn decodes_ok_frame() {
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut src = BytesMut::from("\0\0\0\0\0\0\0\0\x07");
 
         let expected = Frame::Ok;
@@ -355,13 +481,311 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn decodes_health_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\0\x0C");
+
+        let expected = Frame::Health;
+
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn decodes_ack_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x08\x08*\0\0\0\0\0\0\0");
+
+        let expected = Frame::Ack(AckPayload { offset: 42 });
+
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn decodes_offsets_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x10\x0a\x02\0\0\0\0\0\0\0*\0\0\0\0\0\0\0");
+
+        let expected = Frame::Offsets(OffsetsPayload { start: 2, end: 42 });
+
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn encodes_cancel_frame() {
+        let mut headers = HashMap::new();
+        headers.insert("req_id".to_owned(), "7".to_owned());
+
+        let frame = Frame::Cancel(CancelPayload { headers });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(
+            b"\0\0\0\0\0\0\0\x1f\x0b\x01\0\0\0\0\0\0\0\x06\0\0\0\0\0\0\0req_id\x01\0\0\0\0\0\0\x007",
+        );
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn decodes_cancel_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(
+            "\0\0\0\0\0\0\0\x1f\x0b\x01\0\0\0\0\0\0\0\x06\0\0\0\0\0\0\0req_id\x01\0\0\0\0\0\0\x007",
+        );
+
+        let mut headers = HashMap::new();
+        headers.insert("req_id".to_owned(), "7".to_owned());
+
+        let expected = Frame::Cancel(CancelPayload { headers });
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn encodes_commit_frame() {
+        let frame = Frame::Commit(ReservationPayload { offset: 42 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x08\x0f*\0\0\0\0\0\0\0");
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn decodes_abort_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x08\x10*\0\0\0\0\0\0\0");
+
+        let expected = Frame::Abort(ReservationPayload { offset: 42 });
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn round_trips_frame_with_provided_config() {
+        let mut codec = MessageCodec::new(BincodeConfig::varint());
+        let frame = Frame::Ack(AckPayload { offset: 42 });
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        assert_eq!(buffer, BytesMut::from("\0\0\0\0\0\0\0\x01\x08*"));
+
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_signal_frame() {
+        let mut codec = MessageCodec::default();
+        let frame = Frame::Signal(Signal::SubscriberLagging { lag: 42 });
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        assert_eq!(buffer[8], 0x12);
+
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_commit_group_offset_frame() {
+        let mut codec = MessageCodec::default();
+        let frame = Frame::CommitGroupOffset(GroupOffsetPayload { offset: 42 });
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        assert_eq!(&buffer[..], b"\0\0\0\0\0\0\0\x08\x17*\0\0\0\0\0\0\0");
+
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_server_info_frames() {
+        let mut codec = MessageCodec::default();
+        let query = Frame::QueryServerInfo;
+        let info = Frame::ServerInfo(ServerInfoPayload {
+            version: "0.5.0".into(),
+            git_hash: Some("abc1234".into()),
+            uptime_secs: 42,
+            max_frame_size: MAX_MESSAGE_SIZE,
+            max_message_bytes: None,
+            topic_count: 3,
+        });
+        let mut buffer = BytesMut::new();
+
+        codec.encode(query.clone(), &mut buffer).unwrap();
+        assert_eq!(&buffer[..], b"\0\0\0\0\0\0\0\0\x13");
+        codec.encode(info.clone(), &mut buffer).unwrap();
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), query);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), info);
+        assert_eq!(codec.stream_type(), None);
+        assert_eq!(codec.get_path(), None);
+    }
+
+    #[test]
+    fn round_trips_topic_watch_frames() {
+        let topic = TopicName::try_from("/namespace/topic").unwrap();
+        let mut codec = MessageCodec::default();
+        let watch = Frame::WatchTopics;
+        let created = Frame::TopicEvent(TopicEvent::Created(topic.clone()));
+        let removed = Frame::TopicEvent(TopicEvent::Removed(topic));
+        let mut buffer = BytesMut::new();
+
+        codec.encode(watch.clone(), &mut buffer).unwrap();
+        assert_eq!(&buffer[..], b"\0\0\0\0\0\0\0\0\x18");
+        codec.encode(created.clone(), &mut buffer).unwrap();
+        codec.encode(removed.clone(), &mut buffer).unwrap();
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), watch);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), created);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), removed);
+        assert_eq!(codec.stream_type(), None);
+        assert_eq!(codec.get_path(), None);
+    }
+
+    #[test]
+    fn round_trips_truncate_topic_frame() {
+        let topic = TopicName::try_from("/namespace/topic").unwrap();
+        let frame = Frame::TruncateTopic(TruncateTopicPayload {
+            topic: topic.clone(),
+            offset: 42,
+        });
+        let mut encoder = MessageCodec::default();
+        let mut decoder = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        encoder.encode(frame.clone(), &mut buffer).unwrap();
+
+        assert_eq!(decoder.decode(&mut buffer).unwrap().unwrap(), frame);
+        assert_eq!(decoder.stream_type(), None);
+        assert_eq!(decoder.get_path(), Some(&topic));
+    }
+
+    #[test]
+    fn round_trips_largest_message_chunk() {
+        let frame = Frame::MessageChunk(ChunkPayload {
+            header: ChunkHeader {
+                message_id: u64::MAX,
+                index: u32::MAX - 1,
+                total: u32::MAX,
+            },
+            message: Bytes::from(vec![0u8; MAX_CHUNK_SIZE]),
+            ttl: Some(u64::MAX),
+            offset: Some(u64::MAX),
+        });
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        // A chunk carrying the maximum number of bytes must still fit within a single frame
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), frame);
+    }
+
+    #[test]
+    fn fails_to_decode_frame_with_mismatched_config() {
+        let mut encoder = MessageCodec::new(BincodeConfig::varint());
+        let mut decoder = MessageCodec::new(BincodeConfig::fixint());
+        let frame = Frame::Ack(AckPayload { offset: 42 });
+        let mut buffer = BytesMut::new();
+
+        encoder.encode(frame, &mut buffer).unwrap();
+
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
+
     #[test]
     fn fails_to_decode_if_payload_too_large() {
         const PAYLOAD: [u8; MAX_MESSAGE_SIZE as usize + 1] = [0u8; MAX_MESSAGE_SIZE as usize + 1];
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut src = BytesMut::from(&PAYLOAD[..]);
 
         assert!(codec.decode(&mut src).is_err());
     }
+
+    #[test]
+    fn fails_to_decode_partial_frame_claiming_huge_length() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\xff\xff\xff\xff\xff\xff\xff\xff\x04\0\x0b"[..]);
+        let capacity = src.capacity();
+
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(SeliumError::Protocol(ProtocolError::PayloadTooLarge(
+                u64::MAX,
+                MAX_MESSAGE_SIZE
+            )))
+        ));
+        assert!(src.is_empty());
+        assert_eq!(src.capacity(), capacity);
+    }
+
+    #[test]
+    fn reserves_remainder_of_partial_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x17\x04\0\x0b");
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(src.capacity() >= RESERVED_SIZE + 0x17);
+    }
+
+    #[test]
+    fn records_header_of_decoded_frame() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x87\0\t\0\0\0\0\0\0\0namespace\x05\0\0\0\0\0\0\0topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm\0"[..]);
+        let topic = TopicName::try_from("/namespace/topic").unwrap();
+
+        assert_eq!(codec.stream_type(), None);
+        assert_eq!(codec.get_path(), None);
+
+        codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(codec.stream_type(), Some(StreamType::Publisher));
+        assert_eq!(codec.get_path(), Some(&topic));
+    }
+
+    #[test]
+    fn records_header_of_encoded_frame() {
+        let topic = TopicName::try_from("/namespace/topic").unwrap();
+        let other_topic = TopicName::try_from("/namespace/other").unwrap();
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        let frame = Frame::RegisterSubscriber(SubscriberPayload {
+            topic: topic.clone(),
+            retention_policy: 5,
+            operations: vec![],
+            offset: Offset::default(),
+            compression: None,
+            filter_headers: None,
+            group: None,
+            prioritise: false,
+        });
+        codec.encode(frame, &mut buffer).unwrap();
+
+        // Only the first header frame is recorded
+        let frame = Frame::QueryOffsets(QueryOffsetsPayload { topic: other_topic });
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(codec.stream_type(), Some(StreamType::Subscriber));
+        assert_eq!(codec.get_path(), Some(&topic));
+    }
 }
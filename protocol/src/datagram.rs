@@ -0,0 +1,52 @@
+use crate::TopicName;
+use bytes::Bytes;
+use selium_std::encoding::BincodeConfig;
+use selium_std::errors::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+
+/// A message sent to a topic as a QUIC datagram.
+///
+/// Datagrams bypass the topic's log entirely, so they aren't retained, acknowledged or ordered,
+/// and may be dropped at any point between the publisher and a subscriber. The server forwards
+/// each datagram to the connections that have subscribed to datagrams on its topic.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Datagram {
+    pub topic: TopicName,
+    pub message: Bytes,
+}
+
+impl Datagram {
+    pub fn new(topic: TopicName, message: Bytes) -> Self {
+        Self { topic, message }
+    }
+
+    pub fn to_bytes(&self, config: &BincodeConfig) -> Result<Bytes> {
+        let bytes = config.serialize(self).map_err(ProtocolError::SerdeError)?;
+        Ok(bytes.into())
+    }
+
+    pub fn from_bytes(bytes: &[u8], config: &BincodeConfig) -> Result<Self> {
+        let datagram = config
+            .deserialize(bytes)
+            .map_err(ProtocolError::SerdeError)?;
+        Ok(datagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_datagram() {
+        let config = BincodeConfig::default();
+        let topic = TopicName::_create_unchecked("namespace", "topic");
+        let datagram = Datagram::new(topic, Bytes::from_static(b"hello"));
+
+        let bytes = datagram.to_bytes(&config).unwrap();
+        let decoded = Datagram::from_bytes(&bytes, &config).unwrap();
+
+        assert_eq!(decoded, datagram);
+        assert!(Datagram::from_bytes(&bytes[..bytes.len() - 1], &config).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub use selium_std::errors::ErrorCode;
+
 pub const UNKNOWN_ERROR: u32 = 0x0;
 pub const SHUTDOWN_IN_PROGRESS: u32 = 0x1;
 pub const SHUTDOWN: u32 = 0x2;
@@ -5,3 +7,63 @@ pub const STREAM_CLOSED_PREMATURELY: u32 = 0x3;
 pub const INVALID_TOPIC_NAME: u32 = 0x4;
 pub const REPLIER_ALREADY_BOUND: u32 = 0x5;
 pub const CLOUD_AUTH_FAILED: u32 = 0x6;
+pub const TOPIC_NOT_FOUND: u32 = 0x7;
+pub const UNAUTHORIZED: u32 = 0x8;
+pub const COMPRESSION_MISMATCH: u32 = 0x9;
+pub const MESSAGE_TOO_LARGE: u32 = 0xA;
+pub const RESERVATION_NOT_FOUND: u32 = 0xB;
+pub const DATAGRAMS_UNSUPPORTED: u32 = 0xC;
+pub const UNKNOWN_SERVER_NAME: u32 = 0xD;
+pub const LOG_WRITE_FAILED: u32 = 0xE;
+pub const SCHEMA_VIOLATION: u32 = 0xF;
+pub const ONE_WAY_STREAM_UNSUPPORTED: u32 = 0x10;
+pub const TOPIC_CLOSED: u32 = 0x11;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_known_codes_through_enum() {
+        let codes = [
+            (SHUTDOWN_IN_PROGRESS, ErrorCode::ShutdownInProgress),
+            (SHUTDOWN, ErrorCode::Shutdown),
+            (
+                STREAM_CLOSED_PREMATURELY,
+                ErrorCode::StreamClosedPrematurely,
+            ),
+            (INVALID_TOPIC_NAME, ErrorCode::InvalidTopicName),
+            (REPLIER_ALREADY_BOUND, ErrorCode::ReplierAlreadyBound),
+            (CLOUD_AUTH_FAILED, ErrorCode::CloudAuthFailed),
+            (TOPIC_NOT_FOUND, ErrorCode::TopicNotFound),
+            (UNAUTHORIZED, ErrorCode::Unauthorized),
+            (COMPRESSION_MISMATCH, ErrorCode::CompressionMismatch),
+            (MESSAGE_TOO_LARGE, ErrorCode::MessageTooLarge),
+            (RESERVATION_NOT_FOUND, ErrorCode::ReservationNotFound),
+            (DATAGRAMS_UNSUPPORTED, ErrorCode::DatagramsUnsupported),
+            (UNKNOWN_SERVER_NAME, ErrorCode::UnknownServerName),
+            (LOG_WRITE_FAILED, ErrorCode::LogWriteFailed),
+            (SCHEMA_VIOLATION, ErrorCode::SchemaViolation),
+            (
+                ONE_WAY_STREAM_UNSUPPORTED,
+                ErrorCode::OneWayStreamUnsupported,
+            ),
+            (TOPIC_CLOSED, ErrorCode::TopicClosed),
+        ];
+
+        for (code, expected) in codes {
+            let error_code = ErrorCode::from(code);
+            assert_eq!(error_code, expected);
+            assert_eq!(u32::from(error_code), code);
+        }
+    }
+
+    #[test]
+    fn round_trips_unknown_codes_through_enum() {
+        for code in [UNKNOWN_ERROR, 0x100] {
+            let error_code = ErrorCode::from(code);
+            assert_eq!(error_code, ErrorCode::Unknown(code));
+            assert_eq!(u32::from(error_code), code);
+        }
+    }
+}
@@ -1,11 +1,38 @@
-use crate::{Offset, Operation, TopicName};
+use crate::{ChunkHeader, HeaderFilter, Offset, Operation, Signal, TopicName};
 use bytes::{BufMut, Bytes, BytesMut};
+use selium_std::encoding::BincodeConfig;
 use selium_std::errors::{ProtocolError, Result, SeliumError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 type Headers = Option<HashMap<String, String>>;
 
+/// The message header carrying the time at which a producer created a message, as a UNIX
+/// timestamp in milliseconds.
+pub const EVENT_TIME_HEADER: &str = "selium-event-time";
+
+/// The prefix of message headers reserved for Selium's own use. Publishers refuse to send
+/// messages with user headers under this prefix, so they can't be mistaken for Selium's headers.
+pub const RESERVED_HEADER_PREFIX: &str = "selium-";
+
+/// The message header marking whether a message was compressed by a publisher using compression.
+/// Messages from such publishers are compressed unless this header is set to `false`.
+pub const COMPRESSED_HEADER: &str = "selium-compressed";
+
+/// The message header carrying a message's key. Subscribers opened at [Offset::Snapshot] receive
+/// the latest message for each key, before the topic's live messages.
+pub const KEY_HEADER: &str = "selium-key";
+
+/// The message header carrying a message's priority, from 0 (the default) to 255. Prioritised
+/// subscribers receive higher priority messages ahead of lower priority messages that were
+/// written before them, but haven't been delivered yet.
+pub const PRIORITY_HEADER: &str = "selium-priority";
+
+/// The request header carrying the identity of the requestor's client certificate, as a
+/// hex-encoded SHA-512 fingerprint. Set by the server on every request it forwards to a replier,
+/// replacing any value sent by the requestor, and omitted if the requestor's identity is unknown.
+pub const PEER_IDENTITY_HEADER: &str = "selium-peer";
+
 const REGISTER_PUBLISHER: u8 = 0x0;
 const REGISTER_SUBSCRIBER: u8 = 0x1;
 const REGISTER_REPLIER: u8 = 0x2;
@@ -14,6 +41,24 @@ const MESSAGE: u8 = 0x4;
 const BATCH_MESSAGE: u8 = 0x5;
 const ERROR: u8 = 0x6;
 const OK: u8 = 0x7;
+const ACK: u8 = 0x8;
+const QUERY_OFFSETS: u8 = 0x9;
+const OFFSETS: u8 = 0xA;
+const CANCEL: u8 = 0xB;
+const HEALTH: u8 = 0xC;
+const RESERVE: u8 = 0xD;
+const RESERVED: u8 = 0xE;
+const COMMIT: u8 = 0xF;
+const ABORT: u8 = 0x10;
+const REGISTER_DATAGRAM_SUBSCRIBER: u8 = 0x11;
+const SIGNAL: u8 = 0x12;
+const QUERY_SERVER_INFO: u8 = 0x13;
+const SERVER_INFO: u8 = 0x14;
+const TRUNCATE_TOPIC: u8 = 0x15;
+const MESSAGE_CHUNK: u8 = 0x16;
+const COMMIT_GROUP_OFFSET: u8 = 0x17;
+const WATCH_TOPICS: u8 = 0x18;
+const TOPIC_EVENT: u8 = 0x19;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
@@ -25,33 +70,114 @@ pub enum Frame {
     BatchMessage(BatchPayload),
     Error(ErrorPayload),
     Ok,
+    Ack(AckPayload),
+    QueryOffsets(QueryOffsetsPayload),
+    Offsets(OffsetsPayload),
+    Cancel(CancelPayload),
+    /// A liveness check, which the server answers with [Frame::Ok] without opening a topic.
+    Health,
+    /// Writes a message to the log in an uncommitted state, which the server answers with
+    /// [Frame::Reserved].
+    Reserve(MessagePayload),
+    /// The offset reserved for the message sent in the preceding [Frame::Reserve].
+    Reserved(ReservationPayload),
+    /// Commits a reserved message, making it visible to subscribers, which the server answers
+    /// with [Frame::Ok].
+    Commit(ReservationPayload),
+    /// Aborts a reserved message, so that it's never delivered to subscribers, which the server
+    /// answers with [Frame::Ok].
+    Abort(ReservationPayload),
+    /// Subscribes the connection to messages sent to a topic as QUIC datagrams, for as long as
+    /// the stream remains open.
+    RegisterDatagramSubscriber(DatagramSubscriberPayload),
+    /// An advisory notification, which doesn't interrupt the stream.
+    Signal(Signal),
+    /// Requests the server's build and runtime details, which the server answers with
+    /// [Frame::ServerInfo] without opening a topic.
+    QueryServerInfo,
+    ServerInfo(ServerInfoPayload),
+    /// Removes the messages preceding an offset from a topic's log, which the server answers
+    /// with the topic's new [Frame::Offsets].
+    TruncateTopic(TruncateTopicPayload),
+    /// A chunk of a message that was too large to be sent in a single frame, which is
+    /// reassembled with the message's other chunks before it's decoded.
+    MessageChunk(ChunkPayload),
+    /// Commits the offset of the last message processed by a subscriber's consumer group.
+    CommitGroupOffset(GroupOffsetPayload),
+    /// Subscribes to the creation and removal of the server's topics, which the server answers
+    /// with [Frame::Ok], followed by a [Frame::TopicEvent] for each change for as long as the
+    /// stream remains open.
+    WatchTopics,
+    TopicEvent(TopicEvent),
 }
 
 impl Frame {
-    pub fn get_length(&self) -> Result<u64> {
+    pub fn get_length(&self, config: &BincodeConfig) -> Result<u64> {
         Ok(match self {
-            Self::RegisterPublisher(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::RegisterSubscriber(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::RegisterReplier(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::RegisterRequestor(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::Message(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::BatchMessage(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
-            Self::Error(payload) => {
-                bincode::serialized_size(payload).map_err(ProtocolError::SerdeError)?
-            }
+            Self::RegisterPublisher(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::RegisterSubscriber(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::RegisterReplier(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::RegisterRequestor(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Message(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::BatchMessage(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Error(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
             Self::Ok => 0,
+            Self::Ack(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::QueryOffsets(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Offsets(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Cancel(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Health => 0,
+            Self::Reserve(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Reserved(payload) | Self::Commit(payload) | Self::Abort(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::RegisterDatagramSubscriber(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::Signal(signal) => config
+                .serialized_size(signal)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::QueryServerInfo => 0,
+            Self::ServerInfo(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::TruncateTopic(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::MessageChunk(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::CommitGroupOffset(payload) => config
+                .serialized_size(payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Self::WatchTopics => 0,
+            Self::TopicEvent(event) => config
+                .serialized_size(event)
+                .map_err(ProtocolError::SerdeError)?,
         })
     }
 
@@ -65,6 +191,24 @@ impl Frame {
             Self::BatchMessage(_) => BATCH_MESSAGE,
             Self::Error(_) => ERROR,
             Self::Ok => OK,
+            Self::Ack(_) => ACK,
+            Self::QueryOffsets(_) => QUERY_OFFSETS,
+            Self::Offsets(_) => OFFSETS,
+            Self::Cancel(_) => CANCEL,
+            Self::Health => HEALTH,
+            Self::Reserve(_) => RESERVE,
+            Self::Reserved(_) => RESERVED,
+            Self::Commit(_) => COMMIT,
+            Self::Abort(_) => ABORT,
+            Self::RegisterDatagramSubscriber(_) => REGISTER_DATAGRAM_SUBSCRIBER,
+            Self::Signal(_) => SIGNAL,
+            Self::QueryServerInfo => QUERY_SERVER_INFO,
+            Self::ServerInfo(_) => SERVER_INFO,
+            Self::TruncateTopic(_) => TRUNCATE_TOPIC,
+            Self::MessageChunk(_) => MESSAGE_CHUNK,
+            Self::CommitGroupOffset(_) => COMMIT_GROUP_OFFSET,
+            Self::WatchTopics => WATCH_TOPICS,
+            Self::TopicEvent(_) => TOPIC_EVENT,
         }
     }
 
@@ -74,30 +218,97 @@ impl Frame {
             Self::RegisterSubscriber(s) => Some(&s.topic),
             Self::RegisterReplier(s) => Some(&s.topic),
             Self::RegisterRequestor(c) => Some(&c.topic),
+            Self::QueryOffsets(q) => Some(&q.topic),
+            Self::TruncateTopic(t) => Some(&t.topic),
+            Self::RegisterDatagramSubscriber(s) => Some(&s.topic),
             Self::Message(_) => None,
             Self::BatchMessage(_) => None,
             Self::Error(_) => None,
             Self::Ok => None,
+            Self::Ack(_) => None,
+            Self::Offsets(_) => None,
+            Self::Cancel(_) => None,
+            Self::Health => None,
+            Self::Reserve(_) => None,
+            Self::Reserved(_) => None,
+            Self::Commit(_) => None,
+            Self::Abort(_) => None,
+            Self::Signal(_) => None,
+            Self::QueryServerInfo => None,
+            Self::ServerInfo(_) => None,
+            Self::MessageChunk(_) => None,
+            Self::CommitGroupOffset(_) => None,
+            Self::WatchTopics => None,
+            Self::TopicEvent(_) => None,
         }
     }
 
-    pub fn write_to_bytes(self, dst: &mut BytesMut) -> Result<()> {
+    pub fn write_to_bytes(self, dst: &mut BytesMut, config: &BincodeConfig) -> Result<()> {
         match self {
-            Frame::RegisterPublisher(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::RegisterPublisher(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::RegisterSubscriber(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::RegisterSubscriber(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::RegisterReplier(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::RegisterReplier(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::RegisterRequestor(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::RegisterRequestor(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::Message(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::Message(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::Error(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::Error(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
-            Frame::BatchMessage(payload) => bincode::serialize_into(dst.writer(), &payload)
+            Frame::BatchMessage(payload) => config
+                .serialize_into(dst.writer(), &payload)
                 .map_err(ProtocolError::SerdeError)?,
             Frame::Ok => (),
+            Frame::Ack(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::QueryOffsets(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::Offsets(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::Cancel(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::Health => (),
+            Frame::Reserve(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::Reserved(payload) | Frame::Commit(payload) | Frame::Abort(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::RegisterDatagramSubscriber(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::Signal(signal) => config
+                .serialize_into(dst.writer(), &signal)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::QueryServerInfo => (),
+            Frame::ServerInfo(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::TruncateTopic(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::MessageChunk(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::CommitGroupOffset(payload) => config
+                .serialize_into(dst.writer(), &payload)
+                .map_err(ProtocolError::SerdeError)?,
+            Frame::WatchTopics => (),
+            Frame::TopicEvent(event) => config
+                .serialize_into(dst.writer(), &event)
+                .map_err(ProtocolError::SerdeError)?,
         }
 
         Ok(())
@@ -113,7 +324,7 @@ impl Frame {
 
     pub fn batch_size(&self) -> Option<u32> {
         match self {
-            Self::Message(_) => Some(1),
+            Self::Message(_) | Self::Reserve(_) => Some(1),
             Self::BatchMessage(payload) => Some(payload.size),
             _ => None,
         }
@@ -121,8 +332,47 @@ impl Frame {
 
     pub fn message(&self) -> Option<&[u8]> {
         match self {
-            Self::Message(payload) => Some(&payload.message),
+            Self::Message(payload) | Self::Reserve(payload) => Some(&payload.message),
             Self::BatchMessage(payload) => Some(&payload.message),
+            Self::MessageChunk(payload) => Some(&payload.message),
+            _ => None,
+        }
+    }
+
+    pub fn ttl(&self) -> Option<u64> {
+        match self {
+            Self::Message(payload) | Self::Reserve(payload) => payload.ttl,
+            Self::BatchMessage(payload) => payload.ttl,
+            Self::MessageChunk(payload) => payload.ttl,
+            _ => None,
+        }
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Message(payload) => payload.offset,
+            Self::BatchMessage(payload) => payload.offset,
+            Self::MessageChunk(payload) => payload.offset,
+            _ => None,
+        }
+    }
+
+    pub fn sequence_id(&self) -> Option<&SequenceId> {
+        match self {
+            Self::Message(payload) | Self::Reserve(payload) => payload.sequence_id.as_ref(),
+            Self::BatchMessage(payload) => payload.sequence_id.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The event time attached to the message by its producer, if any. See [EVENT_TIME_HEADER].
+    pub fn event_time(&self) -> Option<u64> {
+        match self {
+            Self::Message(payload) | Self::Reserve(payload) => payload
+                .headers
+                .as_ref()
+                .and_then(|headers| headers.get(EVENT_TIME_HEADER))
+                .and_then(|event_time| event_time.parse().ok()),
             _ => None,
         }
     }
@@ -135,33 +385,123 @@ impl Frame {
     }
 }
 
-impl TryFrom<(u8, BytesMut)> for Frame {
-    type Error = SeliumError;
-
-    fn try_from(
-        (message_type, bytes): (u8, BytesMut),
-    ) -> Result<Self, <Frame as TryFrom<(u8, BytesMut)>>::Error> {
+impl Frame {
+    pub fn from_bytes(message_type: u8, bytes: BytesMut, config: &BincodeConfig) -> Result<Self> {
         let frame = match message_type {
             REGISTER_PUBLISHER => Frame::RegisterPublisher(
-                bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?,
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
             ),
             REGISTER_SUBSCRIBER => Frame::RegisterSubscriber(
-                bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?,
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
             ),
             REGISTER_REPLIER => Frame::RegisterReplier(
-                bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?,
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
             ),
             REGISTER_REQUESTOR => Frame::RegisterRequestor(
-                bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?,
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            MESSAGE => Frame::Message(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
             ),
-            MESSAGE => {
-                Frame::Message(bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?)
-            }
             BATCH_MESSAGE => Frame::BatchMessage(
-                bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?,
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            ERROR => Frame::Error(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
             ),
-            ERROR => Frame::Error(bincode::deserialize(&bytes).map_err(ProtocolError::SerdeError)?),
             OK => Frame::Ok,
+            ACK => Frame::Ack(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            QUERY_OFFSETS => Frame::QueryOffsets(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            OFFSETS => Frame::Offsets(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            CANCEL => Frame::Cancel(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            HEALTH => Frame::Health,
+            RESERVE => Frame::Reserve(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            RESERVED => Frame::Reserved(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            COMMIT => Frame::Commit(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            ABORT => Frame::Abort(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            REGISTER_DATAGRAM_SUBSCRIBER => Frame::RegisterDatagramSubscriber(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            SIGNAL => Frame::Signal(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            QUERY_SERVER_INFO => Frame::QueryServerInfo,
+            SERVER_INFO => Frame::ServerInfo(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            TRUNCATE_TOPIC => Frame::TruncateTopic(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            MESSAGE_CHUNK => Frame::MessageChunk(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            COMMIT_GROUP_OFFSET => Frame::CommitGroupOffset(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
+            WATCH_TOPICS => Frame::WatchTopics,
+            TOPIC_EVENT => Frame::TopicEvent(
+                config
+                    .deserialize(&bytes)
+                    .map_err(ProtocolError::SerdeError)?,
+            ),
             _type => return Err(ProtocolError::UnknownMessageType(_type))?,
         };
 
@@ -169,11 +509,22 @@ impl TryFrom<(u8, BytesMut)> for Frame {
     }
 }
 
+impl TryFrom<(u8, BytesMut)> for Frame {
+    type Error = SeliumError;
+
+    fn try_from(
+        (message_type, bytes): (u8, BytesMut),
+    ) -> Result<Self, <Frame as TryFrom<(u8, BytesMut)>>::Error> {
+        Self::from_bytes(message_type, bytes, &BincodeConfig::default())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PublisherPayload {
     pub topic: TopicName,
     pub retention_policy: u64,
     pub operations: Vec<Operation>,
+    pub compression: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -182,6 +533,15 @@ pub struct SubscriberPayload {
     pub retention_policy: u64,
     pub operations: Vec<Operation>,
     pub offset: Offset,
+    pub compression: Option<String>,
+    /// Only delivers messages whose headers pass the filter, or every message if [None].
+    pub filter_headers: Option<HeaderFilter>,
+    /// The consumer group the subscriber has joined, whose committed offset is used to resolve
+    /// [Offset::Committed].
+    pub group: Option<String>,
+    /// Delivers the messages read from the log in order of their [PRIORITY_HEADER], rather than
+    /// in log order.
+    pub prioritise: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -194,16 +554,67 @@ pub struct RequestorPayload {
     pub topic: TopicName,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatagramSubscriberPayload {
+    pub topic: TopicName,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MessagePayload {
     pub headers: Headers,
     pub message: Bytes,
+    /// Optional time-to-live in milliseconds, after which the message expires and will no longer
+    /// be delivered to subscribers.
+    pub ttl: Option<u64>,
+    /// The log offset assigned to the message, set by the server when delivering the message to
+    /// subscribers.
+    pub offset: Option<u64>,
+    /// Optional sequence id used by the server to discard duplicate messages from a producer.
+    pub sequence_id: Option<SequenceId>,
+}
+
+impl MessagePayload {
+    /// Whether the message was compressed, provided that its publisher uses compression. See
+    /// [COMPRESSED_HEADER].
+    pub fn is_compressed(&self) -> bool {
+        self.headers
+            .as_ref()
+            .and_then(|headers| headers.get(COMPRESSED_HEADER))
+            .is_none_or(|compressed| compressed != "false")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BatchPayload {
     pub message: Bytes,
     pub size: u32,
+    /// Optional time-to-live in milliseconds, applied to the batch as a whole.
+    pub ttl: Option<u64>,
+    /// The log offset assigned to the batch, set by the server when delivering the batch to
+    /// subscribers.
+    pub offset: Option<u64>,
+    /// The log offset of the first message in a batch that the server has coalesced from
+    /// consecutive log entries, so that each message in the batch has its own offset, ending at
+    /// `offset`. Set to [None] for batches that were written to the log as a single entry.
+    pub first_offset: Option<u64>,
+    /// Optional sequence id used by the server to discard duplicate batches from a producer.
+    pub sequence_id: Option<SequenceId>,
+}
+
+/// Identifies a message sent by an idempotent producer.
+///
+/// Producers assign a monotonically increasing sequence to each message, so that the server can
+/// discard a message if it has already written the same `(producer_id, sequence)` pair.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct SequenceId {
+    pub producer_id: String,
+    pub sequence: u64,
+}
+
+/// Cancels an in-flight request, identified by the same headers used to route its reply.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CancelPayload {
+    pub headers: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -211,3 +622,78 @@ pub struct ErrorPayload {
     pub code: u32,
     pub message: Bytes,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AckPayload {
+    pub offset: u64,
+}
+
+/// The offset of the last message processed by a consumer group. The group resumes from the
+/// following message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupOffsetPayload {
+    pub offset: u64,
+}
+
+/// Identifies a message reserved in the log by a publisher, which remains invisible to
+/// subscribers until it's committed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReservationPayload {
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueryOffsetsPayload {
+    pub topic: TopicName,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChunkPayload {
+    pub header: ChunkHeader,
+    pub message: Bytes,
+    pub ttl: Option<u64>,
+    /// The log offset of the chunk, which is only set by the server.
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TruncateTopicPayload {
+    pub topic: TopicName,
+    /// The offset to truncate the topic's log to. Only whole segments of the log are removed, so
+    /// some messages preceding this offset may be retained.
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OffsetsPayload {
+    /// The offset of the earliest message still retained in the topic's log.
+    pub start: u64,
+    /// The offset that will be assigned to the next message written to the topic's log.
+    pub end: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerInfoPayload {
+    /// The version of the server crate.
+    pub version: String,
+    /// The git commit that the server was built from, if it was known at build time.
+    pub git_hash: Option<String>,
+    /// The number of seconds since the server was started.
+    pub uptime_secs: u64,
+    /// The maximum size of a single frame's payload, in bytes.
+    pub max_frame_size: u64,
+    /// The maximum size of a single message, in bytes, if the server limits message sizes.
+    pub max_message_bytes: Option<u64>,
+    /// The number of topics currently open on the server.
+    pub topic_count: u64,
+}
+
+/// A change to the topics open on the server, sent to streams watching the server's topics.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TopicEvent {
+    /// The topic has been opened by the first stream to use it.
+    Created(TopicName),
+    /// The topic has been closed, e.g. after being idle. It will be created again by the next
+    /// stream to use it.
+    Removed(TopicName),
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Constrains the messages delivered to a subscriber by their headers, so that the server only
+/// sends the subscriber matching messages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct HeaderFilter {
+    /// The values allowed for each header. A message must match every header to be delivered.
+    pub headers: HashMap<String, HashSet<String>>,
+    /// Whether a message lacking one of the filtered headers is delivered, rather than excluded.
+    pub pass_through_missing: bool,
+}
+
+impl HeaderFilter {
+    /// Allows messages whose `key` header is `value`. Can be called multiple times for the same
+    /// header to allow several values.
+    pub fn allow(&mut self, key: &str, value: &str) {
+        self.headers
+            .entry(key.to_owned())
+            .or_default()
+            .insert(value.to_owned());
+    }
+
+    /// Returns whether a message with the provided headers passes the filter.
+    pub fn matches(&self, headers: Option<&HashMap<String, String>>) -> bool {
+        self.headers.iter().all(|(key, allowed)| {
+            match headers.and_then(|headers| headers.get(key)) {
+                Some(value) => allowed.contains(value),
+                None => self.pass_through_missing,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_messages_with_allowed_header_values() {
+        let mut filter = HeaderFilter::default();
+        filter.allow("region", "eu");
+        filter.allow("region", "us");
+        filter.allow("kind", "order");
+
+        let headers = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert!(filter.matches(Some(&headers(&[("region", "us"), ("kind", "order")]))));
+        assert!(!filter.matches(Some(&headers(&[("region", "au"), ("kind", "order")]))));
+        assert!(!filter.matches(Some(&headers(&[("region", "eu")]))));
+        assert!(!filter.matches(None));
+
+        filter.pass_through_missing = true;
+        assert!(filter.matches(Some(&headers(&[("region", "eu")]))));
+        assert!(filter.matches(None));
+        assert!(!filter.matches(Some(&headers(&[("region", "au")]))));
+    }
+}
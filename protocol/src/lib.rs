@@ -1,9 +1,14 @@
 mod bistream;
+mod chunk;
 mod codec;
+mod datagram;
 mod frame;
+mod header_filter;
 mod offset;
 mod operation;
 mod request_id;
+mod signal;
+mod stream_type;
 mod topic_name;
 
 pub mod error_codes;
@@ -11,9 +16,17 @@ pub mod traits;
 pub mod utils;
 
 pub use bistream::*;
+pub use chunk::*;
 pub use codec::*;
+pub use datagram::*;
 pub use frame::*;
+pub use header_filter::*;
 pub use offset::*;
 pub use operation::*;
 pub use request_id::*;
+pub use signal::*;
+pub use stream_type::*;
 pub use topic_name::*;
+
+/// The default ALPN protocol identifier negotiated between clients and the `Selium` server.
+pub const DEFAULT_ALPN: &str = "hq-29";
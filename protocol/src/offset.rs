@@ -4,6 +4,24 @@ use serde::{Deserialize, Serialize};
 pub enum Offset {
     FromBeginning(u64),
     FromEnd(u64),
+    /// Only messages written to the log after the subscriber has been registered, without
+    /// replaying any historical messages.
+    Latest,
+    /// The first message from which every message with a timestamp at or after the provided UNIX
+    /// timestamp in milliseconds can be read. Depending on the topic's configuration, messages
+    /// are timestamped with either the time they were written to the log, or the event time
+    /// attached by their producer.
+    FromTimestamp(u64),
+    /// The latest message for each key written to the log so far, followed by every message
+    /// written after the snapshot was taken. Keys are read from each message's
+    /// [KEY_HEADER](crate::KEY_HEADER), and messages without a key are left out of the snapshot.
+    ///
+    /// The snapshot is delivered in log order, and the end of the snapshot is marked by a
+    /// [SnapshotComplete](crate::Signal::SnapshotComplete) signal.
+    Snapshot,
+    /// The message following the last message committed by the subscriber's consumer group, or
+    /// the end of the log if the group hasn't committed an offset yet.
+    Committed,
 }
 
 impl Default for Offset {
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// An advisory notification sent by the server on an open stream.
+///
+/// Unlike an error, a signal doesn't end the stream, so clients can react to it while continuing
+/// to consume the stream as usual.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Signal {
+    /// The subscriber has fallen behind the latest message written to the topic by `lag`
+    /// messages, crossing the server's high-water mark.
+    SubscriberLagging { lag: u64 },
+    /// The subscriber has been sent the latest message for each key in the topic, and every
+    /// message that follows is a live message, starting from `offset`.
+    SnapshotComplete { offset: u64 },
+    /// The subscriber has been sent every message read from the topic up to `offset`, in order of
+    /// priority. Sent to prioritised subscribers after each window of messages, as messages within
+    /// a window are no longer delivered in log order.
+    PriorityWindowComplete { offset: u64 },
+}
@@ -0,0 +1,25 @@
+use crate::Frame;
+
+/// The type of stream registered with the `Selium` server, as declared by the stream's header
+/// frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamType {
+    Publisher,
+    Subscriber,
+    Replier,
+    Requestor,
+}
+
+impl StreamType {
+    /// Returns the type of stream registered by the provided frame, or [None] if the frame is
+    /// not a registration frame.
+    pub fn from_frame(frame: &Frame) -> Option<Self> {
+        match frame {
+            Frame::RegisterPublisher(_) => Some(Self::Publisher),
+            Frame::RegisterSubscriber(_) => Some(Self::Subscriber),
+            Frame::RegisterReplier(_) => Some(Self::Replier),
+            Frame::RegisterRequestor(_) => Some(Self::Requestor),
+            _ => None,
+        }
+    }
+}
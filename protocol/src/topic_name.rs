@@ -16,17 +16,33 @@ pub struct TopicName {
 }
 
 impl TopicName {
-    pub fn create(namespace: &str, topic: &str) -> Result<Self> {
-        let s = Self {
+    /// Constructs a TopicName from its namespace and topic components, applying the same
+    /// validation rules as parsing a `/namespace/topic` string.
+    ///
+    /// # Errors
+    /// - Returns [SeliumError::ReservedNamespaceError] if the namespace is reserved.
+    /// - Returns [SeliumError::ParseTopicNameError] if either component is invalid.
+    pub fn new(namespace: &str, topic: &str) -> Result<Self> {
+        #[cfg(not(feature = "__notopiccheck"))]
+        if namespace.starts_with(RESERVED_NAMESPACE) {
+            return Err(SeliumError::ReservedNamespaceError);
+        }
+
+        if !COMPONENT_REGEX.is_match(namespace) || !COMPONENT_REGEX.is_match(topic) {
+            return Err(SeliumError::ParseTopicNameError);
+        }
+
+        Ok(Self {
             namespace: namespace.to_owned(),
             topic: topic.to_owned(),
-        };
+        })
+    }
 
-        if s.is_valid() {
-            Ok(s)
-        } else {
-            Err(SeliumError::ParseTopicNameError)
-        }
+    /// Constructs a TopicName from its namespace and topic components. This is an alias of
+    /// [new](TopicName::new), kept for compatibility.
+    #[deprecated(note = "renamed to `new`")]
+    pub fn create(namespace: &str, topic: &str) -> Result<Self> {
+        Self::new(namespace, topic)
     }
 
     #[doc(hidden)]
@@ -123,11 +139,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn successfully_creates_topic_name_from_parts() {
+        let topic_name = TopicName::new("name_space", "to-pic").unwrap();
+
+        assert_eq!(topic_name.namespace(), "name_space");
+        assert_eq!(topic_name.topic(), "to-pic");
+        assert_eq!(
+            topic_name,
+            TopicName::try_from("/name_space/to-pic").unwrap()
+        );
+    }
+
+    #[test]
+    fn fails_to_create_topic_name_from_invalid_parts() {
+        let parts = [
+            ("", "topic"),
+            ("namespace", ""),
+            ("ns", "topic"),
+            ("namespace", "topic!"),
+            ("name/space", "topic"),
+        ];
+
+        for (namespace, topic) in parts {
+            let result = TopicName::new(namespace, topic);
+            assert!(matches!(result, Err(SeliumError::ParseTopicNameError)));
+        }
+    }
+
+    #[cfg(not(feature = "__notopiccheck"))]
+    #[test]
+    fn fails_to_create_reserved_namespace() {
+        let result = TopicName::new("selium", "topic");
+        assert!(matches!(result, Err(SeliumError::ReservedNamespaceError)));
+    }
+
     #[test]
     fn outputs_formatted_topic_name() {
         let namespace = "namespace";
         let topic = "topic";
-        let topic_name = TopicName::create(namespace, topic).unwrap();
+        let topic_name = TopicName::new(namespace, topic).unwrap();
         let expected = format!("/{namespace}/{topic}");
 
         assert_eq!(topic_name.to_string(), expected);
@@ -1,4 +1,9 @@
+use crate::ChunkHeader;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::HashMap;
+
+/// The size of the length prefixed to a batch, and to each message within it.
+const BATCH_LENGTH_SIZE: usize = std::mem::size_of::<u64>();
 
 pub fn encode_message_batch(batch: Vec<Bytes>) -> Bytes {
     let mut bytes = BytesMut::new();
@@ -14,15 +19,149 @@ pub fn encode_message_batch(batch: Vec<Bytes>) -> Bytes {
     bytes.into()
 }
 
-pub fn decode_message_batch(mut bytes: Bytes) -> Vec<Bytes> {
+/// Decodes a batch encoded by [encode_message_batch], yielding [None] in place of a message
+/// that has been truncated, rather than failing the whole batch.
+///
+/// Each message is located by the length prefixed to it, so the messages following a truncated
+/// message can't be recovered, and decoding ends there. Returns an empty batch if `bytes` is too
+/// short to contain the batch's header.
+pub fn decode_message_batch(mut bytes: Bytes) -> Vec<Option<Bytes>> {
+    if bytes.remaining() < BATCH_LENGTH_SIZE {
+        return Vec::new();
+    }
+
     let num_of_messages = bytes.get_u64();
-    let mut messages = Vec::with_capacity(num_of_messages as usize);
+    // Bound the allocation by the bytes available, as the header may be garbled
+    let capacity = num_of_messages.min((bytes.remaining() / BATCH_LENGTH_SIZE) as u64 + 1);
+    let mut messages = Vec::with_capacity(capacity as usize);
 
     for _ in 0..num_of_messages {
-        let message_len = bytes.get_u64();
-        let message_bytes = bytes.split_to(message_len as usize);
-        messages.push(message_bytes);
+        let message_len = match bytes.remaining() {
+            remaining if remaining >= BATCH_LENGTH_SIZE => bytes.get_u64(),
+            _ => u64::MAX,
+        };
+
+        match usize::try_from(message_len) {
+            Ok(len) if len <= bytes.remaining() => messages.push(Some(bytes.split_to(len))),
+            _ => {
+                messages.push(None);
+                break;
+            }
+        }
     }
 
     messages
 }
+
+/// Encodes a chunk for storage as a single log entry, prefixed by its header.
+pub fn encode_message_chunk(header: ChunkHeader, chunk: &[u8]) -> Bytes {
+    let mut bytes = BytesMut::with_capacity(ChunkHeader::SIZE + chunk.len());
+    header.encode(&mut bytes);
+    bytes.extend_from_slice(chunk);
+
+    bytes.into()
+}
+
+/// Decodes a chunk encoded by [encode_message_chunk], or returns [None] if `bytes` is too short
+/// to contain a header.
+pub fn decode_message_chunk(mut bytes: Bytes) -> Option<(ChunkHeader, Bytes)> {
+    let header = ChunkHeader::decode(&mut bytes)?;
+    Some((header, bytes))
+}
+
+/// Encodes a message for storage as a single log entry, prefixed by its headers.
+pub fn encode_message_with_headers(headers: &HashMap<String, String>, message: &[u8]) -> Bytes {
+    let mut bytes = BytesMut::new();
+    bytes.put_u32(headers.len() as u32);
+
+    for (key, value) in headers {
+        for field in [key, value] {
+            bytes.put_u32(field.len() as u32);
+            bytes.extend_from_slice(field.as_bytes());
+        }
+    }
+
+    bytes.extend_from_slice(message);
+    bytes.into()
+}
+
+/// Decodes a message encoded by [encode_message_with_headers], or returns [None] if `bytes` is
+/// malformed.
+pub fn decode_message_with_headers(mut bytes: Bytes) -> Option<(HashMap<String, String>, Bytes)> {
+    fn field(bytes: &mut Bytes) -> Option<String> {
+        if bytes.remaining() < 4 {
+            return None;
+        }
+
+        let len = bytes.get_u32() as usize;
+
+        if bytes.remaining() < len {
+            return None;
+        }
+
+        String::from_utf8(bytes.split_to(len).to_vec()).ok()
+    }
+
+    if bytes.remaining() < 4 {
+        return None;
+    }
+
+    let count = bytes.get_u32();
+    let mut headers = HashMap::new();
+
+    for _ in 0..count {
+        let key = field(&mut bytes)?;
+        let value = field(&mut bytes)?;
+        headers.insert(key, value);
+    }
+
+    Some((headers, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_intact_messages_before_truncated_message() {
+        let batch = encode_message_batch(vec![
+            Bytes::from("first"),
+            Bytes::from("second"),
+            Bytes::from("third"),
+        ]);
+        let truncated = batch.slice(..batch.len() - 2);
+
+        assert_eq!(
+            decode_message_batch(batch),
+            [
+                Some(Bytes::from("first")),
+                Some(Bytes::from("second")),
+                Some(Bytes::from("third")),
+            ]
+        );
+        assert_eq!(
+            decode_message_batch(truncated),
+            [
+                Some(Bytes::from("first")),
+                Some(Bytes::from("second")),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_garbled_message_length() {
+        let mut bytes = BytesMut::new();
+        bytes.put_u64(2);
+        bytes.put_u64(5);
+        bytes.extend_from_slice(b"first");
+        bytes.put_u64(u64::MAX);
+        bytes.extend_from_slice(b"second");
+
+        assert_eq!(
+            decode_message_batch(bytes.into()),
+            [Some(Bytes::from("first")), None]
+        );
+        assert!(decode_message_batch(Bytes::from_static(b"short")).is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+use std::process::Command;
+
+fn main() {
+    // Record the commit that the server is built from, which is only known when building from a
+    // git checkout
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=SELIUM_GIT_HASH={}", hash.trim());
+    }
+}
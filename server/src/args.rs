@@ -1,13 +1,50 @@
-use clap::{Args, Parser};
+use crate::topic::schema::Schema;
+use clap::{builder::RangedU64ValueParser, Args, Parser};
 use clap_verbosity_flag::Verbosity;
-use std::{net::SocketAddr, path::PathBuf};
+use selium_log::config::TimestampSource;
+use selium_protocol::TopicName;
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1:7001";
+pub const DEFAULT_CA: &str = "certs/server/ca.der";
+pub const DEFAULT_KEY: &str = "certs/server/localhost.key.der";
+pub const DEFAULT_CERT: &str = "certs/server/localhost.der";
+pub const DEFAULT_IDLE_TIMEOUT: u32 = 15_000;
+pub const DEFAULT_ALPN: &str = selium_protocol::DEFAULT_ALPN;
+pub const DEFAULT_DATAGRAM_BUFFER_SIZE: usize = 1_250_000;
+pub const DEFAULT_LOG_SEGMENTS_DIRECTORY: &str = "logs/";
+pub const DEFAULT_LOG_CLEANER_INTERVAL: u64 = 300_000;
+pub const DEFAULT_LOG_MAXIMUM_ENTRIES: u32 = 100_000;
+pub const DEFAULT_LOG_SEGMENT_MAX_BYTES: u64 = 1_073_741_824;
+pub const DEFAULT_FLUSH_POLICY_INTERVAL: u64 = 3000;
+pub const DEFAULT_SUBSCRIBER_POLLING_INTERVAL: u64 = 25;
+pub const DEFAULT_DEDUP_WINDOW: usize = 1000;
+pub const DEFAULT_DEDUP_PRODUCER_TIMEOUT: u64 = 60 * 60 * 1000;
+pub const DEFAULT_SUBSCRIBER_MAX_POLLING_INTERVAL: u64 = 500;
+pub const DEFAULT_SUBSCRIBER_BATCH_MAX_BYTES: usize = 64 * 1024;
+pub const DEFAULT_TOPIC_CHANNEL_SIZE: usize = 100;
+pub const DEFAULT_WRITE_BATCH_SIZE: usize = 1;
+pub const DEFAULT_WRITE_BATCH_DELAY: u64 = 0;
+pub const DEFAULT_RESERVATION_TIMEOUT: u64 = 60_000;
+/// Two full-sized QUIC datagrams, the smallest congestion window a connection can make progress
+/// with.
+pub const MIN_INITIAL_CONGESTION_WINDOW: u64 = 2400;
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
 pub struct UserArgs {
-    /// Address to bind this server to
-    #[clap(short = 'a', long = "bind-addr", default_value = "127.0.0.1:7001")]
-    pub bind_addr: SocketAddr,
+    /// Address to bind this server to. Can be called multiple times, or given a comma separated
+    /// list, to listen on several addresses, e.g. both IPv4 and IPv6
+    #[clap(
+        short = 'a',
+        long = "bind-addr",
+        default_value = DEFAULT_BIND_ADDR,
+        value_delimiter = ','
+    )]
+    pub bind_addr: Vec<SocketAddr>,
 
     #[clap(flatten)]
     pub cert: CertGroup,
@@ -15,17 +52,54 @@ pub struct UserArgs {
     #[clap(flatten)]
     pub log: LogArgs,
 
-    /// Enable stateless retries
+    /// Enable stateless retries, requiring every new connection to validate its address with a
+    /// retry token before the server commits any state to it. Guards against spoofed source
+    /// addresses at the cost of an extra round trip per handshake. Until an address has been
+    /// validated, QUIC limits the server to sending 3 times the data it has received
     #[clap(long = "stateless-retry")]
     pub stateless_retry: bool,
 
+    /// Time in ms that a retry token issued during a stateless retry remains valid - defaults to
+    /// 15 seconds
+    #[clap(long = "retry-token-lifetime")]
+    pub retry_token_lifetime: Option<u64>,
+
+    /// Congestion window in bytes that each connection starts with, limiting how much data can
+    /// be sent before the first acknowledgement. Raising it speeds up new connections on fast
+    /// networks, at the risk of congestion. Must be at least 2,400 bytes. Defaults to 12,000 bytes
+    #[clap(long = "initial-congestion-window", value_parser = clap::value_parser!(u64).range(MIN_INITIAL_CONGESTION_WINDOW..))]
+    pub initial_congestion_window: Option<u64>,
+
     /// File to log TLS keys to for debugging
     #[clap(long = "keylog")]
     pub keylog: bool,
 
     /// Maximum time in ms a client can idle waiting for data - default to 15 seconds
-    #[clap(long = "max-idle-timeout", default_value_t = 15000, value_parser = clap::value_parser!(u32))]
-    pub max_idle_timeout: u32,
+    #[clap(long = "idle-timeout", alias = "max-idle-timeout", default_value_t = DEFAULT_IDLE_TIMEOUT, value_parser = clap::value_parser!(u32))]
+    pub idle_timeout: u32,
+
+    /// Interval in ms between keep-alive pings sent to idle clients. Must be no more than half of
+    /// the idle timeout. Disabled by default.
+    #[clap(long = "keep-alive-interval")]
+    pub keep_alive_interval: Option<u64>,
+
+    /// ALPN protocol identifier that clients must negotiate to connect
+    #[clap(long = "alpn", default_value = DEFAULT_ALPN)]
+    pub alpn: String,
+
+    /// Size in bytes of the buffer for incoming QUIC datagrams, which publishers use to send
+    /// messages that bypass the log. Set to 0 to disable datagrams
+    #[clap(long = "datagram-buffer-size", default_value_t = DEFAULT_DATAGRAM_BUFFER_SIZE)]
+    pub datagram_buffer_size: usize,
+
+    /// Hosts an isolated set of topics for clients requesting the given TLS server name (SNI),
+    /// storing their log segments under the given directory, e.g.
+    /// `tenant-a.example.com=/var/lib/selium/tenant-a`. Can be called multiple times. Once any
+    /// virtual host is configured, connections requesting any other server name are rejected.
+    /// Any trusted client can request any server name, so tenants are only isolated by an
+    /// authenticator that checks which virtual host each stream was routed to.
+    #[clap(long = "virtual-host", value_parser = parse_virtual_host)]
+    pub virtual_hosts: Vec<VirtualHost>,
 
     /// Can be called multiple times to increase output
     #[clap(flatten)]
@@ -35,47 +109,340 @@ pub struct UserArgs {
 #[derive(Args, Debug)]
 pub struct CertGroup {
     /// CA certificate
-    #[clap(long, default_value = "certs/server/ca.der")]
+    #[clap(long, default_value = DEFAULT_CA)]
     pub ca: PathBuf,
     /// TLS private key
     #[clap(
         short = 'k',
         long = "key",
-        default_value = "certs/server/localhost.key.der"
+        default_value = DEFAULT_KEY
     )]
     pub key: PathBuf,
     /// TLS certificate
     #[clap(
         short = 'c',
         long = "cert",
-        default_value = "certs/server/localhost.der"
+        default_value = DEFAULT_CERT
     )]
     pub cert: PathBuf,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct LogArgs {
     /// Path to directory to store log segments.
-    #[clap(long, default_value = "logs/")]
+    #[clap(long, default_value = DEFAULT_LOG_SEGMENTS_DIRECTORY)]
     pub log_segments_directory: PathBuf,
 
+    /// Creates any log directory that doesn't exist at startup. Set to `false` to refuse to start
+    /// unless every log directory already exists.
+    #[clap(long, default_value_t = true, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
+    pub create_log_dir: bool,
+
     /// Interval in seconds to poll log cleaner task - default to 5 minutes.
-    #[clap(long, default_value_t = 300_000)]
+    #[clap(long, default_value_t = DEFAULT_LOG_CLEANER_INTERVAL)]
     pub log_cleaner_interval: u64,
 
     /// Maximum number of entries per log segment.
-    #[clap(long, default_value_t = 100_000)]
+    #[clap(long, default_value_t = DEFAULT_LOG_MAXIMUM_ENTRIES, value_parser = clap::value_parser!(u32).range(1..))]
     pub log_maximum_entries: u32,
 
+    /// Maximum size in bytes of each log segment - default to 1GiB.
+    #[clap(long, default_value_t = DEFAULT_LOG_SEGMENT_MAX_BYTES)]
+    pub log_segment_max_bytes: u64,
+
     /// Number of writes before flushing log to filesystem.
     #[clap(long)]
     pub flush_policy_num_writes: Option<u64>,
 
+    /// Number of unflushed bytes before flushing log to filesystem.
+    #[clap(long)]
+    pub flush_policy_max_unflushed_bytes: Option<u64>,
+
     /// Interval in millis to asynchronously flush log to filesystem.
-    #[clap(long, default_value_t = 3000)]
+    #[clap(long, default_value_t = DEFAULT_FLUSH_POLICY_INTERVAL)]
     pub flush_policy_interval: u64,
 
-    /// Subscriber polling interval in milliseconds.
-    #[clap(long, default_value_t = 25)]
+    /// Minimum subscriber polling interval in milliseconds. Used immediately after new messages are
+    /// received.
+    #[clap(long, default_value_t = DEFAULT_SUBSCRIBER_POLLING_INTERVAL)]
     pub subscriber_polling_interval: u64,
+
+    /// Number of recent sequence ids retained per idempotent producer to discard duplicate
+    /// messages. Set to 0 to disable deduplication.
+    #[clap(long, default_value_t = DEFAULT_DEDUP_WINDOW)]
+    pub dedup_window: usize,
+
+    /// Time in millis after which the sequence ids of an idempotent producer that hasn't sent any
+    /// messages are forgotten.
+    #[clap(long, default_value_t = DEFAULT_DEDUP_PRODUCER_TIMEOUT)]
+    pub dedup_producer_timeout: u64,
+
+    /// Maximum subscriber polling interval in milliseconds. The polling interval backs off
+    /// exponentially up to this value while a topic is idle.
+    #[clap(long, default_value_t = DEFAULT_SUBSCRIBER_MAX_POLLING_INTERVAL)]
+    pub subscriber_max_polling_interval: u64,
+
+    /// Time in millis after which a Pub/Sub topic with no publishers or subscribers is closed,
+    /// releasing its log until it's next opened. Topics are kept open indefinitely if omitted.
+    #[clap(long)]
+    pub topic_idle_timeout: Option<u64>,
+
+    /// Maximum size in bytes of the batches that consecutive messages are coalesced into when
+    /// sent to uncompressed subscribers. Must be less than the maximum frame size of 1MiB. Set to
+    /// 0 to send each message in its own frame.
+    #[clap(long, default_value_t = DEFAULT_SUBSCRIBER_BATCH_MAX_BYTES)]
+    pub subscriber_batch_max_bytes: usize,
+
+    /// Capacity of the channels used to hand new publishers and subscribers to a Pub/Sub topic.
+    /// Raising this lets a topic accept bursts of new streams without blocking, but increases the
+    /// memory reserved by each topic. Must be at least 1.
+    #[clap(long, default_value_t = DEFAULT_TOPIC_CHANNEL_SIZE, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub topic_channel_size: usize,
+
+    /// Number of messages that a subscriber can fall behind the latest message in a Pub/Sub
+    /// topic before it's warned that it's lagging, giving the client a chance to catch up.
+    /// Subscribers are never warned if omitted.
+    #[clap(long)]
+    pub subscriber_lag_warning: Option<u64>,
+
+    /// Maximum number of messages that a subscriber reads from a Pub/Sub topic's log at a time.
+    /// Larger reads mean fewer trips to the log for fast subscribers, at the cost of the memory
+    /// used by each read. Reads run to the end of the current log segment if omitted.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub subscriber_read_ahead: Option<u64>,
+
+    /// Maximum size in bytes of each message written to a topic. Larger messages are rejected
+    /// and reported to the publisher, rather than being written to the log. Messages in a batch
+    /// are checked individually, unless the batch is compressed. Chunked messages are checked as
+    /// a whole, rounded up to a whole number of chunks, and are rejected in their entirety.
+    #[clap(long)]
+    pub max_message_bytes: Option<usize>,
+
+    /// Maximum number of messages received in quick succession that are coalesced into a single
+    /// write to a Pub/Sub topic's log, and flushed together. Set to 1 to write each message
+    /// individually.
+    #[clap(long, default_value_t = DEFAULT_WRITE_BATCH_SIZE)]
+    pub write_batch_size: usize,
+
+    /// Time in millis to wait for further messages to coalesce into a write, once a message has
+    /// been received. Set to 0 to only coalesce messages that have already been received.
+    #[clap(long, default_value_t = DEFAULT_WRITE_BATCH_DELAY)]
+    pub write_batch_delay: u64,
+
+    /// Time in millis after which a message reserved by a publisher is aborted if it hasn't been
+    /// committed, so that subscribers aren't held up by a publisher that never commits.
+    #[clap(long, default_value_t = DEFAULT_RESERVATION_TIMEOUT)]
+    pub reservation_timeout: u64,
+
+    /// Allows clients to truncate Pub/Sub topics, permanently removing their oldest messages.
+    /// Truncation requests are refused unless this is set, even if the client is otherwise
+    /// authorized to access the topic.
+    #[clap(long)]
+    pub allow_topic_truncation: bool,
+
+    /// The timestamp used to expire log segments, and to resolve subscribers seeking by
+    /// timestamp. Either `ingest`, the time each message was written to the log, or `event`, the
+    /// event time attached to each message by its publisher.
+    #[clap(long, default_value = "ingest", value_parser = parse_timestamp_source)]
+    pub log_timestamp_source: TimestampSource,
+
+    /// Stores the log segments of a topic, or of every topic in a namespace, under a different
+    /// directory to the log segments directory, e.g. `/acmeco/orders=/mnt/fast` or
+    /// `/acmeco=/mnt/fast`. Can be called multiple times. Topic overrides take precedence over
+    /// namespace overrides.
+    #[clap(long = "topic-log-directory", value_parser = parse_log_directory_override)]
+    pub topic_log_directories: Vec<LogDirectoryOverride>,
+
+    /// Registers a JSON Schema that every message published to a topic must conform to, read
+    /// from the given file, e.g. `/acmeco/orders=schemas/orders.json`. Can be called multiple
+    /// times. Non-conforming messages are rejected and reported to the publisher. Compressed or
+    /// chunked messages can't be checked, so they're rejected on topics with a schema.
+    #[clap(long = "topic-schema", value_parser = parse_topic_schema)]
+    pub topic_schemas: Vec<TopicSchema>,
+}
+
+impl Default for LogArgs {
+    fn default() -> Self {
+        Self {
+            log_segments_directory: PathBuf::from(DEFAULT_LOG_SEGMENTS_DIRECTORY),
+            create_log_dir: true,
+            log_cleaner_interval: DEFAULT_LOG_CLEANER_INTERVAL,
+            log_maximum_entries: DEFAULT_LOG_MAXIMUM_ENTRIES,
+            log_segment_max_bytes: DEFAULT_LOG_SEGMENT_MAX_BYTES,
+            flush_policy_num_writes: None,
+            flush_policy_max_unflushed_bytes: None,
+            flush_policy_interval: DEFAULT_FLUSH_POLICY_INTERVAL,
+            subscriber_polling_interval: DEFAULT_SUBSCRIBER_POLLING_INTERVAL,
+            dedup_window: DEFAULT_DEDUP_WINDOW,
+            dedup_producer_timeout: DEFAULT_DEDUP_PRODUCER_TIMEOUT,
+            subscriber_max_polling_interval: DEFAULT_SUBSCRIBER_MAX_POLLING_INTERVAL,
+            topic_idle_timeout: None,
+            subscriber_batch_max_bytes: DEFAULT_SUBSCRIBER_BATCH_MAX_BYTES,
+            topic_channel_size: DEFAULT_TOPIC_CHANNEL_SIZE,
+            subscriber_lag_warning: None,
+            subscriber_read_ahead: None,
+            max_message_bytes: None,
+            write_batch_size: DEFAULT_WRITE_BATCH_SIZE,
+            write_batch_delay: DEFAULT_WRITE_BATCH_DELAY,
+            reservation_timeout: DEFAULT_RESERVATION_TIMEOUT,
+            allow_topic_truncation: false,
+            log_timestamp_source: TimestampSource::default(),
+            topic_log_directories: Vec::new(),
+            topic_schemas: Vec::new(),
+        }
+    }
+}
+
+impl LogArgs {
+    /// Returns the directory to store the log segments of the provided topic under, falling back
+    /// to the log segments directory if the topic has no override.
+    pub fn segments_directory(&self, topic: &TopicName) -> &Path {
+        let topic_path = topic.to_string();
+        let namespace_path = format!("/{}", topic.namespace());
+
+        self.topic_log_directories
+            .iter()
+            .find(|o| o.prefix == topic_path)
+            .or_else(|| {
+                self.topic_log_directories
+                    .iter()
+                    .find(|o| o.prefix == namespace_path)
+            })
+            .map_or(&self.log_segments_directory, |o| &o.directory)
+    }
+
+    /// Returns the schema registered for the provided topic, if any.
+    pub fn topic_schema(&self, topic: &TopicName) -> Option<&Schema> {
+        let topic_path = topic.to_string();
+
+        self.topic_schemas
+            .iter()
+            .find(|s| s.topic == topic_path)
+            .map(|s| &s.schema)
+    }
+
+    /// Returns the settings for the topics of a virtual host, which store their log segments
+    /// under the virtual host's directory.
+    ///
+    /// Topic log directory overrides are shared by every virtual host, so each virtual host
+    /// stores its overridden log segments under a subdirectory named after its server name.
+    pub fn for_virtual_host(&self, host: &VirtualHost) -> Self {
+        let topic_log_directories = self
+            .topic_log_directories
+            .iter()
+            .map(|o| LogDirectoryOverride {
+                prefix: o.prefix.clone(),
+                directory: o.directory.join(&host.server_name),
+            })
+            .collect();
+
+        Self {
+            log_segments_directory: host.log_segments_directory.clone(),
+            topic_log_directories,
+            ..self.clone()
+        }
+    }
+}
+
+/// A directory overriding where the log segments of a topic, or a namespace, are stored.
+#[derive(Debug, Clone)]
+pub struct LogDirectoryOverride {
+    /// The topic (`/namespace/topic`) or namespace (`/namespace`) the override applies to.
+    pub prefix: String,
+    pub directory: PathBuf,
+}
+
+/// A schema registered for a topic.
+#[derive(Debug, Clone)]
+pub struct TopicSchema {
+    /// The topic (`/namespace/topic`) the schema applies to.
+    pub topic: String,
+    pub schema: Schema,
+}
+
+/// A TLS server name that is served its own topics, isolated from those of every other server
+/// name.
+#[derive(Debug, Clone)]
+pub struct VirtualHost {
+    pub server_name: String,
+    /// The directory to store the log segments of the virtual host's topics in.
+    pub log_segments_directory: PathBuf,
+}
+
+fn parse_virtual_host(value: &str) -> Result<VirtualHost, String> {
+    let (server_name, directory) = value
+        .split_once('=')
+        .ok_or("Expected a virtual host in the format `server.name=path`")?;
+
+    if server_name.is_empty() {
+        return Err("Missing server name for virtual host".to_owned());
+    }
+
+    if directory.is_empty() {
+        return Err(format!("Missing log directory for `{server_name}`"));
+    }
+
+    Ok(VirtualHost {
+        server_name: server_name.to_owned(),
+        log_segments_directory: PathBuf::from(directory),
+    })
+}
+
+fn parse_timestamp_source(value: &str) -> Result<TimestampSource, String> {
+    match value {
+        "ingest" => Ok(TimestampSource::Ingest),
+        "event" => Ok(TimestampSource::Event),
+        _ => Err(format!(
+            "Invalid timestamp source `{value}`, expected `ingest` or `event`"
+        )),
+    }
+}
+
+fn parse_log_directory_override(value: &str) -> Result<LogDirectoryOverride, String> {
+    let (prefix, directory) = value
+        .split_once('=')
+        .ok_or("Expected an override in the format `/namespace/topic=path`")?;
+
+    let components = prefix
+        .strip_prefix('/')
+        .map(|path| path.split('/').collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let is_valid = match components[..] {
+        [namespace] => TopicName::new(namespace, "topic").is_ok(),
+        [namespace, topic] => TopicName::new(namespace, topic).is_ok(),
+        _ => false,
+    };
+
+    if !is_valid {
+        return Err(format!("Invalid topic or namespace `{prefix}`"));
+    }
+
+    if directory.is_empty() {
+        return Err(format!("Missing log directory for `{prefix}`"));
+    }
+
+    Ok(LogDirectoryOverride {
+        prefix: prefix.to_owned(),
+        directory: PathBuf::from(directory),
+    })
+}
+
+fn parse_topic_schema(value: &str) -> Result<TopicSchema, String> {
+    let (topic, path) = value
+        .split_once('=')
+        .ok_or("Expected a schema in the format `/namespace/topic=path`")?;
+
+    if TopicName::try_from(topic).is_err() {
+        return Err(format!("Invalid topic `{topic}`"));
+    }
+
+    if path.is_empty() {
+        return Err(format!("Missing schema file for `{topic}`"));
+    }
+
+    Ok(TopicSchema {
+        topic: topic.to_owned(),
+        schema: Schema::from_file(path)?,
+    })
 }
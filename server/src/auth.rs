@@ -0,0 +1,53 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use quinn::Connection;
+use selium_protocol::{error_codes::UNAUTHORIZED, Frame};
+
+/// Decides whether a stream may be admitted to the server.
+///
+/// The server invokes the authenticator with the header frame of each new stream, before the
+/// stream is attached to a topic. Returning an error rejects the stream, sending a
+/// [Frame::Error] with the [error code](Authenticator::error_code) and the error's message back
+/// to the client.
+///
+/// Datagrams are authorized with a [Frame::RegisterPublisher] header for their topic, the first
+/// time that a connection sends a datagram to the topic. Unauthorized datagrams are dropped.
+///
+/// A single authenticator is shared by every virtual host, and any client trusted by the
+/// server's certificate authority can request any server name. Virtual hosts therefore only
+/// isolate tenants whose authenticator checks that the client is permitted to use the
+/// `server_name` that its stream was routed to.
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Authorizes a stream opened on `conn`, using its header `frame`.
+    ///
+    /// `server_name` is the virtual host that the connection was routed to, or [None] if the
+    /// server doesn't have any virtual hosts.
+    async fn authorize(
+        &self,
+        conn: &Connection,
+        server_name: Option<&str>,
+        frame: &Frame,
+    ) -> Result<()>;
+
+    /// The error code sent to the client when a stream is rejected.
+    fn error_code(&self) -> u32 {
+        UNAUTHORIZED
+    }
+}
+
+/// An [Authenticator] that admits every stream.
+#[derive(Debug, Default)]
+pub struct AllowAll;
+
+#[async_trait]
+impl Authenticator for AllowAll {
+    async fn authorize(
+        &self,
+        _conn: &Connection,
+        _server_name: Option<&str>,
+        _frame: &Frame,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
@@ -1,13 +1,14 @@
 use std::{pin::Pin, time::Duration};
 
 use anyhow::{anyhow, Context, Result as AnyhowResult};
+use async_trait::async_trait;
 use bytes::BytesMut;
 use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
     future, Sink, SinkExt, Stream, StreamExt,
 };
 use quinn::Connection;
-use selium_protocol::{Frame, MessagePayload, TopicName};
+use selium_protocol::{error_codes::CLOUD_AUTH_FAILED, Frame, MessagePayload, TopicName};
 use selium_std::{
     codecs::BincodeCodec,
     errors::{CodecError, Result, SeliumError},
@@ -17,6 +18,7 @@ use serde::{Deserialize, Serialize};
 use tokio::time::timeout;
 
 use crate::{
+    auth::Authenticator,
     quic::get_pubkey_from_connection,
     server::SharedTopics,
     topic::{reqrep, Socket},
@@ -50,6 +52,35 @@ pub enum AdminResponse {
     _Pad6,
 }
 
+/// Authorizes streams against the namespace assigned to the client's public key by the proxy.
+pub struct CloudAuthenticator {
+    topics: SharedTopics,
+}
+
+impl CloudAuthenticator {
+    pub fn new(topics: SharedTopics) -> Self {
+        Self { topics }
+    }
+}
+
+#[async_trait]
+impl Authenticator for CloudAuthenticator {
+    async fn authorize(
+        &self,
+        conn: &Connection,
+        _server_name: Option<&str>,
+        frame: &Frame,
+    ) -> AnyhowResult<()> {
+        let topic = frame.get_topic().ok_or(anyhow!("Expected header frame"))?;
+
+        do_cloud_auth(conn, topic, &self.topics).await
+    }
+
+    fn error_code(&self) -> u32 {
+        CLOUD_AUTH_FAILED
+    }
+}
+
 // XXX This is horrendously inefficient! Caching is needed.
 pub async fn do_cloud_auth(
     connection: &Connection,
@@ -74,10 +105,13 @@ pub async fn do_cloud_auth(
 
         let topic_tx = ts.get_mut(&proxy_namespace).unwrap();
         topic_tx
-            .send(Socket::Reqrep(reqrep::Socket::Client((
-                Box::pin(si.sink_map_err(|_| SeliumError::RequestFailed)),
-                Box::pin(st),
-            ))))
+            .send(Socket::Reqrep(reqrep::Socket::Client(
+                (
+                    Box::pin(si.sink_map_err(|_| SeliumError::RequestFailed)),
+                    Box::pin(st),
+                ),
+                None,
+            )))
             .await
             .context("Failed to add Requestor to proxy topic")?;
 
@@ -112,6 +146,9 @@ fn channel_pair() -> (
             Ok(msg) => future::ok(Ok(Frame::Message(MessagePayload {
                 headers: None,
                 message: msg,
+                ttl: None,
+                offset: None,
+                sequence_id: None,
             }))),
             Err(e) => future::err(SeliumError::Codec(CodecError::EncodeFailure(e))),
         });
@@ -0,0 +1,164 @@
+use crate::auth::Authenticator;
+use crate::logging::debug;
+use bytes::Bytes;
+use quinn::Connection;
+use selium_protocol::{Datagram, Frame, PublisherPayload, TopicName};
+use selium_std::encoding::BincodeConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type Subscribers = HashMap<TopicName, HashMap<usize, (Connection, usize)>>;
+
+/// Forwards messages sent to a topic as QUIC datagrams to every connection that has subscribed
+/// to datagrams on that topic.
+///
+/// Datagrams never touch the topic's log, so a datagram is only delivered to the subscribers that
+/// are registered at the moment it arrives, and is silently dropped if the subscriber's
+/// connection can't accept it.
+#[derive(Debug, Default)]
+pub struct DatagramRouter {
+    subscribers: Mutex<Subscribers>,
+}
+
+impl DatagramRouter {
+    /// Subscribes `connection` to datagrams sent to `topic` until the returned
+    /// [DatagramRegistration] is dropped.
+    ///
+    /// Registrations are counted per connection, so a connection with several subscriptions to
+    /// the same topic receives each datagram once.
+    pub fn register(
+        self: &Arc<Self>,
+        topic: TopicName,
+        connection: &Connection,
+    ) -> DatagramRegistration {
+        let id = connection.stable_id();
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        subscribers
+            .entry(topic.clone())
+            .or_default()
+            .entry(id)
+            .or_insert_with(|| (connection.clone(), 0))
+            .1 += 1;
+
+        DatagramRegistration {
+            router: self.clone(),
+            topic,
+            id,
+        }
+    }
+
+    /// Reads datagrams from `connection` until it closes, forwarding each datagram to the
+    /// subscribers of its topic.
+    ///
+    /// The first datagram sent to each topic is authorized as though the connection had opened a
+    /// publisher stream on the topic, and the decision is reused for the rest of the connection.
+    pub async fn forward(
+        &self,
+        connection: Connection,
+        authenticator: Arc<dyn Authenticator>,
+        server_name: Option<String>,
+    ) {
+        let config = BincodeConfig::default();
+        let mut authorized = HashMap::new();
+
+        while let Ok(bytes) = connection.read_datagram().await {
+            let topic = match Datagram::from_bytes(&bytes, &config) {
+                Ok(datagram) => datagram.topic,
+                Err(e) => {
+                    debug!("Discarding malformed datagram: {e:?}");
+                    continue;
+                }
+            };
+
+            if !authorized.contains_key(&topic) {
+                let is_authorized = Self::authorize(
+                    &connection,
+                    authenticator.as_ref(),
+                    server_name.as_deref(),
+                    &topic,
+                )
+                .await;
+                authorized.insert(topic.clone(), is_authorized);
+            }
+
+            if authorized[&topic] {
+                self.route(&topic, bytes);
+            }
+        }
+    }
+
+    async fn authorize(
+        connection: &Connection,
+        authenticator: &dyn Authenticator,
+        server_name: Option<&str>,
+        topic: &TopicName,
+    ) -> bool {
+        #[cfg(not(feature = "__cloud"))]
+        {
+            if !topic.is_valid() {
+                return false;
+            }
+        }
+
+        let frame = Frame::RegisterPublisher(PublisherPayload {
+            topic: topic.clone(),
+            retention_policy: 0,
+            operations: vec![],
+            compression: None,
+        });
+
+        match authenticator
+            .authorize(connection, server_name, &frame)
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Discarding unauthorized datagrams for {topic}: {e:?}");
+                false
+            }
+        }
+    }
+
+    fn route(&self, topic: &TopicName, bytes: Bytes) {
+        let subscribers = self.subscribers.lock().unwrap();
+
+        if let Some(connections) = subscribers.get(topic) {
+            for (connection, _) in connections.values() {
+                // Datagrams are best-effort, so a subscriber that can't keep up simply misses out
+                let _ = connection.send_datagram(bytes.clone());
+            }
+        }
+    }
+
+    fn unregister(&self, topic: &TopicName, id: usize) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        if let Some(connections) = subscribers.get_mut(topic) {
+            if let Some((_, count)) = connections.get_mut(&id) {
+                *count -= 1;
+
+                if *count == 0 {
+                    connections.remove(&id);
+                }
+            }
+
+            if connections.is_empty() {
+                subscribers.remove(topic);
+            }
+        }
+    }
+}
+
+/// Keeps a connection subscribed to datagrams on a topic until dropped.
+pub struct DatagramRegistration {
+    router: Arc<DatagramRouter>,
+    topic: TopicName,
+    id: usize,
+}
+
+impl Drop for DatagramRegistration {
+    fn drop(&mut self) {
+        self.router.unregister(&self.topic, self.id);
+    }
+}
@@ -3,8 +3,11 @@ use std::pin::Pin;
 use futures::Sink;
 
 pub mod args;
+pub mod auth;
 #[cfg(feature = "__cloud")]
 mod cloud;
+mod datagram;
+mod logging;
 pub mod quic;
 pub mod server;
 pub mod sink;
@@ -0,0 +1,76 @@
+//! Logging facade for the server.
+//!
+//! By default, events are emitted via the [log] crate. When the `tracing` feature is enabled,
+//! events are emitted via the `tracing` crate instead, and are contextualised by spans carrying
+//! the remote address of each connection and the name of each topic.
+
+use selium_protocol::TopicName;
+use std::future::Future;
+use std::net::SocketAddr;
+
+// Not every level is used in every build configuration
+#[allow(unused_imports)]
+#[cfg(not(feature = "tracing"))]
+pub(crate) use log::{debug, error, info, trace, warn};
+#[allow(unused_imports)]
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, trace, warn};
+#[cfg(feature = "tracing")]
+use tracing::{field, info_span, Instrument, Span};
+
+/// Instruments a connection's future with a span carrying the remote address.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_connection_span<F: Future>(
+    fut: F,
+    remote: SocketAddr,
+) -> impl Future<Output = F::Output> {
+    fut.instrument(info_span!("connection", %remote))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_connection_span<F: Future>(
+    fut: F,
+    _remote: SocketAddr,
+) -> impl Future<Output = F::Output> {
+    fut
+}
+
+/// Instruments a stream's future with a span, nested within the current connection span. The
+/// topic is recorded once the stream's header has been received, via [record_stream_topic].
+#[cfg(feature = "tracing")]
+pub(crate) fn in_stream_span<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    fut.instrument(info_span!("stream", topic = field::Empty))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_stream_span<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    fut
+}
+
+/// Records the topic on the current stream span.
+#[cfg(feature = "tracing")]
+pub(crate) fn record_stream_topic(topic: &TopicName) {
+    Span::current().record("topic", field::display(topic));
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_stream_topic(_topic: &TopicName) {}
+
+/// Instruments a topic's future with a span carrying the topic name.
+///
+/// Topics outlive the stream that created them, so the span is detached from the current span.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_topic_span<F: Future>(
+    fut: F,
+    topic: &TopicName,
+) -> impl Future<Output = F::Output> {
+    fut.instrument(info_span!(parent: None, "topic", %topic))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_topic_span<F: Future>(
+    fut: F,
+    _topic: &TopicName,
+) -> impl Future<Output = F::Output> {
+    fut
+}
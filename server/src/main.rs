@@ -1,13 +1,26 @@
 use anyhow::Result;
 use clap::Parser;
-use env_logger::Builder;
-use log::error;
 use selium_server::{args::UserArgs, server::Server};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = UserArgs::parse();
 
+    init_logger(&args);
+
+    let server = Server::try_from(args)?;
+
+    if let Err(e) = server.listen().await {
+        report_error(e);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "tracing"))]
+fn init_logger(args: &UserArgs) {
+    use env_logger::Builder;
+
     let mut logger = Builder::new();
     logger
         .filter_module(
@@ -15,12 +28,36 @@ async fn main() -> Result<()> {
             args.verbose.log_level_filter(),
         )
         .init();
+}
 
-    let server = Server::try_from(args)?;
+#[cfg(feature = "tracing")]
+fn init_logger(args: &UserArgs) {
+    use tracing::level_filters::LevelFilter;
+    use tracing_subscriber::{filter::Targets, layer::SubscriberExt, util::SubscriberInitExt};
 
-    if let Err(e) = server.listen().await {
-        error!("Error occurred while accepting connections: {:?}", e);
-    }
+    let level = match args.verbose.log_level_filter() {
+        log::LevelFilter::Off => LevelFilter::OFF,
+        log::LevelFilter::Error => LevelFilter::ERROR,
+        log::LevelFilter::Warn => LevelFilter::WARN,
+        log::LevelFilter::Info => LevelFilter::INFO,
+        log::LevelFilter::Debug => LevelFilter::DEBUG,
+        log::LevelFilter::Trace => LevelFilter::TRACE,
+    };
 
-    Ok(())
+    let filter = Targets::new().with_target(env!("CARGO_PKG_NAME").replace('-', "_"), level);
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(filter)
+        .init();
+}
+
+#[cfg(not(feature = "tracing"))]
+fn report_error(e: anyhow::Error) {
+    log::error!("Error occurred while accepting connections: {:?}", e);
+}
+
+#[cfg(feature = "tracing")]
+fn report_error(e: anyhow::Error) {
+    tracing::error!("Error occurred while accepting connections: {:?}", e);
 }
@@ -2,19 +2,27 @@
 //! `<https://github.com/quinn-rs/quinn/blob/main/quinn/examples/server.rs>`
 
 use anyhow::{bail, Context, Result};
+use quinn::congestion::CubicConfig;
 use quinn::{Connection, IdleTimeout, ServerConfig};
 use rustls::server::AllowAnyAuthenticatedClient;
 use rustls::{Certificate, PrivateKey, RootCertStore};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
-use std::{fs, path::Path, sync::Arc};
-
-const ALPN_QUIC_HTTP: &[&[u8]] = &[b"hq-29"];
+use std::{fs, path::Path, sync::Arc, time::Duration};
 
 #[derive(Default)]
 pub struct ConfigOptions {
     pub keylog: bool,
     pub stateless_retry: bool,
-    pub max_idle_timeout: IdleTimeout,
+    /// How long a retry token issued to a client remains valid, or [None] for quinn's default.
+    pub retry_token_lifetime: Option<Duration>,
+    /// The congestion window in bytes that each connection starts with, or [None] for quinn's
+    /// default.
+    pub initial_congestion_window: Option<u64>,
+    pub idle_timeout: IdleTimeout,
+    pub keep_alive_interval: Option<Duration>,
+    pub alpn: String,
+    /// The size of the buffer for incoming datagrams, or [None] to refuse datagrams.
+    pub datagram_buffer_size: Option<usize>,
 }
 
 pub fn server_config(
@@ -30,19 +38,31 @@ pub fn server_config(
         .with_client_cert_verifier(client_cert_verifier)
         .with_single_cert(certs, key)?;
 
-    server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+    // Clients offering any other protocol are refused during the handshake
+    server_crypto.alpn_protocols = vec![options.alpn.into_bytes()];
     if options.keylog {
         server_crypto.key_log = Arc::new(rustls::KeyLogFile::new());
     }
 
     let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
     let transport_config = Arc::get_mut(&mut server_config.transport).unwrap();
-    transport_config.max_concurrent_uni_streams(0_u8.into());
-    transport_config.max_idle_timeout(Some(options.max_idle_timeout));
+    transport_config.max_idle_timeout(Some(options.idle_timeout));
+    transport_config.keep_alive_interval(options.keep_alive_interval);
+    transport_config.datagram_receive_buffer_size(options.datagram_buffer_size);
+    if let Some(window) = options.initial_congestion_window {
+        let mut congestion = CubicConfig::default();
+        congestion.initial_window(window);
+        transport_config.congestion_controller_factory(Arc::new(congestion));
+    }
+
     if options.stateless_retry {
         server_config.use_retry(true);
     }
 
+    if let Some(lifetime) = options.retry_token_lifetime {
+        server_config.retry_token_lifetime(lifetime);
+    }
+
     Ok(server_config)
 }
 
@@ -109,6 +129,20 @@ pub fn load_root_store<T: AsRef<Path>>(ca_file: T) -> Result<RootCertStore> {
     Ok(store)
 }
 
+/// Returns the hex-encoded SHA-512 fingerprint of the certificate that the client presented, or
+/// [None] if the client's identity is unknown.
+pub fn get_peer_identity(connection: &Connection) -> Option<String> {
+    let cert = get_pubkey_from_connection(connection).ok()?;
+    let fingerprint = hmac_sha512::Hash::hash(cert);
+
+    Some(
+        fingerprint
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    )
+}
+
 pub fn get_pubkey_from_connection(connection: &Connection) -> Result<Vec<u8>> {
     let peer_identity = connection
         .peer_identity()
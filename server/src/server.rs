@@ -1,35 +1,145 @@
-use crate::args::{LogArgs, UserArgs};
-use crate::quic::{load_root_store, read_certs, server_config, ConfigOptions};
+use crate::args::{
+    LogArgs, UserArgs, VirtualHost, DEFAULT_ALPN, DEFAULT_BIND_ADDR, DEFAULT_CA, DEFAULT_CERT,
+    DEFAULT_DATAGRAM_BUFFER_SIZE, DEFAULT_IDLE_TIMEOUT, DEFAULT_KEY, MIN_INITIAL_CONGESTION_WINDOW,
+};
+use crate::auth::Authenticator;
+use crate::datagram::DatagramRouter;
+use crate::logging::{self, debug, error, info};
+use crate::quic::{get_peer_identity, load_root_store, read_certs, server_config, ConfigOptions};
 use crate::topic::config::TopicConfig;
+use crate::topic::groups::{ConsumerGroups, CONSUMER_GROUPS_FILE};
 use crate::topic::{pubsub, reqrep, Sender, Socket};
 use anyhow::{anyhow, bail, Context, Result};
-use futures::{future::join_all, stream::FuturesUnordered, SinkExt, StreamExt};
-use log::{error, info};
+use futures::{
+    future::join_all,
+    stream::{self, FuturesUnordered},
+    FutureExt, SinkExt, StreamExt,
+};
 use quinn::{Connecting, Connection, Endpoint, IdleTimeout, VarInt};
-use selium_log::config::{FlushPolicy, LogConfig};
+use selium_log::config::{FlushPolicy, LogConfig, TimestampSource};
 use selium_log::MessageLog;
-use selium_protocol::error_codes::INVALID_TOPIC_NAME;
-use selium_protocol::{error_codes, BiStream, ErrorPayload, Frame, TopicName};
+use selium_protocol::error_codes::{
+    DATAGRAMS_UNSUPPORTED, LOG_WRITE_FAILED, ONE_WAY_STREAM_UNSUPPORTED, TOPIC_NOT_FOUND,
+    UNAUTHORIZED, UNKNOWN_ERROR, UNKNOWN_SERVER_NAME,
+};
+use selium_protocol::{
+    error_codes, BiStream, ErrorPayload, Frame, Offset, ReadHalf, ServerInfoPayload, StreamType,
+    TopicEvent, TopicName, TruncateTopicPayload, MAX_MESSAGE_SIZE,
+};
+use selium_std::errors::SeliumError;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, sync::Arc};
-use tokio::{sync::Mutex, task::JoinHandle};
+use tokio::{
+    select,
+    sync::{broadcast, oneshot, Mutex},
+    task::JoinHandle,
+};
 
 pub(crate) type SharedTopics = Arc<Mutex<HashMap<TopicName, Sender>>>;
 type SharedTopicHandles = Arc<Mutex<FuturesUnordered<JoinHandle<()>>>>;
+type TopicEvents = broadcast::Sender<TopicEvent>;
 
-pub struct Server {
+/// The number of topic events buffered for each topic watcher. Watchers that fall further behind
+/// are disconnected, rather than silently missing events.
+const TOPIC_EVENTS_CAPACITY: usize = 1024;
+
+/// A stream opened by a client. Publishers may register over a one-way stream, as they have
+/// nothing to receive from the server.
+enum IncomingStream {
+    Bi(BiStream),
+    Uni(ReadHalf),
+}
+
+/// The topics served to the clients of a single host, along with the settings used to open their
+/// logs.
+struct HostContext {
+    /// The server name (SNI) that the host serves, or [None] if there are no virtual hosts.
+    server_name: Option<String>,
     topics: SharedTopics,
     topic_handles: SharedTopicHandles,
     log_args: Arc<LogArgs>,
-    endpoint: Endpoint,
+    datagrams: Arc<DatagramRouter>,
+    /// Notifies topic watchers as topics are created and removed.
+    topic_events: TopicEvents,
+}
+
+impl HostContext {
+    fn new(server_name: Option<String>, log_args: LogArgs) -> Self {
+        Self {
+            server_name,
+            topics: Arc::new(Mutex::new(HashMap::new())),
+            topic_handles: Arc::new(Mutex::new(FuturesUnordered::new())),
+            log_args: Arc::new(log_args),
+            datagrams: Arc::default(),
+            topic_events: broadcast::channel(TOPIC_EVENTS_CAPACITY).0,
+        }
+    }
+}
+
+/// Routes each connection to the [HostContext] for the server name (SNI) that its client
+/// requested during the TLS handshake.
+enum Hosts {
+    /// Every connection shares the same topics, regardless of the server name requested.
+    Single(Arc<HostContext>),
+    /// Each server name has its own topics, and connections requesting any other server name are
+    /// rejected.
+    Virtual(HashMap<String, Arc<HostContext>>),
+}
+
+impl Hosts {
+    fn route(&self, server_name: Option<&str>) -> Option<Arc<HostContext>> {
+        match self {
+            Self::Single(host) => Some(host.clone()),
+            Self::Virtual(hosts) => server_name.and_then(|name| hosts.get(name)).cloned(),
+        }
+    }
+
+    fn all(&self) -> Vec<Arc<HostContext>> {
+        match self {
+            Self::Single(host) => vec![host.clone()],
+            Self::Virtual(hosts) => hosts.values().cloned().collect(),
+        }
+    }
+}
+
+pub struct Server {
+    hosts: Arc<Hosts>,
+    endpoints: Vec<Endpoint>,
+    authenticator: Arc<dyn Authenticator>,
+    started: Instant,
 }
 
 impl Server {
+    /// Returns a [ServerBuilder] for constructing a server without parsing command line
+    /// arguments.
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    /// Replaces the [Authenticator] used to admit new streams.
+    pub fn with_authenticator<A>(mut self, authenticator: A) -> Self
+    where
+        A: Authenticator + 'static,
+    {
+        self.authenticator = Arc::new(authenticator);
+        self
+    }
+
     pub async fn listen(&self) -> Result<()> {
+        // Merge incoming connections from every endpoint. Each endpoint's stream ends once the
+        // endpoint has been closed.
+        let mut incoming = stream::select_all(self.endpoints.iter().map(|endpoint| {
+            stream::unfold(endpoint, |endpoint| async move {
+                endpoint.accept().await.map(|conn| (conn, endpoint))
+            })
+            .boxed()
+        }));
+
         loop {
             tokio::select! {
-                Some(conn) = self.endpoint.accept() => {
+                Some(conn) = incoming.next() => {
                     self.connect(conn).await?;
                 },
                 Ok(()) = tokio::signal::ctrl_c() => {
@@ -42,101 +152,558 @@ impl Server {
         Ok(())
     }
 
+    /// The address of the first endpoint that the server is bound to.
     pub fn addr(&self) -> Result<SocketAddr> {
-        let addr = self.endpoint.local_addr()?;
-        Ok(addr)
+        self.handle().addr()
+    }
+
+    /// The addresses of every endpoint that the server is bound to, in the order that they were
+    /// provided.
+    pub fn addrs(&self) -> Result<Vec<SocketAddr>> {
+        self.handle().addrs()
+    }
+
+    /// Returns a cloneable [ServerHandle], which can be used to discover the server's bound
+    /// addresses once the server has been moved into the [listen](Server::listen) loop.
+    ///
+    /// The endpoints are bound when the server is constructed, so the addresses are available
+    /// immediately, even when binding to port 0.
+    pub fn handle(&self) -> ServerHandle {
+        ServerHandle {
+            endpoints: self.endpoints.clone(),
+        }
     }
 
     async fn connect(&self, conn: Connecting) -> Result<()> {
         info!("connection incoming");
-        let topics_clone = self.topics.clone();
-        let topic_handles = self.topic_handles.clone();
-        let log_args = self.log_args.clone();
+        let hosts = self.hosts.clone();
+        let authenticator = self.authenticator.clone();
+        let started = self.started;
+        let remote = conn.remote_address();
 
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(topics_clone, topic_handles, conn, log_args).await {
-                error!("connection failed: {:?}", e);
-            }
-        });
+        tokio::spawn(logging::in_connection_span(
+            async move {
+                if let Err(e) = handle_connection(hosts, conn, authenticator, started).await {
+                    error!("connection failed: {:?}", e);
+                }
+            },
+            remote,
+        ));
 
         Ok(())
     }
 
-    async fn shutdown(&self) -> Result<()> {
+    pub async fn shutdown(&self) -> Result<()> {
         info!("Shutdown signal received: preparing to gracefully shutdown.");
-        self.endpoint.reject_new_connections();
+        self.endpoints
+            .iter()
+            .for_each(Endpoint::reject_new_connections);
 
-        let mut topics = self.topics.lock().await;
-        let mut topic_handles = self.topic_handles.lock().await;
+        for host in self.hosts.all() {
+            let mut topics = host.topics.lock().await;
+            let mut topic_handles = host.topic_handles.lock().await;
 
-        topics.values_mut().for_each(|t| t.close_channel());
-        join_all(topic_handles.iter_mut()).await;
+            topics.values_mut().for_each(|t| t.close_channel());
+            // Release the lock so that topics being reaped can finish
+            drop(topics);
+            join_all(topic_handles.iter_mut()).await;
+        }
+
+        for endpoint in &self.endpoints {
+            endpoint.close(
+                VarInt::from_u32(error_codes::SHUTDOWN),
+                b"Scheduled shutdown.",
+            );
+        }
 
-        self.endpoint.close(
-            VarInt::from_u32(error_codes::SHUTDOWN),
-            b"Scheduled shutdown.",
-        );
-        self.endpoint.wait_idle().await;
+        join_all(self.endpoints.iter().map(Endpoint::wait_idle)).await;
 
         Ok(())
     }
 }
 
-impl TryFrom<UserArgs> for Server {
-    type Error = anyhow::Error;
+/// A cloneable handle to a [Server], for use by code that doesn't own the server.
+#[derive(Clone, Debug)]
+pub struct ServerHandle {
+    endpoints: Vec<Endpoint>,
+}
 
-    fn try_from(args: UserArgs) -> Result<Self, Self::Error> {
-        let root_store = load_root_store(args.cert.ca)?;
-        let (certs, key) = read_certs(args.cert.cert, args.cert.key)?;
-        let log_args = Arc::new(args.log);
+impl ServerHandle {
+    /// The address of the first endpoint that the server is bound to.
+    pub fn addr(&self) -> Result<SocketAddr> {
+        let endpoint = self.endpoints.first().context("Server has no endpoints")?;
+        let addr = endpoint.local_addr()?;
+        Ok(addr)
+    }
+
+    /// The addresses of every endpoint that the server is bound to, in the order that they were
+    /// provided.
+    pub fn addrs(&self) -> Result<Vec<SocketAddr>> {
+        let addrs = self
+            .endpoints
+            .iter()
+            .map(Endpoint::local_addr)
+            .collect::<Result<_, _>>()?;
+
+        Ok(addrs)
+    }
+}
+
+/// Constructs a [Server] programmatically, without parsing command line arguments, for embedding
+/// the server in another binary.
+///
+/// Every setting defaults to the same value as its command line argument in [UserArgs].
+///
+/// ```no_run
+/// # fn main() -> anyhow::Result<()> {
+/// use selium_server::server::Server;
+///
+/// let server = Server::builder()
+///     .bind_addr("127.0.0.1:7001".parse()?)
+///     .cert("certs/server/localhost.der")
+///     .key("certs/server/localhost.key.der")
+///     .ca("certs/server/ca.der")
+///     .log_segments_directory("/var/lib/selium/logs")
+///     .virtual_host("tenant-a.example.com", "/var/lib/selium/tenant-a")
+///     .virtual_host("tenant-b.example.com", "/var/lib/selium/tenant-b")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ServerBuilder {
+    bind_addrs: Vec<SocketAddr>,
+    ca: PathBuf,
+    cert: PathBuf,
+    key: PathBuf,
+    log: LogArgs,
+    stateless_retry: bool,
+    retry_token_lifetime: Option<u64>,
+    initial_congestion_window: Option<u64>,
+    keylog: bool,
+    idle_timeout: u32,
+    keep_alive_interval: Option<u64>,
+    alpn: String,
+    datagram_buffer_size: usize,
+    virtual_hosts: Vec<VirtualHost>,
+}
+
+impl Default for ServerBuilder {
+    fn default() -> Self {
+        Self {
+            bind_addrs: Vec::new(),
+            ca: PathBuf::from(DEFAULT_CA),
+            cert: PathBuf::from(DEFAULT_CERT),
+            key: PathBuf::from(DEFAULT_KEY),
+            log: LogArgs::default(),
+            stateless_retry: false,
+            retry_token_lifetime: None,
+            initial_congestion_window: None,
+            keylog: false,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            keep_alive_interval: None,
+            alpn: DEFAULT_ALPN.to_owned(),
+            datagram_buffer_size: DEFAULT_DATAGRAM_BUFFER_SIZE,
+            virtual_hosts: Vec::new(),
+        }
+    }
+}
+
+impl ServerBuilder {
+    /// Adds an address to bind the server to. Can be called multiple times to listen on several
+    /// addresses. Defaults to `127.0.0.1:7001` if no addresses are added.
+    pub fn bind_addr(mut self, addr: SocketAddr) -> Self {
+        self.bind_addrs.push(addr);
+        self
+    }
+
+    /// The path to the DER encoded TLS certificate presented to clients.
+    pub fn cert(mut self, path: impl AsRef<Path>) -> Self {
+        self.cert = path.as_ref().to_owned();
+        self
+    }
+
+    /// The path to the DER encoded private key for the TLS certificate.
+    pub fn key(mut self, path: impl AsRef<Path>) -> Self {
+        self.key = path.as_ref().to_owned();
+        self
+    }
+
+    /// The path to the DER encoded CA certificate used to verify client certificates.
+    pub fn ca(mut self, path: impl AsRef<Path>) -> Self {
+        self.ca = path.as_ref().to_owned();
+        self
+    }
+
+    /// The directory to store log segments in.
+    pub fn log_segments_directory(mut self, path: impl AsRef<Path>) -> Self {
+        self.log.log_segments_directory = path.as_ref().to_owned();
+        self
+    }
+
+    /// Creates any log directory that doesn't exist when the server is built. Otherwise, building
+    /// the server fails unless every log directory already exists. Enabled by default.
+    pub fn create_log_dir(mut self, enabled: bool) -> Self {
+        self.log.create_log_dir = enabled;
+        self
+    }
+
+    /// The interval at which each log's cleaner task removes expired segments.
+    pub fn log_cleaner_interval(mut self, interval: Duration) -> Self {
+        self.log.log_cleaner_interval = interval.as_millis() as u64;
+        self
+    }
+
+    /// The maximum number of entries per log segment.
+    pub fn log_maximum_entries(mut self, max_entries: u32) -> Self {
+        self.log.log_maximum_entries = max_entries;
+        self
+    }
+
+    /// The maximum size in bytes of each log segment.
+    pub fn log_segment_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.log.log_segment_max_bytes = max_bytes;
+        self
+    }
+
+    /// The timestamp used to expire log segments, and to resolve subscribers seeking by
+    /// timestamp.
+    pub fn log_timestamp_source(mut self, source: TimestampSource) -> Self {
+        self.log.log_timestamp_source = source;
+        self
+    }
+
+    /// Flushes each log to the filesystem after the provided number of writes.
+    pub fn flush_policy_num_writes(mut self, num_writes: u64) -> Self {
+        self.log.flush_policy_num_writes = Some(num_writes);
+        self
+    }
+
+    /// Flushes each log to the filesystem once the provided number of bytes are unflushed.
+    pub fn flush_policy_max_unflushed_bytes(mut self, max_bytes: u64) -> Self {
+        self.log.flush_policy_max_unflushed_bytes = Some(max_bytes);
+        self
+    }
+
+    /// The interval at which each log is asynchronously flushed to the filesystem.
+    pub fn flush_policy_interval(mut self, interval: Duration) -> Self {
+        self.log.flush_policy_interval = interval.as_millis() as u64;
+        self
+    }
+
+    /// Replaces every log and topic setting, including those assigned by this builder's other
+    /// log setters, for settings that don't have a dedicated setter.
+    pub fn log_args(mut self, args: LogArgs) -> Self {
+        self.log = args;
+        self
+    }
+
+    /// Enables stateless retries, requiring every new connection to validate its address with a
+    /// retry token before the server commits any state to it.
+    pub fn stateless_retry(mut self, enabled: bool) -> Self {
+        self.stateless_retry = enabled;
+        self
+    }
+
+    /// How long a retry token issued during a stateless retry remains valid. Saturates at
+    /// [u64::MAX] milliseconds.
+    pub fn retry_token_lifetime(mut self, lifetime: Duration) -> Self {
+        self.retry_token_lifetime = Some(u64::try_from(lifetime.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+
+    /// The congestion window in bytes that each connection starts with. Must be at least
+    /// [MIN_INITIAL_CONGESTION_WINDOW].
+    pub fn initial_congestion_window(mut self, window: u64) -> Self {
+        self.initial_congestion_window = Some(window);
+        self
+    }
+
+    /// Logs TLS keys to the file named by the `SSLKEYLOGFILE` environment variable, for
+    /// debugging.
+    pub fn keylog(mut self, enabled: bool) -> Self {
+        self.keylog = enabled;
+        self
+    }
+
+    /// The maximum time a client can idle waiting for data. Saturates at [u32::MAX]
+    /// milliseconds.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        self
+    }
+
+    /// The interval between keep-alive pings sent to idle clients, which must be no more than
+    /// half of the idle timeout.
+    pub fn keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval.as_millis() as u64);
+        self
+    }
+
+    /// The ALPN protocol identifier that clients must negotiate to connect.
+    pub fn alpn(mut self, alpn: &str) -> Self {
+        self.alpn = alpn.to_owned();
+        self
+    }
+
+    /// The size in bytes of the buffer for incoming QUIC datagrams. Set to 0 to disable
+    /// datagrams.
+    pub fn datagram_buffer_size(mut self, size: usize) -> Self {
+        self.datagram_buffer_size = size;
+        self
+    }
+
+    /// Hosts an isolated set of topics for clients requesting the provided TLS server name
+    /// (SNI), storing their log segments under the provided directory. Can be called multiple
+    /// times to host several server names.
+    ///
+    /// Once any virtual host is added, connections requesting any other server name are
+    /// rejected. Otherwise, every connection shares the same topics, stored under the log
+    /// segments directory.
+    ///
+    /// The server name alone doesn't isolate tenants, as every virtual host shares the server's
+    /// certificate authority and [Authenticator]. The authenticator is given the virtual host
+    /// that each stream was routed to, so that it can refuse clients that belong to another
+    /// tenant.
+    pub fn virtual_host(
+        mut self,
+        server_name: &str,
+        log_segments_directory: impl AsRef<Path>,
+    ) -> Self {
+        self.virtual_hosts.push(VirtualHost {
+            server_name: server_name.to_owned(),
+            log_segments_directory: log_segments_directory.as_ref().to_owned(),
+        });
+        self
+    }
+
+    /// Constructs the [Server], binding it to each of the provided addresses.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the certificates fail to load, if the settings are invalid, or if the
+    /// server fails to bind to any of its addresses.
+    pub fn build(self) -> Result<Server> {
+        let root_store = load_root_store(self.ca)?;
+        let (certs, key) = read_certs(self.cert, self.key)?;
+        let log_args = self.log;
+
+        for dir_override in &log_args.topic_log_directories {
+            if dir_override.directory.exists() && !dir_override.directory.is_dir() {
+                bail!(
+                    "Log directory for {} is not a directory: {}",
+                    dir_override.prefix,
+                    dir_override.directory.display()
+                );
+            }
+
+            check_log_directory(&dir_override.directory, log_args.create_log_dir)?;
+        }
+
+        // Virtual hosts store their topics under their own directories instead
+        if self.virtual_hosts.is_empty() {
+            check_log_directory(&log_args.log_segments_directory, log_args.create_log_dir)?;
+        }
+
+        for host in &self.virtual_hosts {
+            check_log_directory(&host.log_segments_directory, log_args.create_log_dir)?;
+        }
+
+        if log_args.topic_channel_size == 0 {
+            bail!("Topic channel size must be at least 1");
+        }
+
+        if let Some(window) = self.initial_congestion_window {
+            // Connections can't send anything with a window smaller than a couple of datagrams
+            if window < MIN_INITIAL_CONGESTION_WINDOW {
+                bail!(
+                    "Initial congestion window ({window} bytes) must be at least {MIN_INITIAL_CONGESTION_WINDOW} bytes"
+                );
+            }
+        }
+
+        if let Some(interval) = self.keep_alive_interval {
+            // Leave room for at least one more ping to be lost before the connection times out
+            if interval.saturating_mul(2) > u64::from(self.idle_timeout) {
+                bail!(
+                    "Keep-alive interval ({interval}ms) must be no more than half of the idle timeout ({}ms)",
+                    self.idle_timeout
+                );
+            }
+        }
 
         let opts = ConfigOptions {
-            keylog: args.keylog,
-            stateless_retry: args.stateless_retry,
-            max_idle_timeout: IdleTimeout::from(VarInt::from_u32(args.max_idle_timeout)),
+            keylog: self.keylog,
+            stateless_retry: self.stateless_retry,
+            retry_token_lifetime: self.retry_token_lifetime.map(Duration::from_millis),
+            initial_congestion_window: self.initial_congestion_window,
+            idle_timeout: IdleTimeout::from(VarInt::from_u32(self.idle_timeout)),
+            keep_alive_interval: self.keep_alive_interval.map(Duration::from_millis),
+            alpn: self.alpn,
+            datagram_buffer_size: (self.datagram_buffer_size > 0)
+                .then_some(self.datagram_buffer_size),
+        };
+
+        let bind_addrs = if self.bind_addrs.is_empty() {
+            vec![DEFAULT_BIND_ADDR.parse()?]
+        } else {
+            self.bind_addrs
         };
 
         let config = server_config(root_store, certs, key, opts)?;
-        let endpoint = Endpoint::server(config, args.bind_addr)?;
+        let endpoints = bind_addrs
+            .into_iter()
+            .map(|addr| Endpoint::server(config.clone(), addr))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let hosts = if self.virtual_hosts.is_empty() {
+            Hosts::Single(Arc::new(HostContext::new(None, log_args)))
+        } else {
+            let mut hosts = HashMap::new();
+
+            for host in &self.virtual_hosts {
+                let context = HostContext::new(
+                    Some(host.server_name.clone()),
+                    log_args.for_virtual_host(host),
+                );
 
-        // Create hash to store message ordering data
-        let topics = Arc::new(Mutex::new(HashMap::new()));
-        let topic_handles = Arc::new(Mutex::new(FuturesUnordered::new()));
+                if hosts
+                    .insert(host.server_name.clone(), Arc::new(context))
+                    .is_some()
+                {
+                    bail!("Duplicate virtual host: {}", host.server_name);
+                }
+            }
+
+            Hosts::Virtual(hosts)
+        };
 
-        Ok(Self {
-            topics,
-            topic_handles,
-            log_args,
-            endpoint,
+        #[cfg(feature = "__cloud")]
+        let authenticator = match &hosts {
+            Hosts::Single(host) => {
+                Arc::new(crate::cloud::CloudAuthenticator::new(host.topics.clone()))
+            }
+            Hosts::Virtual(_) => bail!("Virtual hosts are not supported in the cloud"),
+        };
+        #[cfg(not(feature = "__cloud"))]
+        let authenticator = Arc::new(crate::auth::AllowAll);
+
+        Ok(Server {
+            hosts: Arc::new(hosts),
+            endpoints,
+            authenticator,
+            started: Instant::now(),
         })
     }
 }
 
+// Ensures that a log directory exists, creating it if allowed, and that its log segments can be
+// written, so that a misconfigured directory stops the server from starting rather than failing
+// each topic as it's opened
+fn check_log_directory(dir: &Path, create: bool) -> Result<()> {
+    if !dir.exists() {
+        if !create {
+            bail!(
+                "Log directory does not exist: {} (use --create-log-dir to create it)",
+                dir.display()
+            );
+        }
+
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory: {}", dir.display()))?;
+    }
+
+    let probe = dir.join(".selium-write-check");
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .with_context(|| format!("Log directory is not writable: {}", dir.display()))
+}
+
+impl From<UserArgs> for ServerBuilder {
+    fn from(args: UserArgs) -> Self {
+        Self {
+            bind_addrs: args.bind_addr,
+            ca: args.cert.ca,
+            cert: args.cert.cert,
+            key: args.cert.key,
+            log: args.log,
+            stateless_retry: args.stateless_retry,
+            retry_token_lifetime: args.retry_token_lifetime,
+            initial_congestion_window: args.initial_congestion_window,
+            keylog: args.keylog,
+            idle_timeout: args.idle_timeout,
+            keep_alive_interval: args.keep_alive_interval,
+            alpn: args.alpn,
+            datagram_buffer_size: args.datagram_buffer_size,
+            virtual_hosts: args.virtual_hosts,
+        }
+    }
+}
+
+impl TryFrom<UserArgs> for Server {
+    type Error = anyhow::Error;
+
+    fn try_from(args: UserArgs) -> Result<Self, Self::Error> {
+        ServerBuilder::from(args).build()
+    }
+}
+
 async fn handle_connection(
-    topics: SharedTopics,
-    topic_handles: SharedTopicHandles,
+    hosts: Arc<Hosts>,
     conn: quinn::Connecting,
-    log_args: Arc<LogArgs>,
+    authenticator: Arc<dyn Authenticator>,
+    started: Instant,
 ) -> Result<()> {
     let connection = conn.await?;
+    let handshake = connection
+        .handshake_data()
+        .unwrap()
+        .downcast::<quinn::crypto::rustls::HandshakeData>()
+        .unwrap();
+
     info!(
         "Connection {} - {}",
         connection.remote_address(),
-        connection
-            .handshake_data()
-            .unwrap()
-            .downcast::<quinn::crypto::rustls::HandshakeData>()
-            .unwrap()
-            .protocol
-            .map_or_else(
-                || "<none>".into(),
-                |x| String::from_utf8_lossy(&x).into_owned()
-            )
+        handshake.protocol.as_ref().map_or_else(
+            || "<none>".into(),
+            |x| String::from_utf8_lossy(x).into_owned()
+        )
     );
 
+    let host = match hosts.route(handshake.server_name.as_deref()) {
+        Some(host) => host,
+        None => {
+            info!(
+                "Rejected connection for unknown server name: {}",
+                handshake.server_name.as_deref().unwrap_or("<none>")
+            );
+            connection.close(
+                VarInt::from_u32(UNKNOWN_SERVER_NAME),
+                b"Unknown server name",
+            );
+            return Ok(());
+        }
+    };
+
+    // Datagrams are read for as long as the connection remains open
+    tokio::spawn({
+        let connection = connection.clone();
+        let authenticator = authenticator.clone();
+        let datagrams = host.datagrams.clone();
+        let server_name = host.server_name.clone();
+        async move {
+            datagrams
+                .forward(connection, authenticator, server_name)
+                .await
+        }
+    });
+
     loop {
         let connection = connection.clone();
-        let stream = connection.accept_bi().await;
+        let stream = select! {
+            stream = connection.accept_bi() => stream.map(|stream| IncomingStream::Bi(stream.into())),
+            stream = connection.accept_uni() => stream.map(|stream| IncomingStream::Uni(stream.into())),
+        };
         let stream = match stream {
             Err(quinn::ConnectionError::ApplicationClosed { .. }) => {
                 info!("Connection closed ({})", connection.remote_address());
@@ -145,143 +712,551 @@ async fn handle_connection(
             Err(e) => {
                 bail!(e)
             }
-            Ok(stream) => BiStream::from(stream),
+            Ok(stream) => stream,
         };
 
-        let topics_clone = topics.clone();
-        let topic_handles_clone = topic_handles.clone();
-        let log_args = log_args.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) = handle_stream(
-                topics_clone,
-                topic_handles_clone,
-                stream,
-                connection,
-                log_args,
-            )
-            .await
-            {
+        let host = host.clone();
+        let authenticator = authenticator.clone();
+
+        tokio::spawn(logging::in_stream_span(async move {
+            let result = match stream {
+                IncomingStream::Bi(stream) => {
+                    handle_stream(host, stream, connection, authenticator, started).await
+                }
+                IncomingStream::Uni(stream) => {
+                    handle_uni_stream(host, stream, connection, authenticator).await
+                }
+            };
+
+            if let Err(e) = result {
                 error!("Request failed: {:?}", e);
             }
-        });
+        }));
+    }
+}
+
+async fn server_info(
+    topics: &SharedTopics,
+    log_args: &LogArgs,
+    started: Instant,
+) -> ServerInfoPayload {
+    ServerInfoPayload {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        git_hash: option_env!("SELIUM_GIT_HASH").map(ToOwned::to_owned),
+        uptime_secs: started.elapsed().as_secs(),
+        max_frame_size: MAX_MESSAGE_SIZE,
+        max_message_bytes: log_args.max_message_bytes.map(|bytes| bytes as u64),
+        topic_count: topics.lock().await.len() as u64,
+    }
+}
+
+// Sends each topic event to the client until it closes the stream. A watcher that falls behind
+// is sent an error and disconnected, so that it knows its view of the topics is incomplete.
+async fn watch_topics(
+    mut stream: BiStream,
+    mut events: broadcast::Receiver<TopicEvent>,
+) -> Result<()> {
+    stream.send(Frame::Ok).await?;
+
+    loop {
+        select! {
+            event = events.recv() => match event {
+                Ok(event) => stream.send(Frame::TopicEvent(event)).await?,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    let payload = ErrorPayload {
+                        code: UNKNOWN_ERROR,
+                        message: format!("Topic watcher missed {missed} events").into(),
+                    };
+                    stream.send(Frame::Error(payload)).await?;
+                    return Ok(());
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            frame = stream.next() => {
+                if !matches!(frame, Some(Ok(_))) {
+                    return Ok(());
+                }
+            }
+        }
     }
 }
 
+// Removes a topic that has stopped running, notifying topic watchers of its removal
+fn remove_topic(topics: &mut HashMap<TopicName, Sender>, events: &TopicEvents, topic: &TopicName) {
+    if topics.remove(topic).is_some() {
+        let _ = events.send(TopicEvent::Removed(topic.clone()));
+    }
+}
+
+/// Opens the topic that a new stream of type `stream_type` is registering with, announcing it to
+/// topic watchers. Publishers and subscribers open a log for the topic, using the retention policy
+/// from their header `frame`.
+async fn spawn_topic(
+    host: &HostContext,
+    ts: &mut HashMap<TopicName, Sender>,
+    topic: &TopicName,
+    frame: &Frame,
+    stream_type: StreamType,
+) -> Result<()> {
+    let HostContext {
+        topics,
+        topic_handles,
+        log_args,
+        topic_events,
+        ..
+    } = host;
+
+    match stream_type {
+        StreamType::Publisher | StreamType::Subscriber => {
+            let retention_period = frame.retention_policy().unwrap();
+            let topic_path = topic.to_string();
+            let segments_path = log_args
+                .segments_directory(topic)
+                .join(topic_path.trim_matches('/'));
+
+            let mut flush_policy = FlushPolicy::default()
+                .interval(Duration::from_millis(log_args.flush_policy_interval));
+
+            if let Some(num_writes) = log_args.flush_policy_num_writes {
+                flush_policy = flush_policy.number_of_writes(num_writes);
+            }
+
+            if let Some(max_bytes) = log_args.flush_policy_max_unflushed_bytes {
+                flush_policy = flush_policy.max_unflushed_bytes(max_bytes);
+            }
+
+            let mut topic_config = TopicConfig::new(
+                Duration::from_millis(log_args.subscriber_polling_interval),
+                Duration::from_millis(log_args.subscriber_max_polling_interval),
+            )
+            .dedup_window(log_args.dedup_window)
+            .dedup_producer_timeout(Duration::from_millis(log_args.dedup_producer_timeout))
+            .coalesce_max_bytes(log_args.subscriber_batch_max_bytes)
+            .channel_size(log_args.topic_channel_size)
+            .write_batch_size(log_args.write_batch_size)
+            .write_batch_delay(Duration::from_millis(log_args.write_batch_delay))
+            .reservation_timeout(Duration::from_millis(log_args.reservation_timeout));
+
+            if let Some(max_bytes) = log_args.max_message_bytes {
+                topic_config = topic_config.max_message_bytes(max_bytes);
+            }
+
+            if let Some(threshold) = log_args.subscriber_lag_warning {
+                topic_config = topic_config.lag_warning_threshold(threshold);
+            }
+
+            if let Some(read_ahead) = log_args.subscriber_read_ahead {
+                topic_config = topic_config.read_ahead(read_ahead);
+            }
+
+            if let Some(idle_timeout) = log_args.topic_idle_timeout {
+                topic_config = topic_config.idle_timeout(Duration::from_millis(idle_timeout));
+            }
+
+            if let Some(schema) = log_args.topic_schema(topic) {
+                topic_config = topic_config.schema(schema.clone());
+            }
+
+            let topic_config = Arc::new(topic_config);
+
+            let log_config = Arc::new(
+                LogConfig::from_path(&segments_path)
+                    .max_index_entries(log_args.log_maximum_entries)
+                    .segment_max_bytes(log_args.log_segment_max_bytes)
+                    .retention_period(Duration::from_millis(retention_period))
+                    .cleaner_interval(Duration::from_millis(log_args.log_cleaner_interval))
+                    .flush_policy(flush_policy)
+                    .timestamp_source(log_args.log_timestamp_source),
+            );
+
+            let log = MessageLog::open(log_config).await?;
+            let groups = ConsumerGroups::open(segments_path.join(CONSUMER_GROUPS_FILE)).await?;
+            let (fut, tx) = pubsub::Topic::pair(log, topic_config);
+            let mut fut = fut.with_consumer_groups(Arc::new(groups));
+            let topics = topics.clone();
+            let topic_events = topic_events.clone();
+            let topic_name = topic.clone();
+
+            let handle = tokio::spawn(logging::in_topic_span(
+                async move {
+                    loop {
+                        match fut.run().await {
+                            Ok(pubsub::TopicExit::Idle) => (),
+                            Ok(pubsub::TopicExit::Closed) => break,
+                            Err(e) => {
+                                // Remove the topic so that it's reopened by the next
+                                // stream, rather than leaving streams stranded on it
+                                error!("Topic stopped unexpectedly: {e:?}");
+                                let mut ts = topics.lock().await;
+                                remove_topic(&mut ts, &topic_events, &topic_name);
+                                break;
+                            }
+                        }
+
+                        // Hold the lock so that no new streams can be added to the topic
+                        // while it's being reaped
+                        let mut ts = topics.lock().await;
+
+                        match fut.try_reap().await {
+                            Ok(true) => {
+                                info!("Reaped idle topic");
+                                remove_topic(&mut ts, &topic_events, &topic_name);
+                                break;
+                            }
+                            Ok(false) => (),
+                            Err(e) => {
+                                error!("Failed to reap idle topic: {e:?}");
+                                remove_topic(&mut ts, &topic_events, &topic_name);
+                                break;
+                            }
+                        }
+                    }
+                },
+                topic,
+            ));
+
+            let mut handles = topic_handles.lock().await;
+            // Discard the handles of topics that have since been reaped
+            while let Some(Some(_)) = handles.next().now_or_never() {}
+            handles.push(handle);
+            ts.insert(topic.clone(), Sender::Pubsub(tx, None));
+        }
+        StreamType::Replier | StreamType::Requestor => {
+            let (fut, tx, bound) = reqrep::Topic::pair();
+            let handle = tokio::spawn(logging::in_topic_span(fut, topic));
+
+            topic_handles.lock().await.push(handle);
+            ts.insert(topic.clone(), Sender::ReqRep(tx, bound));
+        }
+    };
+
+    let _ = topic_events.send(TopicEvent::Created(topic.clone()));
+    Ok(())
+}
+
+// Stops a one-way stream with the provided error code, as there is no other way to tell the client
+// why its stream was refused
+fn refuse_uni_stream(stream: &mut ReadHalf, code: u32) {
+    let _ = stream.stop(VarInt::from_u32(code));
+}
+
+// Registers a publisher over a one-way stream. The stream is never acknowledged, and anything the
+// topic would send to the publisher, such as acknowledgements, is discarded.
+async fn handle_uni_stream(
+    host: Arc<HostContext>,
+    mut stream: ReadHalf,
+    connection: Connection,
+    authenticator: Arc<dyn Authenticator>,
+) -> Result<()> {
+    let frame = match stream.next().await {
+        Some(result) => result?,
+        None => {
+            info!("Stream closed");
+            return Ok(());
+        }
+    };
+
+    let compressed = match &frame {
+        Frame::RegisterPublisher(payload) => payload.compression.is_some(),
+        _ => {
+            debug!("Refused one-way stream that isn't a publisher");
+            refuse_uni_stream(&mut stream, ONE_WAY_STREAM_UNSUPPORTED);
+            return Ok(());
+        }
+    };
+
+    let topic = stream
+        .get_path()
+        .cloned()
+        .ok_or(anyhow!("Expected header frame"))?;
+    logging::record_stream_topic(&topic);
+
+    if let Err(e) = authenticator
+        .authorize(&connection, host.server_name.as_deref(), &frame)
+        .await
+    {
+        debug!("Authentication error: {e:?}");
+        refuse_uni_stream(&mut stream, authenticator.error_code());
+        return Ok(());
+    }
+
+    #[cfg(not(feature = "__cloud"))]
+    {
+        // Note this can only occur if someone circumvents the client lib
+        if !topic.is_valid() {
+            refuse_uni_stream(&mut stream, error_codes::INVALID_TOPIC_NAME);
+            return Ok(());
+        }
+    }
+
+    let mut ts = host.topics.lock().await;
+
+    if !ts.contains_key(&topic) {
+        if let Err(e) = spawn_topic(&host, &mut ts, &topic, &frame, StreamType::Publisher).await {
+            error!("Failed to open topic: {e:?}");
+            refuse_uni_stream(&mut stream, LOG_WRITE_FAILED);
+            return Ok(());
+        }
+    }
+
+    let tx = ts
+        .get_mut(&topic)
+        .ok_or(anyhow!("Topic was closed while opening stream"))?;
+
+    if let Err(payload) = tx.admit(&frame) {
+        refuse_uni_stream(&mut stream, payload.code);
+        return Ok(());
+    }
+
+    let sink = futures::sink::drain().sink_map_err(|never| -> SeliumError { match never {} });
+    tx.send(Socket::Pubsub(pubsub::Socket::Stream(
+        Box::pin(stream),
+        Box::pin(sink),
+        compressed,
+    )))
+    .await
+    .context("Failed to add Publisher stream")?;
+
+    Ok(())
+}
+
 async fn handle_stream(
-    topics: SharedTopics,
-    topic_handles: SharedTopicHandles,
+    host: Arc<HostContext>,
     mut stream: BiStream,
-    _connection: Connection,
-    log_args: Arc<LogArgs>,
+    connection: Connection,
+    authenticator: Arc<dyn Authenticator>,
+    started: Instant,
 ) -> Result<()> {
+    let HostContext {
+        topics,
+        log_args,
+        datagrams,
+        topic_events,
+        ..
+    } = &*host;
+
     // Receive header
     if let Some(result) = stream.next().await {
         let frame = result?;
-        let topic = frame.get_topic().ok_or(anyhow!("Expected header frame"))?;
 
-        #[cfg(feature = "__cloud")]
+        // Health checks only confirm that the server is accepting streams, so they're answered
+        // without authorizing the stream or touching any topics
+        if let Frame::Health = frame {
+            stream.send(Frame::Ok).await?;
+            return Ok(());
+        }
+
+        let topic = stream.get_path().cloned();
+
+        if let Some(topic) = &topic {
+            logging::record_stream_topic(topic);
+        }
+
+        if let Err(e) = authenticator
+            .authorize(&connection, host.server_name.as_deref(), &frame)
+            .await
         {
-            use crate::cloud::do_cloud_auth;
-            use log::debug;
-            use selium_protocol::error_codes::CLOUD_AUTH_FAILED;
+            debug!("Authentication error: {e:?}");
 
-            match do_cloud_auth(&_connection, topic, &topics).await {
-                Ok(_) => stream.send(Frame::Ok).await?,
-                Err(e) => {
-                    debug!("Cloud authentication error: {e:?}");
+            let payload = ErrorPayload {
+                code: authenticator.error_code(),
+                message: e.to_string().into(),
+            };
 
-                    let payload = ErrorPayload {
-                        code: CLOUD_AUTH_FAILED,
-                        message: e.to_string().into(),
-                    };
+            stream.send(Frame::Error(payload)).await?;
 
-                    stream.send(Frame::Error(payload)).await?;
+            return Ok(());
+        }
 
-                    return Ok(());
-                }
-            }
+        // Server info queries don't relate to any topic, but are still subject to authorization,
+        // as they expose the server's configuration
+        if let Frame::QueryServerInfo = frame {
+            let payload = server_info(topics, log_args, started).await;
+            stream.send(Frame::ServerInfo(payload)).await?;
+            return Ok(());
         }
+
+        // Likewise, topic watchers observe every topic, and last until the client closes the
+        // stream
+        if let Frame::WatchTopics = frame {
+            return watch_topics(stream, topic_events.subscribe()).await;
+        }
+
+        let topic = topic.ok_or(anyhow!("Expected header frame"))?;
+
         #[cfg(not(feature = "__cloud"))]
         {
             // Note this can only occur if someone circumvents the client lib
             if !topic.is_valid() {
                 let payload = ErrorPayload {
-                    code: INVALID_TOPIC_NAME,
+                    code: error_codes::INVALID_TOPIC_NAME,
                     message: "Invalid topic name".into(),
                 };
                 stream.send(Frame::Error(payload)).await?;
                 return Ok(());
             }
+        }
+
+        // Datagram subscriptions bypass the topic entirely, and last until the client closes the
+        // stream
+        if let Frame::RegisterDatagramSubscriber(_) = frame {
+            if connection.max_datagram_size().is_none() {
+                let payload = ErrorPayload {
+                    code: DATAGRAMS_UNSUPPORTED,
+                    message: "Datagrams are disabled for this connection".into(),
+                };
+                stream.send(Frame::Error(payload)).await?;
+                return Ok(());
+            }
+
+            let _registration = datagrams.register(topic, &connection);
             stream.send(Frame::Ok).await?;
+            while let Some(Ok(_)) = stream.next().await {}
+
+            return Ok(());
         }
 
         let mut ts = topics.lock().await;
 
-        // Spawn new topic if it doesn't exist yet
-        if !ts.contains_key(topic) {
-            match frame {
-                Frame::RegisterPublisher(_) | Frame::RegisterSubscriber(_) => {
-                    let retention_period = frame.retention_policy().unwrap();
-                    let topic_path = topic.to_string();
-                    let segments_path = log_args
-                        .log_segments_directory
-                        .join(topic_path.trim_matches('/'));
-
-                    let mut flush_policy = FlushPolicy::default()
-                        .interval(Duration::from_millis(log_args.flush_policy_interval));
-
-                    if let Some(num_writes) = log_args.flush_policy_num_writes {
-                        flush_policy = flush_policy.number_of_writes(num_writes);
-                    }
+        // Querying offsets shouldn't create the topic, as there is nothing to retain
+        if let Frame::QueryOffsets(_) = frame {
+            stream.send(Frame::Ok).await?;
 
-                    let topic_config = Arc::new(TopicConfig::new(Duration::from_millis(
-                        log_args.subscriber_polling_interval,
-                    )));
+            let frame = match ts.get_mut(&topic) {
+                Some(Sender::Pubsub(tx, _)) => {
+                    let (offsets_tx, offsets_rx) = oneshot::channel();
+                    tx.send(pubsub::Socket::Offsets(offsets_tx))
+                        .await
+                        .context("Failed to query topic offsets")?;
+                    drop(ts);
 
-                    let log_config = Arc::new(
-                        LogConfig::from_path(segments_path)
-                            .max_index_entries(log_args.log_maximum_entries)
-                            .retention_period(Duration::from_millis(retention_period))
-                            .cleaner_interval(Duration::from_millis(log_args.log_cleaner_interval))
-                            .flush_policy(flush_policy),
-                    );
+                    Frame::Offsets(offsets_rx.await?)
+                }
+                _ => Frame::Error(ErrorPayload {
+                    code: TOPIC_NOT_FOUND,
+                    message: "Topic not found".into(),
+                }),
+            };
 
-                    let log = MessageLog::open(log_config).await?;
-                    let (mut fut, tx) = pubsub::Topic::pair(log, topic_config);
+            stream.send(frame).await?;
+            return Ok(());
+        }
 
-                    let handle = tokio::spawn(async move {
-                        fut.run().await.unwrap();
-                    });
+        // Likewise, truncating a topic shouldn't create it, as there is nothing to remove. As
+        // truncation permanently discards messages, it must also be enabled explicitly on top of
+        // the usual authorization. The result is sent as a single reply.
+        if let Frame::TruncateTopic(TruncateTopicPayload { offset, .. }) = frame {
+            if !log_args.allow_topic_truncation {
+                drop(ts);
 
-                    topic_handles.lock().await.push(handle);
-                    ts.insert(topic.clone(), Sender::Pubsub(tx));
-                }
-                Frame::RegisterReplier(_) | Frame::RegisterRequestor(_) => {
-                    let (fut, tx) = reqrep::Topic::pair();
-                    let handle = tokio::spawn(fut);
+                let payload = ErrorPayload {
+                    code: UNAUTHORIZED,
+                    message: "Topic truncation is disabled on this server".into(),
+                };
+                stream.send(Frame::Error(payload)).await?;
+                return Ok(());
+            }
+
+            let frame = match ts.get_mut(&topic) {
+                Some(Sender::Pubsub(tx, _)) => {
+                    let (offsets_tx, offsets_rx) = oneshot::channel();
+                    tx.send(pubsub::Socket::Truncate(offset, offsets_tx))
+                        .await
+                        .context("Failed to truncate topic")?;
+                    drop(ts);
 
-                    topic_handles.lock().await.push(handle);
-                    ts.insert(topic.clone(), Sender::ReqRep(tx));
+                    match offsets_rx.await? {
+                        Ok(payload) => Frame::Offsets(payload),
+                        Err(e) => Frame::Error(ErrorPayload {
+                            code: UNKNOWN_ERROR,
+                            message: e.to_string().into(),
+                        }),
+                    }
                 }
-                _ => unreachable!(), // because of `topic` instantiation
+                _ => Frame::Error(ErrorPayload {
+                    code: TOPIC_NOT_FOUND,
+                    message: "Topic not found".into(),
+                }),
             };
+
+            stream.send(frame).await?;
+            return Ok(());
         }
 
-        let tx = ts.get_mut(topic).unwrap();
+        // Spawn new topic if it doesn't exist yet
+        if !ts.contains_key(&topic) {
+            // Offset queries and truncations are handled above, so the stream has a type
+            let stream_type = stream.stream_type().unwrap();
 
-        match frame {
-            Frame::RegisterPublisher(_) => {
-                let (_, read) = stream.split();
-                tx.send(Socket::Pubsub(pubsub::Socket::Stream(Box::pin(read))))
+            if let Err(e) = spawn_topic(&host, &mut ts, &topic, &frame, stream_type).await {
+                error!("Failed to open topic: {e:?}");
+                drop(ts);
+
+                let payload = ErrorPayload {
+                    code: LOG_WRITE_FAILED,
+                    message: format!("Failed to open topic log: {e}").into(),
+                };
+                stream.send(Frame::Error(payload)).await?;
+                return Ok(());
+            }
+        }
+
+        // Resolve live subscriptions against the log before acknowledging the stream, so that any
+        // message published once the client has been acknowledged is delivered
+        let frame = match (frame, ts.get(&topic)) {
+            (Frame::RegisterSubscriber(mut payload), Some(Sender::Pubsub(tx, _)))
+                if payload.offset == Offset::Latest =>
+            {
+                let mut tx = tx.clone();
+                // Release the lock while waiting, as an idle topic can't answer until it has
+                // been given the chance to be reaped
+                drop(ts);
+
+                let (offsets_tx, offsets_rx) = oneshot::channel();
+                tx.send(pubsub::Socket::Offsets(offsets_tx))
                     .await
-                    .context("Failed to add Publisher stream")?;
+                    .context("Failed to query topic offsets")?;
+                payload.offset = Offset::FromBeginning(offsets_rx.await?.end);
+
+                ts = topics.lock().await;
+                Frame::RegisterSubscriber(payload)
+            }
+            (frame, _) => frame,
+        };
+
+        let tx = ts
+            .get_mut(&topic)
+            .ok_or(anyhow!("Topic was closed while opening stream"))?;
+
+        // Only acknowledge the stream once we know that the topic will accept it
+        if let Err(payload) = tx.admit(&frame) {
+            stream.send(Frame::Error(payload)).await?;
+            return Ok(());
+        }
+
+        stream.send(Frame::Ok).await?;
+
+        match frame {
+            Frame::RegisterPublisher(payload) => {
+                let (write, read) = stream.split();
+                tx.send(Socket::Pubsub(pubsub::Socket::Stream(
+                    Box::pin(read),
+                    Box::pin(write),
+                    payload.compression.is_some(),
+                )))
+                .await
+                .context("Failed to add Publisher stream")?;
             }
             Frame::RegisterSubscriber(payload) => {
-                let (write, _) = stream.split();
+                let (write, read) = stream.split();
                 tx.send(Socket::Pubsub(pubsub::Socket::Sink(
                     Box::pin(write),
+                    Box::pin(read),
                     payload.offset,
+                    payload.compression.is_none(),
+                    payload.filter_headers,
+                    payload.group,
+                    payload.prioritise,
                 )))
                 .await
                 .context("Failed to add Subscriber sink")?;
@@ -297,10 +1272,10 @@ async fn handle_stream(
             }
             Frame::RegisterRequestor(_) => {
                 let (si, st) = stream.split();
-                tx.send(Socket::Reqrep(reqrep::Socket::Client((
-                    Box::pin(si),
-                    Box::pin(st),
-                ))))
+                tx.send(Socket::Reqrep(reqrep::Socket::Client(
+                    (Box::pin(si), Box::pin(st)),
+                    get_peer_identity(&connection),
+                )))
                 .await
                 .context("Failed to add Requestor")?;
             }
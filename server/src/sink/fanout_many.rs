@@ -9,9 +9,9 @@ use std::{
     task::{Context, Poll},
 };
 
+use crate::logging::error;
 use anyhow::Result;
 use futures::Sink;
-use log::error;
 use tokio::pin;
 
 #[must_use = "sinks do nothing unless you poll them"]
@@ -5,7 +5,7 @@ use std::{
 };
 
 use futures::{ready, Sink, Stream};
-use log::{debug, trace};
+use crate::logging::{debug, trace};
 use pin_project_lite::pin_project;
 
 pin_project! {
@@ -9,9 +9,9 @@ use std::{
     task::{Context, Poll},
 };
 
+use crate::logging::error;
 use anyhow::{anyhow, Result};
 use futures::Sink;
-use log::error;
 use selium_protocol::{Frame, MessagePayload};
 use tokio::pin;
 
@@ -128,6 +128,9 @@ where
         if let Err(e) = sink.start_send(Frame::Message(MessagePayload {
             headers,
             message: payload.message,
+            ttl: payload.ttl,
+            offset: None,
+            sequence_id: None,
         })) {
             error!("Evicting broken sink from Router::start_send with err: {e:?}");
             self.entries.remove(&cid);
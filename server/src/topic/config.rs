@@ -1,14 +1,168 @@
+use super::schema::Schema;
 use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
 
 pub type SharedTopicConfig = Arc<TopicConfig>;
 
+/// The default number of sequences retained per producer for deduplication.
+pub const DEDUP_WINDOW_DEFAULT: usize = 1000;
+
+/// The default duration after which an idle producer's sequences are forgotten.
+pub const DEDUP_PRODUCER_TIMEOUT_DEFAULT: Duration = Duration::from_secs(60 * 60);
+
+/// The default duration after which uncommitted reservations are aborted.
+pub const RESERVATION_TIMEOUT_DEFAULT: Duration = Duration::from_secs(60);
+
+/// The default maximum size in bytes of batches coalesced for subscribers.
+pub const COALESCE_MAX_BYTES_DEFAULT: usize = 64 * 1024;
+
+/// The default capacity of the channels used to hand sockets to a topic and its subscribers.
+pub const CHANNEL_SIZE_DEFAULT: usize = 100;
+
 #[derive(Debug)]
 pub struct TopicConfig {
-    pub polling_interval: Duration,
+    /// The interval used to poll the log immediately after receiving messages.
+    pub min_polling_interval: Duration,
+    /// The upper bound that the polling interval will back off to while the log is idle.
+    pub max_polling_interval: Duration,
+    /// Notifies waiting subscribers that reserved messages have been committed or aborted. New
+    /// messages are announced by the log itself once they've been flushed.
+    pub reservations_resolved: Notify,
+    /// The number of recent sequences retained per idempotent producer, used to discard
+    /// duplicate messages. A window of 0 disables deduplication.
+    pub dedup_window: usize,
+    /// The duration after which the sequences of a producer that hasn't sent any messages are
+    /// forgotten, so that the deduplicator doesn't grow with every producer ever seen.
+    pub dedup_producer_timeout: Duration,
+    /// The duration after which a message reserved by a publisher is aborted if it hasn't been
+    /// committed, so that subscribers waiting on it can move on.
+    pub reservation_timeout: Duration,
+    /// The duration after which a topic with no publishers or subscribers is closed, or [None]
+    /// to keep topics open indefinitely.
+    pub idle_timeout: Option<Duration>,
+    /// The maximum size in bytes of the batches that consecutive messages are coalesced into when
+    /// sent to subscribers. A size of 0 disables coalescing.
+    pub coalesce_max_bytes: usize,
+    /// The capacity of the channels used to hand new sockets to the topic, and new subscribers to
+    /// the topic's subscriber task. Larger channels absorb bursts of new streams without
+    /// blocking, at the cost of the memory reserved for each topic.
+    pub channel_size: usize,
+    /// The maximum size in bytes of each message written to the log, or [None] to accept any
+    /// message that fits within a frame. Messages in a batch are checked individually.
+    pub max_message_bytes: Option<usize>,
+    /// The number of messages that a subscriber can fall behind the latest message in the log
+    /// before it's sent a [Signal::SubscriberLagging](selium_protocol::Signal) warning, or [None]
+    /// to never warn subscribers.
+    pub lag_warning_threshold: Option<u64>,
+    /// The maximum number of messages received in quick succession that are coalesced into a
+    /// single write to the log. A size of 1 writes each message individually.
+    pub write_batch_size: usize,
+    /// How long to wait for further messages to coalesce into a write, once a message has been
+    /// received. A delay of 0 only coalesces messages that have already been received.
+    pub write_batch_delay: Duration,
+    /// The maximum number of messages that a subscriber reads from the log in each poll, or
+    /// [None] to read up to the end of the current segment.
+    pub read_ahead: Option<u64>,
+    /// The schema that every message written to the log must conform to, or [None] to accept
+    /// messages of any format.
+    pub schema: Option<Schema>,
 }
 
 impl TopicConfig {
-    pub fn new(polling_interval: Duration) -> Self {
-        Self { polling_interval }
+    pub fn new(min_polling_interval: Duration, max_polling_interval: Duration) -> Self {
+        Self {
+            min_polling_interval,
+            max_polling_interval: max_polling_interval.max(min_polling_interval),
+            reservations_resolved: Notify::new(),
+            dedup_window: DEDUP_WINDOW_DEFAULT,
+            dedup_producer_timeout: DEDUP_PRODUCER_TIMEOUT_DEFAULT,
+            reservation_timeout: RESERVATION_TIMEOUT_DEFAULT,
+            idle_timeout: None,
+            coalesce_max_bytes: COALESCE_MAX_BYTES_DEFAULT,
+            channel_size: CHANNEL_SIZE_DEFAULT,
+            max_message_bytes: None,
+            lag_warning_threshold: None,
+            write_batch_size: 1,
+            write_batch_delay: Duration::ZERO,
+            read_ahead: None,
+            schema: None,
+        }
+    }
+
+    /// Overrides the default deduplication window.
+    pub fn dedup_window(mut self, window: usize) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Overrides the default duration after which idle producers are forgotten.
+    pub fn dedup_producer_timeout(mut self, timeout: Duration) -> Self {
+        self.dedup_producer_timeout = timeout;
+        self
+    }
+
+    /// Overrides the default duration after which uncommitted reservations are aborted.
+    pub fn reservation_timeout(mut self, timeout: Duration) -> Self {
+        self.reservation_timeout = timeout;
+        self
+    }
+
+    /// Closes the topic once it has had no publishers or subscribers for the provided duration.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Overrides the default maximum size of batches coalesced for subscribers.
+    pub fn coalesce_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.coalesce_max_bytes = max_bytes;
+        self
+    }
+
+    /// Overrides the default capacity of the topic's socket and subscriber channels, which is at
+    /// least 1.
+    pub fn channel_size(mut self, size: usize) -> Self {
+        self.channel_size = size.max(1);
+        self
+    }
+
+    /// Rejects messages larger than the provided size in bytes, rather than writing them to the
+    /// log.
+    pub fn max_message_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_message_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Warns subscribers once they fall the provided number of messages behind the latest
+    /// message in the log.
+    pub fn lag_warning_threshold(mut self, threshold: u64) -> Self {
+        self.lag_warning_threshold = Some(threshold);
+        self
+    }
+
+    /// Coalesces up to the provided number of messages into each write to the log.
+    pub fn write_batch_size(mut self, size: usize) -> Self {
+        self.write_batch_size = size.max(1);
+        self
+    }
+
+    /// Waits up to the provided duration for further messages to coalesce into each write to
+    /// the log.
+    pub fn write_batch_delay(mut self, delay: Duration) -> Self {
+        self.write_batch_delay = delay;
+        self
+    }
+
+    /// Limits each read from the log by a subscriber to the provided number of messages.
+    pub fn read_ahead(mut self, messages: u64) -> Self {
+        self.read_ahead = Some(messages.max(1));
+        self
+    }
+
+    /// Rejects messages that don't conform to the provided schema, rather than writing them to
+    /// the log.
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
     }
 }
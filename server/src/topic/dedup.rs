@@ -0,0 +1,200 @@
+use selium_protocol::SequenceId;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Whether a message has already been written to a topic by its producer.
+#[derive(Debug, PartialEq)]
+pub enum Sequence {
+    /// The message hasn't been seen before, and should be written.
+    New,
+    /// The message has already been seen. Carries the offset that the original was written at,
+    /// or [None] if the original hasn't been written yet.
+    Duplicate(Option<u64>),
+}
+
+/// Tracks the most recent sequences written to a topic by each idempotent producer, in order to
+/// discard messages that have already been written.
+///
+/// Only the latest `window` sequences are retained per producer, so a duplicate that arrives
+/// after its original has fallen out of the window will be written again. A window of 0 disables
+/// deduplication entirely.
+///
+/// Producers that haven't sent a message for the idle timeout are forgotten, so that the
+/// sequences of publishers that have come and gone aren't retained forever.
+#[derive(Debug)]
+pub struct Deduplicator {
+    window: usize,
+    idle_timeout: Duration,
+    producers: HashMap<String, Producer>,
+    last_eviction: Instant,
+}
+
+#[derive(Debug)]
+struct Producer {
+    /// Each recent sequence, mapped to the offset it was written at once it's been written.
+    sequences: BTreeMap<u64, Option<u64>>,
+    last_seen: Instant,
+}
+
+impl Deduplicator {
+    pub fn new(window: usize, idle_timeout: Duration) -> Self {
+        Self {
+            window,
+            idle_timeout,
+            producers: HashMap::new(),
+            last_eviction: Instant::now(),
+        }
+    }
+
+    /// Records the provided sequence id, returning [Sequence::Duplicate] if it has already been
+    /// recorded within the window.
+    pub fn insert(&mut self, id: &SequenceId) -> Sequence {
+        if self.window == 0 {
+            return Sequence::New;
+        }
+
+        let now = Instant::now();
+
+        // Sweeping on every message would be wasteful, so producers are only swept once the
+        // oldest of them could have become idle
+        if now.duration_since(self.last_eviction) >= self.idle_timeout {
+            self.evict_idle(now);
+        }
+
+        let producer = self
+            .producers
+            .entry(id.producer_id.clone())
+            .or_insert_with(|| Producer {
+                sequences: BTreeMap::new(),
+                last_seen: now,
+            });
+        producer.last_seen = now;
+
+        if let Some(&offset) = producer.sequences.get(&id.sequence) {
+            return Sequence::Duplicate(offset);
+        }
+
+        producer.sequences.insert(id.sequence, None);
+
+        if producer.sequences.len() > self.window {
+            producer.sequences.pop_first();
+        }
+
+        Sequence::New
+    }
+
+    /// Records the offset that the provided sequence id was written at, so that duplicates of it
+    /// can be acknowledged with the same offset.
+    pub fn written(&mut self, id: &SequenceId, offset: u64) {
+        if let Some(written) = self
+            .producers
+            .get_mut(&id.producer_id)
+            .and_then(|producer| producer.sequences.get_mut(&id.sequence))
+        {
+            *written = Some(offset);
+        }
+    }
+
+    /// Forgets the provided sequence id, so that a message that failed to be written isn't
+    /// discarded when it's retried.
+    pub fn remove(&mut self, id: &SequenceId) {
+        if let Some(producer) = self.producers.get_mut(&id.producer_id) {
+            producer.sequences.remove(&id.sequence);
+        }
+    }
+
+    /// Forgets every producer that hasn't been seen within the idle timeout of `now`.
+    pub fn evict_idle(&mut self, now: Instant) {
+        let idle_timeout = self.idle_timeout;
+        self.producers
+            .retain(|_, producer| now.duration_since(producer.last_seen) < idle_timeout);
+        self.last_eviction = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn sequence_id(producer_id: &str, sequence: u64) -> SequenceId {
+        SequenceId {
+            producer_id: producer_id.to_owned(),
+            sequence,
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_sequences() {
+        let mut dedup = Deduplicator::new(10, IDLE_TIMEOUT);
+
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+        assert_eq!(dedup.insert(&sequence_id("producer", 1)), Sequence::New);
+        assert_eq!(
+            dedup.insert(&sequence_id("producer", 0)),
+            Sequence::Duplicate(None)
+        );
+        assert_eq!(dedup.insert(&sequence_id("other", 0)), Sequence::New);
+    }
+
+    #[test]
+    fn returns_offset_of_written_duplicates() {
+        let mut dedup = Deduplicator::new(10, IDLE_TIMEOUT);
+
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+        dedup.written(&sequence_id("producer", 0), 42);
+        assert_eq!(
+            dedup.insert(&sequence_id("producer", 0)),
+            Sequence::Duplicate(Some(42))
+        );
+    }
+
+    #[test]
+    fn forgets_sequences_outside_window() {
+        let mut dedup = Deduplicator::new(2, IDLE_TIMEOUT);
+
+        for sequence in 0..3 {
+            assert_eq!(
+                dedup.insert(&sequence_id("producer", sequence)),
+                Sequence::New
+            );
+        }
+
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+        assert_eq!(
+            dedup.insert(&sequence_id("producer", 2)),
+            Sequence::Duplicate(None)
+        );
+    }
+
+    #[test]
+    fn accepts_removed_sequences_again() {
+        let mut dedup = Deduplicator::new(10, IDLE_TIMEOUT);
+
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+        dedup.remove(&sequence_id("producer", 0));
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+    }
+
+    #[test]
+    fn evicts_idle_producers() {
+        let mut dedup = Deduplicator::new(10, IDLE_TIMEOUT);
+
+        assert_eq!(dedup.insert(&sequence_id("idle", 0)), Sequence::New);
+        dedup.evict_idle(Instant::now() + IDLE_TIMEOUT / 2);
+        assert_eq!(dedup.producers.len(), 1);
+
+        dedup.evict_idle(Instant::now() + IDLE_TIMEOUT);
+        assert!(dedup.producers.is_empty());
+        assert_eq!(dedup.insert(&sequence_id("idle", 0)), Sequence::New);
+    }
+
+    #[test]
+    fn disabled_with_empty_window() {
+        let mut dedup = Deduplicator::new(0, IDLE_TIMEOUT);
+
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+        assert_eq!(dedup.insert(&sequence_id("producer", 0)), Sequence::New);
+    }
+}
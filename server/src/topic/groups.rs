@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use selium_std::encoding::BincodeConfig;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// The name of the file in a topic's log directory that its consumer groups' offsets are
+/// stored in.
+pub const CONSUMER_GROUPS_FILE: &str = "consumer_groups";
+
+pub type SharedConsumerGroups = Arc<ConsumerGroups>;
+
+/// The offsets committed by each consumer group subscribed to a topic.
+///
+/// A group's offset is the offset of the next message for its members to read. Every member of a
+/// group shares the same offset, which only ever moves forward, so a commit behind the group's
+/// current offset is discarded. Groups don't divide a topic's messages between their members, so
+/// each member is sent every message from the offset it resumed from.
+///
+/// Offsets are persisted to a sidecar file alongside the topic's log, which is replaced in full on
+/// each commit. Groups created via [Default] are only kept in memory.
+#[derive(Debug, Default)]
+pub struct ConsumerGroups {
+    path: Option<PathBuf>,
+    offsets: Mutex<HashMap<String, u64>>,
+}
+
+impl ConsumerGroups {
+    /// Loads the offsets stored at `path`. The file is created on the first commit if it doesn't
+    /// exist yet.
+    pub async fn open(path: PathBuf) -> Result<Self> {
+        let offsets = match fs::read(&path).await {
+            Ok(bytes) => BincodeConfig::default()
+                .deserialize(&bytes)
+                .with_context(|| format!("Failed to decode consumer groups at {path:?}"))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read consumer groups at {path:?}"))
+            }
+        };
+
+        Ok(Self {
+            path: Some(path),
+            offsets: Mutex::new(offsets),
+        })
+    }
+
+    /// Returns the offset of the next message for the group to read, or [None] if the group
+    /// hasn't committed an offset yet.
+    pub async fn offset(&self, group: &str) -> Option<u64> {
+        self.offsets.lock().await.get(group).copied()
+    }
+
+    /// Moves the group's offset forward to `offset`, returning false if the group's offset is
+    /// already at or beyond it. The offset is only updated once it has been persisted.
+    pub async fn commit(&self, group: &str, offset: u64) -> Result<bool> {
+        let mut offsets = self.offsets.lock().await;
+
+        if offsets
+            .get(group)
+            .is_some_and(|&committed| committed >= offset)
+        {
+            return Ok(false);
+        }
+
+        let mut updated = offsets.clone();
+        updated.insert(group.to_owned(), offset);
+
+        if let Some(path) = &self.path {
+            persist(path, &updated).await?;
+        }
+
+        *offsets = updated;
+
+        Ok(true)
+    }
+}
+
+// The offsets are written to a temporary file that then replaces the existing file, so that a
+// crash mid-write can't leave the file corrupted
+async fn persist(path: &Path, offsets: &HashMap<String, u64>) -> Result<()> {
+    let bytes = BincodeConfig::default().serialize(offsets)?;
+    let tmp_path = path.with_extension("tmp");
+
+    let mut file = File::create(&tmp_path)
+        .await
+        .with_context(|| format!("Failed to create {tmp_path:?}"))?;
+    file.write_all(&bytes).await?;
+    file.sync_all().await?;
+
+    fs::rename(&tmp_path, path)
+        .await
+        .with_context(|| format!("Failed to replace consumer groups at {path:?}"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn only_moves_offsets_forward() {
+        let groups = ConsumerGroups::default();
+
+        assert!(groups.commit("group", 5).await.unwrap());
+        assert!(!groups.commit("group", 3).await.unwrap());
+        assert!(!groups.commit("group", 5).await.unwrap());
+        assert!(groups.commit("other", 1).await.unwrap());
+
+        assert_eq!(groups.offset("group").await, Some(5));
+        assert_eq!(groups.offset("other").await, Some(1));
+        assert_eq!(groups.offset("missing").await, None);
+    }
+
+    #[tokio::test]
+    async fn restores_committed_offsets() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONSUMER_GROUPS_FILE);
+
+        let groups = ConsumerGroups::open(path.clone()).await.unwrap();
+        assert_eq!(groups.offset("group").await, None);
+        groups.commit("group", 42).await.unwrap();
+        drop(groups);
+
+        let groups = ConsumerGroups::open(path).await.unwrap();
+        assert_eq!(groups.offset("group").await, Some(42));
+    }
+}
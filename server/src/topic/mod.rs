@@ -1,9 +1,20 @@
 use anyhow::Result;
 use futures::{channel::mpsc, SinkExt};
+use selium_protocol::{
+    error_codes::{COMPRESSION_MISMATCH, REPLIER_ALREADY_BOUND},
+    ErrorPayload, Frame,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 pub mod config;
+pub mod dedup;
+pub mod groups;
 pub mod pubsub;
 pub mod reqrep;
+pub mod schema;
 
 pub enum Socket {
     Pubsub(pubsub::Socket),
@@ -26,16 +37,28 @@ impl Socket {
     }
 }
 
+/// The format of the messages published to a pubsub topic, as declared by its first publisher.
+///
+/// The format is fixed for as long as the topic is running, even once the publisher that declared
+/// it has gone, as the topic's log may still hold messages in that format. It's only forgotten
+/// when the topic is reaped.
+pub struct MessageFormat {
+    /// The compression algorithm used by publishers, or [None] if messages are uncompressed.
+    compression: Option<String>,
+}
+
 pub enum Sender {
-    Pubsub(mpsc::Sender<pubsub::Socket>),
-    ReqRep(mpsc::Sender<reqrep::Socket>),
+    /// A pubsub topic, along with the format of its messages, once known.
+    Pubsub(mpsc::Sender<pubsub::Socket>, Option<MessageFormat>),
+    /// A reqrep topic, along with a flag indicating whether a replier is bound to it.
+    ReqRep(mpsc::Sender<reqrep::Socket>, Arc<AtomicBool>),
 }
 
 impl Sender {
     pub async fn send(&mut self, sock: Socket) -> Result<()> {
         match self {
-            Self::Pubsub(ref mut s) => s.send(sock.unwrap_pubsub()).await?,
-            Self::ReqRep(ref mut s) => s.send(sock.unwrap_reqrep()).await?,
+            Self::Pubsub(ref mut s, _) => s.send(sock.unwrap_pubsub()).await?,
+            Self::ReqRep(ref mut s, _) => s.send(sock.unwrap_reqrep()).await?,
         }
 
         Ok(())
@@ -43,8 +66,78 @@ impl Sender {
 
     pub fn close_channel(&mut self) {
         match self {
-            Self::Pubsub(ref mut s) => s.close_channel(),
-            Self::ReqRep(ref mut s) => s.close_channel(),
+            Self::Pubsub(ref mut s, _) => s.close_channel(),
+            Self::ReqRep(ref mut s, _) => s.close_channel(),
+        }
+    }
+
+    /// Checks whether a stream with the given header frame can be added to the topic, returning
+    /// the error to send to the client if not.
+    ///
+    /// Repliers claim the topic if it isn't already bound, and the first publisher records the
+    /// topic's compression algorithm for the rest of the topic's lifetime (see [MessageFormat]),
+    /// which all other publishers and subscribers must match.
+    pub fn admit(&mut self, frame: &Frame) -> Result<(), ErrorPayload> {
+        match (self, frame) {
+            // The guard claims the topic, only failing if a replier was already bound
+            (Self::ReqRep(_, bound), Frame::RegisterReplier(_))
+                if bound.swap(true, Ordering::AcqRel) =>
+            {
+                return Err(ErrorPayload {
+                    code: REPLIER_ALREADY_BOUND,
+                    message: "A replier already exists for this topic".into(),
+                });
+            }
+            (Self::Pubsub(_, format), Frame::RegisterPublisher(payload)) => match format {
+                Some(format) if format.compression != payload.compression => {
+                    let message = format!(
+                        "Topic uses {} but publisher uses {}",
+                        describe_compression(&format.compression),
+                        describe_compression(&payload.compression)
+                    );
+                    return Err(compression_mismatch(message));
+                }
+                Some(_) => (),
+                None => {
+                    *format = Some(MessageFormat {
+                        compression: payload.compression.clone(),
+                    })
+                }
+            },
+            (Self::Pubsub(_, Some(format)), Frame::RegisterSubscriber(payload))
+                if format.compression != payload.compression =>
+            {
+                let message = match (&format.compression, &payload.compression) {
+                    (Some(algorithm), None) => {
+                        format!("Topic uses {algorithm} but no decompressor is configured")
+                    }
+                    (None, Some(decompressor)) => {
+                        format!(
+                            "Topic is uncompressed but a {decompressor} decompressor is configured"
+                        )
+                    }
+                    (Some(algorithm), Some(decompressor)) => {
+                        format!("Topic uses {algorithm} but a {decompressor} decompressor is configured")
+                    }
+                    // Excluded by the guard, but rejected rather than panicking if reached
+                    (None, None) => "Subscriber compression does not match the topic".to_owned(),
+                };
+                return Err(compression_mismatch(message));
+            }
+            _ => (),
         }
+
+        Ok(())
+    }
+}
+
+fn describe_compression(algorithm: &Option<String>) -> &str {
+    algorithm.as_deref().unwrap_or("no compression")
+}
+
+fn compression_mismatch(message: String) -> ErrorPayload {
+    ErrorPayload {
+        code: COMPRESSION_MISMATCH,
+        message: message.into(),
     }
 }
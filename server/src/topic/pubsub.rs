@@ -1,32 +1,107 @@
-use super::config::SharedTopicConfig;
+use super::config::{SharedTopicConfig, TopicConfig};
+use super::dedup::{Deduplicator, Sequence};
+use super::groups::SharedConsumerGroups;
+use crate::logging::error;
 use crate::BoxSink;
-use bytes::Bytes;
+use bytes::{Buf, Bytes};
 use futures::{
     channel::mpsc::{self, Receiver, Sender},
-    stream::BoxStream,
+    stream::{BoxStream, FusedStream},
     Future, SinkExt, StreamExt,
 };
 use selium_log::{
     data::LogIterator,
-    message::{Message, MessageSlice},
+    message::{Message, MessageSlice, MessageState},
     MessageLog,
 };
-use selium_protocol::{BatchPayload, Frame, MessagePayload, Offset};
+use selium_protocol::{
+    error_codes::{
+        LOG_WRITE_FAILED, MESSAGE_TOO_LARGE, RESERVATION_NOT_FOUND, SCHEMA_VIOLATION, TOPIC_CLOSED,
+    },
+    utils::{
+        decode_message_batch, decode_message_chunk, decode_message_with_headers,
+        encode_message_batch, encode_message_chunk, encode_message_with_headers,
+    },
+    AckPayload, BatchPayload, ChunkPayload, ErrorPayload, Frame, HeaderFilter, MessagePayload,
+    Offset, OffsetsPayload, ReservationPayload, Signal, KEY_HEADER, MAX_CHUNK_SIZE,
+    PRIORITY_HEADER,
+};
 use selium_std::errors::{Result, SeliumError, TopicError};
-use std::{pin::Pin, sync::Arc, time::Duration};
-use tokio::select;
-use tokio_stream::StreamMap;
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    select,
+    sync::{oneshot, watch},
+    time,
+};
+use tokio_stream::{StreamMap, StreamNotifyClose};
 use tokio_util::sync::CancellationToken;
 
 pub type SharedLog = Arc<MessageLog>;
 pub type ReadFut = Pin<Box<dyn Future<Output = Result<MessageSlice>> + Send>>;
 pub type SleepFut = Pin<Box<dyn Future<Output = ()> + Send>>;
+/// Counts the subscribers attached to a topic, as each subscriber holds a clone for as long as it
+/// remains connected.
+pub type ActiveSubscribers = Arc<()>;
 
-const SOCK_CHANNEL_SIZE: usize = 100;
+/// The length prefix preceding each message in an encoded batch.
+const BATCH_ENTRY_OVERHEAD: usize = std::mem::size_of::<u64>();
+/// The message count preceding the messages in an encoded batch.
+const BATCH_HEADER_SIZE: usize = std::mem::size_of::<u64>();
+/// The version of log entries containing a message, or a batch of messages.
+const MESSAGE_VERSION: u32 = 1;
+/// The version of log entries containing a single chunk of a message, prefixed by its header.
+const CHUNK_VERSION: u32 = 2;
+/// The version of log entries containing a single message, prefixed by its headers.
+const HEADERS_VERSION: u32 = 3;
+/// The maximum number of messages that a prioritised subscriber reorders at a time, bounding the
+/// memory held back for each subscriber when reads from the log aren't otherwise limited.
+const PRIORITY_WINDOW_SIZE: usize = 1024;
+/// The number of times a write to the log is retried following a transient I/O failure.
+const WRITE_RETRIES: u32 = 3;
+/// The delay before retrying a write to the log, multiplied by the number of attempts so far.
+const WRITE_RETRY_DELAY: Duration = Duration::from_millis(10);
 
 pub enum Socket {
-    Stream(BoxStream<'static, Result<Frame>>),
-    Sink(BoxSink<Frame, SeliumError>, Offset),
+    /// A publisher's read half and sink, and whether the publisher's messages are compressed.
+    /// Compressed batches can't be split into their individual messages.
+    Stream(
+        BoxStream<'static, Result<Frame>>,
+        BoxSink<Frame, SeliumError>,
+        bool,
+    ),
+    /// A subscriber's sink and read half, the offset to read from, whether messages can be
+    /// coalesced into batches for the subscriber, the filter that messages must pass to be sent to
+    /// the subscriber, the consumer group it has joined, and whether messages are delivered in
+    /// order of priority. Compressed messages can't be coalesced, as the client decompresses
+    /// batches as a whole.
+    Sink(
+        BoxSink<Frame, SeliumError>,
+        BoxStream<'static, Result<Frame>>,
+        Offset,
+        bool,
+        Option<HeaderFilter>,
+        Option<String>,
+        bool,
+    ),
+    Offsets(oneshot::Sender<OffsetsPayload>),
+    /// Truncates the topic's log to the provided offset, replying with the log's new offsets.
+    /// Subscribers positioned below the new start offset skip ahead to the oldest retained
+    /// message on their next read.
+    Truncate(u64, oneshot::Sender<Result<OffsetsPayload>>),
+}
+
+/// The reason a [Topic] has stopped running.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TopicExit {
+    /// The topic's channel has been closed, and all publishers have disconnected.
+    Closed,
+    /// The topic has had no publishers or subscribers for its configured idle timeout.
+    Idle,
 }
 
 pub struct Subscriber {
@@ -34,45 +109,354 @@ pub struct Subscriber {
     log: SharedLog,
     sink: BoxSink<Frame, SeliumError>,
     buffered_slice: Option<LogIterator>,
+    polling_interval: Duration,
+    coalesce_max_bytes: usize,
+    header_filter: Option<HeaderFilter>,
+    /// Whether the subscriber is waiting to be sent a snapshot of the latest message for each key.
+    snapshot: bool,
+    /// The consumer group the subscriber has joined, and the topic's groups to commit its
+    /// offsets to.
+    group: Option<(String, SharedConsumerGroups)>,
+    /// Whether the subscriber has been warned that it's lagging, and hasn't caught up since.
+    lagging: bool,
+    /// Whether each read from the log is delivered in order of priority, rather than log order.
+    prioritise: bool,
 }
 
 impl Subscriber {
-    pub fn new(offset: u64, log: SharedLog, sink: BoxSink<Frame, SeliumError>) -> Self {
+    pub fn new(
+        offset: u64,
+        log: SharedLog,
+        sink: BoxSink<Frame, SeliumError>,
+        polling_interval: Duration,
+        coalesce_max_bytes: usize,
+    ) -> Self {
         Self {
             offset,
             log: log.clone(),
             sink,
             buffered_slice: None,
+            polling_interval,
+            coalesce_max_bytes,
+            header_filter: None,
+            snapshot: false,
+            group: None,
+            lagging: false,
+            prioritise: false,
+        }
+    }
+
+    /// Only sends the subscriber messages whose headers pass the provided filter.
+    pub fn with_header_filter(mut self, filter: Option<HeaderFilter>) -> Self {
+        self.header_filter = filter;
+        self
+    }
+
+    /// Sends the subscriber the latest message for each key, from its offset onwards, before
+    /// sending any live messages.
+    pub fn with_snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Commits the subscriber's offsets to the provided consumer group.
+    pub fn with_group(mut self, group: Option<String>, groups: SharedConsumerGroups) -> Self {
+        self.group = group.map(|group| (group, groups));
+        self
+    }
+
+    /// Delivers the messages in each read from the log in order of their [PRIORITY_HEADER], from
+    /// highest to lowest, rather than in log order.
+    ///
+    /// The topic keeps a single log, so priorities act as logical lanes over it: messages are
+    /// still written and retained in the order they arrive, and each read from the log is merged
+    /// across lanes before it's delivered. A high priority message therefore overtakes lower
+    /// priority messages written before it that haven't been delivered yet, but never messages
+    /// that have already been sent. Messages are reordered in windows of at most
+    /// [PRIORITY_WINDOW_SIZE] messages, or the topic's `read_ahead` limit if it's smaller, so a
+    /// message can only overtake those in the same window. Messages of the same priority, and
+    /// messages without one, which are treated as priority 0, keep their log order.
+    ///
+    /// As the subscriber can no longer resume from the offset of its latest message, each window
+    /// is followed by a [Signal::PriorityWindowComplete] marking the offset to resume from.
+    /// Prioritised messages are never coalesced, as coalesced batches must be contiguous.
+    pub fn with_priority(mut self, prioritise: bool) -> Self {
+        self.prioritise = prioritise;
+        self
+    }
+
+    /// Commits the offset following `offset` for the subscriber's consumer group, so that the
+    /// group resumes from the next message, replying with an [Frame::Ack] once the offset has
+    /// been persisted, or an error if it couldn't be. Commits from subscribers that haven't
+    /// joined a group are acknowledged, but otherwise ignored.
+    async fn commit(&mut self, offset: u64) {
+        let next_offset = offset.saturating_add(1);
+        let mut frame = Frame::Ack(AckPayload {
+            offset: next_offset,
+        });
+
+        if let Some((group, groups)) = &self.group {
+            if let Err(e) = groups.commit(group, next_offset).await {
+                error!("Failed to commit offset {offset} for consumer group {group}: {e:?}");
+                frame = Frame::Error(ErrorPayload {
+                    code: LOG_WRITE_FAILED,
+                    message: format!("Failed to commit offset {offset}: {e}").into(),
+                });
+            }
+        }
+
+        let _ = self.sink.send(frame).await;
+    }
+
+    /// Sends the latest message for each key written to the log so far, in log order, followed by
+    /// a [Signal::SnapshotComplete] marking the offset from which live messages will be sent.
+    ///
+    /// The snapshot ends early at a reserved message, so that it's delivered as a live message
+    /// once committed, rather than being left out of the snapshot. Messages that don't pass the
+    /// subscriber's header filter are left out, as are messages without a key, which includes
+    /// every batch and chunk.
+    async fn send_snapshot(&mut self) -> Result<()> {
+        let end_offset = self.log.number_of_entries().await;
+        let mut latest = HashMap::new();
+
+        'read: while self.offset < end_offset {
+            let slice = self
+                .log
+                .read_slice(self.offset, Some(end_offset - self.offset))
+                .await
+                .map_err(SeliumError::Log)?;
+            let slice_end = slice.end_offset();
+
+            if let Some(mut messages) = slice.messages() {
+                while let Ok(Some(message)) = messages.next().await {
+                    let offset = messages.next_offset() - 1;
+
+                    if message.headers().state() == MessageState::Uncommitted {
+                        self.offset = offset;
+                        break 'read;
+                    }
+
+                    if message.headers().version() != HEADERS_VERSION {
+                        continue;
+                    }
+
+                    let records = Bytes::copy_from_slice(message.records());
+
+                    if let Some((headers, records)) = decode_message_with_headers(records) {
+                        if let Some(key) = headers.get(KEY_HEADER) {
+                            latest.insert(key.clone(), (offset, headers, records));
+                        }
+                    }
+                }
+            }
+
+            // Leave any messages that can't be read yet to be sent as live messages
+            if slice_end <= self.offset {
+                break;
+            }
+
+            self.offset = slice_end;
+        }
+
+        let mut snapshot = latest.into_values().collect::<Vec<_>>();
+        snapshot.sort_unstable_by_key(|(offset, _, _)| *offset);
+
+        for (offset, headers, message) in snapshot {
+            if let Some(filter) = &self.header_filter {
+                if !filter.matches(Some(&headers)) {
+                    continue;
+                }
+            }
+
+            let frame = Frame::Message(MessagePayload {
+                headers: Some(headers),
+                message,
+                ttl: None,
+                offset: Some(offset),
+                sequence_id: None,
+            });
+
+            let _ = self.sink.send(frame).await;
         }
+
+        let frame = Frame::Signal(Signal::SnapshotComplete {
+            offset: self.offset,
+        });
+        let _ = self.sink.send(frame).await;
+        self.snapshot = false;
+
+        Ok(())
     }
 
-    async fn read_messages(&mut self) {
+    /// Sends the messages in the buffered slice to the subscriber, returning false if reading
+    /// stopped at a reserved message, which must be read again once it has been committed or
+    /// aborted.
+    async fn read_messages(&mut self) -> bool {
+        let mut completed = true;
+        // Frames held back to be sent in order of priority, if the subscriber is prioritised
+        let mut prioritised = Vec::new();
+
         if let Some(slice) = self.buffered_slice.as_mut() {
-            while let Ok(Some(message)) = slice.next().await {
+            let mut coalesced = CoalescedBatch::default();
+
+            loop {
+                // The window is sent once full, resuming from the message that follows it
+                if prioritised.len() >= PRIORITY_WINDOW_SIZE {
+                    send_priority_window(&mut self.sink, &mut prioritised, slice.next_offset())
+                        .await;
+                }
+
+                let Ok(Some(message)) = slice.next().await else {
+                    break;
+                };
+
+                // Messages must be delivered in order, so nothing past a reserved message can be
+                // delivered until it has been committed or aborted
+                if message.headers().state() == MessageState::Uncommitted {
+                    self.offset = slice.next_offset() - 1;
+                    completed = false;
+                    break;
+                }
+
                 let batch_size = message.headers().batch_size();
-                let records = Bytes::copy_from_slice(message.records());
+                let mut records = Bytes::copy_from_slice(message.records());
+                // Allows the subscriber to resume from the following message after reconnecting
+                let offset = Some(slice.next_offset() - 1);
+
+                // Only single messages are stored with headers, so batches and chunks are treated
+                // as lacking every header
+                let message_headers = if message.headers().version() == HEADERS_VERSION {
+                    match decode_message_with_headers(records) {
+                        Some((message_headers, message)) => {
+                            records = message;
+                            Some(message_headers)
+                        }
+                        None => continue,
+                    }
+                } else {
+                    None
+                };
+
+                if let Some(filter) = &self.header_filter {
+                    if !filter.matches(message_headers.as_ref()) {
+                        continue;
+                    }
+                }
+
+                let priority = message_headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(PRIORITY_HEADER))
+                    .and_then(|priority| priority.parse::<u8>().ok())
+                    .unwrap_or_default();
+
+                // Chunks are reassembled by the subscriber, so they're sent as they were published
+                if message.headers().version() == CHUNK_VERSION {
+                    coalesced.send(&mut self.sink).await;
+
+                    if let Some((header, chunk)) = decode_message_chunk(records) {
+                        let frame = Frame::MessageChunk(ChunkPayload {
+                            header,
+                            message: chunk,
+                            ttl: None,
+                            offset,
+                        });
+
+                        if self.prioritise {
+                            prioritised.push((priority, frame));
+                        } else {
+                            let _ = self.sink.send(frame).await;
+                        }
+                    }
+
+                    continue;
+                }
+
+                // Coalesce consecutive single messages to save sending a frame for each of them.
+                // Batches don't carry headers, so messages with headers are always sent alone.
+                if batch_size == 1 && message_headers.is_none() && self.coalesce_max_bytes > 0 {
+                    if !coalesced.fits(&records, offset, self.coalesce_max_bytes) {
+                        coalesced.send(&mut self.sink).await;
+                    }
+
+                    coalesced.push(records, offset);
+                    continue;
+                }
+
+                coalesced.send(&mut self.sink).await;
 
                 let frame = if batch_size > 1 {
                     Frame::BatchMessage(BatchPayload {
                         message: records,
                         size: batch_size,
+                        ttl: None,
+                        offset,
+                        first_offset: None,
+                        sequence_id: None,
                     })
                 } else {
                     Frame::Message(MessagePayload {
-                        headers: None,
+                        headers: message_headers,
                         message: records,
+                        ttl: None,
+                        offset,
+                        sequence_id: None,
                     })
                 };
 
-                let _ = self.sink.send(frame).await;
+                if self.prioritise {
+                    prioritised.push((priority, frame));
+                } else {
+                    let _ = self.sink.send(frame).await;
+                }
             }
+
+            coalesced.send(&mut self.sink).await;
         }
+
+        if self.prioritise {
+            send_priority_window(&mut self.sink, &mut prioritised, self.offset).await;
+        }
+
+        if !completed {
+            self.buffered_slice = None;
+        }
+
+        completed
     }
 
-    async fn poll_for_messages(&mut self, interval: Duration) -> Result<()> {
+    /// Reads any new messages from the log, or waits until either new messages are flushed to the
+    /// log, or the current polling interval elapses.
+    ///
+    /// Polling acts as a safety net for missed notifications. The polling interval doubles on each
+    /// consecutive empty poll, up to the configured maximum, and resets to the configured minimum
+    /// as soon as new messages are read. Reading stops at a reserved message, in which case the
+    /// subscriber waits for it to be committed or aborted in the same way.
+    ///
+    /// Each read is bounded by the topic's `read_ahead` limit, if any. A bounded read that fills
+    /// the limit returns without waiting, so a subscriber that's behind reads the next chunk
+    /// straight away.
+    async fn poll_for_messages(&mut self, config: &TopicConfig) -> Result<()> {
+        if self.snapshot {
+            self.send_snapshot().await?;
+        }
+
+        // Register interest before reading so that flushes made during the read aren't missed.
+        // Written messages can't be read until they've been flushed, so waking on the write
+        // itself would only find the log empty.
+        let log = self.log.clone();
+        let flushed = log.flushed();
+        let resolved = config.reservations_resolved.notified();
+        tokio::pin!(flushed, resolved);
+        flushed.as_mut().enable();
+        resolved.as_mut().enable();
+
+        if let Some(threshold) = config.lag_warning_threshold {
+            self.check_lag(threshold).await;
+        }
+
         let slice = self
             .log
-            .read_slice(self.offset, None)
+            .read_slice(self.offset, config.read_ahead)
             .await
             .map_err(SeliumError::Log)?;
 
@@ -80,24 +464,137 @@ impl Subscriber {
         self.buffered_slice = slice.messages();
 
         if self.buffered_slice.is_some() {
-            self.read_messages().await;
-        } else {
-            tokio::time::sleep(interval).await;
+            self.polling_interval = config.min_polling_interval;
+
+            if self.read_messages().await {
+                return Ok(());
+            }
+        }
+
+        select! {
+            _ = flushed => {},
+            _ = resolved => {},
+            _ = tokio::time::sleep(self.polling_interval) => {
+                self.polling_interval =
+                    (self.polling_interval * 2).min(config.max_polling_interval);
+            }
         }
 
         Ok(())
     }
+
+    /// Warns the client when the number of messages waiting to be read reaches `threshold`. The
+    /// warning is sent once each time the threshold is reached, so the subscriber must catch up
+    /// below the threshold before it's warned again.
+    async fn check_lag(&mut self, threshold: u64) {
+        let lag = self
+            .log
+            .number_of_entries()
+            .await
+            .saturating_sub(self.offset);
+        let lagging = lag >= threshold;
+
+        if lagging && !self.lagging {
+            let frame = Frame::Signal(Signal::SubscriberLagging { lag });
+            let _ = self.sink.send(frame).await;
+        }
+
+        self.lagging = lagging;
+    }
+
+    /// Signals to the client that the topic has closed the stream intentionally, so that it
+    /// isn't mistaken for a lost connection, then closes the sink.
+    async fn close(&mut self) {
+        let frame = Frame::Error(ErrorPayload {
+            code: TOPIC_CLOSED,
+            message: "Topic closed".into(),
+        });
+
+        let _ = self.sink.send(frame).await;
+        let _ = self.sink.close().await;
+    }
+}
+
+/// Consecutive single messages read from the log, which are sent to a subscriber as one batch.
+///
+/// The subscriber numbers the messages in a batch from its first offset, so only messages at
+/// contiguous offsets can be coalesced. Messages that were filtered out or skipped by the log
+/// leave gaps, which end the batch.
+#[derive(Default)]
+struct CoalescedBatch {
+    messages: Vec<Bytes>,
+    size: usize,
+    offset: Option<u64>,
+}
+
+impl CoalescedBatch {
+    /// Returns whether the message at `offset` directly follows the batch, and can be added
+    /// without exceeding `max_bytes` once encoded. An empty batch always fits the message, so
+    /// oversized messages are sent on their own.
+    fn fits(&self, message: &Bytes, offset: Option<u64>, max_bytes: usize) -> bool {
+        if self.messages.is_empty() {
+            return true;
+        }
+
+        let contiguous =
+            matches!((self.offset, offset), (Some(last), Some(next)) if next == last + 1);
+        contiguous
+            && BATCH_HEADER_SIZE + self.size + BATCH_ENTRY_OVERHEAD + message.len() <= max_bytes
+    }
+
+    fn push(&mut self, message: Bytes, offset: Option<u64>) {
+        self.size += BATCH_ENTRY_OVERHEAD + message.len();
+        self.messages.push(message);
+        self.offset = offset;
+    }
+
+    /// Sends the batch to the subscriber, sending a lone message as a regular message frame.
+    async fn send(&mut self, sink: &mut BoxSink<Frame, SeliumError>) {
+        let mut messages = std::mem::take(&mut self.messages);
+        let offset = self.offset.take();
+        self.size = 0;
+
+        let frame = match messages.len() {
+            0 => return,
+            1 => Frame::Message(MessagePayload {
+                headers: None,
+                message: messages.pop().unwrap(),
+                ttl: None,
+                offset,
+                sequence_id: None,
+            }),
+            size => Frame::BatchMessage(BatchPayload {
+                message: encode_message_batch(messages),
+                size: size as u32,
+                ttl: None,
+                offset,
+                // Each message was read from its own contiguous log entry, ending at `offset`
+                first_offset: offset.map(|offset| offset + 1 - size as u64),
+                sequence_id: None,
+            }),
+        };
+
+        let _ = sink.send(frame).await;
+    }
+}
+
+/// A subscriber waiting to be spawned by [Subscribers].
+pub struct PendingSubscriber {
+    subscriber: Pin<Box<Subscriber>>,
+    /// The read half of the subscriber's stream, which ends once the client disconnects.
+    stream: BoxStream<'static, Result<Frame>>,
+    active: ActiveSubscribers,
 }
 
 pub struct Subscribers {
-    notify: Receiver<Pin<Box<Subscriber>>>,
+    notify: Receiver<PendingSubscriber>,
     token: CancellationToken,
     config: SharedTopicConfig,
 }
 
 impl Subscribers {
-    pub fn new(config: SharedTopicConfig) -> (Sender<Pin<Box<Subscriber>>>, Self) {
-        let (tx, notify) = mpsc::channel(SOCK_CHANNEL_SIZE);
+    pub fn new(config: SharedTopicConfig) -> (Sender<PendingSubscriber>, Self) {
+        let (tx, notify) = mpsc::channel(config.channel_size);
         let token = CancellationToken::new();
         let subscribers = Self {
             notify,
@@ -108,87 +605,1636 @@ impl Subscribers {
     }
 
     pub async fn run(&mut self) {
-        while let Some(mut subscriber) = self.notify.next().await {
+        while let Some(pending) = self.notify.next().await {
+            let PendingSubscriber {
+                mut subscriber,
+                mut stream,
+                active,
+            } = pending;
             let token = self.token.clone();
-            let polling_interval = self.config.polling_interval;
+            let config = self.config.clone();
 
             tokio::spawn(async move {
+                // Keep the subscriber counted as active until it disconnects
+                let _active = active;
+
                 loop {
                     select! {
                         _ = token.cancelled() => {
+                            subscriber.close().await;
                             break;
                         },
-                        _ = subscriber.poll_for_messages(polling_interval) => {
+                        frame = stream.next() => match frame {
+                            Some(Ok(Frame::CommitGroupOffset(payload))) => {
+                                subscriber.commit(payload.offset).await;
+                            }
+                            Some(Ok(_)) => (),
+                            // The client has closed the stream, or its connection has been lost
+                            _ => break,
+                        },
+                        _ = subscriber.poll_for_messages(&config) => {
                             continue;
                         }
                     }
                 }
             });
         }
+
+        // The topic has gone away, so signal the remaining subscribers to close
+        self.token.cancel();
     }
 }
 
+/// The topic's handle on a publisher, used to reply to the publisher's messages.
+struct PublisherHandle {
+    acks: watch::Sender<u64>,
+    replies: Sender<Frame>,
+    compressed: bool,
+    /// The ids of chunked messages that were rejected for being too large, whose remaining chunks
+    /// must also be discarded.
+    rejected_chunks: HashSet<u64>,
+}
+
+/// A message reserved by a publisher, which hasn't yet been committed or aborted.
+struct Reservation {
+    publisher: usize,
+    reserved_at: Instant,
+}
+
 pub struct Topic {
-    publishers: StreamMap<usize, BoxStream<'static, Result<Frame>>>,
+    publishers: StreamMap<usize, StreamNotifyClose<BoxStream<'static, Result<Frame>>>>,
+    handles: HashMap<usize, PublisherHandle>,
+    /// The uncommitted messages reserved by publishers, keyed by the message's offset.
+    reservations: HashMap<u64, Reservation>,
     next_stream_id: usize,
-    notify: Sender<Pin<Box<Subscriber>>>,
+    notify: Sender<PendingSubscriber>,
+    active_subscribers: ActiveSubscribers,
+    idle_since: Option<Instant>,
     handle: Receiver<Socket>,
     log: SharedLog,
+    config: SharedTopicConfig,
+    dedup: Deduplicator,
+    groups: SharedConsumerGroups,
 }
 
 impl Topic {
     pub fn pair(log: MessageLog, config: SharedTopicConfig) -> (Self, Sender<Socket>) {
         let log = Arc::new(log);
-        let (tx, rx) = mpsc::channel(SOCK_CHANNEL_SIZE);
+        let (tx, rx) = mpsc::channel(config.channel_size);
         let publishers = StreamMap::new();
-        let (notify, mut subscribers) = Subscribers::new(config);
+        let (notify, mut subscribers) = Subscribers::new(config.clone());
         tokio::spawn(async move { subscribers.run().await });
 
         (
             Self {
                 log,
                 publishers,
+                handles: HashMap::new(),
+                reservations: HashMap::new(),
                 notify,
+                active_subscribers: Arc::new(()),
+                idle_since: None,
                 next_stream_id: 0,
                 handle: rx,
+                dedup: Deduplicator::new(config.dedup_window, config.dedup_producer_timeout),
+                groups: SharedConsumerGroups::default(),
+                config,
             },
             tx,
         )
     }
 
-    pub async fn run(&mut self) -> Result<()> {
+    /// Stores the offsets committed by the topic's consumer groups in `groups`, rather than only
+    /// keeping them in memory.
+    pub fn with_consumer_groups(mut self, groups: SharedConsumerGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Runs the topic until either its channel has been closed and all publishers have
+    /// disconnected, or the topic has been idle for its configured idle timeout.
+    pub async fn run(&mut self) -> Result<TopicExit> {
+        let idle_timeout = self.config.idle_timeout;
+        let check_interval = idle_timeout.map_or(Duration::from_secs(1), |timeout| {
+            (timeout / 2).max(Duration::from_millis(1))
+        });
+        let mut idle_check = time::interval(check_interval);
+        let reservation_timeout = self.config.reservation_timeout;
+        let mut reservation_check =
+            time::interval((reservation_timeout / 2).max(Duration::from_millis(1)));
+
         loop {
             tokio::select! {
-                Some((_, Ok(frame))) = self.publishers.next() => {
-                    if let Frame::Message(_) | Frame::BatchMessage(_) = frame {
-                        let batch_size = frame.batch_size().unwrap();
-                        let message = frame.message().unwrap();
-                        let message = Message::batch(message, batch_size, 1);
-                        self.log.write(message).await?;
+                Some((id, frame)) = self.publishers.next() => match frame {
+                    Some(Ok(frame)) if self.config.write_batch_size > 1 && is_log_write(&frame) => {
+                        self.write_batch(id, frame).await?
                     }
+                    Some(Ok(frame)) => self.write_frame(id, frame).await?,
+                    Some(Err(_)) => (),
+                    // The publisher has disconnected, so its reservations can never be committed
+                    None => self.abort_reservations(id).await,
                 },
-                Some(socket) = self.handle.next() => match socket {
-                    Socket::Stream(st) => {
-                        self.publishers.insert(self.next_stream_id, st);
-                        self.next_stream_id += 1;
+                Some(socket) = self.handle.next() => self.add_socket(socket).await?,
+                _ = idle_check.tick(), if idle_timeout.is_some() && !self.handle.is_terminated() => {
+                    if !self.is_idle() {
+                        self.idle_since = None;
+                        continue;
                     }
-                    Socket::Sink(si, offset) => {
-                        let entries = self.log.number_of_entries().await;
-
-                        let log_offset = match offset {
-                            Offset::FromBeginning(offset) => offset,
-                            Offset::FromEnd(offset) => entries.checked_sub(offset).unwrap_or(entries)
-                        };
 
-                        let subscriber = Box::pin(Subscriber::new(log_offset, self.log.clone(), si));
+                    let idle_since = *self.idle_since.get_or_insert_with(Instant::now);
 
-                        self.notify
-                            .send(subscriber)
-                            .await
-                            .map_err(TopicError::NotifySubscribers)?;
+                    if idle_timeout.is_some_and(|timeout| idle_since.elapsed() >= timeout) {
+                        return Ok(TopicExit::Idle);
                     }
+                },
+                _ = reservation_check.tick(), if !self.reservations.is_empty() => {
+                    self.abort_expired_reservations().await;
+                },
+                // The topic's channel has been closed and all publishers have disconnected, so
+                // there is nothing left to process.
+                else => return Ok(TopicExit::Closed),
+            }
+        }
+    }
+
+    /// Attempts to reap a topic after [run](Topic::run) has returned [TopicExit::Idle], flushing
+    /// and closing its log. Returns false if the topic is no longer idle, in which case it should
+    /// continue running.
+    ///
+    /// This must be called while holding the lock on the server's topics, so that no new sockets
+    /// can be sent to the topic while it's being reaped. Any sockets sent after the topic became
+    /// idle are added to the topic before deciding whether to reap it.
+    pub async fn try_reap(&mut self) -> Result<bool> {
+        let mut received_sockets = false;
+
+        while let Ok(socket) = self.handle.try_recv() {
+            self.add_socket(socket).await?;
+            received_sockets = true;
+        }
+
+        if received_sockets || !self.is_idle() {
+            self.idle_since = None;
+            return Ok(false);
+        }
+
+        self.handle.close();
+        self.log.close().await.map_err(SeliumError::Log)?;
+
+        Ok(true)
+    }
+
+    fn is_idle(&self) -> bool {
+        // The topic holds one reference to the active subscribers itself
+        self.publishers.is_empty() && Arc::strong_count(&self.active_subscribers) == 1
+    }
+
+    async fn write_frame(&mut self, id: usize, frame: Frame) -> Result<()> {
+        match frame {
+            frame if is_log_write(&frame) => {
+                if let Some(message) = self.prepare_message(id, &frame) {
+                    self.write_message(id, &frame, message).await;
+                }
+            }
+            Frame::Commit(ReservationPayload { offset }) => {
+                self.resolve(id, offset, MessageState::Committed).await?;
+            }
+            Frame::Abort(ReservationPayload { offset }) => {
+                self.resolve(id, offset, MessageState::Aborted).await?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Coalesces the frames that publishers send in quick succession after `frame` into a single
+    /// write to the log, up to the topic's write batch size and delay.
+    ///
+    /// Frames that don't write to the log, and publisher disconnections, end the batch, and are
+    /// handled once the batch has been written so that each publisher's frames stay in order.
+    async fn write_batch(&mut self, id: usize, frame: Frame) -> Result<()> {
+        let mut frames = vec![(id, frame)];
+        let mut deferred = None;
+        let deadline = time::sleep(self.config.write_batch_delay);
+        tokio::pin!(deadline);
+
+        while frames.len() < self.config.write_batch_size {
+            // Frames that have already been received are taken even once the delay has elapsed
+            let next = select! {
+                biased;
+                next = self.publishers.next() => next,
+                _ = &mut deadline => break,
+            };
+
+            match next {
+                Some((id, Some(Ok(frame)))) if is_log_write(&frame) => frames.push((id, frame)),
+                Some((_, Some(Err(_)))) => (),
+                next => {
+                    deferred = next;
+                    break;
+                }
+            }
+        }
+
+        let mut prepared = Vec::with_capacity(frames.len());
+
+        for (id, frame) in frames {
+            if let Some(message) = self.prepare_message(id, &frame) {
+                prepared.push((id, frame, message));
+            }
+        }
+
+        let messages = prepared
+            .iter()
+            .map(|(_, _, message)| message.clone())
+            .collect::<Vec<_>>();
+        // Messages that weren't written as part of the batch are written individually, which
+        // retries transient failures and reports persistent ones to their publishers
+        let offsets = match self.log.write_many(messages).await {
+            Ok(offsets) => offsets,
+            Err(e) => {
+                error!("Failed to write batch to log, writing messages individually: {e:?}");
+                Vec::new()
+            }
+        };
+        let mut prepared = prepared.into_iter();
+
+        for ((id, frame, _), offset) in prepared.by_ref().zip(offsets) {
+            self.written(id, &frame, offset).await;
+        }
+
+        for (id, frame, message) in prepared {
+            self.write_message(id, &frame, message).await;
+        }
+
+        match deferred {
+            Some((id, Some(Ok(frame)))) => self.write_frame(id, frame).await?,
+            // The publisher has disconnected, so its reservations can never be committed
+            Some((id, None)) => self.abort_reservations(id).await,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Converts a frame into the message to write to the log, or returns [None] if the frame
+    /// has been rejected for being too large, or is a duplicate of a message already written.
+    fn prepare_message(&mut self, id: usize, frame: &Frame) -> Option<Message> {
+        if self.discard_rejected_chunk(id, frame) {
+            return None;
+        }
+
+        if let Some(size) = self.oversized_message(id, frame) {
+            let message = format!("Message of {size} bytes exceeds the maximum message size");
+            self.reject_message(id, frame, MESSAGE_TOO_LARGE, message);
+            return None;
+        }
+
+        if let Err(message) = self.check_schema(id, frame) {
+            self.reject_message(id, frame, SCHEMA_VIOLATION, message);
+            return None;
+        }
+
+        // Discard retried messages that have already been written to the log
+        if let Some(sequence_id) = frame.sequence_id() {
+            if let Sequence::Duplicate(offset) = self.dedup.insert(sequence_id) {
+                // A duplicate of a message that's still being written is acknowledged along with
+                // the original
+                if let Some(offset) = offset {
+                    self.acknowledge_duplicate(id, frame, offset);
+                }
+
+                return None;
+            }
+        }
+
+        let mut message = match frame {
+            Frame::MessageChunk(payload) => {
+                let records = encode_message_chunk(payload.header, &payload.message);
+                Message::single(&records, CHUNK_VERSION)
+            }
+            // Headers are stored alongside the message, so that subscribers can filter by them
+            Frame::Message(MessagePayload {
+                headers: Some(headers),
+                message,
+                ..
+            })
+            | Frame::Reserve(MessagePayload {
+                headers: Some(headers),
+                message,
+                ..
+            }) if !headers.is_empty() => {
+                let records = encode_message_with_headers(headers, message);
+                Message::single(&records, HEADERS_VERSION)
+            }
+            frame => {
+                let batch_size = frame.batch_size().unwrap();
+                Message::batch(frame.message().unwrap(), batch_size, MESSAGE_VERSION)
+            }
+        };
+
+        if let Some(ttl) = frame.ttl() {
+            message = message.with_ttl(Duration::from_millis(ttl));
+        }
+
+        if let Some(event_time) = frame.event_time() {
+            message = message.with_event_time(event_time);
+        }
+
+        if matches!(frame, Frame::Reserve(_)) {
+            message = message.with_state(MessageState::Uncommitted);
+        }
+
+        Some(message)
+    }
+
+    async fn write_message(&mut self, id: usize, frame: &Frame, message: Message) {
+        match self.write_to_log(id, message).await {
+            Some(offset) => self.written(id, frame, offset).await,
+            None => {
+                // The message wasn't written, so the publisher is free to retry it
+                if let Some(sequence_id) = frame.sequence_id() {
+                    self.dedup.remove(sequence_id);
+                }
+            }
+        }
+    }
+
+    /// Acknowledges a message written to the log at `offset`, or replies with the offset of a
+    /// reserved message.
+    async fn written(&mut self, id: usize, frame: &Frame, offset: u64) {
+        if let Some(sequence_id) = frame.sequence_id() {
+            self.dedup.written(sequence_id, offset);
+        }
+
+        if matches!(frame, Frame::Reserve(_)) {
+            let reply = Frame::Reserved(ReservationPayload { offset });
+            let queued = self
+                .handles
+                .get_mut(&id)
+                .is_some_and(|handle| handle.replies.try_send(reply).is_ok());
+
+            // Waiting for a publisher that isn't keeping up with its replies would stall the
+            // whole topic, so the reservation is abandoned instead
+            if !queued {
+                error!("Failed to reply to reservation at offset {offset}, aborting it");
+                self.abort(&[offset]).await;
+                return;
+            }
+
+            let reservation = Reservation {
+                publisher: id,
+                reserved_at: Instant::now(),
+            };
+            self.reservations.insert(offset, reservation);
+            return;
+        }
+
+        if let Some(handle) = self.handles.get(&id) {
+            handle.acks.send_replace(offset);
+        }
+    }
+
+    /// Writes a message to the log, retrying transient I/O failures. If the message can't be
+    /// written, the publisher is notified and [None] is returned, leaving the topic running.
+    async fn write_to_log(&mut self, id: usize, message: Message) -> Option<u64> {
+        let mut attempts = 0;
+
+        loop {
+            match self.log.write(message.clone()).await {
+                Ok(offset) => return Some(offset),
+                Err(e) if e.is_transient() && attempts < WRITE_RETRIES => {
+                    attempts += 1;
+                    time::sleep(WRITE_RETRY_DELAY * attempts).await;
                 }
+                Err(e) => {
+                    error!("Failed to write message to log: {e:?}");
+                    self.reject(
+                        id,
+                        LOG_WRITE_FAILED,
+                        format!("Failed to write message: {e}"),
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Commits or aborts a message reserved by the publisher, waking any subscribers waiting on
+    /// it. The request is rejected if the publisher hasn't reserved the offset.
+    async fn resolve(&mut self, id: usize, offset: u64, state: MessageState) -> Result<()> {
+        if self.reservations.get(&offset).map(|r| r.publisher) != Some(id) {
+            let message = format!("No message has been reserved at offset {offset}");
+            self.reject(id, RESERVATION_NOT_FOUND, message);
+            return Ok(());
+        }
+
+        let result = if state == MessageState::Committed {
+            self.log.commit(offset).await
+        } else {
+            self.log.abort(offset).await
+        };
+
+        // The reservation is kept if its state couldn't be written, so the publisher can retry
+        if let Err(e) = result {
+            error!("Failed to resolve reservation at offset {offset}: {e:?}");
+            let message = format!("Failed to resolve reservation at offset {offset}: {e}");
+            self.reject(id, LOG_WRITE_FAILED, message);
+            return Ok(());
+        }
+
+        self.reservations.remove(&offset);
+        self.config.reservations_resolved.notify_waiters();
+
+        // The reservation has already been resolved, so a publisher that isn't keeping up with
+        // its replies only misses the confirmation
+        if let Some(handle) = self.handles.get_mut(&id) {
+            let _ = handle.replies.try_send(Frame::Ok);
+        }
+
+        Ok(())
+    }
+
+    /// Aborts every message still reserved by a publisher, so that subscribers aren't left
+    /// waiting for them.
+    async fn abort_reservations(&mut self, id: usize) {
+        self.abort_where(|reservation| reservation.publisher == id)
+            .await;
+    }
+
+    /// Aborts every message that has been reserved for longer than the topic's reservation
+    /// timeout, so that a publisher that never commits can't hold up subscribers indefinitely.
+    async fn abort_expired_reservations(&mut self) {
+        let timeout = self.config.reservation_timeout;
+        self.abort_where(|reservation| reservation.reserved_at.elapsed() >= timeout)
+            .await;
+    }
+
+    async fn abort_where(&mut self, predicate: impl Fn(&Reservation) -> bool) {
+        let mut aborted = Vec::new();
+
+        self.reservations.retain(|&offset, reservation| {
+            let abort = predicate(reservation);
+
+            if abort {
+                aborted.push(offset);
+            }
+
+            !abort
+        });
+
+        self.abort(&aborted).await;
+    }
+
+    /// Aborts the reserved messages at each offset, waking any subscribers waiting on them.
+    async fn abort(&mut self, offsets: &[u64]) {
+        // Messages left uncommitted are treated as aborted once the log is reopened, so a failure
+        // here only delays subscribers until then
+        for &offset in offsets {
+            if let Err(e) = self.log.abort(offset).await {
+                error!("Failed to abort reservation at offset {offset}: {e:?}");
             }
         }
+
+        if !offsets.is_empty() {
+            self.config.reservations_resolved.notify_waiters();
+        }
+    }
+
+    /// Returns whether the frame is a chunk of a message that has already been rejected for being
+    /// too large, forgetting the message once its final chunk has been discarded.
+    fn discard_rejected_chunk(&mut self, id: usize, frame: &Frame) -> bool {
+        let (Frame::MessageChunk(payload), Some(handle)) = (frame, self.handles.get_mut(&id))
+        else {
+            return false;
+        };
+
+        let header = payload.header;
+
+        if header.index + 1 == header.total {
+            handle.rejected_chunks.remove(&header.message_id)
+        } else {
+            handle.rejected_chunks.contains(&header.message_id)
+        }
+    }
+
+    /// Returns the size of the largest message in the frame, if it exceeds the topic's maximum
+    /// message size.
+    ///
+    /// Chunked messages are measured as a whole, assuming that every chunk before the last is
+    /// full. The first chunk is therefore measured as if the message filled all of its chunks, so
+    /// that a message is rejected before any of its chunks are written.
+    fn oversized_message(&self, id: usize, frame: &Frame) -> Option<usize> {
+        let max_bytes = self.config.max_message_bytes?;
+        let compressed = self
+            .handles
+            .get(&id)
+            .is_some_and(|handle| handle.compressed);
+
+        let size = match frame {
+            Frame::BatchMessage(payload) if !compressed => largest_batch_entry(&payload.message)
+                // A malformed batch can't be split, so treat it as a single message
+                .unwrap_or(payload.message.len()),
+            Frame::MessageChunk(payload) => {
+                payload.header.total.saturating_sub(1) as usize * MAX_CHUNK_SIZE
+                    + payload.message.len()
+            }
+            frame => frame.message().map_or(0, <[u8]>::len),
+        };
+
+        (size > max_bytes).then_some(size)
+    }
+
+    /// Checks that every message in the frame conforms to the topic's schema, if it has one,
+    /// returning a description of the violation if not.
+    ///
+    /// A batch is rejected as a whole if any of its messages don't conform. Compressed and
+    /// chunked messages can't be checked without reassembling them, so they're rejected outright.
+    fn check_schema(&self, id: usize, frame: &Frame) -> std::result::Result<(), String> {
+        let Some(schema) = &self.config.schema else {
+            return Ok(());
+        };
+
+        let compressed = self
+            .handles
+            .get(&id)
+            .is_some_and(|handle| handle.compressed);
+
+        match frame {
+            _ if compressed => {
+                Err("Compressed messages can't be checked against the topic's schema".to_owned())
+            }
+            Frame::MessageChunk(_) => {
+                Err("Chunked messages can't be checked against the topic's schema".to_owned())
+            }
+            Frame::BatchMessage(payload) => decode_message_batch(payload.message.clone())
+                .iter()
+                .try_for_each(|message| match message {
+                    Some(message) => schema.validate(message),
+                    None => Err("Batch is malformed".to_owned()),
+                }),
+            frame => frame
+                .message()
+                .map_or(Ok(()), |message| schema.validate(message)),
+        }
+    }
+
+    /// Acknowledges a duplicate of a message that was written to the log at `offset`, so that a
+    /// publisher retrying the message isn't left waiting for an acknowledgement.
+    fn acknowledge_duplicate(&mut self, id: usize, frame: &Frame, offset: u64) {
+        let Some(handle) = self.handles.get_mut(&id) else {
+            return;
+        };
+
+        if matches!(frame, Frame::Reserve(_)) {
+            let reply = Frame::Reserved(ReservationPayload { offset });
+            let _ = handle.replies.try_send(reply);
+        } else {
+            // Publishers only track the latest acknowledged offset, which already covers any
+            // earlier message
+            handle.acks.send_if_modified(|latest| {
+                let modified = offset >= *latest;
+                *latest = (*latest).max(offset);
+                modified
+            });
+        }
+    }
+
+    /// Rejects the message in a frame. Only the first chunk of a chunked message is rejected,
+    /// and the rest are discarded, so that no part of the message is written.
+    fn reject_message(&mut self, id: usize, frame: &Frame, code: u32, message: String) {
+        if let (Frame::MessageChunk(payload), Some(handle)) = (frame, self.handles.get_mut(&id)) {
+            if payload.header.index + 1 < payload.header.total {
+                handle.rejected_chunks.insert(payload.header.message_id);
+            }
+        }
+
+        self.reject(id, code, message);
+    }
+
+    fn reject(&mut self, id: usize, code: u32, message: String) {
+        if let Some(handle) = self.handles.get_mut(&id) {
+            let payload = ErrorPayload {
+                code,
+                message: message.into(),
+            };
+
+            // The rejection is dropped if the publisher isn't keeping up, but the request is
+            // never carried out either way
+            let _ = handle.replies.try_send(Frame::Error(payload));
+        }
+    }
+
+    async fn add_socket(&mut self, socket: Socket) -> Result<()> {
+        match socket {
+            Socket::Stream(st, si, compressed) => {
+                // Discard the handles of publishers that have since closed
+                self.handles
+                    .retain(|id, _| self.publishers.contains_key(id));
+
+                let (acks, replies) = spawn_acknowledger(si, self.config.channel_size);
+                let handle = PublisherHandle {
+                    acks,
+                    replies,
+                    compressed,
+                    rejected_chunks: HashSet::new(),
+                };
+
+                self.publishers
+                    .insert(self.next_stream_id, StreamNotifyClose::new(st));
+                self.handles.insert(self.next_stream_id, handle);
+                self.next_stream_id += 1;
+            }
+            Socket::Sink(si, stream, offset, coalesce, header_filter, group, prioritise) => {
+                let entries = self.log.number_of_entries().await;
+
+                let log_offset = match offset {
+                    Offset::Committed => match &group {
+                        Some(group) => self.groups.offset(group).await.unwrap_or(entries),
+                        None => entries,
+                    },
+                    Offset::FromBeginning(offset) => offset,
+                    Offset::Snapshot => self.log.start_offset().await,
+                    Offset::FromEnd(offset) => entries.checked_sub(offset).unwrap_or(entries),
+                    Offset::Latest => entries,
+                    Offset::FromTimestamp(timestamp) => {
+                        self.log.offset_for_timestamp(timestamp).await
+                    }
+                };
+
+                let subscriber = Box::pin(
+                    Subscriber::new(
+                        log_offset,
+                        self.log.clone(),
+                        si,
+                        self.config.min_polling_interval,
+                        if coalesce && !prioritise {
+                            self.config.coalesce_max_bytes
+                        } else {
+                            0
+                        },
+                    )
+                    .with_header_filter(header_filter)
+                    .with_snapshot(offset == Offset::Snapshot)
+                    .with_group(group, self.groups.clone())
+                    .with_priority(prioritise),
+                );
+
+                let pending = PendingSubscriber {
+                    subscriber,
+                    stream,
+                    active: self.active_subscribers.clone(),
+                };
+
+                self.notify
+                    .send(pending)
+                    .await
+                    .map_err(TopicError::NotifySubscribers)?;
+            }
+            Socket::Offsets(tx) => {
+                let payload = OffsetsPayload {
+                    start: self.log.start_offset().await,
+                    end: self.log.number_of_entries().await,
+                };
+
+                let _ = tx.send(payload);
+            }
+            Socket::Truncate(offset, tx) => {
+                let result = match self.log.truncate_to(offset).await {
+                    Ok(start) => Ok(OffsetsPayload {
+                        start,
+                        end: self.log.number_of_entries().await,
+                    }),
+                    Err(e) => Err(SeliumError::Log(e)),
+                };
+
+                let _ = tx.send(result);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the frame carries a message to be written to the log.
+fn is_log_write(frame: &Frame) -> bool {
+    matches!(
+        frame,
+        Frame::Message(_) | Frame::BatchMessage(_) | Frame::Reserve(_) | Frame::MessageChunk(_)
+    )
+}
+
+/// Spawns a task to acknowledge the offsets of messages written to the log on behalf of a
+/// publisher, and to send any other replies, such as rejections or reserved offsets.
+///
+/// Offsets are sent via a [watch] channel, so that a slow publisher will only ever receive the
+/// latest offset, rather than stalling the topic.
+fn spawn_acknowledger(
+    mut sink: BoxSink<Frame, SeliumError>,
+    channel_size: usize,
+) -> (watch::Sender<u64>, Sender<Frame>) {
+    let (tx, mut rx) = watch::channel(0);
+    let (replies_tx, mut replies_rx) = mpsc::channel(channel_size);
+
+    tokio::spawn(async move {
+        loop {
+            // Replies are sent ahead of any pending acknowledgement, so that a rejection reaches
+            // the publisher before the acknowledgement of a message sent after the rejected one
+            let frame = select! {
+                biased;
+                Some(reply) = replies_rx.next() => reply,
+                changed = rx.changed() => match changed {
+                    Ok(()) => Frame::Ack(AckPayload { offset: *rx.borrow_and_update() }),
+                    Err(_) => break,
+                },
+            };
+
+            if sink.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    (tx, replies_tx)
+}
+
+/// Sends a window of frames to a prioritised subscriber in order of priority, followed by a
+/// [Signal::PriorityWindowComplete] marking the offset to resume from.
+async fn send_priority_window(
+    sink: &mut BoxSink<Frame, SeliumError>,
+    window: &mut Vec<(u8, Frame)>,
+    offset: u64,
+) {
+    // The sort is stable, so messages of the same priority keep their log order
+    window.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+
+    for (_, frame) in window.drain(..) {
+        let _ = sink.send(frame).await;
+    }
+
+    let frame = Frame::Signal(Signal::PriorityWindowComplete { offset });
+    let _ = sink.send(frame).await;
+}
+
+/// Returns the size of the largest message in an encoded batch, or [None] if the batch is
+/// malformed.
+fn largest_batch_entry(mut batch: &[u8]) -> Option<usize> {
+    if batch.remaining() < BATCH_HEADER_SIZE {
+        return None;
+    }
+
+    let mut largest = 0;
+
+    for _ in 0..batch.get_u64() {
+        if batch.remaining() < BATCH_ENTRY_OVERHEAD {
+            return None;
+        }
+
+        let len = usize::try_from(batch.get_u64()).ok()?;
+
+        if batch.remaining() < len {
+            return None;
+        }
+
+        batch.advance(len);
+        largest = largest.max(len);
+    }
+
+    Some(largest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topic::config::CHANNEL_SIZE_DEFAULT;
+    use selium_log::config::{FlushPolicy, LogConfig};
+    use selium_protocol::{split_message, SequenceId};
+    use tempfile::tempdir;
+
+    const MIN_INTERVAL: Duration = Duration::from_millis(1);
+    const MAX_INTERVAL: Duration = Duration::from_millis(8);
+
+    #[tokio::test]
+    async fn polling_interval_backs_off_while_idle_and_resets_after_write() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        let (tx, mut rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber = Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 0);
+
+        for expected in [2, 4, 8, 8] {
+            subscriber.poll_for_messages(&config).await.unwrap();
+            assert_eq!(subscriber.polling_interval, Duration::from_millis(expected));
+        }
+
+        log.write(Message::single(b"Hello, world!", 1))
+            .await
+            .unwrap();
+        log.flush().await.unwrap();
+
+        subscriber.poll_for_messages(&config).await.unwrap();
+        assert_eq!(subscriber.polling_interval, MIN_INTERVAL);
+        assert!(rx.next().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn warns_subscriber_once_each_time_lag_reaches_threshold() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).lag_warning_threshold(5);
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber = Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 0);
+
+        let write = |count| {
+            let log = log.clone();
+
+            async move {
+                for _ in 0..count {
+                    log.write(Message::single(b"Hello, world!", 1))
+                        .await
+                        .unwrap();
+                }
+                log.flush().await.unwrap();
+            }
+        };
+
+        // Lagging behind by 10, 2 and then 6 messages
+        for count in [10, 2, 6] {
+            write(count).await;
+            subscriber.poll_for_messages(&config).await.unwrap();
+        }
+        drop(subscriber);
+
+        let signals: Vec<Frame> = rx
+            .filter(|frame| futures::future::ready(matches!(frame, Frame::Signal(_))))
+            .collect()
+            .await;
+
+        assert_eq!(
+            signals,
+            [
+                Frame::Signal(Signal::SubscriberLagging { lag: 10 }),
+                Frame::Signal(Signal::SubscriberLagging { lag: 6 }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_ahead_bounds_each_read_from_the_log() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).read_ahead(4);
+
+        for i in 0..10 {
+            let message = format!("Message {i}");
+            log.write(Message::single(message.as_bytes(), 1))
+                .await
+                .unwrap();
+        }
+        log.flush().await.unwrap();
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber = Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 0);
+
+        for expected in [4, 8, 10] {
+            subscriber.poll_for_messages(&config).await.unwrap();
+            assert_eq!(subscriber.offset, expected);
+        }
+        drop(subscriber);
+
+        let messages: Vec<Bytes> = rx
+            .map(|frame| match frame {
+                Frame::Message(payload) => payload.message,
+                _ => panic!("Unexpected frame"),
+            })
+            .collect()
+            .await;
+        let expected: Vec<Bytes> = (0..10).map(|i| format!("Message {i}").into()).collect();
+
+        assert_eq!(messages, expected);
+    }
+
+    #[tokio::test]
+    async fn prioritised_subscriber_receives_higher_priority_messages_first() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        for (message, priority) in [
+            ("Bulk 1", None),
+            ("Bulk 2", Some("0")),
+            ("Urgent", Some("9")),
+        ] {
+            let headers = priority
+                .map(|priority| HashMap::from([(PRIORITY_HEADER.to_owned(), priority.to_owned())]))
+                .unwrap_or_else(|| HashMap::from([("type".to_owned(), "bulk".to_owned())]));
+            let records = encode_message_with_headers(&headers, message.as_bytes());
+            log.write(Message::single(&records, HEADERS_VERSION))
+                .await
+                .unwrap();
+        }
+        log.flush().await.unwrap();
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber =
+            Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 0).with_priority(true);
+
+        subscriber.poll_for_messages(&config).await.unwrap();
+        drop(subscriber);
+
+        let frames: Vec<(Option<u64>, Bytes)> = rx
+            .filter_map(|frame| async move {
+                match frame {
+                    Frame::Message(payload) => Some((payload.offset, payload.message)),
+                    Frame::Signal(Signal::PriorityWindowComplete { offset }) => {
+                        Some((Some(offset), Bytes::new()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+            .await;
+
+        assert_eq!(
+            frames,
+            vec![
+                (Some(2), Bytes::from("Urgent")),
+                (Some(0), Bytes::from("Bulk 1")),
+                (Some(1), Bytes::from("Bulk 2")),
+                (Some(3), Bytes::new()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn prioritised_subscriber_reorders_bounded_windows() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        for i in 0..PRIORITY_WINDOW_SIZE {
+            log.write(Message::single(
+                format!("Bulk {i}").as_bytes(),
+                MESSAGE_VERSION,
+            ))
+            .await
+            .unwrap();
+        }
+
+        let headers = HashMap::from([(PRIORITY_HEADER.to_owned(), "9".to_owned())]);
+        let records = encode_message_with_headers(&headers, b"Urgent");
+        log.write(Message::single(&records, HEADERS_VERSION))
+            .await
+            .unwrap();
+        log.flush().await.unwrap();
+
+        let (tx, rx) = mpsc::channel(PRIORITY_WINDOW_SIZE + 3);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber =
+            Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 0).with_priority(true);
+
+        subscriber.poll_for_messages(&config).await.unwrap();
+        drop(subscriber);
+
+        let frames: Vec<(Option<u64>, Bytes)> = rx
+            .filter_map(|frame| async move {
+                match frame {
+                    Frame::Message(payload) => Some((payload.offset, payload.message)),
+                    Frame::Signal(Signal::PriorityWindowComplete { offset }) => {
+                        Some((Some(offset), Bytes::new()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+            .await;
+
+        // The urgent message falls outside the first window, so can't overtake any of it
+        let window = PRIORITY_WINDOW_SIZE as u64;
+        let mut expected: Vec<_> = (0..window)
+            .map(|i| (Some(i), Bytes::from(format!("Bulk {i}"))))
+            .collect();
+        expected.push((Some(window), Bytes::new()));
+        expected.push((Some(window), Bytes::from("Urgent")));
+        expected.push((Some(window + 1), Bytes::new()));
+
+        assert_eq!(frames, expected);
+    }
+
+    async fn read_all_messages(coalesce_max_bytes: usize) -> (usize, Vec<Bytes>) {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        for i in 0..50 {
+            let message = format!("Message {i}");
+            log.write(Message::single(message.as_bytes(), 1))
+                .await
+                .unwrap();
+        }
+
+        let batch = encode_message_batch(vec![Bytes::from("First"), Bytes::from("Second")]);
+        log.write(Message::batch(&batch, 2, 1)).await.unwrap();
+        log.write(Message::single(b"Last", 1)).await.unwrap();
+        log.flush().await.unwrap();
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber =
+            Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, coalesce_max_bytes);
+
+        subscriber.poll_for_messages(&config).await.unwrap();
+        drop(subscriber);
+
+        let frames: Vec<Frame> = rx.collect().await;
+        let num_frames = frames.len();
+        let messages = frames
+            .into_iter()
+            .flat_map(|frame| match frame {
+                Frame::Message(payload) => vec![payload.message],
+                Frame::BatchMessage(payload) => decode_message_batch(payload.message)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                _ => panic!("Unexpected frame"),
+            })
+            .collect();
+
+        (num_frames, messages)
+    }
+
+    #[tokio::test]
+    async fn coalescing_preserves_messages_sent_to_subscriber() {
+        let (num_frames, messages) = read_all_messages(0).await;
+        let (num_coalesced_frames, coalesced_messages) = read_all_messages(128).await;
+
+        assert_eq!(messages.len(), 53);
+        assert_eq!(num_frames, 52);
+        assert_eq!(coalesced_messages, messages);
+        assert!(num_coalesced_frames < num_frames);
+    }
+
+    #[tokio::test]
+    async fn coalesced_batches_break_at_filtered_messages() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = Arc::new(MessageLog::open(log_config).await.unwrap());
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        for (message, region) in [
+            ("One", "eu"),
+            ("Two", "us"),
+            ("Three", "eu"),
+            ("Four", "eu"),
+        ] {
+            let headers = HashMap::from([("region".to_owned(), region.to_owned())]);
+            let records = encode_message_with_headers(&headers, message.as_bytes());
+            log.write(Message::single(&records, HEADERS_VERSION))
+                .await
+                .unwrap();
+        }
+        log.flush().await.unwrap();
+
+        let mut filter = HeaderFilter::default();
+        filter.allow("region", "eu");
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let mut subscriber = Subscriber::new(0, log.clone(), sink, MIN_INTERVAL, 1024)
+            .with_header_filter(Some(filter));
+
+        subscriber.poll_for_messages(&config).await.unwrap();
+        drop(subscriber);
+
+        // Numbers each message in the same manner as the client
+        let messages: Vec<(u64, Bytes)> = rx
+            .flat_map(|frame| {
+                let messages = match frame {
+                    Frame::Message(payload) => vec![(payload.offset.unwrap(), payload.message)],
+                    Frame::BatchMessage(payload) => decode_message_batch(payload.message)
+                        .into_iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(i, message)| (payload.first_offset.unwrap() + i as u64, message))
+                        .collect(),
+                    _ => panic!("Unexpected frame"),
+                };
+                futures::stream::iter(messages)
+            })
+            .collect()
+            .await;
+
+        assert_eq!(
+            messages,
+            vec![
+                (0, Bytes::from("One")),
+                (2, Bytes::from("Three")),
+                (3, Bytes::from("Four")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriber_is_notified_of_writes_before_polling_interval() {
+        let dir = tempdir().unwrap();
+        // Use the default flush policy, so that the message is only flushed by the Flusher task
+        // once its 3 second interval elapses
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let flush_interval = Duration::from_secs(3);
+        let log = MessageLog::open(log_config).await.unwrap();
+        let polling_interval = flush_interval * 10;
+        let config = Arc::new(TopicConfig::new(polling_interval, polling_interval));
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+        tokio::spawn(async move { topic.run().await });
+
+        let (tx, mut rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Sink(
+                sink,
+                futures::stream::pending().boxed(),
+                Offset::FromBeginning(0),
+                true,
+                None,
+                None,
+                false,
+            ))
+            .await
+            .unwrap();
+
+        // Give the subscriber time to find the log empty and start waiting
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let frame = Frame::Message(MessagePayload {
+            headers: None,
+            message: Bytes::from("Hello, world!"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
+        });
+        let publisher = futures::stream::iter([Ok(frame)]).boxed();
+        let (ack_tx, _ack_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let acks = Box::pin(ack_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let start = std::time::Instant::now();
+        handle
+            .send(Socket::Stream(publisher, acks, false))
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(polling_interval, rx.next())
+            .await
+            .expect("subscriber should be notified before the polling interval elapses");
+
+        assert!(received.is_some());
+        assert!(start.elapsed() < flush_interval * 2);
+    }
+
+    #[tokio::test]
+    async fn coalesced_writes_preserve_message_order() {
+        let dir = tempdir().unwrap();
+        let flush_policy = FlushPolicy::default().number_of_writes(1);
+        let log_config = Arc::new(LogConfig::from_path(dir.path()).flush_policy(flush_policy));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL)
+            .write_batch_size(8)
+            .write_batch_delay(Duration::from_millis(10));
+
+        let (mut topic, mut handle) = Topic::pair(log, Arc::new(config));
+        tokio::spawn(async move { topic.run().await });
+
+        let expected = (0..20).map(|i| format!("Message {i}")).collect::<Vec<_>>();
+        let frames = expected.iter().map(|message| {
+            Ok(Frame::Message(MessagePayload {
+                headers: None,
+                message: Bytes::from(message.clone()),
+                ttl: None,
+                offset: None,
+                sequence_id: None,
+            }))
+        });
+        let publisher = futures::stream::iter(frames.collect::<Vec<_>>()).boxed();
+        let (ack_tx, _ack_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let acks = Box::pin(ack_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Stream(publisher, acks, false))
+            .await
+            .unwrap();
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Sink(
+                sink,
+                futures::stream::pending().boxed(),
+                Offset::FromBeginning(0),
+                false,
+                None,
+                None,
+                false,
+            ))
+            .await
+            .unwrap();
+
+        let received = rx
+            .filter_map(|frame| async move { frame.message().map(<[u8]>::to_vec) })
+            .take(expected.len())
+            .collect::<Vec<_>>();
+        let received = tokio::time::timeout(Duration::from_secs(5), received)
+            .await
+            .unwrap();
+
+        let expected = expected
+            .into_iter()
+            .map(String::into_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(received, expected);
+    }
+
+    #[tokio::test]
+    async fn discards_duplicate_sequence_ids() {
+        let dir = tempdir().unwrap();
+        let flush_policy = FlushPolicy::default().number_of_writes(1);
+        let log_config = Arc::new(LogConfig::from_path(dir.path()).flush_policy(flush_policy));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).dedup_window(10));
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+        tokio::spawn(async move { topic.run().await });
+
+        let frame = Frame::Message(MessagePayload {
+            headers: None,
+            message: Bytes::from("Hello, world!"),
+            ttl: None,
+            offset: None,
+            sequence_id: Some(SequenceId {
+                producer_id: "producer".to_owned(),
+                sequence: 0,
+            }),
+        });
+        let (mut frame_tx, frame_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let publisher = frame_rx.map(Ok).boxed();
+        let (ack_tx, mut ack_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let acks = Box::pin(ack_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Stream(publisher, acks, false))
+            .await
+            .unwrap();
+
+        frame_tx.send(frame.clone()).await.unwrap();
+        assert!(matches!(
+            ack_rx.next().await,
+            Some(Frame::Ack(AckPayload { offset: 0 }))
+        ));
+
+        // The retried message is acknowledged with the offset of the original
+        frame_tx.send(frame).await.unwrap();
+        let ack = tokio::time::timeout(Duration::from_secs(5), ack_rx.next())
+            .await
+            .unwrap();
+        assert!(matches!(ack, Some(Frame::Ack(AckPayload { offset: 0 }))));
+
+        let (tx, rx) = oneshot::channel();
+        handle.send(Socket::Offsets(tx)).await.unwrap();
+        let offsets = rx.await.unwrap();
+
+        assert_eq!(offsets.end, 1);
+    }
+
+    #[tokio::test]
+    async fn aborts_expired_reservations() {
+        let dir = tempdir().unwrap();
+        let flush_policy = FlushPolicy::default().number_of_writes(1);
+        let log_config = Arc::new(LogConfig::from_path(dir.path()).flush_policy(flush_policy));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(
+            TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL)
+                .reservation_timeout(Duration::from_millis(50)),
+        );
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+        tokio::spawn(async move { topic.run().await });
+
+        let (mut frame_tx, frame_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let publisher = frame_rx.map(Ok).boxed();
+        let (reply_tx, mut reply_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let replies = Box::pin(reply_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Stream(publisher, replies, false))
+            .await
+            .unwrap();
+
+        let frame = Frame::Reserve(MessagePayload {
+            headers: None,
+            message: Bytes::from("Hello, world!"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
+        });
+        frame_tx.send(frame).await.unwrap();
+        assert!(matches!(
+            reply_rx.next().await,
+            Some(Frame::Reserved(ReservationPayload { offset: 0 }))
+        ));
+
+        // Give the topic time to abort the reservation
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let commit = Frame::Commit(ReservationPayload { offset: 0 });
+        frame_tx.send(commit).await.unwrap();
+        let reply = tokio::time::timeout(Duration::from_secs(5), reply_rx.next())
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Some(Frame::Error(ErrorPayload { code, .. })) if code == RESERVATION_NOT_FOUND
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_every_chunk_of_oversized_message() {
+        let dir = tempdir().unwrap();
+        let flush_policy = FlushPolicy::default().number_of_writes(1);
+        let log_config = Arc::new(LogConfig::from_path(dir.path()).flush_policy(flush_policy));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(
+            TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).max_message_bytes(MAX_CHUNK_SIZE * 2),
+        );
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+        tokio::spawn(async move { topic.run().await });
+
+        let (mut frame_tx, frame_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let publisher = frame_rx.map(Ok).boxed();
+        let (reply_tx, mut reply_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let replies = Box::pin(reply_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Stream(publisher, replies, false))
+            .await
+            .unwrap();
+
+        // Each chunk is within the limit, but the message as a whole isn't
+        let message = Bytes::from(vec![0; MAX_CHUNK_SIZE * 2 + 10]);
+        for (header, chunk) in split_message(1, message) {
+            let frame = Frame::MessageChunk(ChunkPayload {
+                header,
+                message: chunk,
+                ttl: None,
+                offset: None,
+            });
+            frame_tx.send(frame).await.unwrap();
+        }
+
+        let frame = Frame::Message(MessagePayload {
+            headers: None,
+            message: Bytes::from("Hello, world!"),
+            ttl: None,
+            offset: None,
+            sequence_id: None,
+        });
+        frame_tx.send(frame).await.unwrap();
+
+        let reply = tokio::time::timeout(Duration::from_secs(5), reply_rx.next())
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Some(Frame::Error(ErrorPayload { code, .. })) if code == MESSAGE_TOO_LARGE
+        ));
+
+        // None of the chunks were written, so the following message is the first in the log
+        let reply = tokio::time::timeout(Duration::from_secs(5), reply_rx.next())
+            .await
+            .unwrap();
+        assert!(matches!(reply, Some(Frame::Ack(AckPayload { offset: 0 }))));
+    }
+
+    #[tokio::test]
+    async fn topic_survives_failed_flush() {
+        let dir = tempdir().unwrap();
+        // Every write to /dev/full fails, as if the disk were full
+        std::os::unix::fs::symlink("/dev/full", dir.path().join("0.data")).unwrap();
+
+        let flush_policy = FlushPolicy::default().number_of_writes(1);
+        let log_config = Arc::new(LogConfig::from_path(dir.path()).flush_policy(flush_policy));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL));
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+        let task = tokio::spawn(async move { topic.run().await });
+
+        // The first flush fails after its message has been written, so the failure is surfaced
+        // by the second message
+        let frames = ["First", "Second"].map(|message| {
+            Ok(Frame::Message(MessagePayload {
+                headers: None,
+                message: Bytes::from(message),
+                ttl: None,
+                offset: None,
+                sequence_id: None,
+            }))
+        });
+        let publisher = futures::stream::iter(frames)
+            .chain(futures::stream::pending())
+            .boxed();
+        let (reply_tx, reply_rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let replies = Box::pin(reply_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Stream(publisher, replies, false))
+            .await
+            .unwrap();
+
+        let rejection = tokio::time::timeout(
+            Duration::from_secs(5),
+            reply_rx
+                .filter(|frame| futures::future::ready(matches!(frame, Frame::Error(_))))
+                .next(),
+        )
+        .await
+        .expect("publisher should be notified of the failed write");
+
+        assert!(matches!(
+            rejection,
+            Some(Frame::Error(ErrorPayload {
+                code: LOG_WRITE_FAILED,
+                ..
+            }))
+        ));
+
+        // The topic keeps running, and continues to serve requests
+        let (tx, rx) = oneshot::channel();
+        handle.send(Socket::Offsets(tx)).await.unwrap();
+        assert!(rx.await.is_ok());
+        assert!(!task.is_finished());
+    }
+
+    fn subscriber_socket() -> Socket {
+        let (tx, _rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+
+        Socket::Sink(
+            sink,
+            futures::stream::pending().boxed(),
+            Offset::FromBeginning(0),
+            true,
+            None,
+            None,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn larger_channel_accepts_burst_of_subscribers() {
+        const BURST: usize = CHANNEL_SIZE_DEFAULT * 5;
+
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).channel_size(BURST);
+
+        let (mut topic, mut handle) = Topic::pair(log, Arc::new(config));
+
+        // The topic isn't running yet, so each registration must be buffered by the channel
+        for _ in 0..BURST {
+            handle.try_send(subscriber_socket()).unwrap();
+        }
+
+        handle.close_channel();
+        let exit = tokio::time::timeout(Duration::from_secs(5), topic.run())
+            .await
+            .expect("topic should hand off every subscriber without blocking");
+
+        assert_eq!(exit.unwrap(), TopicExit::Closed);
+    }
+
+    #[tokio::test]
+    async fn default_channel_rejects_burst_of_subscribers() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL);
+
+        let (_topic, mut handle) = Topic::pair(log, Arc::new(config));
+
+        let accepted = (0..CHANNEL_SIZE_DEFAULT * 5)
+            .take_while(|_| handle.try_send(subscriber_socket()).is_ok())
+            .count();
+
+        assert!(accepted < CHANNEL_SIZE_DEFAULT * 5);
+    }
+
+    #[tokio::test]
+    async fn signals_subscribers_when_topic_closes() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL));
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+
+        let (tx, mut rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        handle
+            .send(Socket::Sink(
+                sink,
+                futures::stream::pending().boxed(),
+                Offset::FromBeginning(0),
+                true,
+                None,
+                None,
+                false,
+            ))
+            .await
+            .unwrap();
+
+        // Reap the topic once the subscriber has been added
+        handle.close_channel();
+        assert_eq!(topic.run().await.unwrap(), TopicExit::Closed);
+        drop(topic);
+
+        let frame = tokio::time::timeout(Duration::from_secs(1), rx.next())
+            .await
+            .expect("subscriber should be signalled before the sink closes");
+
+        assert!(matches!(
+            frame,
+            Some(Frame::Error(ErrorPayload {
+                code: TOPIC_CLOSED,
+                ..
+            }))
+        ));
+        assert!(rx.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reaps_idle_topic_once_subscribers_disconnect() {
+        let dir = tempdir().unwrap();
+        let log_config = Arc::new(LogConfig::from_path(dir.path()));
+        let log = MessageLog::open(log_config).await.unwrap();
+        let config = Arc::new(
+            TopicConfig::new(MIN_INTERVAL, MAX_INTERVAL).idle_timeout(Duration::from_millis(20)),
+        );
+
+        let (mut topic, mut handle) = Topic::pair(log, config);
+
+        let (tx, mut rx) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        let sink = Box::pin(tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+        let (client, stream) = mpsc::channel(CHANNEL_SIZE_DEFAULT);
+        handle
+            .send(Socket::Sink(
+                sink,
+                stream.boxed(),
+                Offset::FromBeginning(0),
+                true,
+                None,
+                None,
+                false,
+            ))
+            .await
+            .unwrap();
+
+        // A connected subscriber keeps the topic from going idle
+        let result = tokio::time::timeout(Duration::from_millis(200), topic.run()).await;
+        assert!(result.is_err());
+
+        drop(client);
+
+        let exit = tokio::time::timeout(Duration::from_secs(1), topic.run())
+            .await
+            .expect("topic should go idle once its subscriber disconnects");
+        assert_eq!(exit.unwrap(), TopicExit::Idle);
+        assert!(topic.try_reap().await.unwrap());
+        drop(topic);
+
+        // The subscriber closed its own stream, so it's closed without being signalled
+        let frame = tokio::time::timeout(Duration::from_secs(1), rx.next())
+            .await
+            .expect("subscriber's sink should be closed once the topic is reaped");
+        assert!(frame.is_none());
+    }
+
+    #[test]
+    fn finds_largest_batch_entry() {
+        let batch = encode_message_batch(vec![
+            Bytes::from("foo"),
+            Bytes::from("foobar"),
+            Bytes::from("bar"),
+        ]);
+
+        assert_eq!(largest_batch_entry(&batch), Some(6));
+        assert_eq!(largest_batch_entry(&batch[..batch.len() - 1]), None);
+        assert_eq!(largest_batch_entry(b"foo"), None);
     }
 }
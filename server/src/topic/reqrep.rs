@@ -1,21 +1,28 @@
-use crate::{sink::Router, BoxSink};
+use crate::{
+    logging::{error, warn},
+    sink::Router,
+    BoxSink,
+};
 use futures::{
     channel::mpsc::{self, Receiver, Sender},
     ready,
     stream::BoxStream,
-    Future, Sink, SinkExt, Stream, StreamExt,
+    Future, Sink, SinkExt, Stream, StreamExt, TryStreamExt,
 };
-use log::{error, warn};
 use pin_project_lite::pin_project;
 use selium_protocol::{
     error_codes::REPLIER_ALREADY_BOUND,
     traits::{ShutdownSink, ShutdownStream},
-    ErrorPayload, Frame,
+    ErrorPayload, Frame, PEER_IDENTITY_HEADER,
 };
 use selium_std::errors::{Result, SeliumError};
 use std::{
     collections::HashMap,
     pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 use tokio_stream::StreamMap;
@@ -28,11 +35,14 @@ type BoxedBiStream = (
 );
 
 pub enum Socket {
+    /// A requestor's sink and read half, and the identity of the requestor's client certificate,
+    /// if it's known.
     Client(
         (
             BoxSink<Frame, SeliumError>,
             BoxStream<'static, Result<Frame>>,
         ),
+        Option<String>,
     ),
     Server(
         (
@@ -58,12 +68,14 @@ pin_project! {
         buffered_req: Option<Frame>,
         buffered_rep: Option<Frame>,
         buffered_err: Option<(Option<ErrorPayload>, BoxSink<Frame, SeliumError>)>,
+        bound: Arc<AtomicBool>,
     }
 }
 
 impl Topic {
-    pub fn pair() -> (Self, Sender<Socket>) {
+    pub fn pair() -> (Self, Sender<Socket>, Arc<AtomicBool>) {
         let (tx, rx) = mpsc::channel(SOCK_CHANNEL_SIZE);
+        let bound = Arc::new(AtomicBool::new(false));
 
         (
             Self {
@@ -75,12 +87,32 @@ impl Topic {
                 buffered_req: None,
                 buffered_rep: None,
                 buffered_err: None,
+                bound: bound.clone(),
             },
             tx,
+            bound,
         )
     }
 }
 
+// Attaches the requestor's identity to its requests, replacing any identity claimed by the
+// requestor itself, so that repliers can trust the header
+fn with_peer_identity(frame: Frame, identity: Option<&str>) -> Frame {
+    match frame {
+        Frame::Message(mut payload) => {
+            let headers = payload.headers.get_or_insert_with(HashMap::new);
+
+            match identity {
+                Some(identity) => headers.insert(PEER_IDENTITY_HEADER.into(), identity.into()),
+                None => headers.remove(PEER_IDENTITY_HEADER),
+            };
+
+            Frame::Message(payload)
+        }
+        frame => frame,
+    }
+}
+
 impl Future for Topic {
     type Output = ();
 
@@ -94,10 +126,13 @@ impl Future for Topic {
             buffered_req,
             buffered_rep,
             buffered_err,
+            bound,
         } = self.project();
 
         loop {
-            let mut server_pending = false;
+            let mut handle_pending = false;
+            // Without a replier, there is nothing to poll until one binds to the topic
+            let mut server_pending = server.is_none();
             let mut stream_pending = false;
 
             // If we've got a request buffered already, we need to write it to the replier
@@ -139,7 +174,11 @@ impl Future for Topic {
 
             match handle.as_mut().poll_next(cx) {
                 Poll::Ready(Some(sock)) => match sock {
-                    Socket::Client((si, st)) => {
+                    Socket::Client((si, st), identity) => {
+                        let st = st
+                            .map_ok(move |frame| with_peer_identity(frame, identity.as_deref()))
+                            .boxed();
+
                         stream.as_mut().insert(*next_id, st);
                         sink.as_mut().insert(*next_id, si);
 
@@ -179,7 +218,9 @@ impl Future for Topic {
                     return Poll::Pending
                 }
                 // Otherwise, move on with running the stream
-                Poll::Pending => (),
+                Poll::Pending => {
+                    handle_pending = true;
+                }
             }
 
             if server.is_some() {
@@ -200,6 +241,8 @@ impl Future for Topic {
                         ready!(si.poll_flush_unpin(cx)).unwrap();
                         ready!(sink.as_mut().poll_flush(cx)).unwrap();
                         *server = None;
+                        // Allow the next replier to bind to this topic
+                        bound.store(false, Ordering::Release);
                     }
                     // No messages are available at this time
                     Poll::Pending => {
@@ -223,14 +266,22 @@ impl Future for Topic {
 
             match stream.as_mut().poll_next(cx) {
                 // Received message from a client stream
-                Poll::Ready(Some((id, Ok(item)))) => {
-                    let mut payload = item.unwrap_message();
-                    payload
-                        .headers
-                        .get_or_insert(HashMap::new())
-                        .insert("cid".into(), format!("{id}"));
-                    *buffered_req = Some(Frame::Message(payload));
-                }
+                Poll::Ready(Some((id, Ok(item)))) => match item {
+                    Frame::Message(mut payload) => {
+                        payload
+                            .headers
+                            .get_or_insert(HashMap::new())
+                            .insert("cid".into(), format!("{id}"));
+                        *buffered_req = Some(Frame::Message(payload));
+                    }
+                    // Cancellations are routed to the replier in the same way as requests, so
+                    // that the replier can identify the request being cancelled
+                    Frame::Cancel(mut payload) => {
+                        payload.headers.insert("cid".into(), format!("{id}"));
+                        *buffered_req = Some(Frame::Cancel(payload));
+                    }
+                    frame => error!("Received unexpected frame from requestor: {frame:?}"),
+                },
                 // Encountered an error whilst receiving a message from an inner stream
                 Poll::Ready(Some((_, Err(e)))) => {
                     error!("Received invalid message from requestor: {e:?}")
@@ -244,6 +295,9 @@ impl Future for Topic {
                         let si = &mut server.as_mut().as_pin_mut().unwrap().0;
                         ready!(si.poll_flush_unpin(cx)).unwrap();
                     }
+
+                    // There are no requestors to poll, so wait for the replier or a new socket
+                    stream_pending = true;
                 }
                 // No messages are available at this time
                 Poll::Pending => {
@@ -251,7 +305,9 @@ impl Future for Topic {
                 }
             }
 
-            if server_pending && stream_pending {
+            // The handle must also be pending, otherwise no waker is registered to receive new
+            // sockets
+            if handle_pending && server_pending && stream_pending {
                 // Unwrapping is safe as the underlying sink is guaranteed not to error
                 ready!(sink.poll_flush(cx)).unwrap();
 
@@ -265,3 +321,56 @@ impl Future for Topic {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use selium_protocol::MessagePayload;
+    use selium_std::errors::TopicError;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    type Channel = (BoxedBiStream, Receiver<Frame>, Sender<Result<Frame>>);
+
+    // Returns a socket's sink and stream, along with the ends used to drive them
+    fn channel() -> Channel {
+        let (sink_tx, sink_rx) = mpsc::channel(SOCK_CHANNEL_SIZE);
+        let (stream_tx, stream_rx) = mpsc::channel(SOCK_CHANNEL_SIZE);
+        let sink = Box::pin(sink_tx.sink_map_err(|e| TopicError::NotifySubscribers(e).into()));
+
+        ((sink, stream_rx.boxed()), sink_rx, stream_tx)
+    }
+
+    #[tokio::test]
+    async fn routes_requests_from_requestors_added_after_topic_goes_idle() {
+        let (topic, mut handle, _) = Topic::pair();
+        tokio::spawn(topic);
+
+        let (replier, mut requests, _replies) = channel();
+        handle.send(Socket::Server(replier)).await.unwrap();
+
+        // Let the topic go idle with only a replier bound
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let (requestor, _replies, mut send_request) = channel();
+        handle.send(Socket::Client(requestor, None)).await.unwrap();
+        send_request
+            .send(Ok(Frame::Message(MessagePayload {
+                headers: None,
+                message: Bytes::from("request"),
+                ttl: None,
+                offset: None,
+                sequence_id: None,
+            })))
+            .await
+            .unwrap();
+
+        let request = timeout(Duration::from_secs(1), requests.next())
+            .await
+            .expect("request should be routed to the replier")
+            .unwrap();
+
+        assert!(matches!(request, Frame::Message(payload) if payload.message == "request"));
+    }
+}
@@ -0,0 +1,203 @@
+use serde_json::{Map, Value};
+use std::{fs, path::Path};
+
+/// A JSON Schema that every message published to a topic must conform to.
+///
+/// Only the keywords needed to describe the structure of a message are enforced: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties` and `items`. Any other
+/// keyword is ignored, so messages are never rejected by a constraint that isn't understood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema(Value);
+
+impl Schema {
+    /// Reads a schema from a JSON file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .map_err(|e| format!("Failed to read schema `{}`: {e}", path.display()))?;
+
+        Self::from_slice(&bytes).map_err(|e| format!("Invalid schema `{}`: {e}", path.display()))
+    }
+
+    /// Parses a schema from JSON, which must be either an object or a boolean.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, String> {
+        match serde_json::from_slice(bytes).map_err(|e| e.to_string())? {
+            schema @ (Value::Object(_) | Value::Bool(_)) => Ok(Self(schema)),
+            _ => Err("Expected a JSON object or boolean".to_owned()),
+        }
+    }
+
+    /// Checks that a message is a JSON document conforming to the schema, returning a
+    /// description of the first violation if not.
+    pub fn validate(&self, message: &[u8]) -> Result<(), String> {
+        let value = serde_json::from_slice(message)
+            .map_err(|e| format!("Message is not valid JSON: {e}"))?;
+
+        validate(&self.0, &value, "")
+    }
+}
+
+fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let keywords = match schema {
+        Value::Object(keywords) => keywords,
+        Value::Bool(true) => return Ok(()),
+        _ => return Err(violation(path, "is not allowed")),
+    };
+
+    if let Some(expected) = keywords.get("type") {
+        let matches = match expected {
+            Value::String(name) => is_type(value, name),
+            Value::Array(names) => names
+                .iter()
+                .any(|name| name.as_str().is_some_and(|name| is_type(value, name))),
+            _ => true,
+        };
+
+        if !matches {
+            return Err(violation(path, &format!("should be of type {expected}")));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = keywords.get("enum") {
+        if !allowed.contains(value) {
+            return Err(violation(path, "is not one of the allowed values"));
+        }
+    }
+
+    if let Some(expected) = keywords.get("const") {
+        if value != expected {
+            return Err(violation(path, &format!("should be {expected}")));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => validate_object(keywords, fields, path),
+        Value::Array(items) => match keywords.get("items") {
+            Some(schema) => items
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, item)| validate(schema, item, &format!("{path}/{i}"))),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+fn validate_object(
+    keywords: &Map<String, Value>,
+    fields: &Map<String, Value>,
+    path: &str,
+) -> Result<(), String> {
+    if let Some(Value::Array(required)) = keywords.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !fields.contains_key(name) {
+                return Err(violation(
+                    path,
+                    &format!("is missing required field `{name}`"),
+                ));
+            }
+        }
+    }
+
+    let properties = keywords.get("properties").and_then(Value::as_object);
+
+    for (name, field) in fields {
+        let field_path = format!("{path}/{name}");
+
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(schema) => validate(schema, field, &field_path)?,
+            None => {
+                if let Some(schema) = keywords.get("additionalProperties") {
+                    validate(schema, field, &field_path)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn violation(path: &str, reason: &str) -> String {
+    let path = if path.is_empty() { "/" } else { path };
+    format!("Value at `{path}` {reason}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDER_SCHEMA: &[u8] = br#"{
+        "type": "object",
+        "required": ["id", "items"],
+        "properties": {
+            "id": { "type": "integer" },
+            "status": { "enum": ["pending", "shipped"] },
+            "items": { "type": "array", "items": { "type": "string" } }
+        },
+        "additionalProperties": false
+    }"#;
+
+    #[test]
+    fn accepts_conforming_messages() {
+        let schema = Schema::from_slice(ORDER_SCHEMA).unwrap();
+
+        assert_eq!(schema.validate(br#"{"id": 1, "items": []}"#), Ok(()));
+        assert_eq!(
+            schema.validate(br#"{"id": 2, "status": "shipped", "items": ["book"]}"#),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_non_conforming_messages() {
+        let schema = Schema::from_slice(ORDER_SCHEMA).unwrap();
+
+        let cases: [(&[u8], &str); 6] = [
+            (b"not json", "Message is not valid JSON"),
+            (br#"[1, 2]"#, "Value at `/` should be of type \"object\""),
+            (
+                br#"{"id": 1}"#,
+                "Value at `/` is missing required field `items`",
+            ),
+            (
+                br#"{"id": "1", "items": []}"#,
+                "Value at `/id` should be of type \"integer\"",
+            ),
+            (
+                br#"{"id": 1, "items": [1]}"#,
+                "Value at `/items/0` should be of type \"string\"",
+            ),
+            (
+                br#"{"id": 1, "items": [], "notes": ""}"#,
+                "Value at `/notes` is not allowed",
+            ),
+        ];
+
+        for (message, expected) in cases {
+            let err = schema.validate(message).unwrap_err();
+            assert!(err.starts_with(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn rejects_schemas_that_are_not_objects() {
+        assert!(Schema::from_slice(b"true").is_ok());
+        assert!(Schema::from_slice(b"[]").is_err());
+        assert!(Schema::from_slice(b"{").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 
+use crate::encoding::BincodeConfig;
 use crate::traits::codec::{MessageDecoder, MessageEncoder};
 use anyhow::Result;
 use bytes::{Buf, Bytes, BytesMut};
@@ -7,19 +8,32 @@ use serde::{de::DeserializeOwned, Serialize};
 
 /// A basic codec that uses [bincode] to serialize and deserialize
 /// binary message payloads.
+///
+/// By default, payloads are encoded with fixed-width, little-endian integers. A different
+/// [BincodeConfig] can be provided via [BincodeCodec::new], but as the configuration determines
+/// the layout of the encoded payload, publishers and subscribers must use the same configuration.
 #[derive(Debug, Clone)]
 pub struct BincodeCodec<Item> {
+    config: BincodeConfig,
     _marker: PhantomData<Item>,
 }
 
-impl<Item> Default for BincodeCodec<Item> {
-    fn default() -> Self {
+impl<Item> BincodeCodec<Item> {
+    /// Constructs a BincodeCodec that encodes and decodes payloads with the provided `config`.
+    pub fn new(config: BincodeConfig) -> Self {
         Self {
+            config,
             _marker: PhantomData,
         }
     }
 }
 
+impl<Item> Default for BincodeCodec<Item> {
+    fn default() -> Self {
+        Self::new(BincodeConfig::default())
+    }
+}
+
 /// Encodes any `Item` implementing [Serialize](serde::Serialize) into a binary format via
 /// [bincode].
 ///
@@ -30,7 +44,7 @@ impl<Item: Serialize + Clone> MessageEncoder for BincodeCodec<Item> {
     type Item = Item;
 
     fn encode(&self, item: Self::Item) -> Result<Bytes> {
-        Ok(bincode::serialize(&item)?.into())
+        Ok(self.config.serialize(&item)?.into())
     }
 }
 
@@ -44,13 +58,14 @@ impl<Item: DeserializeOwned> MessageDecoder for BincodeCodec<Item> {
     type Item = Item;
 
     fn decode(&self, buffer: &mut BytesMut) -> Result<Self::Item> {
-        Ok(bincode::deserialize_from(buffer.reader())?)
+        Ok(self.config.deserialize_from(buffer.reader())?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encoding::Endianness;
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -87,4 +102,40 @@ mod tests {
 
         assert_eq!(decoded, expected);
     }
+
+    #[test]
+    fn round_trips_with_provided_config() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let config = BincodeConfig::fixint().endianness(Endianness::Big);
+        let encoder = BincodeCodec::new(config);
+        let decoder = BincodeCodec::<Dummy>::new(config);
+
+        let bytes = encoder.encode(&input).unwrap();
+        let expected = Bytes::from("\0\0\0\0\0\0\0\x03foo\0\0\0\0\0\0\0*");
+        assert_eq!(bytes, expected);
+
+        let mut buffer = BytesMut::from(&bytes[..]);
+        let decoded = decoder.decode(&mut buffer).unwrap();
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_with_mismatched_config() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let encoder = BincodeCodec::new(BincodeConfig::varint());
+        let decoder = BincodeCodec::<Dummy>::new(BincodeConfig::fixint());
+
+        let bytes = encoder.encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&bytes[..]);
+
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
 }
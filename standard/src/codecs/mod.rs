@@ -1,5 +1,5 @@
 //! Client codec implementations for commonly used serialization formats, including UTF-8 encoded
-//! strings, and various binary formats, such as bincode.
+//! strings, and various binary formats, such as bincode and protobuf.
 //!
 //! In `Selium`, messages are sent over the wire in a binary format, and thus, the server has no
 //! indication of, or any desire to make sense of the data. This is perfectly suitable for the
@@ -191,8 +191,12 @@
 
 mod bincode_codec;
 mod bytes_codec;
+mod multi_codec;
+mod prost_codec;
 mod string_codec;
 
 pub use bincode_codec::*;
 pub use bytes_codec::*;
+pub use multi_codec::*;
+pub use prost_codec::*;
 pub use string_codec::*;
@@ -0,0 +1,237 @@
+use crate::traits::codec::{MessageDecoder, MessageEncoder};
+use anyhow::{anyhow, Result};
+use bytes::{Bytes, BytesMut};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The message header carrying the content type of a message encoded by a [MultiCodec].
+///
+/// The header falls under the prefix reserved for `Selium`'s own headers, so it can't be set by
+/// publishers directly.
+pub const CONTENT_TYPE_HEADER: &str = "selium-content-type";
+
+/// Combines a [MessageEncoder] and [MessageDecoder] for the same `Item` so that they can be
+/// stored as a single trait object.
+trait Codec<Item>: Send + Sync {
+    fn encode_item(&self, item: Item) -> Result<Bytes>;
+    fn decode_item(&self, buffer: &mut BytesMut) -> Result<Item>;
+}
+
+impl<C, Item> Codec<Item> for C
+where
+    C: MessageEncoder<Item = Item> + MessageDecoder<Item = Item> + Send + Sync,
+    Item: Clone,
+{
+    fn encode_item(&self, item: Item) -> Result<Bytes> {
+        MessageEncoder::encode(self, item)
+    }
+
+    fn decode_item(&self, buffer: &mut BytesMut) -> Result<Item> {
+        MessageDecoder::decode(self, buffer)
+    }
+}
+
+/// A codec that dispatches to one of several registered codecs based on the content type of each
+/// message, allowing a single stream to carry payloads in different formats.
+///
+/// Messages are encoded with the active codec, and carry its content type in the
+/// [CONTENT_TYPE_HEADER]. When decoding, the header is used to select the matching codec from the
+/// registry, so subscribers can decode messages produced by any publisher, as long as they have
+/// registered a codec for its content type.
+///
+/// Messages without the header, such as those sent by publishers using a plain codec, are decoded
+/// with the active codec. Batched and chunked messages don't carry headers, so a publisher using a
+/// `MultiCodec` sends each message on its own, unless it's large enough to be chunked.
+#[derive(Clone)]
+pub struct MultiCodec<Item> {
+    codecs: Vec<(String, Arc<dyn Codec<Item>>)>,
+    active: usize,
+}
+
+impl<Item: Clone> MultiCodec<Item> {
+    /// Constructs a MultiCodec with a single codec registered for `content_type`, which is used
+    /// to encode messages until another codec is made active.
+    pub fn new<C>(content_type: impl Into<String>, codec: C) -> Self
+    where
+        C: MessageEncoder<Item = Item> + MessageDecoder<Item = Item> + Send + Sync + 'static,
+    {
+        Self {
+            codecs: Vec::new(),
+            active: 0,
+        }
+        .with_codec(content_type, codec)
+    }
+
+    /// Registers an additional `codec` for `content_type`, replacing any codec previously
+    /// registered for the same content type.
+    pub fn with_codec<C>(mut self, content_type: impl Into<String>, codec: C) -> Self
+    where
+        C: MessageEncoder<Item = Item> + MessageDecoder<Item = Item> + Send + Sync + 'static,
+    {
+        let content_type = content_type.into();
+        let codec: Arc<dyn Codec<Item>> = Arc::new(codec);
+
+        match self.position(&content_type) {
+            Some(index) => self.codecs[index].1 = codec,
+            None => self.codecs.push((content_type, codec)),
+        }
+
+        self
+    }
+
+    /// Sets the codec used to encode messages to the codec registered for `content_type`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if no codec has been registered for `content_type`.
+    pub fn set_active(&mut self, content_type: &str) -> Result<()> {
+        self.active = self
+            .position(content_type)
+            .ok_or_else(|| anyhow!("No codec registered for content type {content_type}"))?;
+
+        Ok(())
+    }
+
+    /// Returns the content type of the codec used to encode messages.
+    pub fn active(&self) -> &str {
+        &self.codecs[self.active].0
+    }
+
+    fn position(&self, content_type: &str) -> Option<usize> {
+        self.codecs.iter().position(|(ty, _)| ty == content_type)
+    }
+}
+
+/// Encodes `item` with the active codec, setting the [CONTENT_TYPE_HEADER] to its content type
+/// when encoding with headers.
+///
+/// # Errors
+///
+/// Returns [Err] if the active codec fails to encode `item`.
+impl<Item: Clone> MessageEncoder for MultiCodec<Item> {
+    type Item = Item;
+
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        self.codecs[self.active].1.encode_item(item)
+    }
+
+    fn encode_with_headers(
+        &self,
+        item: Item,
+        headers: &mut HashMap<String, String>,
+    ) -> Result<Bytes> {
+        let (content_type, codec) = &self.codecs[self.active];
+        let payload = codec.encode_item(item)?;
+        headers.insert(CONTENT_TYPE_HEADER.to_owned(), content_type.clone());
+
+        Ok(payload)
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload with the codec registered for the content type
+/// in its [CONTENT_TYPE_HEADER], or with the active codec if the message has no content type.
+///
+/// # Errors
+///
+/// Returns [Err] if no codec has been registered for the message's content type, or the selected
+/// codec fails to decode the payload.
+impl<Item: Clone> MessageDecoder for MultiCodec<Item> {
+    type Item = Item;
+
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        self.decode_with_headers(buffer, None)
+    }
+
+    fn decode_with_headers(
+        &self,
+        buffer: &mut BytesMut,
+        headers: Option<&HashMap<String, String>>,
+    ) -> Result<Item> {
+        let content_type = headers.and_then(|headers| headers.get(CONTENT_TYPE_HEADER));
+
+        let index = match content_type {
+            Some(content_type) => self
+                .position(content_type)
+                .ok_or_else(|| anyhow!("No codec registered for content type {content_type}"))?,
+            None => self.active,
+        };
+
+        self.codecs[index].1.decode_item(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::{BincodeCodec, StringCodec};
+
+    fn codec() -> MultiCodec<String> {
+        MultiCodec::new("text/plain", StringCodec)
+            .with_codec("application/bincode", BincodeCodec::default())
+    }
+
+    #[test]
+    fn round_trips_messages_with_different_content_types() {
+        let mut codec = codec();
+
+        let mut text_headers = HashMap::new();
+        let text = codec
+            .encode_with_headers("foo".to_owned(), &mut text_headers)
+            .unwrap();
+
+        codec.set_active("application/bincode").unwrap();
+        let mut binary_headers = HashMap::new();
+        let binary = codec
+            .encode_with_headers("bar".to_owned(), &mut binary_headers)
+            .unwrap();
+
+        assert_eq!(&text[..], b"foo");
+        assert_eq!(text_headers[CONTENT_TYPE_HEADER], "text/plain");
+        assert_eq!(binary_headers[CONTENT_TYPE_HEADER], "application/bincode");
+
+        // The content type of each message selects its codec, regardless of the active codec
+        let decoded = [(text, text_headers), (binary, binary_headers)]
+            .into_iter()
+            .map(|(bytes, headers)| {
+                let mut buffer = BytesMut::from(&bytes[..]);
+                codec
+                    .decode_with_headers(&mut buffer, Some(&headers))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(decoded, ["foo", "bar"]);
+    }
+
+    #[test]
+    fn decodes_messages_without_content_type_with_active_codec() {
+        let codec = codec();
+        let mut buffer = BytesMut::from("foo");
+
+        assert_eq!(codec.decode_with_headers(&mut buffer, None).unwrap(), "foo");
+    }
+
+    #[test]
+    fn fails_to_decode_unregistered_content_type() {
+        let encoder = MultiCodec::new("application/bincode", BincodeCodec::default());
+        let decoder = MultiCodec::new("text/plain", StringCodec);
+
+        let mut headers = HashMap::new();
+        let bytes = encoder
+            .encode_with_headers("foo".to_owned(), &mut headers)
+            .unwrap();
+        let mut buffer = BytesMut::from(&bytes[..]);
+
+        assert!(decoder
+            .decode_with_headers(&mut buffer, Some(&headers))
+            .is_err());
+    }
+
+    #[test]
+    fn fails_to_activate_unregistered_content_type() {
+        let mut codec = codec();
+
+        assert!(codec.set_active("application/json").is_err());
+        assert_eq!(codec.active(), "text/plain");
+    }
+}
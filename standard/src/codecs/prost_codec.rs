@@ -0,0 +1,123 @@
+use std::marker::PhantomData;
+
+use crate::errors::CodecError;
+use crate::traits::codec::{MessageDecoder, MessageEncoder};
+use anyhow::Result;
+use bytes::{Bytes, BytesMut};
+use prost::Message;
+
+/// A codec that uses [prost] to serialize and deserialize protobuf message payloads.
+///
+/// Unlike [BincodeCodec](crate::codecs::BincodeCodec), `ProstCodec` does not require `Item` to
+/// implement [serde] traits, and instead accepts any type implementing [prost::Message], such as
+/// those generated by `prost-build`.
+#[derive(Debug, Clone)]
+pub struct ProstCodec<Item> {
+    _marker: PhantomData<Item>,
+}
+
+impl<Item> Default for ProstCodec<Item> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Encodes any `Item` implementing [Message](prost::Message) into the protobuf wire format.
+///
+/// # Errors
+///
+/// Returns [Err] if `item` fails to encode.
+impl<Item: Message + Clone> MessageEncoder for ProstCodec<Item> {
+    type Item = Item;
+
+    fn encode(&self, item: Self::Item) -> Result<Bytes> {
+        let mut buffer = BytesMut::with_capacity(item.encoded_len());
+
+        item.encode(&mut buffer)
+            .map_err(|e| CodecError::EncodeFailure(e.into()))?;
+
+        Ok(buffer.into())
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into any `Item` implementing
+/// [Message](prost::Message) and [Default].
+///
+/// # Errors
+///
+/// Returns [Err] if the [BytesMut](bytes::BytesMut) payload is not a valid protobuf encoding of
+/// `Item`.
+impl<Item: Message + Default> MessageDecoder for ProstCodec<Item> {
+    type Item = Item;
+
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Self::Item> {
+        let item = Item::decode(buffer).map_err(|e| CodecError::DecodeFailure(e.into()))?;
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    struct Dummy {
+        #[prost(string, tag = "1")]
+        foo: String,
+        #[prost(uint64, tag = "2")]
+        bar: u64,
+    }
+
+    #[test]
+    fn encodes_to_protobuf_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let codec = ProstCodec::default();
+        let bytes = codec.encode(input).unwrap();
+        let expected = Bytes::from("\x0a\x03foo\x10*");
+
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn decodes_protobuf_bytes() {
+        let mut buffer = BytesMut::from("\x0a\x03foo\x10*");
+        let decoder = ProstCodec::<Dummy>::default();
+
+        let expected = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let decoded = decoder.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn round_trips_protobuf_message() {
+        let input = Dummy {
+            foo: "Hello, world!".to_owned(),
+            bar: u64::MAX,
+        };
+
+        let codec = ProstCodec::<Dummy>::default();
+        let mut buffer = BytesMut::from(&codec.encode(input.clone()).unwrap()[..]);
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_invalid_payload() {
+        let mut buffer = BytesMut::from("\x0a\x05fo");
+        let decoder = ProstCodec::<Dummy>::default();
+
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
+}
@@ -92,4 +92,8 @@ impl Compress for BrotliComp {
 
         Ok(encoder.into_inner().into())
     }
+
+    fn algorithm(&self) -> &str {
+        "brotli"
+    }
 }
@@ -1,3 +1,4 @@
+use crate::compression::read_with_limit;
 use crate::traits::compression::Decompress;
 use anyhow::Result;
 use brotli::Decompressor;
@@ -21,4 +22,12 @@ impl Decompress for BrotliDecomp {
 
         Ok(buf.into())
     }
+
+    fn decompress_with_limit(&self, input: Bytes, limit: usize) -> Result<Bytes> {
+        read_with_limit(Decompressor::new(&input[..], BUFFER_SIZE), limit)
+    }
+
+    fn algorithm(&self) -> &str {
+        "brotli"
+    }
 }
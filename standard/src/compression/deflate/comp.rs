@@ -78,4 +78,8 @@ impl Compress for DeflateComp {
 
         Ok(bytes.into())
     }
+
+    fn algorithm(&self) -> &str {
+        self.library.algorithm()
+    }
 }
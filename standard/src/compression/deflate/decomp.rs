@@ -1,4 +1,5 @@
 use super::types::DeflateLibrary;
+use crate::compression::read_with_limit;
 use crate::traits::compression::Decompress;
 use anyhow::Result;
 use bytes::Bytes;
@@ -51,4 +52,15 @@ impl Decompress for DeflateDecomp {
 
         Ok(output.into())
     }
+
+    fn decompress_with_limit(&self, input: Bytes, limit: usize) -> Result<Bytes> {
+        match self.library {
+            DeflateLibrary::Gzip => read_with_limit(GzDecoder::new(&input[..]), limit),
+            DeflateLibrary::Zlib => read_with_limit(ZlibDecoder::new(&input[..]), limit),
+        }
+    }
+
+    fn algorithm(&self) -> &str {
+        self.library.algorithm()
+    }
 }
@@ -7,6 +7,15 @@ pub enum DeflateLibrary {
     Zlib,
 }
 
+impl DeflateLibrary {
+    pub(crate) fn algorithm(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zlib => "zlib",
+        }
+    }
+}
+
 impl Default for DeflateLibrary {
     fn default() -> Self {
         Self::Gzip
@@ -17,4 +17,8 @@ impl Compress for Lz4Comp {
 
         Ok(encoder.finish()?.into())
     }
+
+    fn algorithm(&self) -> &str {
+        "lz4"
+    }
 }
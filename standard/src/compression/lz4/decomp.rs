@@ -1,3 +1,4 @@
+use crate::compression::read_with_limit;
 use crate::traits::compression::Decompress;
 use anyhow::Result;
 use bytes::Bytes;
@@ -18,4 +19,12 @@ impl Decompress for Lz4Decomp {
 
         Ok(buf.into())
     }
+
+    fn decompress_with_limit(&self, input: Bytes, limit: usize) -> Result<Bytes> {
+        read_with_limit(FrameDecoder::new(&input[..]), limit)
+    }
+
+    fn algorithm(&self) -> &str {
+        "lz4"
+    }
 }
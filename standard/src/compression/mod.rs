@@ -203,6 +203,26 @@ pub mod deflate;
 pub mod lz4;
 pub mod zstd;
 
+use crate::errors::DecompressionLimitExceeded;
+use anyhow::Result;
+use bytes::Bytes;
+use std::io::Read;
+
+/// Reads the output of a streaming decompressor, failing as soon as it exceeds `limit` bytes,
+/// rather than buffering the entire output.
+fn read_with_limit(reader: impl Read, limit: usize) -> Result<Bytes> {
+    let mut output = Vec::new();
+    reader
+        .take((limit as u64).saturating_add(1))
+        .read_to_end(&mut output)?;
+
+    if output.len() > limit {
+        return Err(DecompressionLimitExceeded(limit).into());
+    }
+
+    Ok(output.into())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -480,6 +500,46 @@ mod test {
         assert_eq!(payload, output);
     }
 
+    #[test]
+    fn decompression_limit_trips_on_high_ratio_payloads() {
+        const LIMIT: usize = 64 * 1024;
+
+        // Zeros compress extremely well, so a small payload decompresses to far beyond the limit
+        let payload = Bytes::from(vec![0u8; LIMIT * 64]);
+
+        let algorithms: Vec<(Box<dyn Compress>, Box<dyn Decompress>)> = vec![
+            (
+                Box::new(deflate::DeflateComp::gzip()),
+                Box::new(deflate::DeflateDecomp::gzip()),
+            ),
+            (
+                Box::new(deflate::DeflateComp::zlib()),
+                Box::new(deflate::DeflateDecomp::zlib()),
+            ),
+            (Box::new(zstd::ZstdComp::new()), Box::new(zstd::ZstdDecomp)),
+            (
+                Box::new(brotli::BrotliComp::generic()),
+                Box::new(brotli::BrotliDecomp),
+            ),
+            (Box::new(lz4::Lz4Comp), Box::new(lz4::Lz4Decomp)),
+        ];
+
+        for (comp, decomp) in algorithms {
+            let compressed = comp.compress(payload.clone()).unwrap();
+            assert!(compressed.len() < LIMIT);
+
+            let err = decomp
+                .decompress_with_limit(compressed.clone(), LIMIT)
+                .unwrap_err();
+            assert!(err.is::<DecompressionLimitExceeded>());
+
+            let output = decomp
+                .decompress_with_limit(compressed, payload.len())
+                .unwrap();
+            assert_eq!(output, payload);
+        }
+    }
+
     #[test]
     fn lz4() {
         let payload = generate_payload();
@@ -60,4 +60,8 @@ impl Compress for ZstdComp {
         let output = zstd::encode_all(&input[..], self.level)?;
         Ok(output.into())
     }
+
+    fn algorithm(&self) -> &str {
+        "zstd"
+    }
 }
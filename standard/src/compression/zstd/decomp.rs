@@ -1,3 +1,4 @@
+use crate::compression::read_with_limit;
 use crate::traits::compression::Decompress;
 use anyhow::Result;
 use bytes::Bytes;
@@ -13,4 +14,13 @@ impl Decompress for ZstdDecomp {
         let output = zstd::decode_all(&input[..])?;
         Ok(output.into())
     }
+
+    fn decompress_with_limit(&self, input: Bytes, limit: usize) -> Result<Bytes> {
+        let decoder = zstd::Decoder::new(&input[..])?;
+        read_with_limit(decoder, limit)
+    }
+
+    fn algorithm(&self) -> &str {
+        "zstd"
+    }
 }
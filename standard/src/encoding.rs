@@ -0,0 +1,157 @@
+//! Configuration for the [bincode] binary encoding used by Selium's wire protocol and codecs.
+//!
+//! Bincode does not describe its own layout, so the encoding options form part of the wire format.
+//! Any two parties exchanging bincode-encoded data, such as a client and the `Selium` server, or a
+//! publisher and its subscribers, must be configured with the same [BincodeConfig], otherwise
+//! payloads will fail to decode, or worse, decode into incorrect values.
+//!
+//! The default configuration uses fixed-width, little-endian integers, matching the
+//! [bincode::serialize] and [bincode::deserialize] functions.
+
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// The encoding used for integer values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntEncoding {
+    /// Integers are encoded using their full width, e.g. a [u64] always occupies 8 bytes.
+    #[default]
+    Fixed,
+    /// Integers are encoded using a variable number of bytes, depending on their value.
+    Variable,
+}
+
+/// The byte order used for integer values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Options for serializing and deserializing values via [bincode].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BincodeConfig {
+    int_encoding: IntEncoding,
+    endianness: Endianness,
+}
+
+// Bincode's options are encoded in the type system, so each combination needs its own branch.
+macro_rules! with_options {
+    ($config:expr, |$options:ident| $body:expr) => {{
+        let base = DefaultOptions::new().allow_trailing_bytes();
+
+        match ($config.int_encoding, $config.endianness) {
+            (IntEncoding::Fixed, Endianness::Little) => {
+                let $options = base.with_fixint_encoding().with_little_endian();
+                $body
+            }
+            (IntEncoding::Fixed, Endianness::Big) => {
+                let $options = base.with_fixint_encoding().with_big_endian();
+                $body
+            }
+            (IntEncoding::Variable, Endianness::Little) => {
+                let $options = base.with_varint_encoding().with_little_endian();
+                $body
+            }
+            (IntEncoding::Variable, Endianness::Big) => {
+                let $options = base.with_varint_encoding().with_big_endian();
+                $body
+            }
+        }
+    }};
+}
+
+impl BincodeConfig {
+    /// Constructs a BincodeConfig with the provided integer encoding and endianness.
+    pub fn new(int_encoding: IntEncoding, endianness: Endianness) -> Self {
+        Self {
+            int_encoding,
+            endianness,
+        }
+    }
+
+    /// Fixed-width, little-endian integers. This is the default configuration.
+    pub fn fixint() -> Self {
+        Self::new(IntEncoding::Fixed, Endianness::Little)
+    }
+
+    /// Variable-width, little-endian integers, producing more compact payloads.
+    pub fn varint() -> Self {
+        Self::new(IntEncoding::Variable, Endianness::Little)
+    }
+
+    /// Overrides the integer encoding.
+    pub fn int_encoding(mut self, int_encoding: IntEncoding) -> Self {
+        self.int_encoding = int_encoding;
+        self
+    }
+
+    /// Overrides the endianness.
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Serializes `value` into a byte vector.
+    pub fn serialize<T: ?Sized + Serialize>(&self, value: &T) -> bincode::Result<Vec<u8>> {
+        with_options!(self, |options| options.serialize(value))
+    }
+
+    /// Serializes `value` directly into `writer`.
+    pub fn serialize_into<W: Write, T: ?Sized + Serialize>(
+        &self,
+        writer: W,
+        value: &T,
+    ) -> bincode::Result<()> {
+        with_options!(self, |options| options.serialize_into(writer, value))
+    }
+
+    /// Returns the number of bytes `value` will occupy once serialized.
+    pub fn serialized_size<T: ?Sized + Serialize>(&self, value: &T) -> bincode::Result<u64> {
+        with_options!(self, |options| options.serialized_size(value))
+    }
+
+    /// Deserializes a value from a byte slice.
+    pub fn deserialize<'a, T: Deserialize<'a>>(&self, bytes: &'a [u8]) -> bincode::Result<T> {
+        with_options!(self, |options| options.deserialize(bytes))
+    }
+
+    /// Deserializes a value directly from `reader`.
+    pub fn deserialize_from<R: Read, T: DeserializeOwned>(&self, reader: R) -> bincode::Result<T> {
+        with_options!(self, |options| options.deserialize_from(reader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_matches_bincode_defaults() {
+        let value = (42u64, "foo".to_owned());
+        let config = BincodeConfig::default();
+
+        let bytes = config.serialize(&value).unwrap();
+
+        assert_eq!(bytes, bincode::serialize(&value).unwrap());
+        assert_eq!(config.serialized_size(&value).unwrap(), bytes.len() as u64);
+    }
+
+    #[test]
+    fn encodes_integers_with_configured_layout() {
+        let value = 42u32;
+
+        let fixint = BincodeConfig::fixint().serialize(&value).unwrap();
+        let fixint_be = BincodeConfig::fixint()
+            .endianness(Endianness::Big)
+            .serialize(&value)
+            .unwrap();
+        let varint = BincodeConfig::varint().serialize(&value).unwrap();
+
+        assert_eq!(fixint, [42, 0, 0, 0]);
+        assert_eq!(fixint_be, [0, 0, 0, 42]);
+        assert_eq!(varint, [42]);
+    }
+}
@@ -1,10 +1,87 @@
-use quinn::{ConnectError, ConnectionError, WriteError};
+use quinn::{ConnectError, ConnectionError, SendDatagramError, UnknownStream, WriteError};
 use selium_log::error::LogError;
 use std::net::AddrParseError;
 use thiserror::Error;
 
 pub type Result<T, E = SeliumError> = std::result::Result<T, E>;
 
+/// Error codes sent by the `Selium` server when a stream fails, or is refused.
+///
+/// Codes are sent over the wire as a [u32], and can be converted to and from an [ErrorCode] via
+/// the [From] trait. Any code that isn't defined by `Selium`, such as a code returned by a custom
+/// authenticator, is represented as [ErrorCode::Unknown].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    ShutdownInProgress,
+    Shutdown,
+    StreamClosedPrematurely,
+    InvalidTopicName,
+    ReplierAlreadyBound,
+    CloudAuthFailed,
+    TopicNotFound,
+    Unauthorized,
+    CompressionMismatch,
+    MessageTooLarge,
+    ReservationNotFound,
+    DatagramsUnsupported,
+    UnknownServerName,
+    LogWriteFailed,
+    SchemaViolation,
+    OneWayStreamUnsupported,
+    TopicClosed,
+    Unknown(u32),
+}
+
+impl From<u32> for ErrorCode {
+    fn from(code: u32) -> Self {
+        match code {
+            0x1 => Self::ShutdownInProgress,
+            0x2 => Self::Shutdown,
+            0x3 => Self::StreamClosedPrematurely,
+            0x4 => Self::InvalidTopicName,
+            0x5 => Self::ReplierAlreadyBound,
+            0x6 => Self::CloudAuthFailed,
+            0x7 => Self::TopicNotFound,
+            0x8 => Self::Unauthorized,
+            0x9 => Self::CompressionMismatch,
+            0xA => Self::MessageTooLarge,
+            0xB => Self::ReservationNotFound,
+            0xC => Self::DatagramsUnsupported,
+            0xD => Self::UnknownServerName,
+            0xE => Self::LogWriteFailed,
+            0xF => Self::SchemaViolation,
+            0x10 => Self::OneWayStreamUnsupported,
+            0x11 => Self::TopicClosed,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::ShutdownInProgress => 0x1,
+            ErrorCode::Shutdown => 0x2,
+            ErrorCode::StreamClosedPrematurely => 0x3,
+            ErrorCode::InvalidTopicName => 0x4,
+            ErrorCode::ReplierAlreadyBound => 0x5,
+            ErrorCode::CloudAuthFailed => 0x6,
+            ErrorCode::TopicNotFound => 0x7,
+            ErrorCode::Unauthorized => 0x8,
+            ErrorCode::CompressionMismatch => 0x9,
+            ErrorCode::MessageTooLarge => 0xA,
+            ErrorCode::ReservationNotFound => 0xB,
+            ErrorCode::DatagramsUnsupported => 0xC,
+            ErrorCode::UnknownServerName => 0xD,
+            ErrorCode::LogWriteFailed => 0xE,
+            ErrorCode::SchemaViolation => 0xF,
+            ErrorCode::OneWayStreamUnsupported => 0x10,
+            ErrorCode::TopicClosed => 0x11,
+            ErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum CryptoError {
     #[error("Failed to read private key from file.")]
@@ -27,6 +104,15 @@ pub enum CryptoError {
 
     #[error("No valid root cert found in file.")]
     InvalidRootCert,
+
+    #[error("No valid root cert found in CA file {0}.")]
+    InvalidRootCertFile(std::path::PathBuf),
+
+    #[error("Failed to load root certs from the platform's trust store.")]
+    LoadNativeRootsError(#[source] std::io::Error),
+
+    #[error("No valid root certs found in the platform's trust store.")]
+    NoNativeRootsFound,
 }
 
 #[derive(Error, Debug)]
@@ -39,6 +125,12 @@ pub enum ProtocolError {
 
     #[error("Failed to serialize/deserialize message on protocol.")]
     SerdeError(#[source] bincode::Error),
+
+    #[error("Received chunk {1} of message {0} out of sequence.")]
+    ChunkOutOfSequence(u64, u32),
+
+    #[error("Chunked message {0} exceeds the maximum reassembly buffer size ({1} bytes).")]
+    ChunkedMessageTooLarge(u64, usize),
 }
 
 #[derive(Error, Debug)]
@@ -56,6 +148,12 @@ pub enum CodecError {
     DecodeFailure(#[source] anyhow::Error),
 }
 
+/// The error returned when a payload decompresses to more than the permitted number of bytes,
+/// guarding against small payloads crafted to decompress to an excessive size.
+#[derive(Error, Debug)]
+#[error("Decompression limit exceeded: payload decompresses to more than {0} bytes.")]
+pub struct DecompressionLimitExceeded(pub usize);
+
 #[derive(Error, Debug)]
 pub enum QuicError {
     #[error("Error sending message to topic.")]
@@ -67,8 +165,14 @@ pub enum QuicError {
     #[error("An error occured on an existing connection.")]
     ConnectionError(#[from] ConnectionError),
 
+    #[error("Error sending datagram to topic.")]
+    SendDatagramError(#[from] SendDatagramError),
+
     #[error("Too many connection retries.")]
     TooManyRetries,
+
+    #[error("Stream is no longer open.")]
+    UnknownStream(#[from] UnknownStream),
 }
 
 #[derive(Error, Debug)]
@@ -142,6 +246,9 @@ pub enum SeliumError {
     #[error("The request timed out before receiving a reply.")]
     RequestTimeout,
 
+    #[error("The request was cancelled before receiving a reply.")]
+    RequestCancelled,
+
     #[error("Failed to open stream on Selium Cloud endpoint.")]
     OpenCloudStreamFailed(#[source] ConnectionError),
 
@@ -151,15 +258,45 @@ pub enum SeliumError {
     #[error("Cannot connect directly to the Selium cloud endpoint. Use the `selium::cloud` builder instead.")]
     ConnectDirectToCloud,
 
+    #[error("Timed out while connecting to the Selium server.")]
+    ConnectTimeout,
+
+    #[error("Failed to reconnect to the Selium server within the maximum reconnect gap.")]
+    ReconnectGapExceeded,
+
+    #[error("The keep-alive interval ({keep_alive_interval}ms) must be no more than half of the idle timeout ({idle_timeout}ms).")]
+    InvalidKeepAliveInterval {
+        keep_alive_interval: u64,
+        idle_timeout: u64,
+    },
+
     #[error("Poorly formatted topic name, must be in the format [namespace]/[topic]")]
     ParseTopicNameError,
 
     #[error("Cannot use a reserved namespace prefix.")]
     ReservedNamespaceError,
 
+    #[error("Cannot use the reserved message header `{0}`.")]
+    ReservedHeaderError(String),
+
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 
     #[error("Failed to open stream with error: {1}.")]
-    OpenStream(u32, String),
+    OpenStream(ErrorCode, String),
+
+    #[error("The connection was closed by the server with code {code:?}: {reason}")]
+    ConnectionClosed { code: ErrorCode, reason: String },
+
+    #[error("A replier is already bound to this topic.")]
+    ReplierAlreadyBound,
+
+    #[error("The topic has been closed by the server.")]
+    TopicClosed,
+
+    #[error("The server rejected a message with error: {1}.")]
+    MessageRejected(ErrorCode, String),
+
+    #[error("The server can't reply over a one-way stream.")]
+    OneWayStream,
 }
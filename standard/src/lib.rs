@@ -18,7 +18,8 @@
 //! `traits/codec.rs` module.
 //!
 //! - `compression`: Enables all compression implementations.
-//! - `codec`: Enables all client codec implementations.
+//! - `codec`: Enables all client codec implementations. Implies `encoding`.
+//! - `encoding`: Enables the configurable bincode encoding used by the wire protocol.
 //! - `traits`: Enables all traits. Enabled by default.
 //! - `errors`: Enables all errors. Enabled by default.
 
@@ -27,5 +28,7 @@ pub mod codecs;
 #[cfg(feature = "compression")]
 pub mod compression;
 
+#[cfg(feature = "encoding")]
+pub mod encoding;
 pub mod errors;
 pub mod traits;
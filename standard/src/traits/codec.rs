@@ -1,5 +1,6 @@
 use anyhow::Result;
 use bytes::{Bytes, BytesMut};
+use std::collections::HashMap;
 
 /// Provides an `encode` method for implementors to build their own encoder types.
 ///
@@ -8,6 +9,18 @@ pub trait MessageEncoder {
     type Item: Clone;
 
     fn encode(&self, item: Self::Item) -> Result<Bytes>;
+
+    /// Encodes `item` in the same manner as [encode](MessageEncoder::encode), adding any headers
+    /// that decoders need to the message's `headers`.
+    ///
+    /// Defaults to [encode](MessageEncoder::encode), leaving the headers untouched.
+    fn encode_with_headers(
+        &self,
+        item: Self::Item,
+        _headers: &mut HashMap<String, String>,
+    ) -> Result<Bytes> {
+        self.encode(item)
+    }
 }
 
 /// Provides a `decode` method for implementors to build their own decoder types.
@@ -17,4 +30,16 @@ pub trait MessageDecoder {
     type Item;
 
     fn decode(&self, buffer: &mut BytesMut) -> Result<Self::Item>;
+
+    /// Decodes a message in the same manner as [decode](MessageDecoder::decode), given the
+    /// message's headers, or [None] if the message was received without any.
+    ///
+    /// Defaults to [decode](MessageDecoder::decode), ignoring the headers.
+    fn decode_with_headers(
+        &self,
+        buffer: &mut BytesMut,
+        _headers: Option<&HashMap<String, String>>,
+    ) -> Result<Self::Item> {
+        self.decode(buffer)
+    }
 }
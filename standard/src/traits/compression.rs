@@ -1,3 +1,4 @@
+use crate::errors::DecompressionLimitExceeded;
 use anyhow::Result;
 use bytes::Bytes;
 
@@ -5,12 +6,41 @@ use bytes::Bytes;
 pub trait Compress {
     /// Fallibly compress the `input` bytes, and output as bytes.
     fn compress(&self, input: Bytes) -> Result<Bytes>;
+
+    /// The name of the compression algorithm, which is declared to the server so that
+    /// subscribers can be checked against it. Must match the name returned by the corresponding
+    /// [Decompress] implementation.
+    fn algorithm(&self) -> &str {
+        "custom"
+    }
 }
 
 /// Interface to adapt compression implementations for use with Selium.
 pub trait Decompress {
     /// Fallibly decompress the `input` bytes, and output as bytes.
     fn decompress(&self, input: Bytes) -> Result<Bytes>;
+
+    /// Fallibly decompress the `input` bytes, failing with [DecompressionLimitExceeded] if the
+    /// output exceeds `limit` bytes.
+    ///
+    /// The default implementation only checks the limit once the whole payload has been
+    /// decompressed, so implementations backed by a streaming decompressor should override it
+    /// to stop as soon as the limit is exceeded.
+    fn decompress_with_limit(&self, input: Bytes, limit: usize) -> Result<Bytes> {
+        let output = self.decompress(input)?;
+
+        if output.len() > limit {
+            return Err(DecompressionLimitExceeded(limit).into());
+        }
+
+        Ok(output)
+    }
+
+    /// The name of the compression algorithm, which must match the name returned by the
+    /// corresponding [Compress] implementation.
+    fn algorithm(&self) -> &str {
+        "custom"
+    }
 }
 
 /// Interface for applicable compression algorithms and implementations that allow users to
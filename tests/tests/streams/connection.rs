@@ -0,0 +1,1052 @@
+use crate::helpers::{
+    build_server, client_builder, connect_client, run_server, spawn_server_with_args,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use clap::Parser;
+use futures::{SinkExt, StreamExt};
+use quinn::Connection;
+use selium::keep_alive::{BackoffStrategy, ConnectionEvent, KeepAliveState};
+use selium::prelude::*;
+use selium::std::codecs::StringCodec;
+use selium::std::errors::{CryptoError, ErrorCode, SeliumError};
+use selium::TopicEvent;
+use selium_protocol::{Frame, TopicName};
+use selium_server::args::{LogArgs, UserArgs};
+use selium_server::auth::Authenticator;
+use selium_server::server::Server;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const CUSTOM_ALPN: &str = "selium-test";
+const ACCESS_DENIED: u32 = 0x100;
+
+struct DenyNamespace(&'static str);
+
+#[async_trait]
+impl Authenticator for DenyNamespace {
+    async fn authorize(
+        &self,
+        _conn: &Connection,
+        _server_name: Option<&str>,
+        frame: &Frame,
+    ) -> Result<()> {
+        match frame.get_topic() {
+            Some(topic) if topic.namespace() == self.0 => bail!("Access denied"),
+            _ => Ok(()),
+        }
+    }
+
+    fn error_code(&self) -> u32 {
+        ACCESS_DENIED
+    }
+}
+
+// Denies every stream on the given virtual host
+struct DenyServerName(&'static str);
+
+#[async_trait]
+impl Authenticator for DenyServerName {
+    async fn authorize(
+        &self,
+        _conn: &Connection,
+        server_name: Option<&str>,
+        _frame: &Frame,
+    ) -> Result<()> {
+        if server_name == Some(self.0) {
+            bail!("Access denied");
+        }
+
+        Ok(())
+    }
+
+    fn error_code(&self) -> u32 {
+        ACCESS_DENIED
+    }
+}
+
+// Accepts every stream, but never replies to the handshake
+struct StallHandshake;
+
+#[async_trait]
+impl Authenticator for StallHandshake {
+    async fn authorize(
+        &self,
+        _conn: &Connection,
+        _server_name: Option<&str>,
+        _frame: &Frame,
+    ) -> Result<()> {
+        futures::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_mismatched_alpn_is_refused() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--alpn", CUSTOM_ALPN])?;
+    let addr = server.addr()?.to_string();
+
+    // The client offers the default ALPN, which the server doesn't accept
+    let result = connect_client(&addr).await;
+
+    assert!(result.is_err());
+
+    let result = client_builder(selium::custom().with_alpn(CUSTOM_ALPN), &addr)?
+        .connect()
+        .await;
+
+    assert!(result.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_connection_events_across_disconnect() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--idle-timeout", "500"])?;
+    let addr = server.addr()?.to_string();
+
+    // The connection will time out whenever it's idle, forcing the subscriber to reconnect
+    let connection = client_builder(
+        selium::custom()
+            .keep_alive_interval(5_000)?
+            .backoff_strategy(BackoffStrategy::constant().with_max_attempts(10)),
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let events = connection.events();
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/events")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    // Poll the subscriber so that it notices the disconnect
+    tokio::spawn(async move { subscriber.next().await });
+
+    let received = timeout(Duration::from_secs(5), events.take(4).collect::<Vec<_>>()).await?;
+
+    assert_eq!(
+        received,
+        [
+            ConnectionEvent::Disconnected,
+            ConnectionEvent::Reconnecting { attempt: 1 },
+            ConnectionEvent::Connected,
+            ConnectionEvent::Reconnected,
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keep_alive_state_across_disconnect() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--idle-timeout", "500"])?;
+    let addr = server.addr()?.to_string();
+
+    // The connection will time out whenever it's idle, forcing the subscriber to reconnect
+    let connection = client_builder(
+        selium::custom()
+            .keep_alive_interval(5_000)?
+            .backoff_strategy(BackoffStrategy::constant().with_max_attempts(10)),
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/state")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    assert_eq!(subscriber.state(), KeepAliveState::Connected);
+
+    // Poll the subscriber in short bursts, recording each state it passes through until it has
+    // reconnected
+    let mut states = vec![subscriber.state()];
+
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let _ = timeout(Duration::from_millis(10), subscriber.next()).await;
+            let state = subscriber.state();
+
+            if states.last() != Some(&state) {
+                states.push(state);
+            }
+
+            if states.len() > 1 && state == KeepAliveState::Connected {
+                break;
+            }
+        }
+    })
+    .await?;
+
+    assert_eq!(
+        states,
+        [
+            KeepAliveState::Connected,
+            KeepAliveState::Reconnecting {
+                attempt: 1,
+                max: 10
+            },
+            KeepAliveState::Connected,
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_authenticator_rejects_streams() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = build_server(tempdir.path(), &[])?.with_authenticator(DenyNamespace("private"));
+    let server = run_server(server);
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let result = connection
+        .subscriber("/private/topic")
+        .with_decoder(StringCodec)
+        .open()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(SeliumError::OpenStream(ErrorCode::Unknown(ACCESS_DENIED), ref msg)) if msg == "Access denied"
+    ));
+
+    let result = connection
+        .subscriber("/public/topic")
+        .with_decoder(StringCodec)
+        .open()
+        .await;
+
+    assert!(result.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stalled_handshake_times_out() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = build_server(tempdir.path(), &[])?.with_authenticator(StallHandshake);
+    let server = run_server(server);
+    let addr = server.addr()?.to_string();
+
+    let connection = client_builder(
+        selium::custom().connect_timeout(Duration::from_millis(500))?,
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let result = timeout(
+        Duration::from_secs(5),
+        connection
+            .subscriber("/acmeco/stalled")
+            .with_decoder(StringCodec)
+            .open(),
+    )
+    .await?;
+
+    assert!(matches!(result, Err(SeliumError::ConnectTimeout)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_listens_on_multiple_addresses() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--bind-addr", "127.0.0.1:0"])?;
+    let addrs = server.addrs()?;
+    assert_eq!(addrs.len(), 2);
+    assert_ne!(addrs[0], addrs[1]);
+
+    let mut connections = vec![];
+
+    for addr in addrs {
+        let connection = connect_client(&addr.to_string()).await?;
+
+        connections.push(connection);
+    }
+
+    // Both endpoints belong to the same server, so they share topics
+    let mut subscriber = connections[1]
+        .subscriber("/acmeco/endpoints")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connections[0]
+        .publisher("/acmeco/endpoints")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("hello".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("hello".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_handle_exposes_bound_address() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = build_server(tempdir.path(), &[])?;
+    let handle = server.handle();
+
+    // The server is moved into the listen loop, so the handle is the only way to reach it
+    tokio::spawn(async move { server.listen().await });
+
+    let addr = handle.addr()?;
+    assert_ne!(addr.port(), 0);
+
+    let connection = connect_client(&addr.to_string()).await?;
+
+    connection
+        .publisher("/acmeco/handle")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_built_without_parsing_arguments_accepts_connections() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    let server = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert("../certs/server/localhost.der")
+        .key("../certs/server/localhost.key.der")
+        .ca("../certs/server/ca.der")
+        .log_segments_directory(tempdir.path())
+        .flush_policy_num_writes(1)
+        .build()?;
+    let addr = run_server(server).addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/builder")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher.send("Hello".to_owned()).await?;
+
+    // Segments are stored under the configured directory
+    let topic_dir = tempdir.path().join("acmeco/builder");
+    timeout(Duration::from_secs(5), async {
+        while !topic_dir.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    // Invalid settings are rejected, as they are when parsed from arguments
+    let result = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert("../certs/server/localhost.der")
+        .key("../certs/server/localhost.key.der")
+        .ca("../certs/server/ca.der")
+        .idle_timeout(Duration::from_secs(5))
+        .keep_alive_interval(Duration::from_secs(5))
+        .build();
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_zero_topic_channel_size_is_rejected() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    let result = UserArgs::try_parse_from([
+        "",
+        "--log-segments-directory",
+        tempdir.path().to_str().unwrap(),
+        "--topic-channel-size",
+        "0",
+    ]);
+    assert!(result.is_err());
+
+    // Settings supplied to the builder are checked in the same way
+    let log_args = LogArgs {
+        log_segments_directory: tempdir.path().to_owned(),
+        topic_channel_size: 0,
+        ..LogArgs::default()
+    };
+
+    let result = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert("../certs/server/localhost.der")
+        .key("../certs/server/localhost.key.der")
+        .ca("../certs/server/ca.der")
+        .log_args(log_args)
+        .build();
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_misconfigured_keep_alive_is_rejected() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    let result = build_server(
+        tempdir.path(),
+        &["--idle-timeout", "5000", "--keep-alive-interval", "5000"],
+    );
+    assert!(result.is_err());
+
+    let server = spawn_server_with_args(
+        tempdir.path(),
+        &["--idle-timeout", "5000", "--keep-alive-interval", "2500"],
+    )?;
+    let addr = server.addr()?.to_string();
+
+    let result = client_builder(
+        selium::custom()
+            .keep_alive_interval(Duration::from_secs(5))?
+            .idle_timeout(Duration::from_secs(5))?,
+        &addr,
+    )?
+    .connect()
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(SeliumError::InvalidKeepAliveInterval { .. })
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unusable_log_directory_is_rejected() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    // A directory beneath a file can't be created or written to
+    let file = tempdir.path().join("file");
+    std::fs::write(&file, b"")?;
+    let result = build_server(file.join("logs"), &[]);
+    assert!(
+        matches!(result, Err(e) if e.to_string().starts_with("Failed to create log directory"))
+    );
+
+    // Missing directories are only created when allowed
+    let logs_dir = tempdir.path().join("logs");
+    let result = build_server(&logs_dir, &["--create-log-dir=false"]);
+    assert!(matches!(result, Err(e) if e.to_string().starts_with("Log directory does not exist")));
+    assert!(!logs_dir.exists());
+
+    build_server(&logs_dir, &["--create-log-dir"])?;
+    assert!(logs_dir.is_dir());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_ping_does_not_open_topic() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &[])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let rtt = connection.ping().await?;
+    assert!(rtt < Duration::from_secs(5));
+
+    // No topic's log should have been created by the health check
+    assert_eq!(std::fs::read_dir(tempdir.path())?.count(), 0);
+
+    Ok(())
+}
+
+/// Relays UDP datagrams between a single client and the server at `server`, counting the QUIC
+/// Retry packets that the server sends.
+async fn spawn_retry_counting_relay(server: SocketAddr) -> Result<(SocketAddr, Arc<AtomicUsize>)> {
+    let downstream = UdpSocket::bind("127.0.0.1:0").await?;
+    let upstream = UdpSocket::bind("127.0.0.1:0").await?;
+    upstream.connect(server).await?;
+
+    let addr = downstream.local_addr()?;
+    let retries = Arc::new(AtomicUsize::new(0));
+
+    tokio::spawn({
+        let retries = retries.clone();
+
+        async move {
+            let mut client = None;
+            let mut from_client = [0; 65535];
+            let mut from_server = [0; 65535];
+
+            loop {
+                tokio::select! {
+                    Ok((len, addr)) = downstream.recv_from(&mut from_client) => {
+                        client = Some(addr);
+                        let _ = upstream.send(&from_client[..len]).await;
+                    }
+                    Ok(len) = upstream.recv(&mut from_server) => {
+                        let packet = &from_server[..len];
+
+                        // Retry packets have a long header of type 3, with a non-zero version
+                        if len > 5 && packet[0] & 0xb0 == 0xb0 && packet[1..5] != [0; 4] {
+                            retries.fetch_add(1, Ordering::SeqCst);
+                        }
+
+                        if let Some(client) = client {
+                            let _ = downstream.send_to(packet, client).await;
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    Ok((addr, retries))
+}
+
+#[tokio::test]
+async fn test_stateless_retry_completes_handshake() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(
+        tempdir.path(),
+        &[
+            "--stateless-retry",
+            "--retry-token-lifetime",
+            "5000",
+            "--initial-congestion-window",
+            "64000",
+        ],
+    )?;
+    let (addr, retries) = spawn_retry_counting_relay(server.addr()?).await?;
+
+    let connection = connect_client(&addr.to_string()).await?;
+
+    // The server validated the client's address before accepting the connection
+    assert_eq!(retries.load(Ordering::SeqCst), 1);
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/stateless_retry")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/stateless_retry")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("hello".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("hello".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_connections_are_not_retried_without_stateless_retry() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &[])?;
+    let (addr, retries) = spawn_retry_counting_relay(server.addr()?).await?;
+
+    connect_client(&addr.to_string()).await?;
+    assert_eq!(retries.load(Ordering::SeqCst), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_initial_congestion_window_below_minimum_is_rejected() {
+    let result = UserArgs::try_parse_from(["", "--initial-congestion-window", "0"]);
+    assert!(result.is_err());
+
+    let tempdir = TempDir::new().unwrap();
+    let result = Server::builder()
+        .log_args(LogArgs {
+            log_segments_directory: tempdir.path().to_owned(),
+            ..LogArgs::default()
+        })
+        .initial_congestion_window(1000)
+        .build();
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_retry_initial_connect_waits_for_server() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+
+    // Reserve a free port for the server to bind once the client is already connecting
+    let addr = std::net::UdpSocket::bind("127.0.0.1:0")?.local_addr()?;
+    let endpoint = addr.to_string();
+
+    let connect = |retry: bool| {
+        let endpoint = endpoint.clone();
+
+        async move {
+            let strategy = BackoffStrategy::constant()
+                .with_max_attempts(20)
+                .with_step(Duration::from_millis(250));
+
+            let mut builder = selium::custom()
+                .connect_timeout(Duration::from_millis(500))?
+                .backoff_strategy(strategy);
+
+            if retry {
+                builder = builder.retry_initial_connect();
+            }
+
+            builder
+                .endpoint(&endpoint)
+                .with_certificate_authority("../certs/client/ca.der")?
+                .with_cert_and_key(
+                    "../certs/client/localhost.der",
+                    "../certs/client/localhost.key.der",
+                )?
+                .connect()
+                .await
+        }
+    };
+
+    // Without retries, the client fails fast while the server is down
+    assert!(connect(false).await.is_err());
+
+    let client = tokio::spawn(connect(true));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let _server = spawn_server_with_args(tempdir.path(), &["--bind-addr", &endpoint])?;
+
+    let connection = timeout(Duration::from_secs(10), client).await???;
+    connection.ping().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_trusts_any_of_multiple_certificate_authorities() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &[])?;
+    let addr = server.addr()?.to_string();
+
+    // An unrelated CA, which didn't issue the server's certificate
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let other_ca = rcgen::Certificate::from_params(params)?;
+    let other_ca_path = tempdir.path().join("other_ca.der");
+    std::fs::write(&other_ca_path, other_ca.serialize_der()?)?;
+
+    let result = selium::custom()
+        .endpoint(&addr)
+        .with_certificate_authorities(&[&other_ca_path])?
+        .with_cert_and_key(
+            "../certs/client/localhost.der",
+            "../certs/client/localhost.key.der",
+        )?
+        .connect()
+        .await;
+
+    assert!(result.is_err());
+
+    // The server's certificate chains to the second CA
+    let connection = selium::custom()
+        .endpoint(&addr)
+        .with_certificate_authorities(&[
+            other_ca_path.as_path(),
+            "../certs/client/ca.der".as_ref(),
+        ])?
+        .with_cert_and_key(
+            "../certs/client/localhost.der",
+            "../certs/client/localhost.key.der",
+        )?
+        .connect()
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/multi_ca")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/multi_ca")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("hello".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("hello".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_warns_when_certificate_is_due_to_expire() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let one_day = Duration::from_secs(60 * 60 * 24);
+
+    // A client certificate that expires in a day, issued by a CA that the server trusts
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = rcgen::Certificate::from_params(params)?;
+
+    let not_after = SystemTime::now() + one_day;
+    let mut params = rcgen::CertificateParams::new(vec!["localhost".to_owned()]);
+    params.not_after = not_after.into();
+    let cert = rcgen::Certificate::from_params(params)?;
+
+    let ca_path = tempdir.path().join("ca.der");
+    let cert_path = tempdir.path().join("short_lived.der");
+    let key_path = tempdir.path().join("short_lived.key.der");
+    std::fs::write(&ca_path, ca.serialize_der()?)?;
+    std::fs::write(&cert_path, cert.serialize_der_with_signer(&ca)?)?;
+    std::fs::write(&key_path, cert.serialize_private_key_der())?;
+
+    let server = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert("../certs/server/localhost.der")
+        .key("../certs/server/localhost.key.der")
+        .ca(&ca_path)
+        .log_segments_directory(tempdir.path().join("logs"))
+        .build()?;
+    let addr = run_server(server).addr()?.to_string();
+
+    let connect = |window: Duration| {
+        let (tx, rx) = mpsc::channel();
+        let addr = addr.clone();
+        let cert_path = cert_path.clone();
+        let key_path = key_path.clone();
+
+        async move {
+            let client = selium::custom()
+                .cert_expiry_warning(window)?
+                .on_cert_expiry(move |expiry| {
+                    let _ = tx.send(expiry);
+                })
+                .endpoint(&addr)
+                .with_certificate_authority("../certs/client/ca.der")?
+                .with_cert_and_key(&cert_path, &key_path)?
+                .connect()
+                .await?;
+
+            Ok::<_, SeliumError>((client, rx))
+        }
+    };
+
+    // Certificate validity is only precise to the second
+    let expected =
+        UNIX_EPOCH + Duration::from_secs(not_after.duration_since(UNIX_EPOCH)?.as_secs());
+
+    let (client, warnings) = connect(one_day * 7).await?;
+    client.ping().await?;
+    assert_eq!(client.cert_expiry(), Some(expected));
+    assert_eq!(warnings.try_recv()?, expected);
+
+    // The certificate doesn't expire within an hour, so there's nothing to warn about
+    let (client, warnings) = connect(Duration::from_secs(60 * 60)).await?;
+    assert_eq!(client.cert_expiry(), Some(expected));
+    assert!(warnings.try_recv().is_err());
+
+    Ok(())
+}
+
+// Generates a server certificate that's valid for every virtual host's server name, including
+// unhosted ones, returning the paths to the CA, certificate and key
+fn virtual_host_certs(dir: &Path) -> Result<(PathBuf, PathBuf, PathBuf)> {
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = rcgen::Certificate::from_params(params)?;
+    let cert = rcgen::generate_simple_self_signed(vec![
+        "tenant-a.selium".to_owned(),
+        "tenant-b.selium".to_owned(),
+        "tenant-c.selium".to_owned(),
+    ])?;
+
+    let ca_path = dir.join("ca.der");
+    let cert_path = dir.join("vhost.der");
+    let key_path = dir.join("vhost.key.der");
+    std::fs::write(&ca_path, ca.serialize_der()?)?;
+    std::fs::write(&cert_path, cert.serialize_der_with_signer(&ca)?)?;
+    std::fs::write(&key_path, cert.serialize_private_key_der())?;
+
+    Ok((ca_path, cert_path, key_path))
+}
+
+#[tokio::test]
+async fn test_virtual_hosts_route_connections_to_isolated_topics() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let tenant_a_dir = tempdir.path().join("tenant-a");
+    let tenant_b_dir = tempdir.path().join("tenant-b");
+
+    let (ca_path, cert_path, key_path) = virtual_host_certs(tempdir.path())?;
+
+    let server = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert(&cert_path)
+        .key(&key_path)
+        .ca("../certs/server/ca.der")
+        .log_segments_directory(tempdir.path().join("default"))
+        .flush_policy_num_writes(1)
+        .virtual_host("tenant-a.selium", &tenant_a_dir)
+        .virtual_host("tenant-b.selium", &tenant_b_dir)
+        .build()?;
+    let addr = run_server(server).addr()?.to_string();
+
+    let connect = |server_name: &'static str| {
+        let addr = addr.clone();
+        let ca_path = ca_path.clone();
+
+        async move {
+            selium::custom()
+                .with_server_name(server_name)
+                .endpoint(&addr)
+                .with_certificate_authority(&ca_path)?
+                .with_cert_and_key(
+                    "../certs/client/localhost.der",
+                    "../certs/client/localhost.key.der",
+                )?
+                .connect()
+                .await
+        }
+    };
+
+    let tenant_a = connect("tenant-a.selium").await?;
+    let tenant_b = connect("tenant-b.selium").await?;
+
+    let mut subscriber = tenant_a
+        .subscriber("/acmeco/vhost")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    // The same topic name refers to a different topic on each virtual host
+    let mut publisher_b = tenant_b
+        .publisher("/acmeco/vhost")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher_b.send("from tenant b".to_owned()).await?;
+
+    let mut publisher_a = tenant_a
+        .publisher("/acmeco/vhost")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher_a.send("from tenant a".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("from tenant a".to_owned()));
+
+    // Each virtual host stores its log segments under its own directory
+    assert!(tenant_a_dir.join("acmeco/vhost").exists());
+    assert!(tenant_b_dir.join("acmeco/vhost").exists());
+    assert!(!tempdir.path().join("default/acmeco/vhost").exists());
+
+    // Connections requesting a server name that isn't hosted are rejected
+    let result = timeout(Duration::from_secs(5), async {
+        let connection = connect("tenant-c.selium").await?;
+
+        connection
+            .publisher("/acmeco/vhost")
+            .with_encoder(StringCodec)
+            .open()
+            .await
+    })
+    .await?;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_authenticator_sees_virtual_host() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let (ca_path, cert_path, key_path) = virtual_host_certs(tempdir.path())?;
+
+    let server = Server::builder()
+        .bind_addr("127.0.0.1:0".parse()?)
+        .cert(&cert_path)
+        .key(&key_path)
+        .ca("../certs/server/ca.der")
+        .log_segments_directory(tempdir.path().join("default"))
+        .virtual_host("tenant-a.selium", tempdir.path().join("tenant-a"))
+        .virtual_host("tenant-b.selium", tempdir.path().join("tenant-b"))
+        .build()?
+        .with_authenticator(DenyServerName("tenant-b.selium"));
+    let addr = run_server(server).addr()?.to_string();
+
+    let connect = |server_name: &'static str| {
+        let addr = addr.clone();
+        let ca_path = ca_path.clone();
+
+        async move {
+            selium::custom()
+                .with_server_name(server_name)
+                .endpoint(&addr)
+                .with_certificate_authority(&ca_path)?
+                .with_cert_and_key(
+                    "../certs/client/localhost.der",
+                    "../certs/client/localhost.key.der",
+                )?
+                .connect()
+                .await
+        }
+    };
+
+    let tenant_a = connect("tenant-a.selium").await?;
+    let result = tenant_a
+        .subscriber("/acmeco/vhost")
+        .with_decoder(StringCodec)
+        .open()
+        .await;
+    assert!(result.is_ok());
+
+    // The same client is refused by the host that the authenticator denies
+    let tenant_b = connect("tenant-b.selium").await?;
+    let result = tenant_b
+        .subscriber("/acmeco/vhost")
+        .with_decoder(StringCodec)
+        .open()
+        .await;
+    assert!(matches!(
+        result,
+        Err(SeliumError::OpenStream(
+            ErrorCode::Unknown(ACCESS_DENIED),
+            _
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_certificate_authority_file_is_reported() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let bad_ca_path = tempdir.path().join("bad_ca.pem");
+    std::fs::write(&bad_ca_path, "not a certificate")?;
+
+    let result = selium::custom()
+        .endpoint("127.0.0.1:7001")
+        .with_certificate_authorities(&["../certs/client/ca.der".as_ref(), bad_ca_path.as_path()]);
+
+    match result {
+        Err(SeliumError::Crypto(CryptoError::InvalidRootCertFile(path))) => {
+            assert_eq!(path, bad_ca_path)
+        }
+        _ => panic!("Expected an invalid root cert file error"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires a system trust store"]
+async fn test_client_trusts_native_roots_alongside_certificate_authority() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &[])?;
+    let addr = server.addr()?.to_string();
+
+    // The native roots alone don't include the server's private CA
+    let result = selium::custom()
+        .endpoint(&addr)
+        .with_native_roots()?
+        .with_cert_and_key(
+            "../certs/client/localhost.der",
+            "../certs/client/localhost.key.der",
+        )?
+        .connect()
+        .await;
+
+    assert!(result.is_err());
+
+    let connection = selium::custom()
+        .endpoint(&addr)
+        .with_native_roots()?
+        .with_certificate_authority("../certs/client/ca.der")?
+        .with_cert_and_key(
+            "../certs/client/localhost.der",
+            "../certs/client/localhost.key.der",
+        )?
+        .connect()
+        .await;
+
+    assert!(connection.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_topics_reports_created_and_removed_topics() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--topic-idle-timeout", "200"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut events = connection.watch_topics().await?;
+    let topic = TopicName::try_from("/acmeco/watched")?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/watched")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher.send("Hello, world!".to_owned()).await?;
+
+    let created = timeout(Duration::from_secs(5), events.next()).await?;
+    assert_eq!(created.unwrap()?, TopicEvent::Created(topic.clone()));
+
+    publisher.finish().await?;
+
+    let removed = timeout(Duration::from_secs(5), events.next()).await?;
+    assert_eq!(removed.unwrap()?, TopicEvent::Removed(topic));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_info_reports_increasing_uptime() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--max-message-bytes", "4096"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let _publisher = connection
+        .publisher("/acmeco/server_info")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let first = connection.server_info().await?;
+    assert!(!first.version.is_empty());
+    assert_eq!(first.max_frame_size, 1024 * 1024);
+    assert_eq!(first.max_message_bytes, Some(4096));
+    assert_eq!(first.topic_count, 1);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let second = connection.server_info().await?;
+    assert!(second.uptime_secs > first.uptime_secs);
+
+    Ok(())
+}
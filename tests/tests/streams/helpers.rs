@@ -5,12 +5,15 @@ use selium::keep_alive::BackoffStrategy;
 use selium::prelude::*;
 use selium::std::codecs::BincodeCodec;
 use selium::std::errors::SeliumError;
-use selium::{request_reply::Requestor, Client};
+use selium::{
+    request_reply::Requestor, Client, ClientBuilder, CustomWantsConnect, CustomWantsEndpoint,
+};
 use selium_server::args::UserArgs;
 use selium_server::server::Server;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
 
@@ -33,6 +36,7 @@ type Req = KeepAlive<Requestor<BincodeCodec<Request>, BincodeCodec<Response>>>;
 
 pub struct TestClient {
     client: Client,
+    addr: SocketAddr,
     _tempdir: TempDir,
 }
 
@@ -41,24 +45,30 @@ impl TestClient {
         let tempdir = TempDir::new().unwrap();
         let server_addr = start_server(tempdir.path())?;
 
-        let client = selium::custom()
-            .keep_alive(5_000)?
-            .backoff_strategy(BackoffStrategy::constant().with_max_attempts(0))
-            .endpoint(&server_addr.to_string())
-            .with_certificate_authority("../certs/client/ca.der")?
-            .with_cert_and_key(
-                "../certs/client/localhost.der",
-                "../certs/client/localhost.key.der",
-            )?
-            .connect()
-            .await?;
+        let client = client_builder(
+            selium::custom()
+                .keep_alive_interval(5_000)?
+                .backoff_strategy(BackoffStrategy::constant().with_max_attempts(0)),
+            &server_addr.to_string(),
+        )?
+        .connect()
+        .await?;
 
         Ok(Self {
             client,
+            addr: server_addr,
             _tempdir: tempdir,
         })
     }
 
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
     pub fn start_replier(
         &self,
         delay: Option<Duration>,
@@ -79,8 +89,7 @@ impl TestClient {
                         handler(req).await
                     })
                     .open()
-                    .await
-                    .unwrap();
+                    .await?;
 
                 replier.listen().await
             }
@@ -113,8 +122,46 @@ async fn handler(req: Request) -> Result<Response> {
     Ok(res)
 }
 
+/// Connects a client with the default options to the server at `addr`.
+pub async fn connect_client(addr: &str) -> Result<Client, SeliumError> {
+    client_builder(selium::custom(), addr)?.connect().await
+}
+
+/// Points `builder` at the server at `addr`, authenticating with the test client certificates.
+pub fn client_builder(
+    builder: ClientBuilder<CustomWantsEndpoint>,
+    addr: &str,
+) -> Result<ClientBuilder<CustomWantsConnect>, SeliumError> {
+    builder
+        .endpoint(addr)
+        .with_certificate_authority("../certs/client/ca.der")?
+        .with_cert_and_key(
+            "../certs/client/localhost.der",
+            "../certs/client/localhost.key.der",
+        )
+}
+
 pub fn start_server(logs_dir: impl AsRef<Path>) -> Result<SocketAddr> {
-    let args = UserArgs::parse_from([
+    let server = spawn_server(logs_dir)?;
+    let addr = server.addr()?;
+
+    Ok(addr)
+}
+
+pub fn spawn_server(logs_dir: impl AsRef<Path>) -> Result<Arc<Server>> {
+    spawn_server_with_args(logs_dir, &[])
+}
+
+pub fn spawn_server_with_args(
+    logs_dir: impl AsRef<Path>,
+    extra_args: &[&str],
+) -> Result<Arc<Server>> {
+    let server = build_server(logs_dir, extra_args)?;
+    Ok(run_server(server))
+}
+
+pub fn build_server(logs_dir: impl AsRef<Path>, extra_args: &[&str]) -> Result<Server> {
+    let mut args = vec![
         "",
         "--bind-addr",
         SERVER_ADDR,
@@ -128,14 +175,24 @@ pub fn start_server(logs_dir: impl AsRef<Path>) -> Result<SocketAddr> {
         "1",
         "--log-segments-directory",
         logs_dir.as_ref().to_str().unwrap(),
-    ]);
+    ];
 
-    let server = Server::try_from(args)?;
-    let addr = server.addr()?;
+    args.extend_from_slice(extra_args);
+    let args = UserArgs::parse_from(args);
 
-    tokio::spawn(async move {
-        server.listen().await.expect("Failed to spawn server");
+    Server::try_from(args)
+}
+
+pub fn run_server(server: Server) -> Arc<Server> {
+    let server = Arc::new(server);
+
+    tokio::spawn({
+        let server = server.clone();
+
+        async move {
+            server.listen().await.expect("Failed to spawn server");
+        }
     });
 
-    Ok(addr)
+    server
 }
@@ -1,3 +1,4 @@
+mod connection;
 mod helpers;
 mod pub_sub;
 mod request_reply;
@@ -1,10 +1,24 @@
-use crate::helpers::start_server;
+use crate::helpers::{
+    build_server, client_builder, connect_client, spawn_server, spawn_server_with_args,
+    start_server,
+};
 use anyhow::Result;
-use futures::{stream::iter, FutureExt, SinkExt, StreamExt, TryStreamExt};
+use futures::{FutureExt, SinkExt, StreamExt, TryStreamExt};
 use selium::keep_alive::pubsub::KeepAlive;
-use selium::std::codecs::StringCodec;
-use selium::{prelude::*, pubsub::Subscriber};
+use selium::keep_alive::{BackoffStrategy, ReplayConfig};
+use selium::pubsub::{Offset, Signal, Subscriber};
+use selium::std::codecs::{BincodeCodec, MultiCodec, StringCodec};
+use selium::std::compression::zstd::{ZstdComp, ZstdDecomp};
+use selium::std::errors::{CodecError, DecompressionLimitExceeded, ErrorCode, SeliumError};
+use selium::std::traits::codec::MessageEncoder;
+use selium::{batching::BatchConfig, prelude::*};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
+use tokio::time::timeout;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[tokio::test]
 async fn test_pub_sub() -> Result<()> {
@@ -41,16 +55,7 @@ async fn run() -> Result<[Option<String>; 16]> {
     let subscriber3 = start_subscriber(&addr, "/acmeco/something_else").await?;
     let subscriber4 = start_subscriber(&addr, "/bluthco/stocks").await?;
 
-    let connection = selium::custom()
-        .keep_alive(5_000)?
-        .endpoint(&addr)
-        .with_certificate_authority("../certs/client/ca.der")?
-        .with_cert_and_key(
-            "../certs/client/localhost.der",
-            "../certs/client/localhost.key.der",
-        )?
-        .connect()
-        .await?;
+    let connection = connect_client(&addr).await?;
 
     let mut publisher = connection
         .publisher("/acmeco/stocks")
@@ -60,15 +65,15 @@ async fn run() -> Result<[Option<String>; 16]> {
         .await?;
 
     publisher
-        .send_all(&mut iter(vec![
-            Ok("foo".to_owned()),
-            Ok("bar".to_owned()),
-            Ok("foo".to_owned()),
-            Ok("bar".to_owned()),
-            Ok("foo".to_owned()),
-            Ok("bar".to_owned()),
-            Ok("foo".to_owned()),
-        ]))
+        .send_all(vec![
+            "foo".to_owned(),
+            "bar".to_owned(),
+            "foo".to_owned(),
+            "bar".to_owned(),
+            "foo".to_owned(),
+            "bar".to_owned(),
+            "foo".to_owned(),
+        ])
         .await?;
 
     publisher.finish().await?;
@@ -102,23 +107,1924 @@ async fn run() -> Result<[Option<String>; 16]> {
     ])
 }
 
-async fn start_subscriber(addr: &str, topic: &str) -> Result<KeepAlive<Subscriber<StringCodec>>> {
-    let connection = selium::custom()
-        .keep_alive(5_000)?
-        .endpoint(addr)
-        .with_certificate_authority("../certs/client/ca.der")?
-        .with_cert_and_key(
-            "../certs/client/localhost.der",
-            "../certs/client/localhost.key.der",
-        )?
-        .connect()
+#[tokio::test]
+async fn test_send_all_with_batching() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+    let mut subscriber = start_subscriber(&addr, "/acmeco/batched").await?;
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/batched")
+        .with_encoder(StringCodec)
+        .with_batching(BatchConfig::high_throughput())
+        .open()
         .await?;
 
-    Ok(connection
-        .subscriber(topic)
-        // .map("/selium/bonanza.wasm")
-        // .filter("/selium/dodgy_stuff.wasm")
+    let expected = (0..1_000).map(|i| i.to_string()).collect::<Vec<_>>();
+    publisher.send_all(expected.clone()).await?;
+
+    let mut received = Vec::with_capacity(expected.len());
+
+    while received.len() < expected.len() {
+        received.push(subscriber.try_next().await?.unwrap());
+    }
+
+    assert_eq!(received, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_from_stream() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+    let mut subscriber = start_subscriber(&addr, "/acmeco/forwarded").await?;
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/forwarded")
+        .with_encoder(StringCodec)
+        .with_batching(BatchConfig::high_throughput())
+        .open()
+        .await?;
+
+    let expected = (0..10_000).map(|i| i.to_string()).collect::<Vec<_>>();
+    // Yield between items so that the source is genuinely asynchronous
+    let source = futures::stream::iter(expected.clone()).then(|item| async move {
+        tokio::task::yield_now().await;
+        item
+    });
+    publisher.forward_from(source).await?;
+
+    let received = timeout(Duration::from_secs(10), async {
+        let mut received = Vec::with_capacity(expected.len());
+
+        while received.len() < expected.len() {
+            received.push(subscriber.try_next().await?.unwrap());
+        }
+
+        Ok::<_, SeliumError>(received)
+    })
+    .await??;
+
+    assert_eq!(received, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flush_sends_partial_batch() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+    let mut subscriber = start_subscriber(&addr, "/acmeco/flushed").await?;
+
+    let connection = connect_client(&addr).await?;
+
+    // Ensure that the batch can't be sent by reaching its size or interval
+    let mut publisher = connection
+        .publisher("/acmeco/flushed")
+        .with_encoder(StringCodec)
+        .with_batching(BatchConfig::new(1_000, Duration::from_secs(60)))
+        .open()
+        .await?;
+
+    publisher.feed("foo".to_owned()).await?;
+    publisher.feed("bar".to_owned()).await?;
+    publisher.flush().await?;
+
+    let received = timeout(Duration::from_secs(5), async {
+        let first = subscriber.try_next().await?;
+        let second = subscriber.try_next().await?;
+        Ok::<_, SeliumError>([first, second])
+    })
+    .await??;
+
+    assert_eq!(received, [Some("foo".to_owned()), Some("bar".to_owned())]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_publisher_receives_log_offsets() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/offsets")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    assert_eq!(publisher.last_offset(), None);
+
+    publisher
+        .send_all(vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()])
+        .await?;
+
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(2) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_dropping_publisher_warns_of_unsent_batch() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    // Ensure that the batch can't be sent by reaching its size or interval
+    let mut publisher = connection
+        .publisher("/acmeco/dropped")
+        .with_encoder(StringCodec)
+        .with_batching(BatchConfig::new(1_000, Duration::from_secs(60)))
+        .open()
+        .await?;
+
+    publisher.feed("foo".to_owned()).await?;
+    publisher.feed("bar".to_owned()).await?;
+    publisher.feed("baz".to_owned()).await?;
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer({
+            let logs = logs.clone();
+            move || logs.clone()
+        })
+        .finish();
+
+    let guard = subscriber.set_default();
+    drop(publisher);
+    drop(guard);
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone())?;
+    assert!(output.contains("WARN"));
+    assert!(output.contains("unsent batched messages"));
+    assert!(output.contains("count=3"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_skips_expired_messages() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+    let ttl = Duration::from_millis(500);
+
+    let connection = connect_client(&addr).await?;
+
+    let mut ephemeral = connection
+        .publisher("/acmeco/ttl")
+        .with_encoder(StringCodec)
+        .with_message_ttl(ttl)?
+        .open()
+        .await?;
+
+    let mut durable = connection
+        .publisher("/acmeco/ttl")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    ephemeral.send("ephemeral".to_owned()).await?;
+    durable.send("durable".to_owned()).await?;
+    ephemeral.finish().await?;
+    durable.finish().await?;
+
+    tokio::time::sleep(ttl * 2).await;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/ttl")
         .with_decoder(StringCodec)
+        .seek(0.into())
         .open()
-        .await?)
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+    assert_eq!(received, Some("durable".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_message_is_chunked_and_reassembled() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/large")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/large")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // A 5MiB message far exceeds the 1MiB frame limit, so must be split into chunks
+    let large = (0..5 * 1024 * 1024)
+        .map(|i| char::from(b'a' + (i % 26) as u8))
+        .collect::<String>();
+
+    publisher.send(large.clone()).await?;
+    publisher.send("small".to_owned()).await?;
+    publisher.finish().await?;
+
+    let received = timeout(Duration::from_secs(10), subscriber.try_next()).await??;
+    assert_eq!(received.as_deref(), Some(large.as_str()));
+
+    let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+    assert_eq!(received, Some("small".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_topic_offsets() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let result = connection.topic_offsets("/acmeco/progress").await;
+    // The topic doesn't exist until a stream is opened on it.
+    assert!(matches!(result, Err(SeliumError::OpenStream(_, _))));
+
+    let mut publisher = connection
+        .publisher("/acmeco/progress")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let messages = (0..5).map(|i| i.to_string()).collect::<Vec<_>>();
+    publisher.send_all(messages).await?;
+
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(4) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    let offsets = connection.topic_offsets("/acmeco/progress").await?;
+    assert_eq!(offsets, (0, 5));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seek_by_event_time() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--log-timestamp-source", "event"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/events")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Events are published out of order, as if by producers with skewed clocks
+    let base = SystemTime::now() - Duration::from_secs(60 * 60);
+    for (message, secs) in [("a", 0), ("c", 20), ("b", 10), ("d", 30)] {
+        let event_time = base + Duration::from_secs(secs);
+        publisher
+            .send_with_event_time(message.to_owned(), event_time)
+            .await?;
+    }
+
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(3) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    // Seeking to the event time of "b" must not skip it, so delivery begins at "c"
+    let timestamp = (base + Duration::from_secs(10))
+        .duration_since(UNIX_EPOCH)?
+        .as_millis() as u64;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/events")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromTimestamp(timestamp))
+        .open()
+        .await?;
+
+    for expected in ["c", "b", "d"] {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_filters_messages_by_header() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/orders")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for (message, region) in [
+        ("a", Some("eu")),
+        ("b", Some("us")),
+        ("c", None),
+        ("d", Some("eu")),
+        ("e", Some("apac")),
+        ("f", None),
+        ("g", Some("eu")),
+    ] {
+        match region {
+            Some(region) => {
+                let headers = HashMap::from([("region".to_owned(), region.to_owned())]);
+                publisher
+                    .send_with_headers(message.to_owned(), headers)
+                    .await?
+            }
+            None => publisher.send(message.to_owned()).await?,
+        }
+    }
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/orders")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromBeginning(0))
+        .filter_header("region", "eu")
+        .open()
+        .await?;
+
+    let mut pass_through = connection
+        .subscriber("/acmeco/orders")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromBeginning(0))
+        .filter_header("region", "eu")
+        .filter_header("region", "apac")
+        .pass_through_missing_headers()
+        .open()
+        .await?;
+
+    for expected in ["a", "d", "g"] {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    for expected in ["a", "c", "d", "e", "f", "g"] {
+        let received = timeout(Duration::from_secs(5), pass_through.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_receives_snapshot_then_live_messages() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/prices")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for (key, price) in [
+        ("a", "a1"),
+        ("b", "b1"),
+        ("a", "a2"),
+        ("c", "c1"),
+        ("b", "b2"),
+    ] {
+        publisher.send_with_key(price.to_owned(), key).await?;
+    }
+
+    // Messages without a key aren't part of the snapshot
+    publisher.send("unkeyed".to_owned()).await?;
+
+    // Keys can only be attached via send_with_key, so user headers can't forge one
+    let headers = HashMap::from([("selium-key".to_owned(), "a".to_owned())]);
+    let result = publisher
+        .send_with_headers("forged".to_owned(), headers)
+        .await;
+    assert!(matches!(result, Err(SeliumError::ReservedHeaderError(_))));
+
+    // Make sure every message has been written before the snapshot is taken
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(5) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    // Records each message alongside the snapshot boundary, in the order they're observed
+    let observed = Arc::new(Mutex::new(Vec::new()));
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/prices")
+        .with_decoder(StringCodec)
+        .snapshot_then_live()
+        .on_signal({
+            let observed = observed.clone();
+            move |signal| {
+                if let Signal::SnapshotComplete { offset } = signal {
+                    observed.lock().unwrap().push(format!("boundary@{offset}"));
+                }
+            }
+        })
+        .open()
+        .await?;
+
+    // The latest price for each key is delivered in the order it was written
+    for _ in 0..3 {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        observed.lock().unwrap().push(received.unwrap());
+    }
+
+    // Updates after the snapshot are delivered as they're published, whether or not they're keyed
+    publisher.send_with_key("a3".to_owned(), "a").await?;
+    publisher.send("live".to_owned()).await?;
+
+    for _ in 0..2 {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        observed.lock().unwrap().push(received.unwrap());
+    }
+
+    assert_eq!(
+        *observed.lock().unwrap(),
+        ["a2", "c1", "b2", "boundary@6", "a3", "live"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prioritised_subscriber_receives_urgent_messages_first() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/jobs")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("bulk 1".to_owned()).await?;
+    publisher.send_with_priority("bulk 2".to_owned(), 0).await?;
+    publisher
+        .send_with_priority("urgent".to_owned(), 10)
+        .await?;
+    publisher.send_with_priority("bulk 3".to_owned(), 1).await?;
+
+    // Make sure every message is waiting in the log before the subscribers read it
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(3) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    let mut prioritised = connection
+        .subscriber("/acmeco/jobs")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromBeginning(0))
+        .prioritise()
+        .open()
+        .await?;
+
+    let mut in_order = connection
+        .subscriber("/acmeco/jobs")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromBeginning(0))
+        .open()
+        .await?;
+
+    for expected in ["urgent", "bulk 3", "bulk 1", "bulk 2"] {
+        let received = timeout(Duration::from_secs(5), prioritised.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    // Subscribers that haven't opted in receive messages in the order they were published
+    for expected in ["bulk 1", "bulk 2", "urgent", "bulk 3"] {
+        let received = timeout(Duration::from_secs(5), in_order.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_one_way_publisher_reaches_subscriber() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/sensors")
+        .with_decoder(StringCodec)
+        .seek(Offset::FromBeginning(0))
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/sensors")
+        .with_encoder(StringCodec)
+        .one_way()
+        .open()
+        .await?;
+
+    for reading in ["first", "second", "third"] {
+        publisher.send(reading.to_owned()).await?;
+    }
+
+    for expected in ["first", "second", "third"] {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        assert_eq!(received, Some(expected.to_owned()));
+    }
+
+    // There's no return path for the server to acknowledge messages over
+    assert_eq!(publisher.last_offset(), None);
+    assert!(matches!(
+        publisher.reserve("reserved".to_owned()).await,
+        Err(SeliumError::OneWayStream)
+    ));
+
+    // A refused stream is stopped by the server, which surfaces on a later send
+    let mut refused = connection
+        .publisher("/acmeco/sensors")
+        .with_encoder(StringCodec)
+        .with_compression(ZstdComp::default())
+        .one_way()
+        .open()
+        .await?;
+
+    let err = timeout(Duration::from_secs(5), async {
+        loop {
+            if let Err(err) = refused.send("refused".to_owned()).await {
+                return err;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    assert!(matches!(
+        err,
+        SeliumError::OpenStream(ErrorCode::CompressionMismatch, _)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_truncate_topic() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(
+        tempdir.path(),
+        &["--log-maximum-entries", "10", "--allow-topic-truncation"],
+    )?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let result = connection.truncate_topic("/acmeco/forget", 0).await;
+    // The topic doesn't exist until a stream is opened on it.
+    assert!(matches!(result, Err(SeliumError::OpenStream(_, _))));
+
+    let mut publisher = connection
+        .publisher("/acmeco/forget")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let messages = (0..25).map(|i| i.to_string()).collect::<Vec<_>>();
+    publisher.send_all(messages).await?;
+
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(24) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    // Offset 15 shares a segment with offsets 10 to 19, which are all retained
+    let offsets = connection.truncate_topic("/acmeco/forget", 15).await?;
+    assert_eq!(offsets, (10, 25));
+
+    let offsets = connection.topic_offsets("/acmeco/forget").await?;
+    assert_eq!(offsets, (10, 25));
+
+    // Subscribing from before the new start offset skips ahead to the oldest retained message
+    let mut subscriber = connection
+        .subscriber("/acmeco/forget")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .open()
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+    assert_eq!(received, Some("10".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_truncate_topic_is_refused_by_default() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/forget")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher.send("Hello, world!".to_owned()).await?;
+    publisher.flush().await?;
+
+    let result = connection.truncate_topic("/acmeco/forget", 1).await;
+    assert!(matches!(
+        result,
+        Err(SeliumError::OpenStream(ErrorCode::Unauthorized, _))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_resumes_from_last_offset_on_reconnect() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--idle-timeout", "500"])?;
+    let addr = server.addr()?.to_string();
+
+    // Keep the publisher's connection alive, while the subscriber's connection will time out
+    // whenever it's idle, forcing it to reconnect.
+    let publisher_connection = client_builder(selium::custom().keep_alive_interval(100)?, &addr)?
+        .connect()
+        .await?;
+
+    let subscriber_connection = client_builder(
+        selium::custom()
+            .keep_alive_interval(5_000)?
+            .backoff_strategy(BackoffStrategy::constant().with_max_attempts(10)),
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let mut publisher = publisher_connection
+        .publisher("/acmeco/resume")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut subscriber = subscriber_connection
+        .subscriber("/acmeco/resume")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .open()
+        .await?;
+
+    publisher
+        .send_all(vec!["foo".to_owned(), "bar".to_owned()])
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), async {
+        let first = subscriber.try_next().await?;
+        let second = subscriber.try_next().await?;
+        Ok::<_, SeliumError>([first, second])
+    })
+    .await??;
+
+    assert_eq!(received, [Some("foo".to_owned()), Some("bar".to_owned())]);
+
+    // Wait for the subscriber's connection to time out before publishing again
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    publisher.send("baz".to_owned()).await?;
+
+    // The subscriber should resume after "bar", rather than replaying from the original offset
+    let received = timeout(Duration::from_secs(10), subscriber.try_next()).await??;
+    assert_eq!(received, Some("baz".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_idle_topic_is_reaped_and_reopened() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--topic-idle-timeout", "200"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/idle")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/idle")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .open()
+        .await?;
+
+    publisher
+        .send_all(vec!["foo".to_owned(), "bar".to_owned()])
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), async {
+        let first = subscriber.try_next().await?;
+        let second = subscriber.try_next().await?;
+        Ok::<_, SeliumError>([first, second])
+    })
+    .await??;
+
+    assert_eq!(received, [Some("foo".to_owned()), Some("bar".to_owned())]);
+
+    publisher.finish().await?;
+    drop(subscriber);
+
+    // Once every stream has closed, the topic should be reaped after the idle timeout
+    timeout(Duration::from_secs(5), async {
+        while connection.topic_offsets("/acmeco/idle").await.is_ok() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await?;
+
+    // Reopening the topic should restore its messages from the persisted log
+    let mut subscriber = connection
+        .subscriber("/acmeco/idle")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .open()
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), async {
+        let first = subscriber.try_next().await?;
+        let second = subscriber.try_next().await?;
+        Ok::<_, SeliumError>([first, second])
+    })
+    .await??;
+
+    assert_eq!(received, [Some("foo".to_owned()), Some("bar".to_owned())]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_stops_on_server_shutdown() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server(tempdir.path())?;
+    let addr = server.addr()?.to_string();
+
+    let mut subscriber = start_subscriber(&addr, "/acmeco/shutdown").await?;
+
+    tokio::spawn(async move { server.shutdown().await });
+
+    // The subscriber should surface the shutdown rather than attempting to reconnect.
+    let result = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert!(matches!(
+        result,
+        Some(Err(SeliumError::ConnectionClosed {
+            code: ErrorCode::Shutdown,
+            ref reason,
+        })) if reason == "Scheduled shutdown."
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_with_mismatched_compression_is_rejected() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let _publisher = connection
+        .publisher("/acmeco/compressed")
+        .with_encoder(StringCodec)
+        .with_compression(ZstdComp::default())
+        .open()
+        .await?;
+
+    let result = start_subscriber(&addr, "/acmeco/compressed").await;
+    let err = result.err().unwrap().downcast::<SeliumError>()?;
+
+    assert!(matches!(
+        err,
+        SeliumError::Codec(CodecError::DecompressFailure(ref e))
+            if e.to_string() == "Topic uses zstd but no decompressor is configured"
+    ));
+
+    let subscriber = connection
+        .subscriber("/acmeco/compressed")
+        .with_decoder(StringCodec)
+        .with_decompression(ZstdDecomp)
+        .open()
+        .await;
+
+    assert!(subscriber.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_messages_not_conforming_to_topic_schema() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let schema_path = tempdir.path().join("orders.json");
+    std::fs::write(
+        &schema_path,
+        r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}"#,
+    )?;
+
+    let schema_arg = format!("/acmeco/orders={}", schema_path.display());
+    let server = spawn_server_with_args(tempdir.path(), &["--topic-schema", &schema_arg])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/orders")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/orders")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send(r#"{"id": "first"}"#.to_owned()).await?;
+
+    // The rejection is reported as the publisher is polled for acknowledgements
+    let rejection = timeout(Duration::from_secs(5), async {
+        loop {
+            match publisher.flush().await {
+                Ok(()) => tokio::time::sleep(Duration::from_millis(10)).await,
+                Err(err) => return err,
+            }
+        }
+    })
+    .await?;
+
+    assert!(matches!(
+        rejection,
+        SeliumError::MessageRejected(ErrorCode::SchemaViolation, ref msg)
+            if msg == "Value at `/id` should be of type \"integer\""
+    ));
+
+    // The publisher remains usable, and conforming messages are written as usual
+    publisher.send(r#"{"id": 1}"#.to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some(r#"{"id": 1}"#.to_owned()));
+
+    // Topics without a schema accept messages of any format
+    let mut unchecked_subscriber = connection
+        .subscriber("/acmeco/unchecked")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut unchecked = connection
+        .publisher("/acmeco/unchecked")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    unchecked.send("not json".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), unchecked_subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("not json".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_decompression_limit_rejects_high_ratio_messages() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/decompression_bomb")
+        .with_decoder(StringCodec)
+        .with_decompression(ZstdDecomp)
+        .with_max_decompressed_bytes(1024 * 1024)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/decompression_bomb")
+        .with_encoder(StringCodec)
+        .with_compression(ZstdComp::default())
+        .open()
+        .await?;
+
+    // Compresses to a few hundred bytes, but decompresses to 16MiB
+    publisher.send("a".repeat(16 * 1024 * 1024)).await?;
+    publisher.send("small".to_owned()).await?;
+
+    let result = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert!(matches!(
+        result,
+        Some(Err(SeliumError::Codec(CodecError::DecompressFailure(ref e))))
+            if e.is::<DecompressionLimitExceeded>()
+    ));
+
+    // Messages within the limit are still delivered
+    let result = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(result.transpose()?, Some("small".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_messages_below_compression_threshold_are_sent_uncompressed() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/compression_threshold")
+        .with_decoder(StringCodec)
+        .with_decompression(ZstdDecomp)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/compression_threshold")
+        .with_encoder(StringCodec)
+        .with_compression(ZstdComp::default())
+        .with_compression_threshold(256)
+        .open()
+        .await?;
+
+    let large = format!("large-{}", "x".repeat(1024));
+    let messages = ["small-0".to_owned(), large.clone(), "small-1".to_owned()];
+
+    for message in &messages {
+        publisher.send(message.clone()).await?;
+    }
+
+    for expected in &messages {
+        let received = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        assert_eq!(received.as_ref(), Some(expected));
+    }
+
+    // Only the small messages are stored in their original form
+    let data = std::fs::read_dir(tempdir.path().join("acmeco/compression_threshold"))?
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("data".as_ref()))
+        .flat_map(|path| std::fs::read(path).unwrap())
+        .collect::<Vec<_>>();
+    let data = String::from_utf8_lossy(&data);
+
+    assert!(data.contains("small-0"));
+    assert!(data.contains("small-1"));
+    assert!(!data.contains(&large));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paused_subscriber_resumes_without_gaps() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+    let mut subscriber = start_subscriber(&addr, "/acmeco/paused").await?;
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/paused")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_all(vec!["one".to_owned(), "two".to_owned()])
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("one".to_owned()));
+
+    subscriber.pause();
+    assert!(subscriber.is_paused());
+
+    publisher
+        .send_all(vec!["three".to_owned(), "four".to_owned()])
+        .await?;
+
+    // Nothing is delivered while paused, including messages already received
+    let received = timeout(Duration::from_millis(500), subscriber.next()).await;
+    assert!(received.is_err());
+
+    subscriber.resume();
+
+    let received = timeout(Duration::from_secs(5), async {
+        let mut messages = vec![];
+
+        for _ in 0..3 {
+            messages.push(subscriber.try_next().await?);
+        }
+
+        Ok::<_, SeliumError>(messages)
+    })
+    .await??;
+
+    assert_eq!(
+        received,
+        [
+            Some("two".to_owned()),
+            Some("three".to_owned()),
+            Some("four".to_owned())
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_consumer_group_resumes_from_committed_offset() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server(tempdir.path())?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/groups")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_all((0..5).map(|i| format!("message {i}")))
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/groups")
+        .with_decoder(StringCodec)
+        .with_group("billing")
+        .seek(Offset::FromBeginning(0))
+        .open()
+        .await?;
+
+    for i in 0..3 {
+        let message = subscriber.try_next().await?;
+        assert_eq!(message, Some(format!("message {i}")));
+    }
+
+    let offset = subscriber.last_offset().unwrap();
+    // The commit is confirmed once it has been persisted, so the server can be shut down
+    // straight away
+    subscriber.commit(offset).await?;
+    drop(subscriber);
+    drop(publisher);
+    server.shutdown().await?;
+
+    // The group's offset should survive the server restarting
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/groups")
+        .with_decoder(StringCodec)
+        .with_group("billing")
+        .open()
+        .await?;
+
+    let received = timeout(Duration::from_secs(5), async {
+        let first = subscriber.try_next().await?;
+        let second = subscriber.try_next().await?;
+        Ok::<_, SeliumError>([first, second])
+    })
+    .await??;
+
+    assert_eq!(
+        received,
+        [Some("message 3".to_owned()), Some("message 4".to_owned())]
+    );
+
+    Ok(())
+}
+
+async fn start_subscriber(addr: &str, topic: &str) -> Result<KeepAlive<Subscriber<StringCodec>>> {
+    let connection = connect_client(addr).await?;
+
+    Ok(connection
+        .subscriber(topic)
+        // .map("/selium/bonanza.wasm")
+        // .filter("/selium/dodgy_stuff.wasm")
+        .with_decoder(StringCodec)
+        .open()
+        .await?)
+}
+
+#[tokio::test]
+async fn test_publisher_replays_messages_buffered_during_short_outage() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--idle-timeout", "500"])?;
+    let addr = server.addr()?.to_string();
+
+    // Keep the subscriber's connection alive, while the publisher's connection will time out
+    // whenever it's idle, forcing it to reconnect.
+    let publisher_connection = client_builder(
+        selium::custom()
+            .keep_alive_interval(5_000)?
+            .backoff_strategy(
+                BackoffStrategy::constant()
+                    .with_max_attempts(10)
+                    .with_step(Duration::from_millis(500)),
+            ),
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let subscriber_connection = client_builder(selium::custom().keep_alive_interval(100)?, &addr)?
+        .connect()
+        .await?;
+
+    let mut subscriber = subscriber_connection
+        .subscriber("/acmeco/replay")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = publisher_connection
+        .publisher("/acmeco/replay")
+        .with_encoder(StringCodec)
+        .with_replay(ReplayConfig::new(100, 1024 * 1024))
+        .open()
+        .await?;
+
+    // Wait for the publisher's connection to time out before publishing
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let messages = vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()];
+
+    // The messages are accepted into the buffer without waiting for the reconnection
+    timeout(Duration::from_millis(250), async {
+        for message in messages.clone() {
+            publisher.feed(message).await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    timeout(Duration::from_secs(5), publisher.flush()).await??;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.by_ref().take(3).try_collect::<Vec<_>>(),
+    )
+    .await??;
+    assert_eq!(received, messages);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_publisher_fails_after_long_outage() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--idle-timeout", "500"])?;
+    let addr = server.addr()?.to_string();
+
+    // The first reconnection attempt is delayed beyond the publisher's maximum gap
+    let connection = client_builder(
+        selium::custom()
+            .keep_alive_interval(5_000)?
+            .backoff_strategy(
+                BackoffStrategy::constant()
+                    .with_max_attempts(10)
+                    .with_step(Duration::from_secs(5)),
+            ),
+        &addr,
+    )?
+    .connect()
+    .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/replay")
+        .with_encoder(StringCodec)
+        .with_replay(ReplayConfig::new(100, 1024 * 1024).max_gap(Duration::from_millis(200)))
+        .open()
+        .await?;
+
+    // Wait for the publisher's connection to time out before publishing
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    publisher.feed("foo".to_owned()).await?;
+    let result = timeout(Duration::from_secs(2), publisher.flush()).await?;
+
+    assert!(matches!(result, Err(SeliumError::ReconnectGapExceeded)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_subscriber_yields_published_bytes() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/raw")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let messages = (0..5).map(|i| format!("Message {i}")).collect::<Vec<_>>();
+    publisher.send_all(messages.clone()).await?;
+    publisher.flush().await?;
+
+    // Raw subscribers seek just like any other subscriber
+    let subscriber = connection
+        .subscriber("/acmeco/raw")
+        .raw()
+        .seek(2.into())
+        .open()
+        .await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(3).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    let expected = messages[2..]
+        .iter()
+        .map(|message| StringCodec.encode(message.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(received, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_publishers_past_stream_limit_wait_for_capacity() -> Result<()> {
+    // The server allows 100 concurrent streams per connection by default
+    const PUBLISHERS: usize = 250;
+
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let publisher = connection
+        .publisher("/acmeco/burst")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Each duplicate closes its stream once it has published, freeing capacity for the others
+    let duplicates = (0..PUBLISHERS).map(|i| {
+        let publisher = &publisher;
+
+        async move {
+            let mut duplicate = publisher.duplicate().await?;
+            duplicate.send(format!("Message {i}")).await?;
+            duplicate.finish().await
+        }
+    });
+
+    let results = timeout(
+        Duration::from_secs(10),
+        futures::future::join_all(duplicates),
+    )
+    .await?;
+
+    assert_eq!(results.len(), PUBLISHERS);
+    assert!(results.iter().all(Result::is_ok));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscriber_observes_strictly_increasing_offsets() -> Result<()> {
+    const MESSAGES: usize = 50;
+
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut first = connection
+        .publisher("/acmeco/ordering")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    let mut second = first.duplicate().await?;
+
+    // Interleave the duplicates, so the order is decided by arrival at the server
+    let (first_result, second_result) = tokio::join!(
+        first.send_all((0..MESSAGES).map(|i| format!("first {i}"))),
+        second.send_all((0..MESSAGES).map(|i| format!("second {i}"))),
+    );
+    first_result?;
+    second_result?;
+    first.flush().await?;
+    second.flush().await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/ordering")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .open()
+        .await?;
+
+    let mut offsets = Vec::with_capacity(MESSAGES * 2);
+
+    for _ in 0..MESSAGES * 2 {
+        timeout(Duration::from_secs(5), subscriber.next())
+            .await?
+            .unwrap()?;
+        offsets.push(subscriber.last_offset().unwrap());
+    }
+
+    assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(offsets.last(), Some(&(MESSAGES as u64 * 2 - 1)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_messages_over_max_message_bytes() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--max-message-bytes", "16"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/max_message_bytes")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/max_message_bytes")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("x".repeat(32)).await?;
+
+    // The rejection is reported as the publisher is polled for acknowledgements
+    let rejection = timeout(Duration::from_secs(5), async {
+        loop {
+            match publisher.flush().await {
+                Ok(()) => tokio::time::sleep(Duration::from_millis(10)).await,
+                Err(err) => return err,
+            }
+        }
+    })
+    .await?;
+
+    assert!(matches!(
+        rejection,
+        SeliumError::MessageRejected(ErrorCode::MessageTooLarge, _)
+    ));
+
+    // The publisher remains usable, and the limit applies to each message in a batch rather than
+    // the batch as a whole
+    publisher.send("valid".to_owned()).await?;
+
+    let received = timeout(Duration::from_secs(5), subscriber.next()).await?;
+    assert_eq!(received.transpose()?, Some("valid".to_owned()));
+
+    let mut batched = connection
+        .publisher("/acmeco/max_message_bytes")
+        .with_encoder(StringCodec)
+        .with_batching(BatchConfig::new(10, Duration::from_secs(60)))
+        .open()
+        .await?;
+
+    let batch = (0..5).map(|i| format!("Batched {i}")).collect::<Vec<_>>();
+    batched.send_all(batch.clone()).await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(5).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    assert_eq!(received, batch);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_live_subscriber_only_receives_new_messages() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/live")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_all(vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()])
+        .await?;
+
+    // Make sure the historical messages have been written before subscribing
+    timeout(Duration::from_secs(5), async {
+        while publisher.last_offset() != Some(2) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            publisher.flush().await?;
+        }
+
+        Ok::<_, SeliumError>(())
+    })
+    .await??;
+
+    let subscriber = connection
+        .subscriber("/acmeco/live")
+        .with_decoder(StringCodec)
+        .live()
+        .open()
+        .await?;
+
+    // Messages published immediately after opening must not be skipped
+    let messages = (0..3).map(|i| format!("Live {i}")).collect::<Vec<_>>();
+    publisher.send_all(messages.clone()).await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(3).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    assert_eq!(received, messages);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_topic_log_directory_override() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let fast_dir = TempDir::new().unwrap();
+
+    // Overrides must not point to a file
+    let file = tempdir.path().join("file");
+    std::fs::write(&file, b"")?;
+    let file_override = format!("/acmeco/fast={}", file.display());
+    assert!(build_server(tempdir.path(), &["--topic-log-directory", &file_override]).is_err());
+    std::fs::remove_file(file)?;
+
+    let dir_override = format!("/acmeco/fast={}", fast_dir.path().display());
+    let server = spawn_server_with_args(tempdir.path(), &["--topic-log-directory", &dir_override])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    for topic in ["/acmeco/fast", "/acmeco/slow"] {
+        let mut publisher = connection
+            .publisher(topic)
+            .with_encoder(StringCodec)
+            .open()
+            .await?;
+
+        publisher.send("foo".to_owned()).await?;
+        publisher.finish().await?;
+    }
+
+    let has_segments = |path: std::path::PathBuf| {
+        path.is_dir() && std::fs::read_dir(path).unwrap().next().is_some()
+    };
+
+    // Only the overridden topic is stored in its dedicated directory
+    assert!(has_segments(fast_dir.path().join("acmeco/fast")));
+    assert!(!tempdir.path().join("acmeco/fast").exists());
+    assert!(has_segments(tempdir.path().join("acmeco/slow")));
+    assert!(!fast_dir.path().join("acmeco/slow").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_topic_log_open_failure_is_reported() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    // The topic's log can't be created beneath a file
+    std::fs::write(tempdir.path().join("acmeco"), b"")?;
+
+    let connection = connect_client(&addr).await?;
+
+    let result = connection
+        .publisher("/acmeco/orders")
+        .with_encoder(StringCodec)
+        .open()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(SeliumError::OpenStream(ErrorCode::LogWriteFailed, _))
+    ));
+
+    // The connection is still usable for topics that can be opened
+    let mut publisher = connection
+        .publisher("/other/orders")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    publisher.send("foo".to_owned()).await?;
+    publisher.finish().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reserved_message_is_delivered_once_committed() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/reserve")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/reserve")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut other = publisher.duplicate().await?;
+
+    publisher.send("before".to_owned()).await?;
+    let reserved = publisher.reserve("reserved".to_owned()).await?;
+    assert_eq!(reserved.offset(), 1);
+
+    other.send("after".to_owned()).await?;
+
+    let first = timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+    assert_eq!(first, Some("before".to_owned()));
+
+    // Nothing after the reserved message is delivered until it has been committed
+    let pending = timeout(Duration::from_millis(500), subscriber.try_next()).await;
+    assert!(pending.is_err());
+
+    reserved.commit().await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        (&mut subscriber).take(2).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    assert_eq!(received, ["reserved", "after"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_aborted_reservation_is_never_delivered() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/reserve")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/reserve")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut other = publisher.duplicate().await?;
+
+    publisher
+        .reserve("aborted".to_owned())
+        .await?
+        .abort()
+        .await?;
+    other.send("first".to_owned()).await?;
+
+    // Reservations left unresolved are aborted once the publisher disconnects
+    let _ = publisher.reserve("abandoned".to_owned()).await?;
+    publisher.finish().await?;
+    other.send("second".to_owned()).await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(2).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    assert_eq!(received, ["first", "second"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multi_codec_decodes_each_message_by_its_content_type() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connection = connect_client(&addr).await?;
+    let codec = || {
+        MultiCodec::new("text/plain", StringCodec)
+            .with_codec("application/bincode", BincodeCodec::<String>::default())
+    };
+
+    let subscriber = connection
+        .subscriber("/acmeco/multi_codec")
+        .with_decoder(codec())
+        .open()
+        .await?;
+
+    let mut text_publisher = connection
+        .publisher("/acmeco/multi_codec")
+        .with_encoder(codec())
+        .with_batching(BatchConfig::high_throughput())
+        .open()
+        .await?;
+
+    let mut binary_codec = codec();
+    binary_codec.set_active("application/bincode")?;
+    let mut binary_publisher = connection
+        .publisher("/acmeco/multi_codec")
+        .with_encoder(binary_codec)
+        .open()
+        .await?;
+
+    text_publisher.send("text".to_owned()).await?;
+    binary_publisher.send("binary".to_owned()).await?;
+
+    let mut received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(2).try_collect::<Vec<_>>(),
+    )
+    .await??;
+    received.sort();
+
+    assert_eq!(received, ["binary", "text"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_committing_expired_reservation_is_rejected() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--reservation-timeout", "50"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/reserve")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let reserved = publisher.reserve("expired".to_owned()).await?;
+
+    // Give the topic time to abort the reservation
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let result = timeout(Duration::from_secs(5), reserved.commit()).await?;
+    assert!(
+        matches!(
+            result,
+            Err(SeliumError::MessageRejected(
+                ErrorCode::ReservationNotFound,
+                _
+            ))
+        ),
+        "{result:?}"
+    );
+
+    // A reservation that's still held is committed once acknowledged
+    let reserved = publisher.reserve("committed".to_owned()).await?;
+    timeout(Duration::from_secs(5), reserved.commit()).await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreliable_messages_reach_live_subscriber() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let addr = start_server(tempdir.path())?.to_string();
+
+    let connect = || async { connect_client(&addr).await };
+
+    let publisher_connection = connect().await?;
+    let subscriber_connection = connect().await?;
+
+    let mut subscriber = subscriber_connection
+        .subscriber("/acmeco/datagrams")
+        .with_decoder(StringCodec)
+        .open_unreliable()
+        .await?;
+
+    let mut other = subscriber_connection
+        .subscriber("/acmeco/other_datagrams")
+        .with_decoder(StringCodec)
+        .open_unreliable()
+        .await?;
+
+    let mut publisher = publisher_connection
+        .publisher("/acmeco/datagrams")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Datagrams may be dropped, so keep sending until one arrives
+    let received = timeout(Duration::from_secs(5), async {
+        loop {
+            publisher.send_unreliable("foo".to_owned()).await?;
+
+            if let Ok(Some(message)) = timeout(Duration::from_millis(100), subscriber.next()).await
+            {
+                return message;
+            }
+        }
+    })
+    .await??;
+
+    assert_eq!(received, "foo");
+    assert!(other.next().now_or_never().is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreliable_messages_are_refused_when_datagrams_disabled() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--datagram-buffer-size", "0"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/datagrams")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let result = publisher.send_unreliable("foo".to_owned()).await;
+    assert!(matches!(result, Err(SeliumError::Quic(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lagging_subscriber_is_signalled() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    let server = spawn_server_with_args(tempdir.path(), &["--subscriber-lag-warning", "10"])?;
+    let addr = server.addr()?.to_string();
+
+    let connection = connect_client(&addr).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/lagging")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_all((0..20).map(|i| format!("Message {i}")))
+        .await?;
+    publisher.finish().await?;
+
+    let signals = Arc::new(Mutex::new(Vec::new()));
+
+    // Replaying the topic from the beginning leaves the subscriber 20 messages behind
+    let subscriber = connection
+        .subscriber("/acmeco/lagging")
+        .with_decoder(StringCodec)
+        .seek(0.into())
+        .on_signal({
+            let signals = signals.clone();
+            move |signal| signals.lock().unwrap().push(signal)
+        })
+        .open()
+        .await?;
+
+    let received = timeout(
+        Duration::from_secs(5),
+        subscriber.take(20).try_collect::<Vec<_>>(),
+    )
+    .await??;
+
+    assert_eq!(received.len(), 20);
+    assert_eq!(
+        *signals.lock().unwrap(),
+        [Signal::SubscriberLagging { lag: 20 }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blocking_client_publishes_and_subscribes() -> Result<()> {
+    let tempdir = TempDir::new().unwrap();
+    // The server runs on its own runtime, so that the test itself isn't in an async context
+    let server_runtime = tokio::runtime::Runtime::new()?;
+    let addr = server_runtime
+        .block_on(async { start_server(tempdir.path()) })?
+        .to_string();
+
+    let client = selium::blocking::Client::connect(connect_client(&addr))?;
+
+    let mut subscriber = client.subscriber("/acmeco/blocking", StringCodec)?;
+    let mut publisher = client.publisher("/acmeco/blocking", StringCodec)?;
+
+    for message in ["foo", "bar", "baz"] {
+        publisher.publish(message.to_owned())?;
+    }
+    publisher.finish()?;
+
+    let received = subscriber.by_ref().take(3).collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(received, ["foo", "bar", "baz"]);
+
+    Ok(())
 }
@@ -1,8 +1,15 @@
-use crate::helpers::{Request, Response, TestClient};
+use crate::helpers::{client_builder, Request, Response, TestClient};
 use anyhow::Result;
-use futures::future::try_join_all;
+use futures::future::{select, try_join_all};
+use futures::SinkExt;
+use selium::prelude::*;
+use selium::request_reply::CancellationToken;
+use selium::std::codecs::{BincodeCodec, StringCodec};
 use selium::std::errors::SeliumError;
-use std::time::Duration;
+use selium::ClientPool;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
 use uuid::Uuid;
 
 #[tokio::test]
@@ -63,8 +70,348 @@ async fn concurrent_requests_are_routed_successfully() -> Result<()> {
 async fn fails_to_bind_multiple_repliers_to_topic() -> Result<()> {
     let client = TestClient::start().await?;
 
+    let first = client.start_replier(None);
+    let second = client.start_replier(None);
+
+    // Whichever replier loses the race to bind to the topic will fail
+    let (result, _) = select(first, second).await.factor_first();
+    assert!(result.unwrap().is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_replier_bound_to_occupied_endpoint() -> Result<()> {
+    let client = TestClient::start().await?;
+    let first = client.start_replier(None);
+
+    // Wait for the first replier to start serving requests before binding the second
+    let mut requestor = client.requestor(None).await?;
+    requestor.request(Request::Ping).await?;
+
+    let second = client.start_replier(None).await?;
+    assert!(matches!(second, Err(SeliumError::ReplierAlreadyBound)));
+
+    // The original binding is unaffected
+    assert!(!first.is_finished());
+    assert_eq!(requestor.request(Request::Ping).await?, Response::Pong);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancelled_request_returns_immediately() -> Result<()> {
+    let client = TestClient::start().await?;
+    client.start_replier(Some(Duration::from_secs(5)));
+
+    let mut requestor = client.requestor(None).await?;
+    let token = CancellationToken::new();
+
+    tokio::spawn({
+        let token = token.clone();
+
+        async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        }
+    });
+
+    let started = Instant::now();
+    let reply = requestor
+        .request_with_cancellation(Request::Ping, token)
+        .await;
+
+    assert!(matches!(reply, Err(SeliumError::RequestCancelled)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pooled_requestors_complete_concurrent_requests() -> Result<()> {
+    let client = TestClient::start().await?;
+    client.start_replier(None);
+
+    let endpoint = client_builder(
+        selium::custom().keep_alive_interval(5_000)?,
+        &client.addr().to_string(),
+    )?;
+    let pool = ClientPool::new(endpoint, 4).await?;
+
+    assert_eq!(pool.size(), 4);
+
+    let requests = (0..100).map(|i| {
+        let builder = pool
+            .requestor("/test/endpoint")
+            .with_request_encoder(BincodeCodec::<Request>::default())
+            .with_reply_decoder(BincodeCodec::<Response>::default());
+
+        async move {
+            let mut requestor = builder.open().await?;
+            let msg = format!("Hello {i}");
+            let reply = requestor.request(Request::Echo(msg.clone())).await?;
+
+            Ok::<_, SeliumError>((reply, msg))
+        }
+    });
+
+    let replies = timeout(Duration::from_secs(10), try_join_all(requests)).await??;
+    assert_eq!(replies.len(), 100);
+
+    for (reply, msg) in replies {
+        assert_eq!(reply, Response::Echo(msg));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn requestor_registered_after_idle_replier_receives_reply() -> Result<()> {
+    let client = TestClient::start().await?;
+    client.start_replier(None);
+
+    // Give the topic time to go idle before the requestor registers
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut requestor = client.requestor(None).await?;
+    let reply = timeout(
+        Duration::from_secs(5),
+        requestor.request(Request::Echo("Hello".to_owned())),
+    )
+    .await??;
+
+    assert_eq!(reply, Response::Echo("Hello".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn requests_wait_for_a_free_slot_once_max_inflight_is_reached() -> Result<()> {
+    let client = TestClient::start().await?;
+    client.start_replier(Some(Duration::from_millis(600)));
+
+    let requestor = client
+        .client()
+        .requestor("/test/endpoint")
+        .with_request_encoder(BincodeCodec::<Request>::default())
+        .with_reply_decoder(BincodeCodec::<Response>::default())
+        .with_request_timeout(Duration::from_secs(1))?
+        .with_max_inflight(1)
+        .open()
+        .await?;
+
+    // Each reply takes 600ms, so the second request would time out if it were dispatched
+    // alongside the first, rather than waiting for the first reply to free the slot
+    let started = Instant::now();
+    let tasks = (0..2).map(|_| {
+        let mut requestor = requestor.clone();
+        tokio::spawn(async move { requestor.request(Request::Ping).await })
+    });
+
+    for reply in try_join_all(tasks).await? {
+        assert_eq!(reply?, Response::Pong);
+    }
+
+    assert!(started.elapsed() >= Duration::from_millis(1200));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fast_request_is_not_blocked_behind_slow_request() -> Result<()> {
+    let client = TestClient::start().await?;
+
+    tokio::spawn({
+        let client = client.client().clone();
+
+        async move {
+            let mut replier = client
+                .replier("/test/endpoint")
+                .with_request_decoder(BincodeCodec::<Request>::default())
+                .with_reply_encoder(BincodeCodec::<Response>::default())
+                .with_handler(|req| async move {
+                    let res = match req {
+                        Request::Ping => Response::Pong,
+                        Request::Echo(msg) => {
+                            tokio::time::sleep(Duration::from_secs(2)).await;
+                            Response::Echo(msg)
+                        }
+                    };
+
+                    Ok::<_, SeliumError>(res)
+                })
+                .with_max_concurrency(2)
+                .open()
+                .await?;
+
+            replier.listen().await
+        }
+    });
+
+    let mut slow_requestor = client.requestor(None).await?;
+    let mut fast_requestor = client.requestor(None).await?;
+
+    let slow = tokio::spawn(async move {
+        slow_requestor
+            .request(Request::Echo("slow".to_owned()))
+            .await
+    });
+
+    // Make sure the slow request reaches the replier first
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let fast = timeout(
+        Duration::from_secs(1),
+        fast_requestor.request(Request::Ping),
+    )
+    .await??;
+    assert_eq!(fast, Response::Pong);
+    assert!(!slow.is_finished());
+
+    assert_eq!(slow.await??, Response::Echo("slow".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn handler_observes_request_headers_and_peer_identity() -> Result<()> {
+    let client = TestClient::start().await?;
+
+    tokio::spawn({
+        let client = client.client().clone();
+
+        async move {
+            let mut replier = client
+                .replier("/test/endpoint")
+                .with_request_decoder(BincodeCodec::<Request>::default())
+                .with_reply_encoder(BincodeCodec::<Response>::default())
+                .with_handler_ctx(|_, ctx| async move {
+                    let trace_id = ctx.header("trace_id").unwrap_or_default();
+                    let peer = ctx.peer_identity().unwrap_or_default();
+
+                    Ok::<_, SeliumError>(Response::Echo(format!("{trace_id} {peer}")))
+                })
+                .open()
+                .await?;
+
+            replier.listen().await
+        }
+    });
+
+    let mut requestor = client.requestor(None).await?;
+    let headers = HashMap::from([
+        ("trace_id".to_owned(), "abc123".to_owned()),
+        ("selium-peer".to_owned(), "forged".to_owned()),
+    ]);
+
+    let reply = requestor
+        .request_with_headers(Request::Ping, headers)
+        .await?;
+    let Response::Echo(reply) = reply else {
+        panic!("Expected an echoed reply, got {reply:?}");
+    };
+    let (trace_id, peer) = reply.split_once(' ').unwrap();
+
+    assert_eq!(trace_id, "abc123");
+    // The server replaces the identity claimed by the requestor with its certificate's
+    // fingerprint
+    assert_eq!(peer.len(), 128);
+    assert!(peer.chars().all(|c| c.is_ascii_hexdigit()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancelled_listen_completes_in_flight_request() -> Result<()> {
+    let client = TestClient::start().await?;
+    let token = CancellationToken::new();
+
+    let listener = tokio::spawn({
+        let client = client.client().clone();
+        let token = token.clone();
+
+        async move {
+            let mut replier = client
+                .replier("/test/endpoint")
+                .with_request_decoder(BincodeCodec::<Request>::default())
+                .with_reply_encoder(BincodeCodec::<Response>::default())
+                .with_handler(|req| async move {
+                    let res = match req {
+                        Request::Ping => Response::Pong,
+                        Request::Echo(msg) => {
+                            tokio::time::sleep(Duration::from_millis(500)).await;
+                            Response::Echo(msg)
+                        }
+                    };
+
+                    Ok::<_, SeliumError>(res)
+                })
+                .open()
+                .await?;
+
+            replier.listen_with_cancellation(token).await
+        }
+    });
+
+    let mut requestor = client.requestor(None).await?;
+
+    let request = tokio::spawn(async move {
+        requestor
+            .request(Request::Echo("in flight".to_owned()))
+            .await
+    });
+
+    // Make sure the request is being handled before cancelling
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    token.cancel();
+
+    timeout(Duration::from_secs(2), listener).await???;
+    assert_eq!(request.await??, Response::Echo("in flight".to_owned()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn prioritized_requests_progress_while_publisher_saturates_connection() -> Result<()> {
+    let client = TestClient::start().await?;
     client.start_replier(None);
-    assert!(client.start_replier(None).await.unwrap().is_err());
+
+    let mut publisher = client
+        .client()
+        .publisher("/test/bulk")
+        .with_encoder(StringCodec)
+        .with_priority(-1)
+        .open()
+        .await?;
+    assert_eq!(publisher.priority(), -1);
+
+    let mut requestor = client
+        .client()
+        .requestor("/test/endpoint")
+        .with_request_encoder(BincodeCodec::<Request>::default())
+        .with_reply_decoder(BincodeCodec::<Response>::default())
+        .with_priority(10)
+        .open()
+        .await?;
+    assert_eq!(requestor.priority(), 10);
+
+    // Keep the connection saturated with large messages for the duration of the test
+    let bulk = tokio::spawn(async move {
+        let message = "x".repeat(256 * 1024);
+
+        while publisher.send(message.clone()).await.is_ok() {}
+    });
+
+    // Give the publisher a head start, so that its data is queued ahead of the requests
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    for _ in 0..10 {
+        let reply = timeout(Duration::from_secs(2), requestor.request(Request::Ping)).await??;
+        assert_eq!(reply, Response::Pong);
+    }
+
+    assert!(!bulk.is_finished());
+    bulk.abort();
 
     Ok(())
 }